@@ -8,68 +8,62 @@
 //! - match 表达式
 //! - 循环控制（break, continue）
 
-fn main() {
-    println!("=== Rust 控制流学习 ===");
+use std::io::{self, Write};
 
-    // 1. if 表达式
-    println!("\n1. if 表达式：");
-    if_expressions_demo();
+use learn_rust::lesson::Demo;
 
-    // 2. loop 循环
-    println!("\n2. loop 循环：");
-    loop_demo();
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("if_expressions", "if 表达式", if_expressions_demo),
+    ("loops", "loop 循环", loop_demo),
+    ("while_loops", "while 循环", while_demo),
+    ("for_loops", "for 循环", for_demo),
+    ("match", "match 表达式", match_demo),
+    ("nested_loops", "嵌套循环和标签", nested_loops_demo),
+];
 
-    // 3. while 循环
-    println!("\n3. while 循环：");
-    while_demo();
+/// 本课的标题
+pub const TITLE: &str = "Rust 控制流学习";
 
-    // 4. for 循环
-    println!("\n4. for 循环：");
-    for_demo();
-
-    // 5. match 表达式
-    println!("\n5. match 表达式：");
-    match_demo();
-
-    // 6. 嵌套循环和标签
-    println!("\n6. 嵌套循环和标签：");
-    nested_loops_demo();
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
 }
 
-fn if_expressions_demo() {
+fn if_expressions_demo(out: &mut dyn Write) -> io::Result<()> {
     // 基本 if
     let number = 6;
 
     if number % 4 == 0 {
-        println!("数字能被 4 整除");
+        writeln!(out, "数字能被 4 整除")?;
     } else if number % 3 == 0 {
-        println!("数字能被 3 整除");
+        writeln!(out, "数字能被 3 整除")?;
     } else if number % 2 == 0 {
-        println!("数字能被 2 整除");
+        writeln!(out, "数字能被 2 整除")?;
     } else {
-        println!("数字不能被 4、3、2 整除");
+        writeln!(out, "数字不能被 4、3、2 整除")?;
     }
 
     // if 作为表达式
     let condition = true;
     let number = if condition { 5 } else { 6 };
-    println!("条件表达式的值：{}", number);
+    writeln!(out, "条件表达式的值：{}", number)?;
 
     // 复杂条件
     let age = 25;
     let has_license = true;
 
     if age >= 18 && has_license {
-        println!("可以开车");
+        writeln!(out, "可以开车")?;
     } else if age >= 18 {
-        println!("需要先获得驾照");
+        writeln!(out, "需要先获得驾照")?;
     } else {
-        println!("年龄不够");
+        writeln!(out, "年龄不够")?;
     }
+    Ok(())
 }
 
-fn loop_demo() {
-    println!("使用 loop 计算平方:");
+fn loop_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "使用 loop 计算平方:")?;
 
     let mut counter = 0;
 
@@ -80,94 +74,97 @@ fn loop_demo() {
             break counter * 2; // 从 loop 返回值
         }
 
-        print!("{} ", counter * counter);
+        write!(out, "{} ", counter * counter)?;
     };
 
-    println!("\n循环结束，返回值：{}", result);
+    writeln!(out, "\n循环结束，返回值：{}", result)?;
+    Ok(())
 }
 
-fn while_demo() {
-    println!("使用 while 倒计时:");
+fn while_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "使用 while 倒计时:")?;
 
     let mut number = 5;
 
     while number != 0 {
-        println!("{}!", number);
+        writeln!(out, "{}!", number)?;
         number -= 1;
     }
 
-    println!("发射！🚀");
+    writeln!(out, "发射！🚀")?;
 
     // while let 模式匹配
     let mut stack = vec![1, 2, 3];
 
-    println!("\n使用 while let 弹出栈元素:");
+    writeln!(out, "\n使用 while let 弹出栈元素:")?;
     while let Some(top) = stack.pop() {
-        println!("弹出: {}", top);
+        writeln!(out, "弹出: {}", top)?;
     }
+    Ok(())
 }
 
-fn for_demo() {
+fn for_demo(out: &mut dyn Write) -> io::Result<()> {
     // 遍历数组
     let a = [10, 20, 30, 40, 50];
 
-    println!("遍历数组:");
+    writeln!(out, "遍历数组:")?;
     for element in a {
-        println!("值: {}", element);
+        writeln!(out, "值: {}", element)?;
     }
 
     // 使用范围
-    println!("\n使用范围 (1..4):");
+    writeln!(out, "\n使用范围 (1..4):")?;
     for number in 1..4 {
-        println!("数字: {}", number);
+        writeln!(out, "数字: {}", number)?;
     }
 
     // 包含结束值的范围
-    println!("\n使用范围 (1..=4):");
+    writeln!(out, "\n使用范围 (1..=4):")?;
     for number in 1..=4 {
-        println!("数字: {}", number);
+        writeln!(out, "数字: {}", number)?;
     }
 
     // 反向遍历
-    println!("\n反向遍历:");
+    writeln!(out, "\n反向遍历:")?;
     for number in (1..4).rev() {
-        println!("数字: {}", number);
+        writeln!(out, "数字: {}", number)?;
     }
 
     // 带索引的遍历
     let names = ["Alice", "Bob", "Charlie"];
-    println!("\n带索引的遍历:");
+    writeln!(out, "\n带索引的遍历:")?;
     for (index, name) in names.iter().enumerate() {
-        println!("{}: {}", index, name);
+        writeln!(out, "{}: {}", index, name)?;
     }
+    Ok(())
 }
 
-fn match_demo() {
+fn match_demo(out: &mut dyn Write) -> io::Result<()> {
     let number = 13;
 
     // 基本 match
-    println!("基本 match:");
+    writeln!(out, "基本 match:")?;
     match number {
-        1 => println!("一"),
-        2 | 3 | 5 | 7 | 11 => println!("这是一个小质数"),
-        13..=19 => println!("十几"),
-        _ => println!("其他数字"),
+        1 => writeln!(out, "一")?,
+        2 | 3 | 5 | 7 | 11 => writeln!(out, "这是一个小质数")?,
+        13..=19 => writeln!(out, "十几")?,
+        _ => writeln!(out, "其他数字")?,
     }
 
     // match 绑定值
     let x = Some(5);
 
     match x {
-        Some(i) => println!("匹配到 Some，值为：{}", i),
-        None => println!("匹配到 None"),
+        Some(i) => writeln!(out, "匹配到 Some，值为：{}", i)?,
+        None => writeln!(out, "匹配到 None")?,
     }
 
     // match 守卫
     let num = Some(4);
 
     match num {
-        Some(x) if x < 5 => println!("小于五：{}", x),
-        Some(x) => println!("等于或大于五：{}", x),
+        Some(x) if x < 5 => writeln!(out, "小于五：{}", x)?,
+        Some(x) => writeln!(out, "等于或大于五：{}", x)?,
         None => (),
     }
 
@@ -175,20 +172,22 @@ fn match_demo() {
     let point = (3, 5);
 
     match point {
-        (0, y) => println!("在 y 轴上，y = {}", y),
-        (x, 0) => println!("在 x 轴上，x = {}", x),
-        (x, y) => println!("不在轴上：({}, {})", x, y),
+        (0, y) => writeln!(out, "在 y 轴上，y = {}", y)?,
+        (x, 0) => writeln!(out, "在 x 轴上，x = {}", x)?,
+        (x, y) => writeln!(out, "不在轴上：({}, {})", x, y)?,
     }
+    Ok(())
 }
 
-fn nested_loops_demo() {
-    println!("嵌套循环和循环标签:");
+#[allow(unused_labels, clippy::never_loop)] // 演示循环标签的写法，每层循环只执行一次
+fn nested_loops_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "嵌套循环和循环标签:")?;
 
     'outer: loop {
-        println!("进入外层循环");
+        writeln!(out, "进入外层循环")?;
 
         'inner: loop {
-            println!("  进入内层循环");
+            writeln!(out, "  进入内层循环")?;
 
             // 这会跳出内层循环
             break;
@@ -197,11 +196,11 @@ fn nested_loops_demo() {
             // break 'outer;
         }
 
-        println!("这行会被执行");
+        writeln!(out, "这行会被执行")?;
         break 'outer;
     }
 
-    println!("跳出外层循环");
+    writeln!(out, "跳出外层循环")?;
 
     // 实际例子：查找数字
     let matrix = [[1, 2, 3], [4, 5, 6], [7, 8, 9]];
@@ -210,14 +209,16 @@ fn nested_loops_demo() {
     'search: for (i, row) in matrix.iter().enumerate() {
         for (j, &value) in row.iter().enumerate() {
             if value == target {
-                println!("找到 {} 在位置 ({}, {})", target, i, j);
+                writeln!(out, "找到 {} 在位置 ({}, {})", target, i, j)?;
                 break 'search;
             }
         }
     }
+    Ok(())
 }
 
 // 辅助函数：判断数字类型
+#[allow(dead_code)] // 只在测试中使用
 fn classify_number(n: i32) -> &'static str {
     match n {
         n if n < 0 => "负数",
@@ -229,6 +230,7 @@ fn classify_number(n: i32) -> &'static str {
 }
 
 // 辅助函数：计算阶乘
+#[allow(dead_code)] // 只在测试中使用
 fn factorial(n: u32) -> u32 {
     match n {
         0 | 1 => 1,
@@ -237,17 +239,19 @@ fn factorial(n: u32) -> u32 {
 }
 
 // 辅助函数：FizzBuzz 游戏
-fn fizz_buzz(limit: i32) {
-    println!("\nFizzBuzz 游戏 (1 到 {}):", limit);
+#[allow(dead_code)] // 留给读者调用的练习
+fn fizz_buzz(out: &mut dyn Write, limit: i32) -> io::Result<()> {
+    writeln!(out, "\nFizzBuzz 游戏 (1 到 {}):", limit)?;
 
     for i in 1..=limit {
         match (i % 3, i % 5) {
-            (0, 0) => println!("FizzBuzz"),
-            (0, _) => println!("Fizz"),
-            (_, 0) => println!("Buzz"),
-            (_, _) => println!("{}", i),
+            (0, 0) => writeln!(out, "FizzBuzz")?,
+            (0, _) => writeln!(out, "Fizz")?,
+            (_, 0) => writeln!(out, "Buzz")?,
+            (_, _) => writeln!(out, "{}", i)?,
         }
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -6,48 +6,41 @@
 //! - 类型推断和类型注解
 //! - 类型转换
 
-fn main() {
-    println!("=== Rust 数据类型学习 ===");
+use std::io::{self, Write};
 
-    // 1. 标量类型
-    println!("\n1. 标量类型：");
-    scalar_types_demo();
+use learn_rust::lesson::Demo;
 
-    // 2. 复合类型
-    println!("\n2. 复合类型：");
-    compound_types_demo();
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("scalar_types", "标量类型", scalar_types_demo),
+    ("compound_types", "复合类型", compound_types_demo),
+    ("type_inference", "类型推断和注解", type_inference_demo),
+    ("type_conversion", "类型转换", type_conversion_demo),
+    ("numeric_operations", "数值操作", numeric_operations_demo),
+    ("chars_and_strings", "字符和字符串", char_and_string_demo),
+];
 
-    // 3. 类型推断和注解
-    println!("\n3. 类型推断和注解：");
-    type_inference_demo();
+/// 本课的标题
+pub const TITLE: &str = "Rust 数据类型学习";
 
-    // 4. 类型转换
-    println!("\n4. 类型转换：");
-    type_conversion_demo();
-
-    // 5. 数值操作
-    println!("\n5. 数值操作：");
-    numeric_operations_demo();
-
-    // 6. 字符和字符串
-    println!("\n6. 字符和字符串：");
-    char_and_string_demo();
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
 }
 
-fn scalar_types_demo() {
+fn scalar_types_demo(out: &mut dyn Write) -> io::Result<()> {
     // 整数类型
-    println!("整数类型：");
+    writeln!(out, "整数类型：")?;
     let decimal = 98_222; // 十进制
     let hex = 0xff; // 十六进制
     let octal = 0o77; // 八进制
     let binary = 0b1111_0000; // 二进制
     let byte = b'A'; // 字节（仅限 u8）
 
-    println!("  十进制: {decimal}");
-    println!("  十六进制: {hex}");
-    println!("  八进制: {octal}");
-    println!("  二进制: {binary}");
-    println!("  字节: {byte}");
+    writeln!(out, "  十进制: {decimal}")?;
+    writeln!(out, "  十六进制: {hex}")?;
+    writeln!(out, "  八进制: {octal}")?;
+    writeln!(out, "  二进制: {binary}")?;
+    writeln!(out, "  字节: {byte}")?;
 
     // 不同大小的整数
     let small: i8 = 127; // -128 到 127
@@ -56,161 +49,168 @@ fn scalar_types_demo() {
     let large: i64 = 9223372036854775807;
     let huge: i128 = 170141183460469231731687303715884105727;
 
-    println!("  i8: {small}");
-    println!("  i16: {medium}");
-    println!("  i32: {standard}");
-    println!("  i64: {large}");
-    println!("  i128: {huge}");
+    writeln!(out, "  i8: {small}")?;
+    writeln!(out, "  i16: {medium}")?;
+    writeln!(out, "  i32: {standard}")?;
+    writeln!(out, "  i64: {large}")?;
+    writeln!(out, "  i128: {huge}")?;
 
     // 无符号整数
     let unsigned: u32 = 4294967295;
-    println!("  u32: {unsigned}");
+    writeln!(out, "  u32: {unsigned}")?;
 
     // 架构相关的整数
     let pointer_sized: isize = 64; // 依赖于架构
-    println!("  isize: {pointer_sized}");
+    writeln!(out, "  isize: {pointer_sized}")?;
 
     // 浮点类型
-    println!("\n浮点类型：");
+    writeln!(out, "\n浮点类型：")?;
     let f1 = 2.0; // f64（默认）
     let f2: f32 = 3.0; // f32
 
-    println!("  f64: {f1}");
-    println!("  f32: {f2}");
+    writeln!(out, "  f64: {f1}")?;
+    writeln!(out, "  f32: {f2}")?;
 
     // 科学记数法
     let scientific = 1e6; // 1,000,000
-    println!("  科学记数法: {scientific}");
+    writeln!(out, "  科学记数法: {scientific}")?;
 
     // 布尔类型
-    println!("\n布尔类型：");
+    writeln!(out, "\n布尔类型：")?;
     let t = true;
     let f: bool = false;
 
-    println!("  true: {t}");
-    println!("  false: {f}");
+    writeln!(out, "  true: {t}")?;
+    writeln!(out, "  false: {f}")?;
 
     // 字符类型
-    println!("\n字符类型：");
+    writeln!(out, "\n字符类型：")?;
     let c = 'z';
     let z = 'ℤ';
     let heart_eyed_cat = '😻';
 
-    println!("  ASCII 字符: {c}");
-    println!("  Unicode 字符: {z}");
-    println!("  Emoji: {heart_eyed_cat}");
+    writeln!(out, "  ASCII 字符: {c}")?;
+    writeln!(out, "  Unicode 字符: {z}")?;
+    writeln!(out, "  Emoji: {heart_eyed_cat}")?;
+    Ok(())
 }
 
-fn compound_types_demo() {
+fn compound_types_demo(out: &mut dyn Write) -> io::Result<()> {
     // 元组类型
-    println!("元组类型：");
+    writeln!(out, "元组类型：")?;
     let tup: (i32, f64, u8) = (500, 6.4, 1);
 
     // 解构元组
     let (x, y, z) = tup;
-    println!("  解构元组: x={x}, y={y}, z={z}");
+    writeln!(out, "  解构元组: x={x}, y={y}, z={z}")?;
 
     // 通过索引访问
     let five_hundred = tup.0;
     let six_point_four = tup.1;
     let one = tup.2;
 
-    println!("  索引访问: {five_hundred}, {six_point_four}, {one}");
+    writeln!(out, "  索引访问: {five_hundred}, {six_point_four}, {one}")?;
 
     // 单元类型（空元组）
     let unit = ();
-    println!("  单元类型: {unit:?}");
+    writeln!(out, "  单元类型: {unit:?}")?;
 
     // 数组类型
-    println!("\n数组类型：");
+    writeln!(out, "\n数组类型：")?;
     let a = [1, 2, 3, 4, 5];
-    println!("  数组: {a:?}");
+    writeln!(out, "  数组: {a:?}")?;
 
     // 指定类型和长度
     let b: [i32; 5] = [1, 2, 3, 4, 5];
-    println!("  指定类型数组: {b:?}");
+    writeln!(out, "  指定类型数组: {b:?}")?;
 
     // 相同值初始化
     let c = [3; 5]; // [3, 3, 3, 3, 3]
-    println!("  重复值数组: {c:?}");
+    writeln!(out, "  重复值数组: {c:?}")?;
 
     // 访问数组元素
     let first = a[0];
     let second = a[1];
-    println!("  数组元素: first={first}, second={second}");
+    writeln!(out, "  数组元素: first={first}, second={second}")?;
 
     // 多维数组
     let matrix = [[1, 2, 3], [4, 5, 6], [7, 8, 9]];
-    println!("  二维数组: {matrix:?}");
+    writeln!(out, "  二维数组: {matrix:?}")?;
 
     // 获取数组长度
-    println!("  数组长度: {}", a.len());
+    writeln!(out, "  数组长度: {}", a.len())?;
+    Ok(())
 }
 
-fn type_inference_demo() {
+#[allow(clippy::useless_vec)]
+fn type_inference_demo(out: &mut dyn Write) -> io::Result<()> {
     // 类型推断
     let guess: i32 = "42".parse().expect("不是一个数字!");
-    println!("推断类型（需要类型注解）: {guess}");
+    writeln!(out, "推断类型（需要类型注解）: {guess}")?;
 
     // 显式类型注解
     let guess: u32 = "42".parse().expect("不是一个数字!");
-    println!("显式类型注解: {guess}");
+    writeln!(out, "显式类型注解: {guess}")?;
 
     // 编译器可以推断的情况
     let numbers = vec![1, 2, 3, 4, 5];
     let sum: i32 = numbers.iter().sum(); // 从上下文推断
-    println!("从上下文推断类型: {sum}");
+    writeln!(out, "从上下文推断类型: {sum}")?;
 
     // 多种可能的类型
     let x = 5; // i32（默认）
     let y = 5.0; // f64（默认）
-    println!("默认类型: x={x} (i32), y={y} (f64)");
+    writeln!(out, "默认类型: x={x} (i32), y={y} (f64)")?;
 
     // 类型后缀
     let a = 5u32; // u32
     let b = 5.0f32; // f32
-    println!("类型后缀: a={a} (u32), b={b} (f32)");
+    writeln!(out, "类型后缀: a={a} (u32), b={b} (f32)")?;
+    Ok(())
 }
 
-fn type_conversion_demo() {
+#[allow(clippy::approx_constant, clippy::from_str_radix_10)] // 3.14159 只是示例数值；演示 from_str_radix
+fn type_conversion_demo(out: &mut dyn Write) -> io::Result<()> {
     // as 关键字进行类型转换
     let a = 13u8;
     let b = 7u32;
     let c = a as u32 + b;
-    println!("使用 as 转换: {a} as u32 + {b} = {c}");
+    writeln!(out, "使用 as 转换: {a} as u32 + {b} = {c}")?;
 
     // 可能丢失精度的转换
     let f = 3.14159f64;
     let i = f as i32;
-    println!("浮点到整数转换: {f} as i32 = {i}");
+    writeln!(out, "浮点到整数转换: {f} as i32 = {i}")?;
 
     // 布尔值转换
     let t = true;
     let f = false;
-    println!(
+    writeln!(
+        out,
         "布尔值转换: true as i32 = {}, false as i32 = {}",
         t as i32, f as i32
-    );
+    )?;
 
     // 字符转换
     let c = 'A';
-    println!("字符转换: 'A' as u8 = {}", c as u8);
+    writeln!(out, "字符转换: 'A' as u8 = {}", c as u8)?;
 
     // 使用 From/Into trait
     let s = "5";
     let i = i32::from_str_radix(s, 10).unwrap();
-    println!("字符串解析: \"{s}\" -> {i}");
+    writeln!(out, "字符串解析: \"{s}\" -> {i}")?;
 
     // TryFrom 用于可能失败的转换
     use std::convert::TryFrom;
     let big_number: i64 = 1000;
     match i32::try_from(big_number) {
-        Ok(small_number) => println!("成功转换: {big_number} -> {small_number}"),
-        Err(e) => println!("转换失败: {e:?}"),
+        Ok(small_number) => writeln!(out, "成功转换: {big_number} -> {small_number}")?,
+        Err(e) => writeln!(out, "转换失败: {e:?}")?,
     }
+    Ok(())
 }
 
-fn numeric_operations_demo() {
+fn numeric_operations_demo(out: &mut dyn Write) -> io::Result<()> {
     // 基本数学运算
     let sum = 5 + 10;
     let difference = 95.5 - 4.3;
@@ -218,93 +218,97 @@ fn numeric_operations_demo() {
     let quotient = 56.7 / 32.2;
     let remainder = 43 % 5;
 
-    println!("数学运算:");
-    println!("  加法: 5 + 10 = {sum}");
-    println!("  减法: 95.5 - 4.3 = {difference}");
-    println!("  乘法: 4 * 30 = {product}");
-    println!("  除法: 56.7 / 32.2 = {quotient}");
-    println!("  求余: 43 % 5 = {remainder}");
+    writeln!(out, "数学运算:")?;
+    writeln!(out, "  加法: 5 + 10 = {sum}")?;
+    writeln!(out, "  减法: 95.5 - 4.3 = {difference}")?;
+    writeln!(out, "  乘法: 4 * 30 = {product}")?;
+    writeln!(out, "  除法: 56.7 / 32.2 = {quotient}")?;
+    writeln!(out, "  求余: 43 % 5 = {remainder}")?;
 
     // 整数溢出
     let max_u8 = u8::MAX;
-    println!("\nu8 最大值: {max_u8}");
+    writeln!(out, "\nu8 最大值: {max_u8}")?;
 
     // 在 debug 模式下会 panic，在 release 模式下会回绕
     // let overflow = max_u8 + 1; // 这会导致溢出
 
     // 安全的溢出处理
     let result = max_u8.wrapping_add(1);
-    println!("回绕加法: {max_u8}.wrapping_add(1) = {result}");
+    writeln!(out, "回绕加法: {max_u8}.wrapping_add(1) = {result}")?;
 
     let result = max_u8.saturating_add(1);
-    println!("饱和加法: {max_u8}.saturating_add(1) = {result}");
+    writeln!(out, "饱和加法: {max_u8}.saturating_add(1) = {result}")?;
 
     let result = max_u8.checked_add(1);
-    println!("检查加法: {max_u8}.checked_add(1) = {result:?}");
+    writeln!(out, "检查加法: {max_u8}.checked_add(1) = {result:?}")?;
 
     // 位运算
     let a = 0b1100u8;
     let b = 0b1010u8;
 
-    println!("\n位运算 (a={a:04b}, b={b:04b}):");
-    println!("  AND: {a:04b} & {b:04b} = {:04b}", a & b);
-    println!("  OR:  {a:04b} | {b:04b} = {:04b}", a | b);
-    println!("  XOR: {a:04b} ^ {b:04b} = {:04b}", a ^ b);
-    println!("  NOT: !{a:04b} = {:04b}", !a);
-    println!("  左移: {a:04b} << 1 = {:04b}", a << 1);
-    println!("  右移: {a:04b} >> 1 = {:04b}", a >> 1);
+    writeln!(out, "\n位运算 (a={a:04b}, b={b:04b}):")?;
+    writeln!(out, "  AND: {a:04b} & {b:04b} = {:04b}", a & b)?;
+    writeln!(out, "  OR:  {a:04b} | {b:04b} = {:04b}", a | b)?;
+    writeln!(out, "  XOR: {a:04b} ^ {b:04b} = {:04b}", a ^ b)?;
+    writeln!(out, "  NOT: !{a:04b} = {:04b}", !a)?;
+    writeln!(out, "  左移: {a:04b} << 1 = {:04b}", a << 1)?;
+    writeln!(out, "  右移: {a:04b} >> 1 = {:04b}", a >> 1)?;
+    Ok(())
 }
 
-fn char_and_string_demo() {
+fn char_and_string_demo(out: &mut dyn Write) -> io::Result<()> {
     // 字符类型
     let c1 = 'a';
     let c2 = '中';
     let c3 = '🦀';
 
-    println!("字符类型:");
-    println!("  ASCII: {c1}");
-    println!("  中文: {c2}");
-    println!("  Emoji: {c3}");
+    writeln!(out, "字符类型:")?;
+    writeln!(out, "  ASCII: {c1}")?;
+    writeln!(out, "  中文: {c2}")?;
+    writeln!(out, "  Emoji: {c3}")?;
 
     // 字符的大小
-    println!("  字符大小: {} 字节", std::mem::size_of::<char>());
+    writeln!(out, "  字符大小: {} 字节", std::mem::size_of::<char>())?;
 
     // 字符串字面量
     let s1 = "Hello, world!";
-    println!("\n字符串字面量: {s1}");
+    writeln!(out, "\n字符串字面量: {s1}")?;
 
     // String 类型
     let mut s2 = String::from("Hello");
     s2.push_str(", world!");
-    println!("String 类型: {s2}");
+    writeln!(out, "String 类型: {s2}")?;
 
     // 原始字符串
     let raw_string = r#"这是一个原始字符串，包含 \ 和 " 等特殊字符"#;
-    println!("原始字符串: {raw_string}");
+    writeln!(out, "原始字符串: {raw_string}")?;
 
     // 多行字符串
     let multiline = "这是第一行\n这是第二行\n这是第三行";
-    println!("多行字符串:\n{multiline}");
+    writeln!(out, "多行字符串:\n{multiline}")?;
+    Ok(())
 }
 
 // 辅助函数：类型信息
+#[allow(dead_code)] // 辅助函数示例，演示中没有调用
 fn type_of<T>(_: &T) -> &'static str {
     std::any::type_name::<T>()
 }
 
 // 辅助函数：内存大小
-fn print_type_info<T>(value: &T, name: &str) {
-    println!(
+#[allow(dead_code)]
+fn print_type_info<T>(out: &mut dyn Write, value: &T, name: &str) -> io::Result<()> {
+    writeln!(
+        out,
         "{name}: 类型={}, 大小={} 字节",
         type_of(value),
         std::mem::size_of::<T>()
-    );
+    )?;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
     #[test]
     fn test_integer_types() {
         let x: i32 = 42;
@@ -315,6 +319,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_floating_point() {
         let x = 3.14159;
         assert!((x - 3.14159_f64).abs() < f64::EPSILON);
@@ -324,6 +329,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_boolean() {
         let t = true;
         let f = false;
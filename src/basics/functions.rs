@@ -7,71 +7,102 @@
 //! - 函数指针
 //! - 闭包基础
 
-fn main() {
-    println!("=== Rust 函数学习 ===");
-    
-    // 1. 基本函数调用
-    println!("\n1. 基本函数：");
-    greet();
-    greet_person("Alice");
-    
-    // 2. 有返回值的函数
-    println!("\n2. 有返回值的函数：");
+use std::io::{self, Write};
+
+use learn_rust::lesson::Demo;
+
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("basic", "基本函数", basic_functions_demo),
+    ("return_values", "有返回值的函数", return_values_demo),
+    ("multiple_returns", "返回多个值", multiple_returns_demo),
+    ("expressions", "表达式和语句", expressions_demo),
+    ("early_return", "提前返回", early_return_demo),
+    ("function_pointers", "函数指针", function_pointers_demo),
+    ("higher_order", "高阶函数", higher_order_demo),
+    ("recursion", "递归函数", recursion_demo),
+    ("methods", "方法语法预览", methods_demo),
+];
+
+/// 本课的标题
+pub const TITLE: &str = "Rust 函数学习";
+
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
+}
+
+fn basic_functions_demo(out: &mut dyn Write) -> io::Result<()> {
+    greet(out)?;
+    greet_person(out, "Alice")?;
+    Ok(())
+}
+
+fn return_values_demo(out: &mut dyn Write) -> io::Result<()> {
     let result = add(5, 3);
-    println!("5 + 3 = {}", result);
+    writeln!(out, "5 + 3 = {}", result)?;
     
     let product = multiply(4, 6);
-    println!("4 × 6 = {}", product);
-    
-    // 3. 返回多个值
-    println!("\n3. 返回多个值：");
+    writeln!(out, "4 × 6 = {}", product)?;
+    Ok(())
+}
+
+fn multiple_returns_demo(out: &mut dyn Write) -> io::Result<()> {
     let (sum, diff) = add_and_subtract(10, 3);
-    println!("10 + 3 = {}, 10 - 3 = {}", sum, diff);
-    
-    // 4. 表达式和语句
-    println!("\n4. 表达式和语句：");
+    writeln!(out, "10 + 3 = {}, 10 - 3 = {}", sum, diff)?;
+    Ok(())
+}
+
+fn expressions_demo(out: &mut dyn Write) -> io::Result<()> {
     let y = {
         let x = 3;
         x + 1  // 这是表达式，没有分号
     };
-    println!("表达式的值: {}", y);
-    
-    // 5. 提前返回
-    println!("\n5. 提前返回：");
-    println!("绝对值 -5: {}", absolute_value(-5));
-    println!("绝对值 3: {}", absolute_value(3));
-    
-    // 6. 函数指针
-    println!("\n6. 函数指针：");
+    writeln!(out, "表达式的值: {}", y)?;
+    Ok(())
+}
+
+fn early_return_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "绝对值 -5: {}", absolute_value(-5))?;
+    writeln!(out, "绝对值 3: {}", absolute_value(3))?;
+    Ok(())
+}
+
+fn function_pointers_demo(out: &mut dyn Write) -> io::Result<()> {
     let operation = add;
-    println!("通过函数指针调用: {}", operation(2, 3));
-    
-    // 7. 高阶函数
-    println!("\n7. 高阶函数：");
+    writeln!(out, "通过函数指针调用: {}", operation(2, 3))?;
+    Ok(())
+}
+
+fn higher_order_demo(out: &mut dyn Write) -> io::Result<()> {
     let numbers = vec![1, 2, 3, 4, 5];
     let doubled = apply_to_all(numbers, double);
-    println!("翻倍后的数组: {:?}", doubled);
-    
-    // 8. 递归函数
-    println!("\n8. 递归函数：");
-    println!("5 的阶乘: {}", factorial(5));
-    println!("斐波那契数列第 10 项: {}", fibonacci(10));
-    
-    // 9. 方法语法预览
-    println!("\n9. 方法语法预览：");
+    writeln!(out, "翻倍后的数组: {:?}", doubled)?;
+    Ok(())
+}
+
+fn recursion_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "5 的阶乘: {}", factorial(5))?;
+    writeln!(out, "斐波那契数列第 10 项: {}", fibonacci(10))?;
+    Ok(())
+}
+
+fn methods_demo(out: &mut dyn Write) -> io::Result<()> {
     let rect = Rectangle { width: 30, height: 50 };
-    println!("矩形面积: {}", rect.area());
-    println!("矩形周长: {}", rect.perimeter());
+    writeln!(out, "矩形面积: {}", rect.area())?;
+    writeln!(out, "矩形周长: {}", rect.perimeter())?;
+    Ok(())
 }
 
 // 无参数无返回值
-fn greet() {
-    println!("Hello, World!");
+fn greet(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "Hello, World!")?;
+    Ok(())
 }
 
 // 有参数无返回值
-fn greet_person(name: &str) {
-    println!("Hello, {}!", name);
+fn greet_person(out: &mut dyn Write, name: &str) -> io::Result<()> {
+    writeln!(out, "Hello, {}!", name)?;
+    Ok(())
 }
 
 // 有参数有返回值
@@ -80,6 +111,7 @@ fn add(a: i32, b: i32) -> i32 {
 }
 
 // 显式使用 return
+#[allow(clippy::needless_return)] // 对比显式 return 的写法
 fn multiply(a: i32, b: i32) -> i32 {
     return a * b;  // 使用 return 关键字
 }
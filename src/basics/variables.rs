@@ -7,85 +7,109 @@
 //! - 变量遮蔽 (shadowing)
 //! - 作用域
 
-fn main() {
-    println!("=== Rust 变量和可变性学习 ===");
+use std::io::{self, Write};
+
+use learn_rust::lesson::Demo;
+
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("immutable", "不可变变量", immutable_demo),
+    ("mutable", "可变变量", mutable_demo),
+    ("constants", "常量", constants_demo),
+    ("shadowing", "变量遮蔽", shadowing_demo),
+    ("shadowing_types", "遮蔽改变类型", shadowing_types_demo),
+    ("scope", "变量作用域", demonstrate_scope),
+    ("destructuring", "解构赋值", destructuring_demo),
+];
+
+/// 本课的标题
+pub const TITLE: &str = "Rust 变量和可变性学习";
+
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
+}
 
-    // 1. 不可变变量（默认）
-    println!("\n1. 不可变变量：");
+// 不可变变量（默认）
+fn immutable_demo(out: &mut dyn Write) -> io::Result<()> {
     let x = 5;
-    println!("x = {}", x);
+    writeln!(out, "x = {}", x)?;
     // x = 6; // 这行会编译错误，因为 x 是不可变的
+    Ok(())
+}
 
-    // 2. 可变变量
-    println!("\n2. 可变变量：");
+fn mutable_demo(out: &mut dyn Write) -> io::Result<()> {
     let mut y = 5;
-    println!("y 的初始值: {}", y);
+    writeln!(out, "y 的初始值: {}", y)?;
     y = 6;
-    println!("y 的新值: {}", y);
+    writeln!(out, "y 的新值: {}", y)?;
+    Ok(())
+}
 
-    // 3. 常量
-    println!("\n3. 常量：");
+fn constants_demo(out: &mut dyn Write) -> io::Result<()> {
     const THREE_HOURS_IN_SECONDS: u32 = 60 * 60 * 3;
-    println!("三小时的秒数: {}", THREE_HOURS_IN_SECONDS);
+    writeln!(out, "三小时的秒数: {}", THREE_HOURS_IN_SECONDS)?;
+    Ok(())
+}
 
-    // 4. 变量遮蔽
-    println!("\n4. 变量遮蔽：");
+fn shadowing_demo(out: &mut dyn Write) -> io::Result<()> {
     let z = 5;
-    println!("z 的第一个值: {}", z);
+    writeln!(out, "z 的第一个值: {}", z)?;
 
     let z = z + 1; // 遮蔽前一个 z
-    println!("z 的第二个值: {}", z);
+    writeln!(out, "z 的第二个值: {}", z)?;
 
     {
         let z = z * 2; // 在内部作用域中遮蔽
-        println!("内部作用域中 z 的值: {}", z);
+        writeln!(out, "内部作用域中 z 的值: {}", z)?;
     }
 
-    println!("外部作用域中 z 的值: {}", z);
+    writeln!(out, "外部作用域中 z 的值: {}", z)?;
+    Ok(())
+}
 
-    // 5. 遮蔽允许改变类型
-    println!("\n5. 遮蔽改变类型：");
+// 遮蔽允许改变类型
+fn shadowing_types_demo(out: &mut dyn Write) -> io::Result<()> {
     let spaces = "   ";
-    println!("spaces 是字符串: '{}'", spaces);
+    writeln!(out, "spaces 是字符串: '{}'", spaces)?;
 
     let spaces = spaces.len();
-    println!("spaces 现在是数字: {}", spaces);
-
-    // 6. 变量作用域演示
-    println!("\n6. 变量作用域：");
-    demonstrate_scope();
+    writeln!(out, "spaces 现在是数字: {}", spaces)?;
+    Ok(())
+}
 
-    // 7. 解构赋值
-    println!("\n7. 解构赋值：");
+fn destructuring_demo(out: &mut dyn Write) -> io::Result<()> {
     let (a, b) = (1, 2);
-    println!("a = {}, b = {}", a, b);
+    writeln!(out, "a = {}, b = {}", a, b)?;
 
     let (mut x, y) = (1, 2);
     x += y;
-    println!("x = {}, y = {}", x, y);
+    writeln!(out, "x = {}, y = {}", x, y)?;
+    Ok(())
 }
 
-fn demonstrate_scope() {
+fn demonstrate_scope(out: &mut dyn Write) -> io::Result<()> {
     let outer_var = "我在外部作用域";
-    println!("外部变量: {}", outer_var);
+    writeln!(out, "外部变量: {}", outer_var)?;
 
     {
         let inner_var = "我在内部作用域";
-        println!("内部变量: {}", inner_var);
-        println!("内部作用域也能访问外部变量: {}", outer_var);
+        writeln!(out, "内部变量: {}", inner_var)?;
+        writeln!(out, "内部作用域也能访问外部变量: {}", outer_var)?;
 
         // 内部作用域的变量遮蔽
         let outer_var = "我遮蔽了外部变量";
-        println!("遮蔽后的外部变量: {}", outer_var);
+        writeln!(out, "遮蔽后的外部变量: {}", outer_var)?;
     }
 
-    println!("回到外部作用域: {}", outer_var);
+    writeln!(out, "回到外部作用域: {}", outer_var)?;
     // println!("{}", inner_var); // 这行会编译错误，inner_var 已超出作用域
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
+    #[allow(unused_assignments)]
     fn test_variables() {
         let x = 5;
         assert_eq!(x, 5);
@@ -12,96 +12,116 @@ use std::sync::{Arc, Mutex, mpsc, RwLock, Barrier};
 use std::thread;
 use std::time::Duration;
 use std::collections::HashMap;
+use std::io::{self, Write};
 
-fn main() {
-    println!("=== Rust 并发编程学习 ===\n");
-    
-    // 1. 基本线程操作
-    println!("1. 基本线程操作：");
-    basic_threads_demo();
-    println!();
-    
-    // 2. 消息传递
-    println!("2. 消息传递：");
-    message_passing_demo();
-    println!();
-    
-    // 3. 共享状态
-    println!("3. 共享状态：");
-    shared_state_demo();
-    println!();
-    
-    // 4. 高级并发模式
-    println!("4. 高级并发模式：");
-    advanced_concurrency_demo();
+use learn_rust::lesson::Demo;
+
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("threads", "基本线程操作", basic_threads_demo),
+    ("message_passing", "消息传递", message_passing_demo),
+    ("shared_state", "共享状态", shared_state_demo),
+    ("advanced", "高级并发模式", advanced_concurrency_demo),
+];
+
+/// 本课的标题
+pub const TITLE: &str = "Rust 并发编程学习";
+
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
+}
+
+/// 写出子线程通过日志通道发回的行
+///
+/// 子线程不能借用 `out`，它们把要输出的内容发送到日志通道，由当前线程写出。
+fn write_log(out: &mut dyn Write, lines: &mpsc::Receiver<String>) -> io::Result<()> {
+    for line in lines.try_iter() {
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
 }
 
 /// 基本线程操作演示
-fn basic_threads_demo() {
+fn basic_threads_demo(out: &mut dyn Write) -> io::Result<()> {
+    let (log, lines) = mpsc::channel();
+
     // 创建简单线程
-    let handle = thread::spawn(|| {
+    let child_log = log.clone();
+    let handle = thread::spawn(move || {
         for i in 1..10 {
-            println!("  子线程: {}", i);
+            child_log.send(format!("  子线程: {}", i)).unwrap();
             thread::sleep(Duration::from_millis(50));
         }
     });
     
     for i in 1..5 {
-        println!("  主线程: {}", i);
+        write_log(out, &lines)?;
+        writeln!(out, "  主线程: {}", i)?;
         thread::sleep(Duration::from_millis(50));
     }
     
     // 等待线程完成
     handle.join().unwrap();
-    println!("  子线程执行完毕");
+    write_log(out, &lines)?;
+    writeln!(out, "  子线程执行完毕")?;
     
     // 线程间移动数据
     let v = vec![1, 2, 3];
+    let child_log = log.clone();
     let handle = thread::spawn(move || {
-        println!("  线程中的向量: {:?}", v);
+        child_log.send(format!("  线程中的向量: {:?}", v)).unwrap();
     });
     
     handle.join().unwrap();
+    write_log(out, &lines)?;
     
     // 多个线程
     let mut handles = vec![];
     
     for i in 0..3 {
+        let log = log.clone();
         let handle = thread::spawn(move || {
-            println!("  线程 {} 开始执行", i);
+            log.send(format!("  线程 {} 开始执行", i)).unwrap();
             thread::sleep(Duration::from_millis(100));
-            println!("  线程 {} 执行完毕", i);
+            log.send(format!("  线程 {} 执行完毕", i)).unwrap();
             i * 2
         });
         handles.push(handle);
     }
     
     // 收集结果
-    println!("  等待所有线程完成...");
+    writeln!(out, "  等待所有线程完成...")?;
     for handle in handles {
         let result = handle.join().unwrap();
-        println!("  线程返回结果: {}", result);
+        write_log(out, &lines)?;
+        writeln!(out, "  线程返回结果: {}", result)?;
     }
+    Ok(())
 }
 
 /// 消息传递演示
-fn message_passing_demo() {
+fn message_passing_demo(out: &mut dyn Write) -> io::Result<()> {
+    let (log, lines) = mpsc::channel();
+
     // 基本的消息传递
     let (tx, rx) = mpsc::channel();
     
-    thread::spawn(move || {
+    let first_log = log.clone();
+    let first = thread::spawn(move || {
         let val = String::from("hello from thread");
         tx.send(val).unwrap();
-        println!("  发送方: 消息已发送");
+        first_log.send("  发送方: 消息已发送".to_string()).unwrap();
     });
     
     let received = rx.recv().unwrap();
-    println!("  接收方: 收到消息 '{}'", received);
+    writeln!(out, "  接收方: 收到消息 '{}'", received)?;
+    first.join().unwrap();
+    write_log(out, &lines)?;
     
     // 发送多个消息
     let (tx, rx) = mpsc::channel();
     
-    thread::spawn(move || {
+    let sender = thread::spawn(move || {
         let vals = vec![
             String::from("hi"),
             String::from("from"),
@@ -113,13 +133,15 @@ fn message_passing_demo() {
             tx.send(val).unwrap();
             thread::sleep(Duration::from_millis(100));
         }
-        println!("  发送方: 所有消息已发送");
+        log.send("  发送方: 所有消息已发送".to_string()).unwrap();
     });
     
-    println!("  接收方: 开始接收消息");
+    writeln!(out, "  接收方: 开始接收消息")?;
     for received in rx {
-        println!("  接收到: {}", received);
+        writeln!(out, "  接收到: {}", received)?;
     }
+    sender.join().unwrap();
+    write_log(out, &lines)?;
     
     // 多个发送方
     let (tx, rx) = mpsc::channel();
@@ -150,24 +172,28 @@ fn message_passing_demo() {
     });
     
     // 接收来自多个发送方的消息
-    println!("  从多个发送方接收消息:");
+    writeln!(out, "  从多个发送方接收消息:")?;
     for received in rx {
-        println!("  收到: {}", received);
+        writeln!(out, "  收到: {}", received)?;
     }
+    Ok(())
 }
 
 /// 共享状态演示
-fn shared_state_demo() {
+fn shared_state_demo(out: &mut dyn Write) -> io::Result<()> {
     // 基本的 Mutex 使用
     let counter = Arc::new(Mutex::new(0));
     let mut handles = vec![];
     
+    let (log, lines) = mpsc::channel();
+
     for _ in 0..10 {
         let counter = Arc::clone(&counter);
+        let log = log.clone();
         let handle = thread::spawn(move || {
             let mut num = counter.lock().unwrap();
             *num += 1;
-            println!("  线程更新计数器: {}", *num);
+            log.send(format!("  线程更新计数器: {}", *num)).unwrap();
         });
         handles.push(handle);
     }
@@ -175,8 +201,9 @@ fn shared_state_demo() {
     for handle in handles {
         handle.join().unwrap();
     }
+    write_log(out, &lines)?;
     
-    println!("  最终计数器值: {}", *counter.lock().unwrap());
+    writeln!(out, "  最终计数器值: {}", *counter.lock().unwrap())?;
     
     // 共享复杂数据结构
     let data = Arc::new(Mutex::new(HashMap::new()));
@@ -184,10 +211,11 @@ fn shared_state_demo() {
     
     for i in 0..5 {
         let data = Arc::clone(&data);
+        let log = log.clone();
         let handle = thread::spawn(move || {
             let mut map = data.lock().unwrap();
             map.insert(format!("key_{}", i), i * 10);
-            println!("  线程 {} 插入数据", i);
+            log.send(format!("  线程 {} 插入数据", i)).unwrap();
         });
         handles.push(handle);
     }
@@ -195,9 +223,10 @@ fn shared_state_demo() {
     for handle in handles {
         handle.join().unwrap();
     }
+    write_log(out, &lines)?;
     
     let final_data = data.lock().unwrap();
-    println!("  最终数据: {:?}", *final_data);
+    writeln!(out, "  最终数据: {:?}", *final_data)?;
     
     // RwLock 读写锁演示
     let data = Arc::new(RwLock::new(vec![1, 2, 3, 4, 5]));
@@ -206,9 +235,10 @@ fn shared_state_demo() {
     // 多个读者
     for i in 0..3 {
         let data = Arc::clone(&data);
+        let log = log.clone();
         let handle = thread::spawn(move || {
             let reader = data.read().unwrap();
-            println!("  读者 {} 读取数据: {:?}", i, *reader);
+            log.send(format!("  读者 {} 读取数据: {:?}", i, *reader)).unwrap();
             thread::sleep(Duration::from_millis(100));
         });
         handles.push(handle);
@@ -216,62 +246,68 @@ fn shared_state_demo() {
     
     // 一个写者
     let data_writer = Arc::clone(&data);
+    let log = log.clone();
     let write_handle = thread::spawn(move || {
         thread::sleep(Duration::from_millis(50));
         let mut writer = data_writer.write().unwrap();
         writer.push(6);
-        println!("  写者添加元素 6");
+        log.send("  写者添加元素 6".to_string()).unwrap();
     });
     handles.push(write_handle);
     
     for handle in handles {
         handle.join().unwrap();
     }
+    write_log(out, &lines)?;
     
     let final_data = data.read().unwrap();
-    println!("  RwLock 最终数据: {:?}", *final_data);
+    writeln!(out, "  RwLock 最终数据: {:?}", *final_data)?;
+    Ok(())
 }
 
 /// 高级并发模式演示
-fn advanced_concurrency_demo() {
+fn advanced_concurrency_demo(out: &mut dyn Write) -> io::Result<()> {
     // 工作者池模式
-    println!("  工作者池模式:");
-    worker_pool_demo();
+    writeln!(out, "  工作者池模式:")?;
+    worker_pool_demo(out)?;
     
     // 屏障同步
-    println!("  屏障同步:");
-    barrier_demo();
+    writeln!(out, "  屏障同步:")?;
+    barrier_demo(out)?;
     
     // 生产者-消费者模式
-    println!("  生产者-消费者模式:");
-    producer_consumer_demo();
+    writeln!(out, "  生产者-消费者模式:")?;
+    producer_consumer_demo(out)?;
     
     // 原子操作
-    println!("  原子操作:");
-    atomic_demo();
+    writeln!(out, "  原子操作:")?;
+    atomic_demo(out)?;
+    Ok(())
 }
 
 /// 工作者池演示
-fn worker_pool_demo() {
+fn worker_pool_demo(out: &mut dyn Write) -> io::Result<()> {
     let (sender, receiver) = mpsc::channel();
     let receiver = Arc::new(Mutex::new(receiver));
+    let (log, lines) = mpsc::channel();
     let mut workers = vec![];
     
     // 创建工作者线程
     for id in 0..3 {
         let receiver = Arc::clone(&receiver);
+        let log = log.clone();
         let worker = thread::spawn(move || {
             loop {
                 let message = receiver.lock().unwrap().recv();
                 match message {
                     Ok(job) => {
-                        println!("  工作者 {} 收到任务: {}", id, job);
+                        log.send(format!("  工作者 {} 收到任务: {}", id, job)).unwrap();
                         // 模拟工作
                         thread::sleep(Duration::from_millis(200));
-                        println!("  工作者 {} 完成任务: {}", id, job);
+                        log.send(format!("  工作者 {} 完成任务: {}", id, job)).unwrap();
                     }
                     Err(_) => {
-                        println!("  工作者 {} 断开连接", id);
+                        log.send(format!("  工作者 {} 断开连接", id)).unwrap();
                         break;
                     }
                 }
@@ -292,24 +328,27 @@ fn worker_pool_demo() {
     for worker in workers {
         worker.join().unwrap();
     }
+    write_log(out, &lines)
 }
 
 /// 屏障同步演示
-fn barrier_demo() {
+fn barrier_demo(out: &mut dyn Write) -> io::Result<()> {
     let n = 3;
     let barrier = Arc::new(Barrier::new(n));
+    let (log, lines) = mpsc::channel();
     let mut handles = vec![];
     
     for i in 0..n {
         let c = Arc::clone(&barrier);
+        let log = log.clone();
         let handle = thread::spawn(move || {
-            println!("  线程 {} 开始工作", i);
+            log.send(format!("  线程 {} 开始工作", i)).unwrap();
             thread::sleep(Duration::from_millis((i as u64 + 1) * 100));
-            println!("  线程 {} 工作完成，等待其他线程", i);
+            log.send(format!("  线程 {} 工作完成，等待其他线程", i)).unwrap();
             
             c.wait();
             
-            println!("  线程 {} 所有线程都完成了，继续执行", i);
+            log.send(format!("  线程 {} 所有线程都完成了，继续执行", i)).unwrap();
         });
         handles.push(handle);
     }
@@ -317,38 +356,41 @@ fn barrier_demo() {
     for handle in handles {
         handle.join().unwrap();
     }
+    write_log(out, &lines)
 }
 
 /// 生产者-消费者模式演示
-fn producer_consumer_demo() {
+fn producer_consumer_demo(out: &mut dyn Write) -> io::Result<()> {
     let (tx, rx) = mpsc::channel();
-    let buffer_size = 5;
+    let (log, lines) = mpsc::channel();
     
     // 生产者
+    let producer_log = log.clone();
     let producer = thread::spawn(move || {
         for i in 0..10 {
-            println!("  生产者: 生产物品 {}", i);
+            producer_log.send(format!("  生产者: 生产物品 {}", i)).unwrap();
             tx.send(i).unwrap();
             thread::sleep(Duration::from_millis(150));
         }
-        println!("  生产者: 生产完毕");
+        producer_log.send("  生产者: 生产完毕".to_string()).unwrap();
     });
     
     // 消费者
     let consumer = thread::spawn(move || {
         while let Ok(item) = rx.recv() {
-            println!("  消费者: 消费物品 {}", item);
+            log.send(format!("  消费者: 消费物品 {}", item)).unwrap();
             thread::sleep(Duration::from_millis(200));
         }
-        println!("  消费者: 消费完毕");
+        log.send("  消费者: 消费完毕".to_string()).unwrap();
     });
     
     producer.join().unwrap();
     consumer.join().unwrap();
+    write_log(out, &lines)
 }
 
 /// 原子操作演示
-fn atomic_demo() {
+fn atomic_demo(out: &mut dyn Write) -> io::Result<()> {
     use std::sync::atomic::{AtomicUsize, Ordering};
     
     let counter = Arc::new(AtomicUsize::new(0));
@@ -368,22 +410,25 @@ fn atomic_demo() {
         handle.join().unwrap();
     }
     
-    println!("  原子计数器最终值: {}", counter.load(Ordering::SeqCst));
+    writeln!(out, "  原子计数器最终值: {}", counter.load(Ordering::SeqCst))?;
     
     // 比较并交换
     let value = Arc::new(AtomicUsize::new(10));
     let value_clone = Arc::clone(&value);
+    let (log, lines) = mpsc::channel();
     
     let handle = thread::spawn(move || {
         let old_value = value_clone.compare_exchange(10, 20, Ordering::SeqCst, Ordering::SeqCst);
         match old_value {
-            Ok(v) => println!("  比较并交换成功，旧值: {}", v),
-            Err(v) => println!("  比较并交换失败，当前值: {}", v),
+            Ok(v) => log.send(format!("  比较并交换成功，旧值: {}", v)).unwrap(),
+            Err(v) => log.send(format!("  比较并交换失败，当前值: {}", v)).unwrap(),
         }
     });
     
     handle.join().unwrap();
-    println!("  比较并交换后的值: {}", value.load(Ordering::SeqCst));
+    write_log(out, &lines)?;
+    writeln!(out, "  比较并交换后的值: {}", value.load(Ordering::SeqCst))?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -505,6 +550,7 @@ mod tests {
     }
     
     #[test]
+    #[allow(clippy::vec_init_then_push)] // 按接收顺序逐个放入
     fn test_multiple_producers() {
         let (tx, rx) = mpsc::channel();
         let tx1 = tx.clone();
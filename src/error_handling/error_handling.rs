@@ -8,35 +8,29 @@
 //! - 错误传播
 
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 
-fn main() {
-    println!("=== Rust 错误处理学习 ===");
+use learn_rust::lesson::Demo;
 
-    // 1. 不可恢复错误 (panic!)
-    println!("\n1. 不可恢复错误：");
-    unrecoverable_errors_demo();
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("unrecoverable", "不可恢复错误", unrecoverable_errors_demo),
+    ("recoverable", "可恢复错误", recoverable_errors_demo),
+    ("question_mark", "? 操作符", question_mark_operator_demo),
+    ("custom_errors", "自定义错误类型", custom_error_types_demo),
+    ("best_practices", "错误处理的最佳实践", best_practices_demo),
+];
 
-    // 2. 可恢复错误 (Result)
-    println!("\n2. 可恢复错误：");
-    recoverable_errors_demo();
+/// 本课的标题
+pub const TITLE: &str = "Rust 错误处理学习";
 
-    // 3. ? 操作符
-    println!("\n3. ? 操作符：");
-    question_mark_operator_demo();
-
-    // 4. 自定义错误类型
-    println!("\n4. 自定义错误类型：");
-    custom_error_types_demo();
-
-    // 5. 错误处理的最佳实践
-    println!("\n5. 错误处理的最佳实践：");
-    best_practices_demo();
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
 }
 
-fn unrecoverable_errors_demo() {
+fn unrecoverable_errors_demo(out: &mut dyn Write) -> io::Result<()> {
     // panic! 宏会导致程序崩溃
-    println!("演示 panic! 的使用场景（注释掉以避免程序崩溃）");
+    writeln!(out, "演示 panic! 的使用场景（注释掉以避免程序崩溃）")?;
 
     // panic!("程序崩溃了！");
 
@@ -50,124 +44,127 @@ fn unrecoverable_errors_demo() {
 
     // 更安全的替代方案
     match some_option {
-        Some(value) => println!("值: {value}"),
-        None => println!("没有值，避免了 panic"),
+        Some(value) => writeln!(out, "值: {value}")?,
+        None => writeln!(out, "没有值，避免了 panic")?,
     }
 
     // expect() 提供更好的错误信息
     let some_result: Result<i32, &str> = Err("出错了");
     match some_result {
-        Ok(value) => println!("成功: {value}"),
-        Err(e) => println!("错误: {e}"),
+        Ok(value) => writeln!(out, "成功: {value}")?,
+        Err(e) => writeln!(out, "错误: {e}")?,
     }
+    Ok(())
 }
 
-fn recoverable_errors_demo() {
+fn recoverable_errors_demo(out: &mut dyn Write) -> io::Result<()> {
     // 文件操作可能失败
     let filename = "hello.txt";
 
     match File::open(filename) {
-        Ok(file) => println!("成功打开文件: {file:?}"),
-        Err(error) => println!("打开文件失败: {error}"),
+        Ok(file) => writeln!(out, "成功打开文件: {file:?}")?,
+        Err(error) => writeln!(out, "打开文件失败: {error}")?,
     }
 
-    // 使用 unwrap_or_else 处理错误
-    let file = File::open(filename).unwrap_or_else(|error| {
-        println!("创建新文件，因为: {error}");
-        File::create(filename).unwrap_or_else(|error| {
-            panic!("创建文件也失败了: {error:?}");
-        })
-    });
+    // 使用 or_else 在打开失败时改为创建文件
+    let file = File::open(filename).or_else(|error| {
+        writeln!(out, "创建新文件，因为: {error}")?;
+        File::create(filename)
+    })?;
 
-    println!("文件处理完成: {file:?}");
+    writeln!(out, "文件处理完成: {file:?}")?;
 
     // 数字解析示例
     let number_str = "42";
     match number_str.parse::<i32>() {
-        Ok(number) => println!("解析成功: {number}"),
-        Err(error) => println!("解析失败: {error}"),
+        Ok(number) => writeln!(out, "解析成功: {number}")?,
+        Err(error) => writeln!(out, "解析失败: {error}")?,
     }
 
     let invalid_str = "not_a_number";
     match invalid_str.parse::<i32>() {
-        Ok(number) => println!("解析成功: {number}"),
-        Err(error) => println!("解析失败: {error}"),
+        Ok(number) => writeln!(out, "解析成功: {number}")?,
+        Err(error) => writeln!(out, "解析失败: {error}")?,
     }
+    Ok(())
 }
 
-fn question_mark_operator_demo() {
+fn question_mark_operator_demo(out: &mut dyn Write) -> io::Result<()> {
     // 使用 ? 操作符简化错误处理
     match read_username_from_file("username.txt") {
-        Ok(username) => println!("用户名: {username}"),
-        Err(error) => println!("读取用户名失败: {error}"),
+        Ok(username) => writeln!(out, "用户名: {username}")?,
+        Err(error) => writeln!(out, "读取用户名失败: {error}")?,
     }
 
     // 链式操作的错误处理
     match parse_and_double("25") {
-        Ok(result) => println!("解析并翻倍: {result}"),
-        Err(error) => println!("操作失败: {error}"),
+        Ok(result) => writeln!(out, "解析并翻倍: {result}")?,
+        Err(error) => writeln!(out, "操作失败: {error}")?,
     }
 
     match parse_and_double("invalid") {
-        Ok(result) => println!("解析并翻倍: {result}"),
-        Err(error) => println!("操作失败: {error}"),
+        Ok(result) => writeln!(out, "解析并翻倍: {result}")?,
+        Err(error) => writeln!(out, "操作失败: {error}")?,
     }
 
     // Option 的 ? 操作符
     match get_first_and_last(&[1, 2, 3, 4, 5]) {
-        Some((first, last)) => println!("首尾元素: {first}, {last}"),
-        None => println!("数组为空或只有一个元素"),
+        Some((first, last)) => writeln!(out, "首尾元素: {first}, {last}")?,
+        None => writeln!(out, "数组为空或只有一个元素")?,
     }
+    Ok(())
 }
 
-fn custom_error_types_demo() {
+fn custom_error_types_demo(out: &mut dyn Write) -> io::Result<()> {
     // 使用自定义错误类型
     match divide_numbers(10.0, 2.0) {
-        Ok(result) => println!("除法结果: {result}"),
-        Err(error) => println!("除法错误: {error}"),
+        Ok(result) => writeln!(out, "除法结果: {result}")?,
+        Err(error) => writeln!(out, "除法错误: {error}")?,
     }
 
     match divide_numbers(10.0, 0.0) {
-        Ok(result) => println!("除法结果: {result}"),
-        Err(error) => println!("除法错误: {error}"),
+        Ok(result) => writeln!(out, "除法结果: {result}")?,
+        Err(error) => writeln!(out, "除法错误: {error}")?,
     }
 
     // 复合错误类型示例
     match process_user_data("25", "john@example.com") {
-        Ok(user) => println!("用户: {user:?}"),
-        Err(error) => println!("处理用户数据失败: {error}"),
+        Ok(user) => writeln!(out, "用户: {user:?}")?,
+        Err(error) => writeln!(out, "处理用户数据失败: {error}")?,
     }
 
     match process_user_data("invalid_age", "invalid_email") {
-        Ok(user) => println!("用户: {user:?}"),
-        Err(error) => println!("处理用户数据失败: {error}"),
+        Ok(user) => writeln!(out, "用户: {user:?}")?,
+        Err(error) => writeln!(out, "处理用户数据失败: {error}")?,
     }
+    Ok(())
 }
 
-fn best_practices_demo() {
-    println!("错误处理最佳实践:");
+fn best_practices_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "错误处理最佳实践:")?;
 
     // 1. 使用 Result 而不是 panic!
-    println!("1. 优先使用 Result 类型");
+    writeln!(out, "1. 优先使用 Result 类型")?;
 
     // 2. 提供有意义的错误信息
     match validate_email("invalid_email") {
-        Ok(email) => println!("有效邮箱: {email}"),
-        Err(error) => println!("邮箱验证失败: {error}"),
+        Ok(email) => writeln!(out, "有效邮箱: {email}")?,
+        Err(error) => writeln!(out, "邮箱验证失败: {error}")?,
     }
 
     // 3. 错误的分层处理
     match high_level_operation() {
-        Ok(result) => println!("高级操作成功: {result}"),
-        Err(error) => println!("高级操作失败: {error}"),
+        Ok(result) => writeln!(out, "高级操作成功: {result}")?,
+        Err(error) => writeln!(out, "高级操作失败: {error}")?,
     }
 
     // 4. 使用类型系统确保错误处理
     let safe_result = safe_division(10, 2);
-    println!("安全除法: {safe_result:?}");
+    writeln!(out, "安全除法: {safe_result:?}")?;
 
     let safe_result = safe_division(10, 0);
-    println!("安全除法: {safe_result:?}");
+    writeln!(out, "安全除法: {safe_result:?}")?;
+    Ok(())
 }
 
 // 使用 ? 操作符的函数
@@ -179,6 +176,7 @@ fn read_username_from_file(filename: &str) -> Result<String, io::Error> {
 }
 
 // 更简洁的版本
+#[allow(dead_code)] // 与上面的版本对比，演示中没有调用
 fn read_username_from_file_v2(filename: &str) -> Result<String, io::Error> {
     let mut username = String::new();
     File::open(filename)?.read_to_string(&mut username)?;
@@ -232,6 +230,7 @@ fn divide_numbers(x: f64, y: f64) -> Result<f64, DivisionError> {
 
 // 复合错误类型
 #[derive(Debug)]
+#[allow(dead_code)] // 演示只关心构造时的校验
 struct User {
     age: u32,
     email: String,
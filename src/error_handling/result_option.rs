@@ -6,128 +6,131 @@
 //! - 组合器方法
 //! - 错误转换和链式操作
 
-fn main() {
-    println!("=== Rust Result 和 Option 学习 ===");
+use std::io::{self, Write};
 
-    // 1. Option 枚举详解
-    println!("\n1. Option 枚举详解：");
-    option_details_demo();
+use learn_rust::lesson::Demo;
 
-    // 2. Result 枚举详解
-    println!("\n2. Result 枚举详解：");
-    result_details_demo();
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("option_details", "Option 枚举详解", option_details_demo),
+    ("result_details", "Result 枚举详解", result_details_demo),
+    ("combinators", "组合器方法", combinator_methods_demo),
+    ("error_conversion", "错误转换和链式操作", error_conversion_demo),
+    ("practical", "实际应用场景", practical_applications_demo),
+];
 
-    // 3. 组合器方法
-    println!("\n3. 组合器方法：");
-    combinator_methods_demo();
+/// 本课的标题
+pub const TITLE: &str = "Rust Result 和 Option 学习";
 
-    // 4. 错误转换和链式操作
-    println!("\n4. 错误转换和链式操作：");
-    error_conversion_demo();
-
-    // 5. 实际应用场景
-    println!("\n5. 实际应用场景：");
-    practical_applications_demo();
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
 }
 
-fn option_details_demo() {
+#[allow(clippy::unnecessary_literal_unwrap)] // 用字面量演示各个方法的结果
+fn option_details_demo(out: &mut dyn Write) -> io::Result<()> {
     // Option 的创建
     let some_number = Some(5);
     let no_number: Option<i32> = None;
 
-    println!("Option 的基本使用:");
-    println!("  Some(5): {some_number:?}");
-    println!("  None: {no_number:?}");
+    writeln!(out, "Option 的基本使用:")?;
+    writeln!(out, "  Some(5): {some_number:?}")?;
+    writeln!(out, "  None: {no_number:?}")?;
 
     // Option 的方法
-    println!("\nOption 的方法:");
+    writeln!(out, "\nOption 的方法:")?;
 
     // is_some() 和 is_none()
-    println!("  some_number.is_some(): {}", some_number.is_some());
-    println!("  no_number.is_none(): {}", no_number.is_none());
+    writeln!(out, "  some_number.is_some(): {}", some_number.is_some())?;
+    writeln!(out, "  no_number.is_none(): {}", no_number.is_none())?;
 
     // unwrap_or() 和 unwrap_or_else()
-    println!("  some_number.unwrap_or(0): {}", some_number.unwrap_or(0));
-    println!("  no_number.unwrap_or(0): {}", no_number.unwrap_or(0));
+    writeln!(out, "  some_number.unwrap_or(0): {}", some_number.unwrap_or(0))?;
+    writeln!(out, "  no_number.unwrap_or(0): {}", no_number.unwrap_or(0))?;
 
+    // 闭包只在值为 None 时才会调用
+    let mut computed = false;
     let default_value = no_number.unwrap_or_else(|| {
-        println!("    计算默认值...");
+        computed = true;
         10
     });
-    println!("  no_number.unwrap_or_else(): {default_value}");
+    writeln!(out, "  no_number.unwrap_or_else(): {default_value}（调用了闭包: {computed}）")?;
 
     // map() 方法
     let doubled = some_number.map(|x| x * 2);
-    println!("  some_number.map(|x| x * 2): {doubled:?}");
+    writeln!(out, "  some_number.map(|x| x * 2): {doubled:?}")?;
 
     let doubled_none = no_number.map(|x| x * 2);
-    println!("  no_number.map(|x| x * 2): {doubled_none:?}");
+    writeln!(out, "  no_number.map(|x| x * 2): {doubled_none:?}")?;
 
     // and_then() 方法
     let result = some_number.and_then(|x| if x > 0 { Some(x * x) } else { None });
-    println!("  some_number.and_then(square if positive): {result:?}");
+    writeln!(out, "  some_number.and_then(square if positive): {result:?}")?;
 
     // filter() 方法
     let filtered = some_number.filter(|&x| x > 3);
-    println!("  some_number.filter(|&x| x > 3): {filtered:?}");
+    writeln!(out, "  some_number.filter(|&x| x > 3): {filtered:?}")?;
 
     // take() 方法
     let mut option = Some(42);
     let taken = option.take();
-    println!("  taken: {taken:?}, option after take: {option:?}");
+    writeln!(out, "  taken: {taken:?}, option after take: {option:?}")?;
+    Ok(())
 }
 
-fn result_details_demo() {
+#[allow(clippy::unnecessary_literal_unwrap, clippy::unnecessary_lazy_evaluations)]
+fn result_details_demo(out: &mut dyn Write) -> io::Result<()> {
     // Result 的创建
     let success: Result<i32, &str> = Ok(42);
     let failure: Result<i32, &str> = Err("Something went wrong");
 
-    println!("Result 的基本使用:");
-    println!("  Ok(42): {success:?}");
-    println!("  Err: {failure:?}");
+    writeln!(out, "Result 的基本使用:")?;
+    writeln!(out, "  Ok(42): {success:?}")?;
+    writeln!(out, "  Err: {failure:?}")?;
 
     // Result 的方法
-    println!("\nResult 的方法:");
+    writeln!(out, "\nResult 的方法:")?;
 
     // is_ok() 和 is_err()
-    println!("  success.is_ok(): {}", success.is_ok());
-    println!("  failure.is_err(): {}", failure.is_err());
+    writeln!(out, "  success.is_ok(): {}", success.is_ok())?;
+    writeln!(out, "  failure.is_err(): {}", failure.is_err())?;
 
     // unwrap_or() 和 unwrap_or_else()
-    println!("  success.unwrap_or(0): {}", success.unwrap_or(0));
-    println!("  failure.unwrap_or(0): {}", failure.unwrap_or(0));
+    writeln!(out, "  success.unwrap_or(0): {}", success.unwrap_or(0))?;
+    writeln!(out, "  failure.unwrap_or(0): {}", failure.unwrap_or(0))?;
 
     // map() 和 map_err()
     let doubled = success.map(|x| x * 2);
-    println!("  success.map(|x| x * 2): {doubled:?}");
+    writeln!(out, "  success.map(|x| x * 2): {doubled:?}")?;
 
     let mapped_error = failure.map_err(|e| format!("Error: {e}"));
-    println!("  failure.map_err(): {mapped_error:?}");
+    writeln!(out, "  failure.map_err(): {mapped_error:?}")?;
 
     // and_then()
     let chained = success.and_then(|x| if x > 40 { Ok(x + 10) } else { Err("Too small") });
-    println!("  success.and_then(): {chained:?}");
+    writeln!(out, "  success.and_then(): {chained:?}")?;
 
     // or_else()
     let recovered: Result<i32, &str> = failure.or_else(|_| Ok(100));
-    println!("  failure.or_else(|| Ok(100)): {recovered:?}");
+    writeln!(out, "  failure.or_else(|| Ok(100)): {recovered:?}")?;
+    Ok(())
 }
 
-fn combinator_methods_demo() {
-    println!("组合器方法演示:");
+#[allow(clippy::useless_vec)]
+fn combinator_methods_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "组合器方法演示:")?;
 
     // Option 链式操作
     let numbers = vec![Some(1), None, Some(3), Some(4)];
 
     let processed: Vec<Option<i32>> = numbers.iter().map(|opt| opt.map(|x| x * 2)).collect();
 
-    println!("  原始: {numbers:?}");
-    println!("  翻倍: {processed:?}");
+    writeln!(out, "  原始: {numbers:?}")?;
+    writeln!(out, "  翻倍: {processed:?}")?;
 
     // filter_map 移除 None 值
     let only_values: Vec<i32> = numbers.iter().filter_map(|&opt| opt).collect();
 
-    println!("  过滤 None: {only_values:?}");
+    writeln!(out, "  过滤 None: {only_values:?}")?;
 
     // Option 的 zip 操作
     let opt1 = Some(1);
@@ -135,10 +138,10 @@ fn combinator_methods_demo() {
     let opt3: Option<i32> = None;
 
     let zipped = opt1.zip(opt2);
-    println!("  Some(1).zip(Some(2)): {zipped:?}");
+    writeln!(out, "  Some(1).zip(Some(2)): {zipped:?}")?;
 
     let zipped_none = opt1.zip(opt3);
-    println!("  Some(1).zip(None): {zipped_none:?}");
+    writeln!(out, "  Some(1).zip(None): {zipped_none:?}")?;
 
     // Result 的链式操作
     let results = vec![Ok(1), Err("error"), Ok(3)];
@@ -146,71 +149,73 @@ fn combinator_methods_demo() {
     let processed_results: Vec<Result<i32, &str>> =
         results.iter().map(|res| res.map(|x| x * 2)).collect();
 
-    println!("  Result 翻倍: {processed_results:?}");
+    writeln!(out, "  Result 翻倍: {processed_results:?}")?;
 
     // collect() 用于 Result
     let all_ok = vec![Ok(1), Ok(2), Ok(3)];
     let collected: Result<Vec<i32>, &str> = all_ok.into_iter().collect();
-    println!("  全部成功收集: {collected:?}");
+    writeln!(out, "  全部成功收集: {collected:?}")?;
 
     let with_error = vec![Ok(1), Err("error"), Ok(3)];
     let collected_error: Result<Vec<i32>, &str> = with_error.into_iter().collect();
-    println!("  有错误的收集: {collected_error:?}");
+    writeln!(out, "  有错误的收集: {collected_error:?}")?;
+    Ok(())
 }
 
-fn error_conversion_demo() {
-    println!("错误转换演示:");
+fn error_conversion_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "错误转换演示:")?;
 
     // 使用 ? 操作符进行错误传播
     match parse_numbers_and_sum(&["1", "2", "3"]) {
-        Ok(sum) => println!("  数字总和: {sum}"),
-        Err(e) => println!("  解析错误: {e}"),
+        Ok(sum) => writeln!(out, "  数字总和: {sum}")?,
+        Err(e) => writeln!(out, "  解析错误: {e}")?,
     }
 
     match parse_numbers_and_sum(&["1", "invalid", "3"]) {
-        Ok(sum) => println!("  数字总和: {sum}"),
-        Err(e) => println!("  解析错误: {e}"),
+        Ok(sum) => writeln!(out, "  数字总和: {sum}")?,
+        Err(e) => writeln!(out, "  解析错误: {e}")?,
     }
 
     // 复杂的错误转换
     match complex_operation("5", "10") {
-        Ok(result) => println!("  复杂操作结果: {result}"),
-        Err(e) => println!("  复杂操作错误: {e}"),
+        Ok(result) => writeln!(out, "  复杂操作结果: {result}")?,
+        Err(e) => writeln!(out, "  复杂操作错误: {e}")?,
     }
 
     match complex_operation("invalid", "10") {
-        Ok(result) => println!("  复杂操作结果: {result}"),
-        Err(e) => println!("  复杂操作错误: {e}"),
+        Ok(result) => writeln!(out, "  复杂操作结果: {result}")?,
+        Err(e) => writeln!(out, "  复杂操作错误: {e}")?,
     }
 
     // 多种错误类型的处理
     match multi_error_function(true) {
-        Ok(value) => println!("  多错误函数成功: {value}"),
-        Err(e) => println!("  多错误函数失败: {e:?}"),
+        Ok(value) => writeln!(out, "  多错误函数成功: {value}")?,
+        Err(e) => writeln!(out, "  多错误函数失败: {e:?}")?,
     }
 
     match multi_error_function(false) {
-        Ok(value) => println!("  多错误函数成功: {value}"),
-        Err(e) => println!("  多错误函数失败: {e:?}"),
+        Ok(value) => writeln!(out, "  多错误函数成功: {value}")?,
+        Err(e) => writeln!(out, "  多错误函数失败: {e:?}")?,
     }
+    Ok(())
 }
 
-fn practical_applications_demo() {
-    println!("实际应用场景:");
+fn practical_applications_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "实际应用场景:")?;
 
     // 配置解析
     let config = parse_config(&["port=8080", "host=localhost", "debug=true"]);
 
     match config {
-        Ok(cfg) => println!("  配置: {cfg:?}"),
-        Err(e) => println!("  配置解析失败: {e}"),
+        Ok(cfg) => writeln!(out, "  配置: {cfg:?}")?,
+        Err(e) => writeln!(out, "  配置解析失败: {e}")?,
     }
 
     // 数据库查询模拟
     let user_id = 123;
     match find_user(user_id) {
-        Some(user) => println!("  找到用户: {user:?}"),
-        None => println!("  用户 {user_id} 不存在"),
+        Some(user) => writeln!(out, "  找到用户: {user:?}")?,
+        None => writeln!(out, "  用户 {user_id} 不存在")?,
     }
 
     // 文件处理管道
@@ -218,8 +223,8 @@ fn practical_applications_demo() {
 
     for filename in files {
         match process_file(filename) {
-            Ok(content) => println!("  {filename}: {content}"),
-            Err(e) => println!("  {filename}: 错误 - {e}"),
+            Ok(content) => writeln!(out, "  {filename}: {content}")?,
+            Err(e) => writeln!(out, "  {filename}: 错误 - {e}")?,
         }
     }
 
@@ -228,10 +233,11 @@ fn practical_applications_demo() {
 
     for url in urls {
         match fetch_data(url) {
-            Ok(data) => println!("  {url}: {data}"),
-            Err(e) => println!("  {url}: {e}"),
+            Ok(data) => writeln!(out, "  {url}: {data}")?,
+            Err(e) => writeln!(out, "  {url}: {e}")?,
         }
     }
+    Ok(())
 }
 
 // 辅助函数
@@ -260,6 +266,7 @@ fn complex_operation(s1: &str, s2: &str) -> Result<i32, String> {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)] // 错误内容只通过 Debug 输出
 enum MyError {
     ParseError(std::num::ParseIntError),
     DivisionByZero,
@@ -287,6 +294,7 @@ fn multi_error_function(success: bool) -> Result<i32, MyError> {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)] // 演示只关心解析是否成功
 struct Config {
     port: u16,
     host: String,
@@ -332,6 +340,7 @@ fn parse_config(lines: &[&str]) -> Result<Config, String> {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 struct User {
     id: u32,
     name: String,
@@ -372,6 +381,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::unnecessary_literal_unwrap)]
     fn test_option_methods() {
         let some_value = Some(10);
         let none_value: Option<i32> = None;
@@ -384,6 +394,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unnecessary_literal_unwrap)]
     fn test_result_methods() {
         let ok_value: Result<i32, &str> = Ok(10);
         let err_value: Result<i32, &str> = Err("error");
@@ -421,6 +432,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bind_instead_of_map)]
     fn test_option_combinators() {
         let opt1 = Some(5);
         let opt2 = Some(10);
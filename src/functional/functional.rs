@@ -9,68 +9,68 @@
 
 use std::collections::HashMap;
 use std::thread;
+use std::io::{self, Write};
 
-fn main() {
-    println!("=== Rust 函数式编程学习 ===\n");
+use learn_rust::lesson::Demo;
 
-    // 1. 闭包演示
-    println!("1. 闭包 (Closures)：");
-    closure_demo();
-    println!();
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("closures", "闭包 (Closures)", closure_demo),
+    ("iterators", "迭代器 (Iterators)", iterator_demo),
+    ("patterns", "函数式编程模式", functional_patterns_demo),
+    ("advanced", "高级函数式技巧", advanced_functional_demo),
+];
 
-    // 2. 迭代器演示
-    println!("2. 迭代器 (Iterators)：");
-    iterator_demo();
-    println!();
+/// 本课的标题
+pub const TITLE: &str = "Rust 函数式编程学习";
 
-    // 3. 函数式编程模式
-    println!("3. 函数式编程模式：");
-    functional_patterns_demo();
-    println!();
-
-    // 4. 高级函数式技巧
-    println!("4. 高级函数式技巧：");
-    advanced_functional_demo();
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
 }
 
 /// 闭包演示
-fn closure_demo() {
+fn closure_demo(out: &mut dyn Write) -> io::Result<()> {
     // 基本闭包
-    let expensive_closure = |num| {
-        println!("  计算中...");
+    let mut expensive_closure = |num| -> io::Result<u32> {
+        writeln!(out, "  计算中...")?;
         thread::sleep(std::time::Duration::from_millis(100));
-        num
+        Ok(num)
     };
 
-    println!("  调用闭包: {}", expensive_closure(5));
+    let result = expensive_closure(5)?;
+    writeln!(out, "  调用闭包: {}", result)?;
 
     // 闭包捕获环境
     let x = 4;
     let equal_to_x = |z| z == x;
     let y = 4;
     assert!(equal_to_x(y));
-    println!("  闭包捕获环境: {} == {} 是 {}", y, x, equal_to_x(y));
+    writeln!(out, "  闭包捕获环境: {} == {} 是 {}", y, x, equal_to_x(y))?;
 
     // 不同的闭包类型推断
     let example_closure = |x| x;
     let s = example_closure(String::from("hello"));
-    println!("  类型推断闭包: '{}'", s);
+    writeln!(out, "  类型推断闭包: '{}'", s)?;
 
     // 缓存闭包结果
+    let calls = std::cell::Cell::new(0);
     let mut expensive_result = Cacher::new(|a| {
-        println!("  执行昂贵计算...");
+        calls.set(calls.get() + 1);
         thread::sleep(std::time::Duration::from_millis(50));
         a * 2
     });
 
-    println!(
+    writeln!(
+        out,
         "  第一次调用 expensive_result.value(10): {}",
         expensive_result.value(10)
-    );
-    println!(
+    )?;
+    writeln!(
+        out,
         "  第二次调用 expensive_result.value(10): {}",
         expensive_result.value(10)
-    );
+    )?;
+    writeln!(out, "  昂贵计算执行了 {} 次", calls.get())?;
 
     // move 闭包
     let x = vec![1, 2, 3];
@@ -78,15 +78,16 @@ fn closure_demo() {
     // println!("can't use x here: {:?}", x); // 这行会编译错误
     let y = vec![1, 2, 3];
     assert!(equal_to_x(y));
-    println!("  move 闭包成功执行");
+    writeln!(out, "  move 闭包成功执行")?;
 
     // 作为函数参数的闭包
     let list = vec![1, 2, 3];
-    println!("  排序前: {:?}", list);
+    writeln!(out, "  排序前: {:?}", list)?;
 
     let mut list = list;
     list.sort_by_key(|item| *item);
-    println!("  按绝对值排序后: {:?}", list);
+    writeln!(out, "  按绝对值排序后: {:?}", list)?;
+    Ok(())
 }
 
 /// 缓存结构体
@@ -122,88 +123,91 @@ where
 }
 
 /// 迭代器演示
-fn iterator_demo() {
+#[allow(clippy::useless_vec, clippy::unnecessary_fold)] // 演示 fold 的通用写法
+fn iterator_demo(out: &mut dyn Write) -> io::Result<()> {
     // 基本迭代器
     let v1 = vec![1, 2, 3];
     let v1_iter = v1.iter();
 
-    println!("  遍历 Vector:");
+    writeln!(out, "  遍历 Vector:")?;
     for val in v1_iter {
-        println!("    值: {}", val);
+        writeln!(out, "    值: {}", val)?;
     }
 
     // 迭代器适配器
     let v1: Vec<i32> = vec![1, 2, 3];
     let v2: Vec<_> = v1.iter().map(|x| x + 1).collect();
-    println!("  map 适配器: {:?} -> {:?}", v1, v2);
+    writeln!(out, "  map 适配器: {:?} -> {:?}", v1, v2)?;
 
     // 过滤器
     let v1: Vec<i32> = vec![1, 2, 3, 4, 5, 6];
     let v2: Vec<_> = v1.into_iter().filter(|x| *x > 3).collect();
-    println!("  filter 适配器: 过滤 > 3 的元素: {:?}", v2);
+    writeln!(out, "  filter 适配器: 过滤 > 3 的元素: {:?}", v2)?;
 
     // 链式调用
     let v1: Vec<i32> = vec![1, 2, 3, 4, 5];
     let result: Vec<_> = v1.iter().map(|x| x * x).filter(|&x| x > 5).collect();
-    println!("  链式调用 (平方后过滤 > 5): {:?}", result);
+    writeln!(out, "  链式调用 (平方后过滤 > 5): {:?}", result)?;
 
     // fold 和 reduce
     let v = vec![1, 2, 3, 4, 5];
     let sum = v.iter().fold(0, |acc, x| acc + x);
-    println!("  fold 求和: {}", sum);
+    writeln!(out, "  fold 求和: {}", sum)?;
 
     let product = v.iter().fold(1, |acc, x| acc * x);
-    println!("  fold 求积: {}", product);
+    writeln!(out, "  fold 求积: {}", product)?;
 
     // find 和 any
     let v = vec![1, 2, 3, 4, 5];
     let found = v.iter().find(|&&x| x > 3);
-    println!("  find 第一个 > 3 的元素: {:?}", found);
+    writeln!(out, "  find 第一个 > 3 的元素: {:?}", found)?;
 
     let any_even = v.iter().any(|&x| x % 2 == 0);
-    println!("  any 是否有偶数: {}", any_even);
+    writeln!(out, "  any 是否有偶数: {}", any_even)?;
 
     let all_positive = v.iter().all(|&x| x > 0);
-    println!("  all 是否都为正数: {}", all_positive);
+    writeln!(out, "  all 是否都为正数: {}", all_positive)?;
 
     // enumerate
     let v = vec!["apple", "banana", "cherry"];
-    println!("  enumerate 枚举:");
+    writeln!(out, "  enumerate 枚举:")?;
     for (index, value) in v.iter().enumerate() {
-        println!("    索引 {}: {}", index, value);
+        writeln!(out, "    索引 {}: {}", index, value)?;
     }
 
     // zip
     let names = vec!["Alice", "Bob", "Charlie"];
     let scores = vec![95, 87, 92];
     let pairs: Vec<_> = names.iter().zip(scores.iter()).collect();
-    println!("  zip 配对: {:?}", pairs);
+    writeln!(out, "  zip 配对: {:?}", pairs)?;
+    Ok(())
 }
 
 /// 函数式编程模式演示
-fn functional_patterns_demo() {
+#[allow(clippy::useless_vec)]
+fn functional_patterns_demo(out: &mut dyn Write) -> io::Result<()> {
     // 函数作为参数
     let numbers = vec![1, 2, 3, 4, 5];
 
     let doubled = apply_operation(&numbers, |x| x * 2);
-    println!("  函数作为参数 - 翻倍: {:?}", doubled);
+    writeln!(out, "  函数作为参数 - 翻倍: {:?}", doubled)?;
 
     let squared = apply_operation(&numbers, |x| x * x);
-    println!("  函数作为参数 - 平方: {:?}", squared);
+    writeln!(out, "  函数作为参数 - 平方: {:?}", squared)?;
 
     // 函数作为返回值
     let add_one = create_adder(1);
     let add_five = create_adder(5);
 
-    println!("  返回函数 - add_one(10): {}", add_one(10));
-    println!("  返回函数 - add_five(10): {}", add_five(10));
+    writeln!(out, "  返回函数 - add_one(10): {}", add_one(10))?;
+    writeln!(out, "  返回函数 - add_five(10): {}", add_five(10))?;
 
     // 组合函数
     let add_two = |x| x + 2;
     let multiply_three = |x| x * 3;
 
     let composed = compose(add_two, multiply_three);
-    println!("  函数组合 - (5 + 2) * 3: {}", composed(5));
+    writeln!(out, "  函数组合 - (5 + 2) * 3: {}", composed(5))?;
 
     // 数据处理管道
     let words = vec!["hello", "world", "rust", "programming"];
@@ -213,7 +217,7 @@ fn functional_patterns_demo() {
         .map(|word| word.to_uppercase())
         .collect::<Vec<String>>()
         .join(" ");
-    println!("  数据处理管道: '{}'", result);
+    writeln!(out, "  数据处理管道: '{}'", result)?;
 
     // 分组操作
     let people = vec![
@@ -236,14 +240,16 @@ fn functional_patterns_demo() {
     ];
 
     let grouped = group_by(people, |person| person.age);
-    println!("  按年龄分组:");
+    writeln!(out, "  按年龄分组:")?;
     for (age, people) in grouped {
-        println!(
+        writeln!(
+            out,
             "    年龄 {}: {:?}",
             age,
             people.iter().map(|p| &p.name).collect::<Vec<_>>()
-        );
+        )?;
     }
+    Ok(())
 }
 
 #[derive(Debug, Clone)]
@@ -289,19 +295,20 @@ where
 }
 
 /// 高级函数式技巧演示
-fn advanced_functional_demo() {
+#[allow(clippy::useless_vec)]
+fn advanced_functional_demo(out: &mut dyn Write) -> io::Result<()> {
     // 惰性求值
     let lazy_iter = (0..1000000).map(|x| x * x).filter(|&x| x % 2 == 0).take(5);
 
-    println!("  惰性求值 - 前5个偶数平方:");
+    writeln!(out, "  惰性求值 - 前5个偶数平方:")?;
     for val in lazy_iter {
-        println!("    {}", val);
+        writeln!(out, "    {}", val)?;
     }
 
     // 无限迭代器
     let fibonacci = Fibonacci::new();
     let first_ten: Vec<_> = fibonacci.take(10).collect();
-    println!("  斐波那契数列前10项: {:?}", first_ten);
+    writeln!(out, "  斐波那契数列前10项: {:?}", first_ten)?;
 
     // 迭代器的性能优化
     let large_vec: Vec<i32> = (0..100000).collect();
@@ -309,7 +316,7 @@ fn advanced_functional_demo() {
     let start = std::time::Instant::now();
     let _sum: i64 = large_vec.iter().map(|x| (*x as i64) * 2).sum();
     let duration = start.elapsed();
-    println!("  迭代器版本耗时: {:?}", duration);
+    writeln!(out, "  迭代器版本耗时: {:?}", duration)?;
 
     let start = std::time::Instant::now();
     let mut _sum: i64 = 0;
@@ -317,23 +324,24 @@ fn advanced_functional_demo() {
         _sum += (*i as i64) * 2;
     }
     let duration = start.elapsed();
-    println!("  循环版本耗时: {:?}", duration); // 自定义迭代器
+    writeln!(out, "  循环版本耗时: {:?}", duration)?; // 自定义迭代器
     let counter = Counter::new();
     let result: Vec<_> = counter.take(5).collect();
-    println!("  自定义计数器迭代器: {:?}", result);
+    writeln!(out, "  自定义计数器迭代器: {:?}", result)?;
 
     // 函数式错误处理
     let numbers = vec!["1", "2", "not_a_number", "4"];
     let parsed: Result<Vec<i32>, _> = numbers.iter().map(|s| s.parse::<i32>()).collect();
 
     match parsed {
-        Ok(nums) => println!("  解析成功: {:?}", nums),
-        Err(e) => println!("  解析失败: {}", e),
+        Ok(nums) => writeln!(out, "  解析成功: {:?}", nums)?,
+        Err(e) => writeln!(out, "  解析失败: {}", e)?,
     }
 
     // 只保留成功的解析结果
     let successful: Vec<i32> = numbers.iter().filter_map(|s| s.parse().ok()).collect();
-    println!("  只保留成功解析: {:?}", successful);
+    writeln!(out, "  只保留成功解析: {:?}", successful)?;
+    Ok(())
 }
 
 /// 斐波那契迭代器
@@ -357,7 +365,7 @@ impl Iterator for Fibonacci {
     fn next(&mut self) -> Option<Self::Item> {
         let current = self.current;
         self.current = self.next;
-        self.next = current + self.next;
+        self.next += current;
         Some(current)
     }
 }
@@ -416,6 +424,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_vec, clippy::unnecessary_fold)]
     fn test_fold() {
         let v = vec![1, 2, 3, 4, 5];
         let sum = v.iter().fold(0, |acc, x| acc + x);
@@ -423,6 +432,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn test_find() {
         let v = vec![1, 2, 3, 4, 5];
         let found = v.iter().find(|&&x| x > 3);
@@ -474,6 +484,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn test_error_handling_with_iterators() {
         let numbers = vec!["1", "2", "3"];
         let parsed: Result<Vec<i32>, _> = numbers.iter().map(|s| s.parse::<i32>()).collect();
@@ -7,115 +7,115 @@
 //! - 泛型方法
 //! - 泛型约束
 
-fn main() {
-    println!("=== Rust 泛型学习 ===");
+use std::io::{self, Write};
 
-    // 1. 泛型函数
-    println!("\n1. 泛型函数：");
-    generic_functions_demo();
+use learn_rust::lesson::Demo;
 
-    // 2. 泛型结构体
-    println!("\n2. 泛型结构体：");
-    generic_structs_demo();
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("generic_functions", "泛型函数", generic_functions_demo),
+    ("generic_structs", "泛型结构体", generic_structs_demo),
+    ("generic_enums", "泛型枚举", generic_enums_demo),
+    ("generic_methods", "泛型方法", generic_methods_demo),
+    ("generic_constraints", "泛型约束", generic_constraints_demo),
+];
 
-    // 3. 泛型枚举
-    println!("\n3. 泛型枚举：");
-    generic_enums_demo();
+/// 本课的标题
+pub const TITLE: &str = "Rust 泛型学习";
 
-    // 4. 泛型方法
-    println!("\n4. 泛型方法：");
-    generic_methods_demo();
-
-    // 5. 泛型约束
-    println!("\n5. 泛型约束：");
-    generic_constraints_demo();
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
 }
 
-fn generic_functions_demo() {
+#[allow(clippy::approx_constant)] // 3.14 只是示例数值
+fn generic_functions_demo(out: &mut dyn Write) -> io::Result<()> {
     // 简单的泛型函数
-    println!("泛型函数演示:");
+    writeln!(out, "泛型函数演示:")?;
 
     // 查找最大值
     let numbers = vec![34, 50, 25, 100, 65];
     let max_num = largest(&numbers);
-    println!("  数字列表的最大值: {max_num}");
+    writeln!(out, "  数字列表的最大值: {max_num}")?;
 
     let chars = vec!['y', 'm', 'a', 'q'];
     let max_char = largest(&chars);
-    println!("  字符列表的最大值: {max_char}");
+    writeln!(out, "  字符列表的最大值: {max_char}")?;
 
     // 交换两个值
     let mut x = 5;
     let mut y = 10;
-    println!("  交换前: x = {x}, y = {y}");
+    writeln!(out, "  交换前: x = {x}, y = {y}")?;
     swap(&mut x, &mut y);
-    println!("  交换后: x = {x}, y = {y}");
+    writeln!(out, "  交换后: x = {x}, y = {y}")?;
 
     let mut s1 = String::from("hello");
     let mut s2 = String::from("world");
-    println!("  交换前: s1 = {s1}, s2 = {s2}");
+    writeln!(out, "  交换前: s1 = {s1}, s2 = {s2}")?;
     swap(&mut s1, &mut s2);
-    println!("  交换后: s1 = {s1}, s2 = {s2}");
+    writeln!(out, "  交换后: s1 = {s1}, s2 = {s2}")?;
 
     // 打印不同类型的值
-    print_value(42);
-    print_value("Hello, Rust!");
-    print_value(3.14);
-    print_value(vec![1, 2, 3]);
+    print_value(out, 42)?;
+    print_value(out, "Hello, Rust!")?;
+    print_value(out, 3.14)?;
+    print_value(out, vec![1, 2, 3])?;
+    Ok(())
 }
 
-fn generic_structs_demo() {
+fn generic_structs_demo(out: &mut dyn Write) -> io::Result<()> {
     // 泛型结构体
-    println!("泛型结构体演示:");
+    writeln!(out, "泛型结构体演示:")?;
 
     // Point 结构体
     let integer_point = Point { x: 5, y: 10 };
     let float_point = Point { x: 1.0, y: 4.0 };
 
-    println!("  整数点: ({}, {})", integer_point.x, integer_point.y);
-    println!("  浮点数点: ({}, {})", float_point.x, float_point.y);
+    writeln!(out, "  整数点: ({}, {})", integer_point.x, integer_point.y)?;
+    writeln!(out, "  浮点数点: ({}, {})", float_point.x, float_point.y)?;
 
     // 混合类型的 Point
     let mixed_point = PointMixed { x: 5, y: 4.0 };
-    println!("  混合类型点: ({}, {})", mixed_point.x, mixed_point.y);
+    writeln!(out, "  混合类型点: ({}, {})", mixed_point.x, mixed_point.y)?;
 
     // 容器结构体
     let int_container = Container::new(42);
     let string_container = Container::new(String::from("Hello"));
 
-    println!("  整数容器: {}", int_container.get());
-    println!("  字符串容器: {}", string_container.get());
+    writeln!(out, "  整数容器: {}", int_container.get())?;
+    writeln!(out, "  字符串容器: {}", string_container.get())?;
 
     // 多个值的容器
     let pair = Pair::new(1, 2);
-    println!("  数对: ({}, {})", pair.first(), pair.second());
+    writeln!(out, "  数对: ({}, {})", pair.first(), pair.second())?;
 
     let string_pair = Pair::new("hello".to_string(), "world".to_string());
-    println!(
+    writeln!(
+        out,
         "  字符串对: ({}, {})",
         string_pair.first(),
         string_pair.second()
-    );
+    )?;
+    Ok(())
 }
 
-fn generic_enums_demo() {
-    println!("泛型枚举演示:");
+fn generic_enums_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "泛型枚举演示:")?;
 
     // 自定义 Option 类型
     let some_int = MyOption::Some(5);
     let no_int: MyOption<i32> = MyOption::None;
 
-    println!("  MyOption<i32>:");
-    print_my_option(&some_int);
-    print_my_option(&no_int);
+    writeln!(out, "  MyOption<i32>:")?;
+    print_my_option(out, &some_int)?;
+    print_my_option(out, &no_int)?;
 
     // 自定义 Result 类型
     let success: MyResult<i32, String> = MyResult::Ok(42);
     let failure: MyResult<i32, String> = MyResult::Err("Something went wrong".to_string());
 
-    println!("  MyResult<i32, String>:");
-    print_my_result(&success);
-    print_my_result(&failure);
+    writeln!(out, "  MyResult<i32, String>:")?;
+    print_my_result(out, &success)?;
+    print_my_result(out, &failure)?;
 
     // 多种类型的容器
     let list_int = List::Cons(1, Box::new(List::Cons(2, Box::new(List::Nil))));
@@ -124,67 +124,71 @@ fn generic_enums_demo() {
         Box::new(List::Cons("world".to_string(), Box::new(List::Nil))),
     );
 
-    println!("  链表:");
-    println!("    整数链表长度: {}", list_length(&list_int));
-    println!("    字符串链表长度: {}", list_length(&list_str));
+    writeln!(out, "  链表:")?;
+    writeln!(out, "    整数链表长度: {}", list_length(&list_int))?;
+    writeln!(out, "    字符串链表长度: {}", list_length(&list_str))?;
+    Ok(())
 }
 
-fn generic_methods_demo() {
-    println!("泛型方法演示:");
+fn generic_methods_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "泛型方法演示:")?;
 
     // Point 的方法
     let p1 = Point { x: 5, y: 10 };
     let p2 = Point { x: 3, y: 4 };
 
     let distance = p1.distance_from(&p2);
-    println!("  点之间的距离: {distance:.2}");
+    writeln!(out, "  点之间的距离: {distance:.2}")?;
 
     // 混合类型点的方法
     let mixed = PointMixed { x: 5, y: 4.0 };
     let mixed2 = PointMixed { x: 3, y: 2.0 };
 
     let new_point = mixed.mixup(mixed2);
-    println!("  混合后的点: ({}, {})", new_point.x, new_point.y);
+    writeln!(out, "  混合后的点: ({}, {})", new_point.x, new_point.y)?;
 
     // 容器的泛型方法
     let container = Container::new(vec![1, 2, 3]);
     let mapped_container = container.map(|v| v.len());
-    println!("  映射后的容器: {}", mapped_container.get());
+    writeln!(out, "  映射后的容器: {}", mapped_container.get())?;
 
     // Pair 的方法
     let pair = Pair::new(String::from("hello"), 42);
     let (first, second) = pair.into_parts();
-    println!("  拆分的 Pair: {first}, {second}");
+    writeln!(out, "  拆分的 Pair: {first}, {second}")?;
+    Ok(())
 }
 
-fn generic_constraints_demo() {
-    println!("泛型约束演示:");
+#[allow(clippy::useless_vec)]
+fn generic_constraints_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "泛型约束演示:")?;
 
     // 需要 Display trait 的函数
-    display_largest(&vec![1, 2, 3, 10, 5]);
-    display_largest(&vec!["hello", "world", "rust"]);
+    display_largest(out, &vec![1, 2, 3, 10, 5])?;
+    display_largest(out, &vec!["hello", "world", "rust"])?;
 
     // 需要 Clone trait 的函数
     let numbers = vec![1, 5, 3, 9, 2];
     let cloned_max = clone_largest(&numbers);
-    println!("  克隆的最大值: {cloned_max}");
+    writeln!(out, "  克隆的最大值: {cloned_max}")?;
 
     // 需要 PartialEq trait 的函数
     let list = vec![1, 2, 3, 4, 5];
-    println!("  列表中是否包含 3: {}", contains(&list, &3));
-    println!("  列表中是否包含 10: {}", contains(&list, &10));
+    writeln!(out, "  列表中是否包含 3: {}", contains(&list, &3))?;
+    writeln!(out, "  列表中是否包含 10: {}", contains(&list, &10))?;
 
     // 多个约束
     let comparable = Comparable::new(5, 10);
-    println!("  比较结果: {:?}", comparable.compare());
+    writeln!(out, "  比较结果: {:?}", comparable.compare())?;
 
     // 条件化的方法实现
     let pair_cmp = Pair::new(1, 2);
-    println!("  最大值: {}", pair_cmp.max());
+    writeln!(out, "  最大值: {}", pair_cmp.max())?;
 
     // where 子句
     let summary = create_summary(String::from("标题"), String::from("这是内容"));
-    println!("  摘要: {}", summary.summarize());
+    writeln!(out, "  摘要: {}", summary.summarize())?;
+    Ok(())
 }
 
 // 泛型函数
@@ -204,8 +208,9 @@ fn swap<T>(a: &mut T, b: &mut T) {
     std::mem::swap(a, b);
 }
 
-fn print_value<T: std::fmt::Debug>(value: T) {
-    println!("  值: {value:?}");
+fn print_value<T: std::fmt::Debug>(out: &mut dyn Write, value: T) -> io::Result<()> {
+    writeln!(out, "  值: {value:?}")?;
+    Ok(())
 }
 
 // 泛型结构体
@@ -251,6 +256,7 @@ enum List<T> {
 
 // 结构体方法的实现
 impl<T> Point<T> {
+    #[allow(dead_code)] // 只在测试中使用
     fn new(x: T, y: T) -> Self {
         Point { x, y }
     }
@@ -325,18 +331,20 @@ impl<T: PartialOrd> Pair<T, T> {
 }
 
 // 辅助函数
-fn print_my_option<T: std::fmt::Debug>(option: &MyOption<T>) {
+fn print_my_option<T: std::fmt::Debug>(out: &mut dyn Write, option: &MyOption<T>) -> io::Result<()> {
     match option {
-        MyOption::Some(value) => println!("    Some({value:?})"),
-        MyOption::None => println!("    None"),
+        MyOption::Some(value) => writeln!(out, "    Some({value:?})")?,
+        MyOption::None => writeln!(out, "    None")?,
     }
+    Ok(())
 }
 
-fn print_my_result<T: std::fmt::Debug, E: std::fmt::Debug>(result: &MyResult<T, E>) {
+fn print_my_result<T: std::fmt::Debug, E: std::fmt::Debug>(out: &mut dyn Write, result: &MyResult<T, E>) -> io::Result<()> {
     match result {
-        MyResult::Ok(value) => println!("    Ok({value:?})"),
-        MyResult::Err(error) => println!("    Err({error:?})"),
+        MyResult::Ok(value) => writeln!(out, "    Ok({value:?})")?,
+        MyResult::Err(error) => writeln!(out, "    Err({error:?})")?,
     }
+    Ok(())
 }
 
 fn list_length<T>(list: &List<T>) -> usize {
@@ -347,9 +355,10 @@ fn list_length<T>(list: &List<T>) -> usize {
 }
 
 // 带约束的泛型函数
-fn display_largest<T: PartialOrd + std::fmt::Display>(list: &[T]) {
+fn display_largest<T: PartialOrd + std::fmt::Display>(out: &mut dyn Write, list: &[T]) -> io::Result<()> {
     let largest = largest(list);
-    println!("  最大值是: {largest}");
+    writeln!(out, "  最大值是: {largest}")?;
+    Ok(())
 }
 
 fn clone_largest<T: PartialOrd + Clone>(list: &[T]) -> T {
@@ -427,7 +436,7 @@ mod tests {
     #[test]
     fn test_point() {
         let p1 = Point::new(3, 4);
-        let p2 = Point::new(0, 0);
+        let _p2 = Point::new(0, 0);
 
         // 注意：distance_from 需要特定的约束，这里可能无法直接测试
         // 我们测试基本的点创建
@@ -0,0 +1,266 @@
+//! 课程：从 learn 中运行各个学习模块的演示代码
+//!
+//! 每个学习模块仍然是一个独立的二进制（`cargo run --bin ownership`），
+//! 同时也作为库模块编译，导出 `TITLE` 和 `SECTIONS`。这里把它们包装成 `Lesson`，
+//! 由 `learn lesson ownership` 或 `learn lesson ownership::move_semantics` 运行。
+//!
+//! 演示函数把输出写入调用方传入的 `&mut dyn Write`：独立运行时写到标准输出，
+//! 从 learn 中运行时直接在进程内调用，测试中也可以写入缓冲区检查输出。
+
+use crate::progress::{ProgressTracker, Status, Topic};
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::io::{self, Write};
+
+/// 小节的演示函数
+pub type Demo = fn(&mut dyn Write) -> io::Result<()>;
+
+/// 课程中的一个小节
+#[derive(Debug, Clone, Copy)]
+pub struct Section {
+    /// 小节标识，如 `move_semantics`
+    pub name: &'static str,
+    pub title: &'static str,
+    demo: Demo,
+}
+
+impl Section {
+    /// 运行小节的演示代码，输出写入 `out`
+    pub fn run(&self, out: &mut dyn Write) -> io::Result<()> {
+        (self.demo)(out)
+    }
+}
+
+/// 一门可以从 learn 中运行的课程
+pub trait Lesson {
+    /// 课程标识，与对应的二进制同名
+    fn name(&self) -> &'static str;
+
+    /// 课程所属的学习主题
+    fn topic(&self) -> Topic;
+
+    /// 按顺序排列的小节
+    fn sections(&self) -> Vec<Section>;
+
+    /// 运行整门课程
+    fn run(&self, out: &mut dyn Write) -> io::Result<()>;
+
+    /// 按标识查找小节
+    fn section(&self, name: &str) -> Option<Section> {
+        self.sections().into_iter().find(|section| section.name == name)
+    }
+}
+
+/// 由学习模块的 `TITLE` 和 `SECTIONS` 组成的课程
+struct ModuleLesson {
+    name: &'static str,
+    topic: Topic,
+    title: &'static str,
+    sections: &'static [(&'static str, &'static str, Demo)],
+}
+
+impl Lesson for ModuleLesson {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn topic(&self) -> Topic {
+        self.topic.clone()
+    }
+
+    fn sections(&self) -> Vec<Section> {
+        self.sections.iter()
+            .map(|&(name, title, demo)| Section { name, title, demo })
+            .collect()
+    }
+
+    fn run(&self, out: &mut dyn Write) -> io::Result<()> {
+        run_sections(self.title, self.sections, out)
+    }
+}
+
+macro_rules! lesson {
+    ($topic:ident, $($module:ident)::+) => {
+        ModuleLesson {
+            name: lesson!(@last $($module)::+),
+            topic: Topic::$topic,
+            title: crate::$($module)::+::TITLE,
+            sections: crate::$($module)::+::SECTIONS,
+        }
+    };
+    (@last $first:ident :: $($rest:ident)::+) => { lesson!(@last $($rest)::+) };
+    (@last $last:ident) => { stringify!($last) };
+}
+
+/// 所有课程，按学习顺序排列
+static LESSONS: &[ModuleLesson] = &[
+    lesson!(Variables, basics::variables),
+    lesson!(Functions, basics::functions),
+    lesson!(ControlFlow, basics::control_flow),
+    lesson!(DataTypes, basics::data_types),
+    lesson!(Ownership, ownership::ownership),
+    lesson!(References, ownership::references),
+    lesson!(Slices, ownership::slices),
+    lesson!(Structs, structs_enums::structs),
+    lesson!(Enums, structs_enums::enums),
+    lesson!(PatternMatching, structs_enums::pattern_matching),
+    lesson!(ErrorHandling, error_handling::error_handling),
+    lesson!(ResultOption, error_handling::result_option),
+    lesson!(Generics, generics_traits::generics),
+    lesson!(Collections, collections::collections),
+    lesson!(Closures, functional::functional),
+    lesson!(Threads, concurrency::concurrency),
+];
+
+/// 查找课程或小节时的错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum LessonError {
+    UnknownLesson(String),
+    UnknownSection { lesson: String, section: String },
+}
+
+impl fmt::Display for LessonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LessonError::UnknownLesson(name) => {
+                let names: Vec<&str> = LESSONS.iter().map(|lesson| lesson.name).collect();
+                write!(f, "未知的课程 '{}'，可选：{}", name, names.join(", "))
+            }
+            LessonError::UnknownSection { lesson, section } => {
+                write!(f, "课程 '{}' 中没有小节 '{}'", lesson, section)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LessonError {}
+
+/// 所有课程
+pub fn lessons() -> Vec<&'static dyn Lesson> {
+    LESSONS.iter().map(|lesson| lesson as &dyn Lesson).collect()
+}
+
+/// 按标识查找课程
+pub fn find(name: &str) -> Option<&'static dyn Lesson> {
+    lessons().into_iter().find(|lesson| lesson.name() == name)
+}
+
+/// 解析 `课程` 或 `课程::小节` 形式的路径
+pub fn resolve(path: &str) -> Result<(&'static dyn Lesson, Option<Section>), LessonError> {
+    let (name, section) = match path.split_once("::") {
+        Some((name, section)) => (name, Some(section)),
+        None => (path, None),
+    };
+    let lesson = find(name).ok_or_else(|| LessonError::UnknownLesson(name.to_string()))?;
+
+    match section {
+        None => Ok((lesson, None)),
+        Some(section) => lesson.section(section)
+            .map(|found| (lesson, Some(found)))
+            .ok_or_else(|| LessonError::UnknownSection {
+                lesson: name.to_string(),
+                section: section.to_string(),
+            }),
+    }
+}
+
+/// 开始一门课程时把对应主题标记为进行中
+///
+/// 已经完成或待复习的主题保持不变。返回状态是否发生了变化。
+pub fn mark_started(tracker: &mut ProgressTracker, lesson: &dyn Lesson, now: DateTime<Utc>) -> bool {
+    let topic = lesson.topic();
+    if tracker.get_status(&topic).is_some_and(|status| *status != Status::NotStarted) {
+        return false;
+    }
+    // 只有标记为已完成时才会检查前置主题，这里不会失败
+    tracker.update_status_at(topic, Status::InProgress, now, Some(format!("课程：{}", lesson.name()))).is_ok()
+}
+
+/// 依次运行所有小节，每个小节前写出编号和标题
+pub fn run_sections(title: &str, sections: &[(&str, &str, Demo)], out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "=== {} ===", title)?;
+    for (i, (_, section, demo)) in sections.iter().enumerate() {
+        writeln!(out, "\n{}. {}：", i + 1, section)?;
+        demo(out)?;
+    }
+    Ok(())
+}
+
+/// 学习模块作为独立二进制运行时的入口
+///
+/// 第一个命令行参数是小节名称时只运行该小节，否则运行整门课程。
+pub fn run_main(title: &str, sections: &[(&str, &str, Demo)]) {
+    let only = std::env::args().nth(1);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let result = match only.as_deref() {
+        None => run_sections(title, sections, &mut out),
+        Some(only) => match sections.iter().find(|(name, _, _)| *name == only) {
+            Some((_, _, demo)) => demo(&mut out),
+            None => {
+                let names: Vec<&str> = sections.iter().map(|(name, _, _)| *name).collect();
+                eprintln!("错误：没有小节 '{}'，可选：{}", only, names.join(", "));
+                std::process::exit(1);
+            }
+        },
+    };
+    if let Err(e) = result {
+        eprintln!("错误：{}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_lesson_is_a_binary() {
+        let manifest = include_str!("../Cargo.toml");
+        for lesson in lessons() {
+            assert!(
+                manifest.contains(&format!("name = \"{}\"", lesson.name())),
+                "课程 {} 没有对应的二进制", lesson.name()
+            );
+            assert!(!lesson.sections().is_empty(), "{}", lesson.name());
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let (lesson, section) = resolve("ownership::move_semantics").unwrap();
+        assert_eq!(lesson.topic(), Topic::Ownership);
+        assert_eq!(section.unwrap().title, "移动语义");
+
+        assert!(resolve("enums").unwrap().1.is_none());
+        assert_eq!(resolve("nope").err(), Some(LessonError::UnknownLesson("nope".to_string())));
+        assert!(matches!(resolve("ownership::nope"), Err(LessonError::UnknownSection { .. })));
+    }
+
+    #[test]
+    fn test_run_in_process() {
+        let lesson = find("variables").unwrap();
+        let mut out = Vec::new();
+        lesson.run(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("=== Rust 变量和可变性学习 ===\n\n1. 不可变变量：\nx = 5\n"));
+
+        let mut out = Vec::new();
+        lesson.section("constants").unwrap().run(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "三小时的秒数: 10800\n");
+    }
+
+    #[test]
+    fn test_mark_started() {
+        let mut tracker = ProgressTracker::new();
+        let now = Utc::now();
+        let lesson = find("slices").unwrap();
+        assert!(mark_started(&mut tracker, lesson, now));
+        assert_eq!(tracker.get_status(&Topic::Slices), Some(&Status::InProgress));
+        assert!(!mark_started(&mut tracker, lesson, now));
+
+        tracker.force_update_status(Topic::Enums, Status::Completed);
+        assert!(!mark_started(&mut tracker, find("enums").unwrap(), now));
+        assert_eq!(tracker.get_status(&Topic::Enums), Some(&Status::Completed));
+    }
+}
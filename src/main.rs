@@ -1,33 +1,321 @@
 //! Rust 学习项目主入口
-//! 
+//!
 //! 这是一个用于学习 Rust 编程语言的项目。
 //! 包含了从基础语法到高级特性的各种示例代码。
+//!
+//! 同时也是 `learn` 命令行工具，用来管理保存在磁盘上的学习进度：
+//!
+//! ```text
+//! learn status                 查看进度
+//! learn start ownership        开始学习某个主题
+//! learn done ownership         完成某个主题
+//! learn review                 查看今天的复习队列
+//! learn review ownership good  记录一次复习结果
+//! learn report --format md     生成报告
+//! learn next                   推荐接下来学习的主题
+//! learn lessons                列出所有课程和小节
+//! learn lesson ownership       运行一门课程（不带参数时显示菜单）
+//! learn lesson ownership::move_semantics  只运行一个小节
+//! learn completions bash       生成 shell 补全脚本
+//! ```
+
+use chrono::Utc;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use learn_rust::lesson;
+use learn_rust::progress::{
+    PrerequisiteError, ProgressTracker, ReportFormat, ReviewOutcome, Status, Topic, STAGES, TOPICS,
+};
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process;
+
+/// Rust 学习进度管理工具
+#[derive(Parser)]
+#[command(name = "learn", version, about)]
+struct Cli {
+    /// 进度文件路径
+    #[arg(short, long, global = true, default_value = "progress.json")]
+    file: PathBuf,
+
+    /// 不使用表情符号（适用于无法显示 emoji 的终端）
+    #[arg(long, global = true)]
+    no_emoji: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 查看学习进度
+    Status,
+    /// 开始学习一个主题
+    Start {
+        #[arg(value_parser = parse_topic)]
+        topic: Topic,
+        /// 备注
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+    /// 把一个主题标记为已完成
+    Done {
+        #[arg(value_parser = parse_topic)]
+        topic: Topic,
+        /// 备注
+        #[arg(short, long)]
+        note: Option<String>,
+        /// 即使前置主题没有完成也标记
+        #[arg(long)]
+        force: bool,
+    },
+    /// 查看复习队列，或者记录一次复习结果
+    Review {
+        #[arg(value_parser = parse_topic, requires = "outcome")]
+        topic: Option<Topic>,
+        /// 复习结果：again, hard, good, easy
+        outcome: Option<ReviewOutcome>,
+    },
+    /// 生成进度报告
+    Report {
+        /// 报告格式：terminal, md, html, json
+        #[arg(long, default_value = "terminal")]
+        format: ReportFormat,
+        /// 输出文件，默认输出到终端
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// 推荐接下来学习的主题
+    Next,
+    /// 列出所有课程和小节
+    Lessons,
+    /// 运行课程，并把对应主题标记为进行中
+    Lesson {
+        /// 课程或小节，如 ownership 或 ownership::move_semantics；省略时显示菜单
+        path: Option<String>,
+    },
+    /// 生成 shell 补全脚本
+    Completions {
+        shell: Shell,
+    },
+}
+
+fn parse_topic(id: &str) -> Result<Topic, String> {
+    Topic::from_id(id).ok_or_else(|| {
+        let ids: Vec<&str> = TOPICS.iter().map(|info| info.id).collect();
+        format!("未知的主题 '{}'，可选：{}", id, ids.join(", "))
+    })
+}
 
 fn main() {
+    let cli = Cli::parse();
+
+    let Some(command) = cli.command else {
+        show_welcome();
+        return;
+    };
+
+    if let Err(error) = run(command, &cli.file, !cli.no_emoji) {
+        eprintln!("错误：{}", error);
+        process::exit(1);
+    }
+}
+
+fn run(command: Command, file: &PathBuf, emoji: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if let Command::Completions { shell } = command {
+        clap_complete::generate(shell, &mut Cli::command(), "learn", &mut io::stdout());
+        return Ok(());
+    }
+
+    let mut tracker = ProgressTracker::load_or_default(file)?;
+    let now = Utc::now();
+    let due = tracker.refresh_reviews(now);
+
+    match command {
+        Command::Status => {
+            tracker.render_report(ReportFormat::Terminal.renderer(emoji).as_ref(), &mut io::stdout())?;
+            if !due.is_empty() {
+                println!("有 {} 个主题到了复习时间，运行 'learn review' 查看", due.len());
+            }
+        }
+        Command::Start { topic, note } => {
+            let name = topic.info().name_zh;
+            tracker.update_status_at(topic.clone(), Status::InProgress, now, note)?;
+            match topic.info().binary {
+                Some(binary) => println!("开始学习：{}（cargo run --bin {}）", name, binary),
+                None => println!("开始学习：{}（这个主题的学习代码还在编写中）", name),
+            }
+        }
+        Command::Done { topic, note, force } => {
+            let name = topic.info().name_zh;
+            if force {
+                tracker.force_update_status_at(topic, Status::Completed, now, note);
+            } else {
+                tracker.update_status_at(topic, Status::Completed, now, note).map_err(|error| match error {
+                    PrerequisiteError::MissingPrerequisites { missing, .. } => {
+                        let names: Vec<&str> = missing.iter().map(|t| t.info().id).collect();
+                        format!("{} 的前置主题尚未完成：{}（使用 --force 强制标记）", name, names.join(", "))
+                    }
+                    other => other.to_string(),
+                })?;
+            }
+            println!("已完成：{}", name);
+        }
+        Command::Review { topic: Some(topic), outcome: Some(outcome) } => {
+            let card = tracker.record_review(&topic, outcome, now);
+            println!("已记录 {} 的复习结果：{}，下次复习：{}（间隔 {} 天）",
+                     topic.info().name_zh,
+                     outcome,
+                     card.due.format("%Y-%m-%d"),
+                     card.interval_days);
+        }
+        Command::Review { .. } => {
+            let queue = tracker.review_queue(now);
+            if queue.is_empty() {
+                println!("今天没有需要复习的主题");
+            }
+            for topic in queue {
+                let info = topic.info();
+                println!("   {} {} ({})", Status::NeedsReview.icon(emoji), info.name_zh, info.id);
+            }
+        }
+        Command::Report { format, output } => {
+            let renderer = format.renderer(emoji);
+            match output {
+                Some(path) => {
+                    let mut file = File::create(&path)?;
+                    tracker.render_report(renderer.as_ref(), &mut file)?;
+                    file.flush()?;
+                    println!("报告已写入 {}", path.display());
+                }
+                None => tracker.render_report(renderer.as_ref(), &mut io::stdout())?,
+            }
+        }
+        Command::Next => {
+            let next = tracker.next_recommended();
+            if next.is_empty() {
+                println!("所有主题都已完成 🎉");
+            }
+            for topic in next.iter().take(5) {
+                let info = topic.info();
+                println!("   {} {} ({}) - 预计 {} 小时",
+                         tracker.get_status(topic).unwrap_or(&Status::NotStarted).icon(emoji),
+                         info.name_zh,
+                         info.id,
+                         info.estimated_hours);
+            }
+        }
+        Command::Lessons => {
+            for lesson in lesson::lessons() {
+                let status = tracker.get_status(&lesson.topic()).unwrap_or(&Status::NotStarted);
+                println!("{} {}", status.icon(emoji), lesson.name());
+                for section in lesson.sections() {
+                    println!("     {}::{}  {}", lesson.name(), section.name, section.title);
+                }
+            }
+        }
+        Command::Lesson { path } => {
+            let path = match path {
+                Some(path) => path,
+                None => match choose_lesson()? {
+                    Some(path) => path,
+                    None => return Ok(()),
+                },
+            };
+            let (lesson, section) = lesson::resolve(&path)?;
+            if lesson::mark_started(&mut tracker, lesson, now) {
+                println!("开始学习：{}", lesson.topic().info().name_zh);
+            }
+            // 先保存进度，课程输出较长时中途退出也不会丢失
+            tracker.save(file)?;
+
+            let mut stdout = io::stdout();
+            match section {
+                Some(section) => {
+                    println!("=== {} ===", section.title);
+                    section.run(&mut stdout)?;
+                }
+                None => lesson.run(&mut stdout)?,
+            }
+        }
+        Command::Completions { .. } => unreachable!("已在前面处理"),
+    }
+
+    tracker.save(file)?;
+    Ok(())
+}
+
+/// 显示课程菜单并读取选择，输入为空时返回 `None`
+///
+/// 可以输入序号、课程名或 `课程::小节`。
+fn choose_lesson() -> io::Result<Option<String>> {
+    let lessons = lesson::lessons();
+    println!("📚 选择要运行的课程：");
+    for (i, lesson) in lessons.iter().enumerate() {
+        println!("   {:>2}. {} - {}", i + 1, lesson.name(), lesson.topic().info().name_zh);
+    }
+    print!("输入序号或名称（直接回车退出）：");
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    let choice = line.trim();
+    if choice.is_empty() {
+        return Ok(None);
+    }
+    let path = match choice.parse::<usize>() {
+        Ok(n) if (1..=lessons.len()).contains(&n) => lessons[n - 1].name().to_string(),
+        _ => choice.to_string(),
+    };
+    Ok(Some(path))
+}
+
+fn show_welcome() {
     println!("🦀 欢迎来到 Rust 学习之旅！");
     println!();
     println!("📚 可用的学习模块：");
-    println!("   基础语法: cargo run --bin variables, functions, control_flow, data_types");
-    println!("   所有权系统: cargo run --bin ownership, references, slices");
-    println!("   结构体枚举: cargo run --bin structs, enums, pattern_matching");
-    println!("   错误处理: cargo run --bin error_handling, result_option");
-    println!("   泛型特征: cargo run --bin generics, traits, lifetimes");
-    println!("   集合类型: cargo run --bin collections, vectors, strings, hashmaps");
-    println!("   函数式编程: cargo run --bin closures, iterators");
-    println!("   并发编程: cargo run --bin threads, channels, shared_state");
-    println!("   项目练习: cargo run --bin calculator, todo_app, file_search");
+    for stage in STAGES {
+        let binaries: Vec<&str> = stage.topics().iter().filter_map(|topic| topic.info().binary).collect();
+        println!("   {}: cargo run --bin {}", stage.name_zh, binaries.join(", "));
+    }
     println!();
-    println!("💡 提示：使用 'cargo run --bin <模块名>' 来运行特定的学习模块");
+    println!("💡 提示：使用 'learn lesson <模块名>' 或 'cargo run --bin <模块名>' 来运行特定的学习模块");
     println!("📖 查看学习计划：cat rust_learning_plan.md");
-    println!("📊 跟踪学习进度：编辑 learning_progress.md");
+    println!("📊 跟踪学习进度：learn status / learn start <主题> / learn done <主题>");
     println!();
     println!("🚀 开始你的 Rust 学习之旅吧！");
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        let cli = Cli::try_parse_from(["learn", "done", "ownership", "--force"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Done { topic: Topic::Ownership, force: true, .. })
+        ));
+
+        let cli = Cli::try_parse_from(["learn", "report", "--format", "md"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Report { format: ReportFormat::Markdown, .. })));
+
+        assert!(Cli::try_parse_from(["learn", "start", "no_such_topic"]).is_err());
+        assert!(Cli::try_parse_from(["learn", "review", "enums"]).is_err());
+
+        let cli = Cli::try_parse_from(["learn", "lesson", "ownership::move_semantics"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Lesson { path: Some(ref p) }) if p == "ownership::move_semantics"));
+    }
+}
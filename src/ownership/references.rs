@@ -6,71 +6,69 @@
 //! - 借用规则
 //! - 悬垂引用的避免
 
-fn main() {
-    println!("=== Rust 引用和借用学习 ===");
-    
-    // 1. 基本引用
-    println!("\n1. 基本引用：");
-    basic_references_demo();
-    
-    // 2. 可变引用
-    println!("\n2. 可变引用：");
-    mutable_references_demo();
-    
-    // 3. 借用规则
-    println!("\n3. 借用规则：");
-    borrowing_rules_demo();
-    
-    // 4. 引用的作用域
-    println!("\n4. 引用的作用域：");
-    reference_scope_demo();
-    
-    // 5. 函数参数中的引用
-    println!("\n5. 函数参数中的引用：");
-    function_parameters_demo();
+use std::io::{self, Write};
+
+use learn_rust::lesson::Demo;
+
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("basic_references", "基本引用", basic_references_demo),
+    ("mutable_references", "可变引用", mutable_references_demo),
+    ("borrowing_rules", "借用规则", borrowing_rules_demo),
+    ("reference_scope", "引用的作用域", reference_scope_demo),
+    ("function_parameters", "函数参数中的引用", function_parameters_demo),
+];
+
+/// 本课的标题
+pub const TITLE: &str = "Rust 引用和借用学习";
+
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
 }
 
-fn basic_references_demo() {
+fn basic_references_demo(out: &mut dyn Write) -> io::Result<()> {
     let s1 = String::from("hello");
     
     // 创建引用，不会获取所有权
     let len = calculate_length(&s1);
     
-    println!("字符串 '{}' 的长度是 {}。", s1, len);
+    writeln!(out, "字符串 '{}' 的长度是 {}。", s1, len)?;
     // s1 在这里仍然有效，因为我们没有移动它
     
     // 多个不可变引用
     let r1 = &s1;
     let r2 = &s1;
-    println!("r1: {}, r2: {}", r1, r2);
+    writeln!(out, "r1: {}, r2: {}", r1, r2)?;
     
     // 引用的引用
     let s = String::from("world");
     let r = &s;        // r 是 String 的引用
     let rr = &r;       // rr 是引用的引用
-    println!("s: {}, r: {}, rr: {}", s, r, rr);
+    writeln!(out, "s: {}, r: {}, rr: {}", s, r, rr)?;
+    Ok(())
 }
 
-fn mutable_references_demo() {
+fn mutable_references_demo(out: &mut dyn Write) -> io::Result<()> {
     let mut s = String::from("hello");
     
     // 创建可变引用
     change(&mut s);
     
-    println!("修改后的字符串: {}", s);
+    writeln!(out, "修改后的字符串: {}", s)?;
     
     // 一次只能有一个可变引用
     let r1 = &mut s;
     r1.push_str(", world");
-    println!("通过可变引用修改: {}", r1);
+    writeln!(out, "通过可变引用修改: {}", r1)?;
     
     // r1 在这里不再使用，所以可以创建新的引用
     let r2 = &s;
-    println!("创建新的不可变引用: {}", r2);
+    writeln!(out, "创建新的不可变引用: {}", r2)?;
+    Ok(())
 }
 
-fn borrowing_rules_demo() {
-    println!("借用规则演示：");
+fn borrowing_rules_demo(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "借用规则演示：")?;
     
     let mut s = String::from("hello");
     
@@ -78,92 +76,98 @@ fn borrowing_rules_demo() {
     {
         let r1 = &s;     // 没问题
         let r2 = &s;     // 没问题
-        println!("r1: {}, r2: {}", r1, r2);
+        writeln!(out, "r1: {}, r2: {}", r1, r2)?;
         // r1 和 r2 在这里不再使用
     }
     
     {
         let r3 = &mut s; // 没问题
         r3.push_str(", world");
-        println!("r3: {}", r3);
+        writeln!(out, "r3: {}", r3)?;
         // r3 在这里不再使用
     }
     
     // 规则2：引用必须总是有效的
-    println!("最终的字符串: {}", s);
+    writeln!(out, "最终的字符串: {}", s)?;
     
     // 演示借用检查器的工作
-    demonstrate_borrow_checker();
+    demonstrate_borrow_checker(out)?;
+    Ok(())
 }
 
-fn demonstrate_borrow_checker() {
+fn demonstrate_borrow_checker(out: &mut dyn Write) -> io::Result<()> {
     let mut data = vec![1, 2, 3, 4, 5];
     
     // 同时拥有不可变引用
     let first = &data[0];
     let second = &data[1];
     
-    println!("第一个元素: {}, 第二个元素: {}", first, second);
+    writeln!(out, "第一个元素: {}, 第二个元素: {}", first, second)?;
     
     // 在不可变引用使用完毕后，可以创建可变引用
     data.push(6);
-    println!("添加元素后: {:?}", data);
+    writeln!(out, "添加元素后: {:?}", data)?;
     
     // 演示作用域规则
     let r1 = &data;
     let r2 = &data;
-    println!("r1: {:?}, r2: {:?}", r1, r2);
+    writeln!(out, "r1: {:?}, r2: {:?}", r1, r2)?;
     // r1 和 r2 的生命周期在这里结束
     
     let r3 = &mut data;
     r3.push(7);
-    println!("r3: {:?}", r3);
+    writeln!(out, "r3: {:?}", r3)?;
+    Ok(())
 }
 
-fn reference_scope_demo() {
+#[allow(clippy::useless_vec)]
+fn reference_scope_demo(out: &mut dyn Write) -> io::Result<()> {
     let mut s = String::from("hello");
     
     let r1 = &s;          // 没问题
     let r2 = &s;          // 没问题
-    println!("{} and {}", r1, r2);
+    writeln!(out, "{} and {}", r1, r2)?;
     // 此位置之后 r1 和 r2 不再使用
     
     let r3 = &mut s;      // 没问题
-    println!("{}", r3);
+    writeln!(out, "{}", r3)?;
     
     // 非词法作用域生命周期 (NLL) 的示例
     let data = vec![1, 2, 3];
     let first = &data[0];           // 开始借用
-    println!("第一个元素: {}", first); // 最后使用借用
+    writeln!(out, "第一个元素: {}", first)?; // 最后使用借用
     // 借用在这里结束，即使作用域还没结束
     
     // 现在可以获取可变引用了（在新版本 Rust 中）
     // let mut data = data; // 如果需要修改
+    Ok(())
 }
 
-fn function_parameters_demo() {
+fn function_parameters_demo(out: &mut dyn Write) -> io::Result<()> {
     let s = String::from("hello world");
     
     // 传递不可变引用
     let word = first_word(&s);
-    println!("第一个单词: {}", word);
+    writeln!(out, "第一个单词: {}", word)?;
     
     let mut s2 = String::from("hello world");
     
     // 传递可变引用
     make_uppercase(&mut s2);
-    println!("转换为大写: {}", s2);
+    writeln!(out, "转换为大写: {}", s2)?;
     
     // 返回引用
     let longest = longest_string("hello", "world");
-    println!("最长的字符串: {}", longest);
+    writeln!(out, "最长的字符串: {}", longest)?;
     
     // 多个引用参数
     let result = compare_strings(&s, &s2);
-    println!("字符串比较结果: {:?}", result);
+    writeln!(out, "字符串比较结果: {:?}", result)?;
+    Ok(())
 }
 
 // 计算字符串长度，不获取所有权
+#[allow(clippy::ptr_arg)] // 演示对 String 的引用
 fn calculate_length(s: &String) -> usize {
     s.len()
 } // s 在这里超出作用域，但因为它不拥有引用值，所以什么也不会发生
@@ -206,10 +210,12 @@ fn compare_strings(s1: &String, s2: &String) -> std::cmp::Ordering {
 }
 
 // 演示引用作为结构体字段（需要生命周期）
+#[allow(dead_code)] // 只在测试中使用
 struct StringHolder<'a> {
     content: &'a str,
 }
 
+#[allow(dead_code)] // 只在测试中使用
 impl<'a> StringHolder<'a> {
     fn new(s: &'a str) -> StringHolder<'a> {
         StringHolder { content: s }
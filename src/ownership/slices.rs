@@ -6,31 +6,27 @@
 //! - 切片的语法和使用
 //! - 切片作为函数参数
 
-fn main() {
-    println!("=== Rust 切片学习 ===");
-    
-    // 1. 字符串切片
-    println!("\n1. 字符串切片：");
-    string_slices_demo();
-    
-    // 2. 数组切片
-    println!("\n2. 数组切片：");
-    array_slices_demo();
-    
-    // 3. 切片的范围语法
-    println!("\n3. 切片的范围语法：");
-    slice_range_syntax_demo();
-    
-    // 4. 切片作为函数参数
-    println!("\n4. 切片作为函数参数：");
-    slice_parameters_demo();
-    
-    // 5. 可变切片
-    println!("\n5. 可变切片：");
-    mutable_slices_demo();
+use std::io::{self, Write};
+
+use learn_rust::lesson::Demo;
+
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("string_slices", "字符串切片", string_slices_demo),
+    ("array_slices", "数组切片", array_slices_demo),
+    ("range_syntax", "切片的范围语法", slice_range_syntax_demo),
+    ("slice_parameters", "切片作为函数参数", slice_parameters_demo),
+    ("mutable_slices", "可变切片", mutable_slices_demo),
+];
+
+/// 本课的标题
+pub const TITLE: &str = "Rust 切片学习";
+
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
 }
 
-fn string_slices_demo() {
+fn string_slices_demo(out: &mut dyn Write) -> io::Result<()> {
     let s = String::from("hello world");
     
     // 字符串切片
@@ -38,30 +34,31 @@ fn string_slices_demo() {
     let world = &s[6..11];   // 或者 &s[6..]
     let whole = &s[..];      // 整个字符串
     
-    println!("原字符串: {s}");
-    println!("hello: {hello}");
-    println!("world: {world}");
-    println!("整个字符串: {whole}");
+    writeln!(out, "原字符串: {s}")?;
+    writeln!(out, "hello: {hello}")?;
+    writeln!(out, "world: {world}")?;
+    writeln!(out, "整个字符串: {whole}")?;
     
     // 使用 first_word 函数
     let word = first_word(&s);
-    println!("第一个单词: {word}");
+    writeln!(out, "第一个单词: {word}")?;
     
     // 字符串字面量就是切片
     let s_literal = "Hello, world!";
-    println!("字符串字面量: {s_literal}");
-    println!("字面量类型: &str");
+    writeln!(out, "字符串字面量: {s_literal}")?;
+    writeln!(out, "字面量类型: &str")?;
     
     // 中文字符串切片（注意字节边界）
     let chinese = String::from("你好世界");
     let hello_cn = &chinese[0..6];   // "你好" 占6个字节
     let world_cn = &chinese[6..12];  // "世界" 占6个字节
-    println!("中文字符串: {chinese}");
-    println!("你好: {hello_cn}");
-    println!("世界: {world_cn}");
+    writeln!(out, "中文字符串: {chinese}")?;
+    writeln!(out, "你好: {hello_cn}")?;
+    writeln!(out, "世界: {world_cn}")?;
+    Ok(())
 }
 
-fn array_slices_demo() {
+fn array_slices_demo(out: &mut dyn Write) -> io::Result<()> {
     let a = [1, 2, 3, 4, 5];
     
     // 数组切片
@@ -70,115 +67,120 @@ fn array_slices_demo() {
     let last_two = &a[3..];     // [4, 5]
     let all = &a[..];           // [1, 2, 3, 4, 5]
     
-    println!("原数组: {a:?}");
-    println!("切片 [1..4]: {slice:?}");
-    println!("前三个 [..3]: {first_three:?}");
-    println!("后两个 [3..]: {last_two:?}");
-    println!("全部 [..]: {all:?}");
+    writeln!(out, "原数组: {a:?}")?;
+    writeln!(out, "切片 [1..4]: {slice:?}")?;
+    writeln!(out, "前三个 [..3]: {first_three:?}")?;
+    writeln!(out, "后两个 [3..]: {last_two:?}")?;
+    writeln!(out, "全部 [..]: {all:?}")?;
     
     // 不同类型的数组
     let numbers: [i32; 6] = [10, 20, 30, 40, 50, 60];
     let middle = &numbers[2..4];
-    println!("数字数组: {numbers:?}");
-    println!("中间部分: {middle:?}");
+    writeln!(out, "数字数组: {numbers:?}")?;
+    writeln!(out, "中间部分: {middle:?}")?;
     
     // 字符数组
     let chars = ['a', 'b', 'c', 'd', 'e'];
     let char_slice = &chars[1..4];
-    println!("字符数组: {chars:?}");
-    println!("字符切片: {char_slice:?}");
+    writeln!(out, "字符数组: {chars:?}")?;
+    writeln!(out, "字符切片: {char_slice:?}")?;
+    Ok(())
 }
 
-fn slice_range_syntax_demo() {
+fn slice_range_syntax_demo(out: &mut dyn Write) -> io::Result<()> {
     let data = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
     
-    println!("原数据: {data:?}");
+    writeln!(out, "原数据: {data:?}")?;
     
     // 不同的范围语法
-    println!("不同的范围语法：");
-    println!("  [2..5]: {:?}", &data[2..5]);     // [2, 3, 4]
-    println!("  [..3]: {:?}", &data[..3]);       // [0, 1, 2]
-    println!("  [7..]: {:?}", &data[7..]);       // [7, 8, 9]
-    println!("  [..]: {:?}", &data[..]);         // 全部
+    writeln!(out, "不同的范围语法：")?;
+    writeln!(out, "  [2..5]: {:?}", &data[2..5])?;     // [2, 3, 4]
+    writeln!(out, "  [..3]: {:?}", &data[..3])?;       // [0, 1, 2]
+    writeln!(out, "  [7..]: {:?}", &data[7..])?;       // [7, 8, 9]
+    writeln!(out, "  [..]: {:?}", &data[..])?;         // 全部
     
     // 包含结束值的范围
-    println!("  [2..=5]: {:?}", &data[2..=5]);   // [2, 3, 4, 5]
+    writeln!(out, "  [2..=5]: {:?}", &data[2..=5])?;   // [2, 3, 4, 5]
     
     // 使用变量作为索引
     let start = 3;
     let end = 7;
     let variable_slice = &data[start..end];
-    println!("  [{start}..{end}]: {variable_slice:?}");
+    writeln!(out, "  [{start}..{end}]: {variable_slice:?}")?;
     
     // 字符串的范围
     let text = "Hello, Rust!";
-    println!("\n字符串范围：");
-    println!("  原文本: {text}");
-    println!("  [0..5]: {}", &text[0..5]);      // "Hello"
-    println!("  [7..]: {}", &text[7..]);        // "Rust!"
-    println!("  [..5]: {}", &text[..5]);        // "Hello"
+    writeln!(out, "\n字符串范围：")?;
+    writeln!(out, "  原文本: {text}")?;
+    writeln!(out, "  [0..5]: {}", &text[0..5])?;      // "Hello"
+    writeln!(out, "  [7..]: {}", &text[7..])?;        // "Rust!"
+    writeln!(out, "  [..5]: {}", &text[..5])?;        // "Hello"
+    Ok(())
 }
 
-fn slice_parameters_demo() {
+fn slice_parameters_demo(out: &mut dyn Write) -> io::Result<()> {
     let s = String::from("hello world programming");
     
     // 使用字符串切片作为参数的函数
     let word_count = count_words(&s);
-    println!("单词数量: {word_count}");
+    writeln!(out, "单词数量: {word_count}")?;
     
     let contains_rust = contains_word(&s, "programming");
-    println!("包含 'programming': {contains_rust}");
+    writeln!(out, "包含 'programming': {contains_rust}")?;
     
     // 数组切片作为参数
     let numbers = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
     let sum = sum_slice(&numbers);
-    println!("数组总和: {sum}");
+    writeln!(out, "数组总和: {sum}")?;
     
     let sum_part = sum_slice(&numbers[2..7]);
-    println!("部分总和 [2..7]: {sum_part}");
+    writeln!(out, "部分总和 [2..7]: {sum_part}")?;
     
     // 查找最大值
     let max = find_max(&numbers);
-    println!("最大值: {max:?}");
+    writeln!(out, "最大值: {max:?}")?;
     
     // 空切片
     let empty: &[i32] = &[];
     let empty_max = find_max(empty);
-    println!("空切片的最大值: {empty_max:?}");
+    writeln!(out, "空切片的最大值: {empty_max:?}")?;
+    Ok(())
 }
 
-fn mutable_slices_demo() {
+fn mutable_slices_demo(out: &mut dyn Write) -> io::Result<()> {
     let mut arr = [1, 2, 3, 4, 5];
-    println!("原数组: {arr:?}");
+    writeln!(out, "原数组: {arr:?}")?;
     
     // 可变切片
     let slice = &mut arr[1..4];
     slice[0] = 10;  // 修改原数组的第二个元素
     slice[1] = 20;  // 修改原数组的第三个元素
     
-    println!("修改后的数组: {arr:?}");
+    writeln!(out, "修改后的数组: {arr:?}")?;
     
     // 使用可变切片的函数
     let mut data = [5, 2, 8, 1, 9, 3];
-    println!("排序前: {data:?}");
+    writeln!(out, "排序前: {data:?}")?;
     
     sort_slice(&mut data);
-    println!("排序后: {data:?}");
+    writeln!(out, "排序后: {data:?}")?;
     
     // 部分排序
     let mut mixed = [9, 2, 7, 4, 1, 8, 5];
-    println!("部分排序前: {mixed:?}");
+    writeln!(out, "部分排序前: {mixed:?}")?;
     sort_slice(&mut mixed[1..5]);  // 只排序中间部分
-    println!("部分排序后: {mixed:?}");
+    writeln!(out, "部分排序后: {mixed:?}")?;
     
     // 交换元素
     let mut values = [10, 20, 30, 40, 50];
-    println!("交换前: {values:?}");
+    writeln!(out, "交换前: {values:?}")?;
     swap_elements(&mut values, 1, 3);
-    println!("交换后: {values:?}");
+    writeln!(out, "交换后: {values:?}")?;
+    Ok(())
 }
 
 // 查找第一个单词
+#[allow(clippy::redundant_slicing)] // 演示整个字符串的切片
 fn first_word(s: &str) -> &str {
     let bytes = s.as_bytes();
     
@@ -228,6 +230,7 @@ fn swap_elements(slice: &mut [i32], i: usize, j: usize) {
 }
 
 // 获取字符串的安全子串
+#[allow(dead_code)] // 只在测试中使用
 fn safe_substring(s: &str, start: usize, len: usize) -> Option<&str> {
     let end = start + len;
     if end <= s.len() {
@@ -238,11 +241,13 @@ fn safe_substring(s: &str, start: usize, len: usize) -> Option<&str> {
 }
 
 // 反转切片
+#[allow(dead_code)] // 只在测试中使用
 fn reverse_slice(slice: &mut [i32]) {
     slice.reverse();
 }
 
 // 查找子切片
+#[allow(dead_code)] // 只在测试中使用
 fn find_subslice(haystack: &[i32], needle: &[i32]) -> Option<usize> {
     haystack.windows(needle.len())
         .position(|window| window == needle)
@@ -0,0 +1,273 @@
+//! 主题之间的前置依赖关系
+
+use super::{ProgressTracker, Status, Topic};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// 前置依赖相关的错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrerequisiteError {
+    /// 前置主题尚未完成
+    MissingPrerequisites { topic: Topic, missing: Vec<Topic> },
+    /// 依赖关系中出现环，按依赖方向列出环上的主题
+    Cycle(Vec<Topic>),
+}
+
+impl fmt::Display for PrerequisiteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PrerequisiteError::MissingPrerequisites { topic, missing } => {
+                write!(f, "{:?} 的前置主题尚未完成: {:?}", topic, missing)
+            }
+            PrerequisiteError::Cycle(cycle) => {
+                let path: Vec<String> = cycle.iter().map(|topic| format!("{:?}", topic)).collect();
+                write!(f, "前置依赖存在环: {}", path.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for PrerequisiteError {}
+
+/// 前置依赖图（有向无环图）
+///
+/// 边 `topic -> prerequisite` 表示学习 `topic` 之前需要先完成 `prerequisite`。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PrerequisiteGraph {
+    edges: BTreeMap<Topic, BTreeSet<Topic>>,
+}
+
+impl PrerequisiteGraph {
+    /// 创建没有任何依赖的空图
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 内置的学习路线依赖图
+    pub fn standard() -> Self {
+        use Topic::*;
+
+        let edges: &[(Topic, &[Topic])] = &[
+            (Functions, &[Variables]),
+            (ControlFlow, &[Variables]),
+            (DataTypes, &[Variables]),
+            (Ownership, &[Functions, DataTypes]),
+            (References, &[Ownership]),
+            (Slices, &[References]),
+            (Structs, &[Ownership]),
+            (Enums, &[Structs]),
+            (PatternMatching, &[Enums, ControlFlow]),
+            (ErrorHandling, &[PatternMatching]),
+            (ResultOption, &[ErrorHandling]),
+            (Generics, &[Structs, Enums]),
+            (Traits, &[Generics]),
+            (Lifetimes, &[Generics, References]),
+            (Collections, &[Generics]),
+            (Vectors, &[Collections]),
+            (Strings, &[Collections, Slices]),
+            (HashMaps, &[Collections]),
+            (Closures, &[Functions, Traits]),
+            (Iterators, &[Closures, Vectors]),
+            (Threads, &[Closures]),
+            (Channels, &[Threads]),
+            (SharedState, &[Threads, Traits]),
+            (Calculator, &[ControlFlow, DataTypes]),
+            (TodoApp, &[Structs, Vectors, ResultOption]),
+            (FileSearch, &[Strings, Iterators, ErrorHandling]),
+        ];
+
+        let mut graph = Self::new();
+        for (topic, prerequisites) in edges {
+            for prerequisite in prerequisites.iter() {
+                graph.add_prerequisite(topic.clone(), prerequisite.clone())
+                    .expect("内置依赖图不应有环");
+            }
+        }
+        graph
+    }
+
+    /// 添加一条依赖边，若会形成环则拒绝
+    pub fn add_prerequisite(&mut self, topic: Topic, prerequisite: Topic) -> Result<(), PrerequisiteError> {
+        if topic == prerequisite {
+            return Err(PrerequisiteError::Cycle(vec![topic.clone(), topic]));
+        }
+        // prerequisite 已经（间接）依赖 topic 时，再加这条边就会成环
+        if let Some(mut path) = self.path(&prerequisite, &topic) {
+            path.insert(0, topic);
+            return Err(PrerequisiteError::Cycle(path));
+        }
+        self.edges.entry(topic).or_default().insert(prerequisite);
+        Ok(())
+    }
+
+    /// 主题的直接前置主题
+    pub fn prerequisites(&self, topic: &Topic) -> Vec<Topic> {
+        self.edges.get(topic)
+            .map(|set| set.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 查找图中的任意一个环
+    ///
+    /// 通过 `add_prerequisite` 构建的图不会有环；这个方法用于校验其他来源的数据。
+    pub fn find_cycle(&self) -> Option<Vec<Topic>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            Visiting,
+            Done,
+        }
+
+        fn visit(
+            graph: &PrerequisiteGraph,
+            topic: &Topic,
+            marks: &mut BTreeMap<Topic, Mark>,
+            stack: &mut Vec<Topic>,
+        ) -> Option<Vec<Topic>> {
+            match marks.get(topic) {
+                Some(Mark::Done) => return None,
+                Some(Mark::Visiting) => {
+                    let start = stack.iter().position(|t| t == topic).unwrap_or(0);
+                    let mut cycle = stack[start..].to_vec();
+                    cycle.push(topic.clone());
+                    return Some(cycle);
+                }
+                None => {}
+            }
+
+            marks.insert(topic.clone(), Mark::Visiting);
+            stack.push(topic.clone());
+            for prerequisite in graph.prerequisites(topic) {
+                if let Some(cycle) = visit(graph, &prerequisite, marks, stack) {
+                    return Some(cycle);
+                }
+            }
+            stack.pop();
+            marks.insert(topic.clone(), Mark::Done);
+            None
+        }
+
+        let mut marks = BTreeMap::new();
+        for topic in self.edges.keys() {
+            if let Some(cycle) = visit(self, topic, &mut marks, &mut Vec::new()) {
+                return Some(cycle);
+            }
+        }
+        None
+    }
+
+    /// 沿依赖方向从 `from` 到 `to` 的路径（包含两端）
+    fn path(&self, from: &Topic, to: &Topic) -> Option<Vec<Topic>> {
+        if from == to {
+            return Some(vec![from.clone()]);
+        }
+        for next in self.edges.get(from)? {
+            if let Some(mut rest) = self.path(next, to) {
+                rest.insert(0, from.clone());
+                return Some(rest);
+            }
+        }
+        None
+    }
+}
+
+impl ProgressTracker {
+    /// 当前使用的前置依赖图
+    pub fn prerequisites(&self) -> &PrerequisiteGraph {
+        &self.prerequisites
+    }
+
+    /// 替换前置依赖图
+    pub fn set_prerequisites(&mut self, graph: PrerequisiteGraph) -> Result<(), PrerequisiteError> {
+        if let Some(cycle) = graph.find_cycle() {
+            return Err(PrerequisiteError::Cycle(cycle));
+        }
+        self.prerequisites = graph;
+        Ok(())
+    }
+
+    /// 主题尚未完成的前置主题
+    ///
+    /// 处于待复习状态的前置主题视为已经学过。
+    pub fn missing_prerequisites(&self, topic: &Topic) -> Vec<Topic> {
+        self.prerequisites.prerequisites(topic)
+            .into_iter()
+            .filter(|prerequisite| !matches!(
+                self.get_status(prerequisite),
+                Some(Status::Completed) | Some(Status::NeedsReview)
+            ))
+            .collect()
+    }
+
+    /// 推荐接下来学习的主题
+    ///
+    /// 返回所有未完成且前置主题都已完成的主题，按阶段顺序排列。
+    pub fn next_recommended(&self) -> Vec<Topic> {
+        let mut topics: Vec<Topic> = self.progress.iter()
+            .filter(|(_, status)| matches!(status, Status::NotStarted | Status::InProgress))
+            .map(|(topic, _)| topic.clone())
+            .filter(|topic| self.missing_prerequisites(topic).is_empty())
+            .collect();
+        topics.sort_by_key(|topic| (topic.stage_index(), topic.clone()));
+        topics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_graph_is_acyclic() {
+        let graph = PrerequisiteGraph::standard();
+        assert_eq!(graph.find_cycle(), None);
+        assert_eq!(graph.prerequisites(&Topic::References), vec![Topic::Ownership]);
+        assert!(graph.prerequisites(&Topic::Lifetimes).contains(&Topic::Generics));
+    }
+
+    #[test]
+    fn test_cycle_is_rejected() {
+        let mut graph = PrerequisiteGraph::new();
+        graph.add_prerequisite(Topic::Slices, Topic::References).unwrap();
+        graph.add_prerequisite(Topic::References, Topic::Ownership).unwrap();
+
+        let err = graph.add_prerequisite(Topic::Ownership, Topic::Slices).unwrap_err();
+        assert_eq!(
+            err,
+            PrerequisiteError::Cycle(vec![
+                Topic::Ownership, Topic::Slices, Topic::References, Topic::Ownership
+            ])
+        );
+        assert!(graph.add_prerequisite(Topic::Enums, Topic::Enums).is_err());
+    }
+
+    #[test]
+    fn test_completion_requires_prerequisites() {
+        let mut tracker = ProgressTracker::new();
+        let err = tracker.update_status(Topic::References, Status::Completed).unwrap_err();
+        assert_eq!(
+            err,
+            PrerequisiteError::MissingPrerequisites {
+                topic: Topic::References,
+                missing: vec![Topic::Ownership],
+            }
+        );
+        assert_eq!(tracker.get_status(&Topic::References), Some(&Status::NotStarted));
+
+        // 开始学习不受限制
+        assert!(tracker.update_status(Topic::References, Status::InProgress).is_ok());
+        tracker.force_update_status(Topic::Ownership, Status::Completed);
+        assert!(tracker.update_status(Topic::References, Status::Completed).is_ok());
+    }
+
+    #[test]
+    fn test_next_recommended() {
+        let mut tracker = ProgressTracker::new();
+        assert_eq!(tracker.next_recommended(), vec![Topic::Variables]);
+
+        tracker.update_status(Topic::Variables, Status::Completed).unwrap();
+        assert_eq!(
+            tracker.next_recommended(),
+            vec![Topic::Functions, Topic::ControlFlow, Topic::DataTypes]
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! 状态变更历史与学习时间统计
+
+use super::{ProgressTracker, Status, Topic, STAGES};
+use chrono::{DateTime, Datelike, Duration, IsoWeek, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// 一次状态变更记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusChange {
+    pub topic: Topic,
+    pub from: Status,
+    pub to: Status,
+    pub at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl ProgressTracker {
+    /// 全部状态变更记录
+    pub fn history(&self) -> &[StatusChange] {
+        &self.history
+    }
+
+    /// 某个主题的状态变更记录，按时间排序
+    pub fn topic_history(&self, topic: &Topic) -> Vec<&StatusChange> {
+        let mut changes: Vec<&StatusChange> = self.history.iter()
+            .filter(|change| change.topic == *topic)
+            .collect();
+        changes.sort_by_key(|change| change.at);
+        changes
+    }
+
+    /// 主题处于某个状态的累计时长
+    ///
+    /// 如果主题当前仍处于该状态，则计算到 `now` 为止。
+    pub fn time_in_status(&self, topic: &Topic, status: &Status, now: DateTime<Utc>) -> Duration {
+        let changes = self.topic_history(topic);
+        let mut total = Duration::zero();
+
+        for (i, change) in changes.iter().enumerate() {
+            if change.to != *status {
+                continue;
+            }
+            let end = changes.get(i + 1).map_or(now, |next| next.at);
+            if end > change.at {
+                total += end - change.at;
+            }
+        }
+        total
+    }
+
+    /// 每个主题处于进行中状态的累计时长（只包含时长大于零的主题）
+    pub fn time_in_progress_by_topic(&self, now: DateTime<Utc>) -> BTreeMap<Topic, Duration> {
+        let topics: HashSet<&Topic> = self.history.iter().map(|change| &change.topic).collect();
+        topics.into_iter()
+            .map(|topic| (topic.clone(), self.time_in_status(topic, &Status::InProgress, now)))
+            .filter(|(_, duration)| *duration > Duration::zero())
+            .collect()
+    }
+
+    /// 每个 ISO 周首次完成的主题数量
+    ///
+    /// 复习后再次完成的主题不会重复计数。
+    pub fn completed_per_week(&self) -> BTreeMap<IsoWeek, usize> {
+        let mut first_completion: BTreeMap<&Topic, DateTime<Utc>> = BTreeMap::new();
+        for change in self.history.iter().filter(|change| change.to == Status::Completed) {
+            first_completion.entry(&change.topic)
+                .and_modify(|at| *at = (*at).min(change.at))
+                .or_insert(change.at);
+        }
+
+        let mut per_week = BTreeMap::new();
+        for at in first_completion.values() {
+            *per_week.entry(at.iso_week()).or_insert(0) += 1;
+        }
+        per_week
+    }
+
+    /// 主题从第一次开始学习到第一次完成所用的时间
+    pub fn completion_time(&self, topic: &Topic) -> Option<Duration> {
+        let changes = self.topic_history(topic);
+        let started = changes.iter().find(|change| change.to == Status::InProgress)?.at;
+        let completed = changes.iter()
+            .find(|change| change.to == Status::Completed && change.at >= started)?
+            .at;
+        Some(completed - started)
+    }
+
+    /// 一组主题的平均完成时间，没有已完成的主题时返回 `None`
+    pub fn average_completion_time(&self, topics: &[Topic]) -> Option<Duration> {
+        let times: Vec<Duration> = topics.iter()
+            .filter_map(|topic| self.completion_time(topic))
+            .collect();
+        if times.is_empty() {
+            return None;
+        }
+        let total: Duration = times.iter().copied().sum();
+        Some(total / times.len() as i32)
+    }
+
+    /// 按阶段统计的平均完成时间
+    pub fn average_completion_time_by_stage(&self) -> Vec<(&'static str, Option<Duration>)> {
+        STAGES.iter()
+            .map(|stage| (stage.name_zh, self.average_completion_time(&stage.topics())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, d, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_transitions_are_recorded() {
+        let mut tracker = ProgressTracker::new();
+        tracker.force_update_status_at(Topic::Variables, Status::InProgress, day(2), None);
+        tracker.force_update_status_at(Topic::Variables, Status::InProgress, day(3), None);
+        tracker.force_update_status_at(Topic::Variables, Status::Completed, day(4), Some("很简单".to_string()));
+
+        let changes = tracker.topic_history(&Topic::Variables);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].from, Status::NotStarted);
+        assert_eq!(changes[1].from, Status::InProgress);
+        assert_eq!(changes[1].note.as_deref(), Some("很简单"));
+    }
+
+    #[test]
+    fn test_time_in_status() {
+        let mut tracker = ProgressTracker::new();
+        tracker.force_update_status_at(Topic::Ownership, Status::InProgress, day(2), None);
+        tracker.force_update_status_at(Topic::Ownership, Status::NeedsReview, day(4), None);
+        tracker.force_update_status_at(Topic::Ownership, Status::InProgress, day(10), None);
+
+        let now = day(11);
+        assert_eq!(tracker.time_in_status(&Topic::Ownership, &Status::InProgress, now), Duration::days(3));
+        assert_eq!(tracker.time_in_status(&Topic::Ownership, &Status::NeedsReview, now), Duration::days(6));
+
+        let by_topic = tracker.time_in_progress_by_topic(now);
+        assert_eq!(by_topic.len(), 1);
+        assert_eq!(by_topic[&Topic::Ownership], Duration::days(3));
+    }
+
+    #[test]
+    fn test_completed_per_week() {
+        let mut tracker = ProgressTracker::new();
+        // 2026-03-02 是周一，2026-03-09 是下一周的周一
+        tracker.force_update_status_at(Topic::Variables, Status::Completed, day(2), None);
+        tracker.force_update_status_at(Topic::Functions, Status::Completed, day(5), None);
+        tracker.force_update_status_at(Topic::ControlFlow, Status::Completed, day(9), None);
+        tracker.force_update_status_at(Topic::Variables, Status::NeedsReview, day(10), None);
+        tracker.force_update_status_at(Topic::Variables, Status::Completed, day(11), None);
+
+        let per_week: Vec<usize> = tracker.completed_per_week().into_values().collect();
+        assert_eq!(per_week, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_average_completion_time_by_stage() {
+        let mut tracker = ProgressTracker::new();
+        tracker.force_update_status_at(Topic::Variables, Status::InProgress, day(1), None);
+        tracker.force_update_status_at(Topic::Variables, Status::Completed, day(2), None);
+        tracker.force_update_status_at(Topic::Functions, Status::InProgress, day(2), None);
+        tracker.force_update_status_at(Topic::Functions, Status::Completed, day(5), None);
+
+        let stages = tracker.average_completion_time_by_stage();
+        assert_eq!(stages[0], ("基础语法", Some(Duration::days(2))));
+        assert_eq!(stages[1], ("所有权系统", None));
+    }
+}
@@ -0,0 +1,379 @@
+//! 与 `learning_progress.md` 中的复选框同步
+//!
+//! 导入时，勾选的条目会把对应主题标记为已完成；写回时只改动映射到主题的
+//! 复选框和空白的“完成日期”，其余内容（包括用户写的笔记）原样保留。
+
+use super::{ProgressTracker, Status, Topic};
+use crate::utils::fs::write_atomic;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 复选框条目（或整个小节）到主题的映射
+///
+/// 条目为 `None` 时表示整个小节对应一个主题：全部勾选才算完成。
+const TOPIC_RULES: &[(&str, Option<&str>, Topic)] = &[
+    ("基础语法", Some("变量与可变性"), Topic::Variables),
+    ("基础语法", Some("数据类型"), Topic::DataTypes),
+    ("基础语法", Some("函数"), Topic::Functions),
+    ("基础语法", Some("控制流"), Topic::ControlFlow),
+    ("所有权系统初步", None, Topic::Ownership),
+    ("实践项目：简单计算器", None, Topic::Calculator),
+    ("所有权系统深入", None, Topic::References),
+    ("复合数据类型", Some("结构体"), Topic::Structs),
+    ("复合数据类型", Some("枚举"), Topic::Enums),
+    ("复合数据类型", Some("模式匹配"), Topic::PatternMatching),
+    ("复合数据类型", Some("Option 和 Result"), Topic::ResultOption),
+    ("集合类型", None, Topic::Collections),
+    ("集合类型", Some("Vector"), Topic::Vectors),
+    ("集合类型", Some("String 和字符串切片"), Topic::Strings),
+    ("集合类型", Some("HashMap"), Topic::HashMaps),
+    ("实践项目：待办事项管理器", None, Topic::TodoApp),
+    ("错误处理", None, Topic::ErrorHandling),
+    ("泛型与特征", Some("泛型函数和结构体"), Topic::Generics),
+    ("泛型与特征", Some("特征定义与实现"), Topic::Traits),
+    ("泛型与特征", Some("生命周期参数"), Topic::Lifetimes),
+    ("函数式编程特性", Some("闭包"), Topic::Closures),
+    ("函数式编程特性", Some("迭代器"), Topic::Iterators),
+    ("实践项目：文件搜索工具", None, Topic::FileSearch),
+    ("并发编程", Some("线程创建与管理"), Topic::Threads),
+    ("并发编程", Some("消息传递"), Topic::Channels),
+    ("并发编程", Some("共享状态并发"), Topic::SharedState),
+];
+
+/// 复选框条目
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub checked: bool,
+    /// 所在行号（从 0 开始）
+    pub line: usize,
+}
+
+/// 以 `###` 标题开头的一个小节
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecklistSection {
+    pub title: String,
+    pub items: Vec<ChecklistItem>,
+    /// “完成日期”一行中填写的日期
+    pub completed_on: Option<NaiveDate>,
+    /// “完成日期”所在行号
+    pub date_line: Option<usize>,
+}
+
+impl ChecklistSection {
+    fn item(&self, text: &str) -> Option<&ChecklistItem> {
+        self.items.iter().find(|item| item.text == text)
+    }
+}
+
+/// 解析 Markdown 中所有包含复选框的小节
+pub fn parse_checklist(markdown: &str) -> Vec<ChecklistSection> {
+    let mut sections = Vec::new();
+    let mut current: Option<ChecklistSection> = None;
+
+    for (line_no, line) in markdown.lines().enumerate() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with('#') || trimmed == "---" {
+            if let Some(section) = current.take() {
+                sections.push(section);
+            }
+            if let Some(title) = trimmed.strip_prefix("### ") {
+                current = Some(ChecklistSection {
+                    title: title.trim().to_string(),
+                    items: Vec::new(),
+                    completed_on: None,
+                    date_line: None,
+                });
+            }
+            continue;
+        }
+
+        let Some(section) = current.as_mut() else {
+            continue;
+        };
+        if let Some((checked, text)) = parse_checkbox(trimmed) {
+            section.items.push(ChecklistItem {
+                text: text.to_string(),
+                checked,
+                line: line_no,
+            });
+        } else if let Some(value) = date_value(trimmed) {
+            section.completed_on = parse_date(value);
+            section.date_line = Some(line_no);
+        }
+    }
+    if let Some(section) = current {
+        sections.push(section);
+    }
+
+    sections.retain(|section| !section.items.is_empty());
+    sections
+}
+
+/// 解析 `- [ ] 文本` / `- [x] 文本`
+fn parse_checkbox(line: &str) -> Option<(bool, &str)> {
+    let rest = line.strip_prefix("- [")?;
+    let mut chars = rest.chars();
+    let mark = chars.next()?;
+    let text = chars.as_str().strip_prefix("] ")?;
+    match mark {
+        ' ' => Some((false, text.trim())),
+        'x' | 'X' => Some((true, text.trim())),
+        _ => None,
+    }
+}
+
+/// 取出 `**完成日期：** 2026-03-01` 中冒号后面的部分
+fn date_value(line: &str) -> Option<&str> {
+    let rest = &line[line.find("完成日期")? + "完成日期".len()..];
+    let rest = rest.trim_start_matches(['：', ':', '*', ' ']);
+    Some(rest.trim())
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y/%m/%d"))
+        .ok()
+}
+
+/// 主题是否已经学过（已完成或待复习）
+fn is_learned(status: Option<&Status>) -> bool {
+    matches!(status, Some(Status::Completed) | Some(Status::NeedsReview))
+}
+
+impl ProgressTracker {
+    /// 从 Markdown 复选框导入进度，返回状态发生变化的主题
+    ///
+    /// - 勾选的条目：主题标记为已完成，时间取小节的完成日期（没有则为当前时间）
+    /// - 未勾选的条目：已学过的主题退回未开始，其他状态保持不变
+    /// - 整个小节对应的主题：部分勾选时标记为进行中
+    pub fn import_markdown(&mut self, markdown: &str) -> Vec<Topic> {
+        let sections = parse_checklist(markdown);
+        let mut changed = Vec::new();
+
+        for (section_title, item_text, topic) in TOPIC_RULES {
+            let Some(section) = sections.iter().find(|s| s.title == *section_title) else {
+                continue;
+            };
+            let (checked, total) = match item_text {
+                Some(text) => match section.item(text) {
+                    Some(item) => (usize::from(item.checked), 1),
+                    None => continue,
+                },
+                None => (
+                    section.items.iter().filter(|item| item.checked).count(),
+                    section.items.len(),
+                ),
+            };
+
+            let current = self.get_status(topic).cloned();
+            let new_status = if checked == total {
+                (!is_learned(current.as_ref())).then_some(Status::Completed)
+            } else if checked > 0 {
+                (current != Some(Status::InProgress)).then_some(Status::InProgress)
+            } else {
+                is_learned(current.as_ref()).then_some(Status::NotStarted)
+            };
+
+            if let Some(status) = new_status {
+                let at = section.completed_on
+                    .filter(|_| status == Status::Completed)
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .map(|dt| dt.and_utc())
+                    .unwrap_or_else(Utc::now);
+                let note = Some("从 learning_progress.md 导入".to_string());
+                // 清单文件记录的是实际学习情况，不再检查前置主题
+                self.force_update_status_at(topic.clone(), status, at, note);
+                changed.push(topic.clone());
+            }
+        }
+        changed
+    }
+
+    /// 按当前进度改写 Markdown，返回新的文本
+    ///
+    /// 只修改映射到主题的复选框，以及全部完成但日期仍为空白的“完成日期”。
+    pub fn export_markdown(&self, markdown: &str) -> String {
+        let sections = parse_checklist(markdown);
+        let mut lines: Vec<String> = markdown.lines().map(str::to_string).collect();
+
+        for section in &sections {
+            let rules: Vec<_> = TOPIC_RULES.iter()
+                .filter(|(title, _, _)| *title == section.title)
+                .collect();
+            if rules.is_empty() {
+                continue;
+            }
+
+            let mut checks: Vec<bool> = section.items.iter().map(|item| item.checked).collect();
+            for (_, item_text, topic) in &rules {
+                let learned = is_learned(self.get_status(topic));
+                match item_text {
+                    Some(text) => {
+                        if let Some(i) = section.items.iter().position(|item| item.text == *text) {
+                            checks[i] = learned;
+                        }
+                    }
+                    None if learned => checks.iter_mut().for_each(|c| *c = true),
+                    None if section.items.iter().all(|item| item.checked) => {
+                        checks.iter_mut().for_each(|c| *c = false)
+                    }
+                    None => {}
+                }
+            }
+
+            for (item, checked) in section.items.iter().zip(&checks) {
+                if item.checked != *checked {
+                    lines[item.line] = set_checkbox(&lines[item.line], *checked);
+                }
+            }
+
+            if let (Some(line), None) = (section.date_line, section.completed_on) {
+                if checks.iter().all(|c| *c) {
+                    if let Some(at) = self.latest_completion(rules.iter().map(|(_, _, t)| t)) {
+                        lines[line] = fill_date(&lines[line], at);
+                    }
+                }
+            }
+        }
+
+        let mut output = lines.join("\n");
+        if markdown.ends_with('\n') {
+            output.push('\n');
+        }
+        output
+    }
+
+    /// 从 Markdown 文件导入进度
+    pub fn import_markdown_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<Vec<Topic>> {
+        let markdown = fs::read_to_string(path)?;
+        Ok(self.import_markdown(&markdown))
+    }
+
+    /// 按当前进度改写 Markdown 文件
+    ///
+    /// 通过 `write_atomic` 写入，中途崩溃也不会截断文件里的笔记。
+    pub fn export_markdown_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let markdown = fs::read_to_string(path)?;
+        write_atomic(path, &self.export_markdown(&markdown))
+    }
+
+    /// 一组主题中最近一次标记为已完成的时间
+    fn latest_completion<'a>(&self, topics: impl Iterator<Item = &'a Topic>) -> Option<DateTime<Utc>> {
+        let topics: Vec<&Topic> = topics.collect();
+        self.history()
+            .iter()
+            .filter(|change| change.to == Status::Completed && topics.contains(&&change.topic))
+            .map(|change| change.at)
+            .max()
+    }
+}
+
+fn set_checkbox(line: &str, checked: bool) -> String {
+    let (from, to) = if checked { ("- [ ]", "- [x]") } else { ("- [x]", "- [ ]") };
+    line.replacen(from, to, 1).replacen("- [X]", to, 1)
+}
+
+/// 把“完成日期”后面的空白（`___`）替换为日期
+fn fill_date(line: &str, at: DateTime<Utc>) -> String {
+    let date = at.format("%Y-%m-%d").to_string();
+    match line.find('_') {
+        Some(start) => {
+            let end = line[start..].find(|c| c != '_').map_or(line.len(), |i| start + i);
+            format!("{}{}{}", &line[..start], date, &line[end..])
+        }
+        None => format!("{} {}", line.trim_end(), date),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const LEARNING_PROGRESS: &str = include_str!("../../learning_progress.md");
+
+    #[test]
+    fn test_rules_match_learning_progress() {
+        let sections = parse_checklist(LEARNING_PROGRESS);
+        for (title, item, topic) in TOPIC_RULES {
+            let section = sections.iter().find(|s| s.title == *title);
+            assert!(section.is_some(), "{:?}: 找不到小节 {}", topic, title);
+            if let Some(text) = item {
+                assert!(section.unwrap().item(text).is_some(), "{:?}: 找不到条目 {}", topic, text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_import_markdown() {
+        let markdown = "\
+### 基础语法
+- [x] 变量与可变性
+- [ ] 函数
+
+**完成日期：** 2026-03-05
+
+### 所有权系统初步
+- [x] 所有权概念
+- [ ] 移动语义
+";
+        let mut tracker = ProgressTracker::new();
+        tracker.force_update_status(Topic::Functions, Status::Completed);
+
+        let changed = tracker.import_markdown(markdown);
+        assert_eq!(changed, vec![Topic::Variables, Topic::Functions, Topic::Ownership]);
+        assert_eq!(tracker.get_status(&Topic::Variables), Some(&Status::Completed));
+        assert_eq!(tracker.get_status(&Topic::Functions), Some(&Status::NotStarted));
+        assert_eq!(tracker.get_status(&Topic::Ownership), Some(&Status::InProgress));
+
+        let change = &tracker.topic_history(&Topic::Variables)[0];
+        assert_eq!(change.at.format("%Y-%m-%d").to_string(), "2026-03-05");
+    }
+
+    #[test]
+    fn test_export_preserves_notes() {
+        let markdown = "\
+### 函数式编程特性
+- [ ] 闭包
+- [ ] 迭代器
+- [x] 函数式编程模式
+
+**完成日期：** ___________
+
+我的笔记：闭包捕获环境要注意 move
+";
+        let day = |d| Utc.with_ymd_and_hms(2026, 3, d, 9, 0, 0).unwrap();
+        let mut tracker = ProgressTracker::new();
+        tracker.force_update_status_at(Topic::Closures, Status::Completed, day(8), None);
+        tracker.force_update_status_at(Topic::Iterators, Status::Completed, day(12), None);
+        tracker.force_update_status_at(Topic::Iterators, Status::NeedsReview, day(20), None);
+
+        let exported = tracker.export_markdown(markdown);
+        assert!(exported.contains("- [x] 闭包\n- [x] 迭代器\n- [x] 函数式编程模式"));
+        assert!(exported.contains("**完成日期：** 2026-03-12"));
+        assert!(exported.ends_with("我的笔记：闭包捕获环境要注意 move\n"));
+
+        // 再导入不应产生变化
+        let mut reimported = ProgressTracker::new();
+        reimported.force_update_status(Topic::Closures, Status::Completed);
+        reimported.force_update_status(Topic::Iterators, Status::NeedsReview);
+        assert!(reimported.import_markdown(&exported).is_empty());
+    }
+
+    #[test]
+    fn test_export_markdown_file() {
+        let path = std::env::temp_dir().join(format!("learn_rust_checklist_{}.md", std::process::id()));
+        fs::write(&path, "### 基础语法\n- [ ] 变量与可变性\n\n笔记\n").unwrap();
+        let mut tracker = ProgressTracker::new();
+        tracker.force_update_status(Topic::Variables, Status::Completed);
+
+        tracker.export_markdown_file(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "### 基础语法\n- [x] 变量与可变性\n\n笔记\n");
+        fs::remove_file(&path).unwrap();
+    }
+}
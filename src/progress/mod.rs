@@ -0,0 +1,208 @@
+//! 学习进度跟踪工具
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+mod graph;
+mod history;
+mod markdown;
+pub mod registry;
+mod report;
+mod review;
+mod storage;
+mod team;
+
+pub use graph::{PrerequisiteError, PrerequisiteGraph};
+pub use history::StatusChange;
+pub use markdown::{parse_checklist, ChecklistItem, ChecklistSection};
+pub use registry::{StageInfo, TopicInfo, STAGES, TOPICS};
+pub use report::{HtmlReport, JsonReport, MarkdownReport, ReportFormat, ReportRenderer, TerminalReport};
+pub use review::{ReviewCard, ReviewOutcome};
+pub use team::{LeaderboardEntry, StuckTopic, TeamStore, TopicCompletion};
+
+/// 学习主题
+///
+/// 变体的声明顺序就是学习顺序，`Ord` 按此顺序比较。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Topic {
+    Variables,
+    Functions,
+    ControlFlow,
+    DataTypes,
+    Ownership,
+    References,
+    Slices,
+    Structs,
+    Enums,
+    PatternMatching,
+    ErrorHandling,
+    ResultOption,
+    Generics,
+    Traits,
+    Lifetimes,
+    Collections,
+    Vectors,
+    Strings,
+    HashMaps,
+    Closures,
+    Iterators,
+    Threads,
+    Channels,
+    SharedState,
+    Calculator,
+    TodoApp,
+    FileSearch,
+}
+
+/// 学习状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Status {
+    NotStarted,
+    InProgress,
+    Completed,
+    NeedsReview,
+}
+
+/// 学习进度跟踪器
+#[derive(Debug)]
+pub struct ProgressTracker {
+    progress: HashMap<Topic, Status>,
+    /// 所有状态变更记录，按时间先后追加
+    history: Vec<StatusChange>,
+    /// 主题之间的前置依赖
+    prerequisites: PrerequisiteGraph,
+    /// 已完成主题的复习卡片
+    reviews: BTreeMap<Topic, ReviewCard>,
+    /// 从文件中读到、但当前版本不认识的主题，保存时原样写回
+    unknown_topics: BTreeMap<String, serde_json::Value>,
+    unknown_reviews: BTreeMap<String, ReviewCard>,
+    /// 当前版本无法解析的历史记录（例如涉及不认识的主题），保存时写在历史末尾
+    unknown_history: Vec<serde_json::Value>,
+}
+
+impl ProgressTracker {
+    /// 创建新的进度跟踪器
+    pub fn new() -> Self {
+        let mut tracker = Self {
+            progress: HashMap::new(),
+            history: Vec::new(),
+            prerequisites: PrerequisiteGraph::standard(),
+            reviews: BTreeMap::new(),
+            unknown_topics: BTreeMap::new(),
+            unknown_reviews: BTreeMap::new(),
+            unknown_history: Vec::new(),
+        };
+        
+        // 初始化所有主题为未开始状态
+        for info in TOPICS {
+            tracker.progress.insert(info.topic.clone(), Status::NotStarted);
+        }
+        
+        tracker
+    }
+    
+    /// 更新主题状态
+    ///
+    /// 前置主题未完成时不允许标记为已完成，见 [`ProgressTracker::update_status_at`]。
+    pub fn update_status(&mut self, topic: Topic, status: Status) -> Result<(), PrerequisiteError> {
+        self.update_status_at(topic, status, Utc::now(), None)
+    }
+    
+    /// 更新主题状态并附上备注
+    pub fn update_status_with_note(&mut self, topic: Topic, status: Status, note: &str) -> Result<(), PrerequisiteError> {
+        self.update_status_at(topic, status, Utc::now(), Some(note.to_string()))
+    }
+    
+    /// 以指定时间更新主题状态
+    ///
+    /// 标记为已完成时检查前置主题，有未完成的前置主题时返回
+    /// `PrerequisiteError::MissingPrerequisites`，状态保持不变。
+    pub fn update_status_at(
+        &mut self,
+        topic: Topic,
+        status: Status,
+        at: DateTime<Utc>,
+        note: Option<String>,
+    ) -> Result<(), PrerequisiteError> {
+        if status == Status::Completed {
+            let missing = self.missing_prerequisites(&topic);
+            if !missing.is_empty() {
+                return Err(PrerequisiteError::MissingPrerequisites { topic, missing });
+            }
+        }
+        self.force_update_status_at(topic, status, at, note);
+        Ok(())
+    }
+    
+    /// 不检查前置主题，直接更新主题状态
+    pub fn force_update_status(&mut self, topic: Topic, status: Status) {
+        self.force_update_status_at(topic, status, Utc::now(), None);
+    }
+    
+    /// 不检查前置主题，以指定时间更新主题状态
+    ///
+    /// 用于导入已有的学习记录和用户明确要求的强制标记。状态确实发生变化时才会
+    /// 写入历史记录；第一次完成时会安排复习。
+    pub fn force_update_status_at(
+        &mut self,
+        topic: Topic,
+        status: Status,
+        at: DateTime<Utc>,
+        note: Option<String>,
+    ) {
+        let from = self.progress.get(&topic).cloned().unwrap_or(Status::NotStarted);
+        if from != status {
+            self.history.push(StatusChange {
+                topic: topic.clone(),
+                from,
+                to: status.clone(),
+                at,
+                note,
+            });
+        }
+        if status == Status::Completed {
+            self.schedule_first_review(&topic, at);
+        }
+        self.progress.insert(topic, status);
+    }
+    
+    /// 获取主题状态
+    pub fn get_status(&self, topic: &Topic) -> Option<&Status> {
+        self.progress.get(topic)
+    }
+    
+    /// 获取完成的主题数量
+    pub fn completed_count(&self) -> usize {
+        self.progress.values()
+            .filter(|&status| *status == Status::Completed)
+            .count()
+    }
+    
+    /// 获取总主题数量
+    pub fn total_count(&self) -> usize {
+        self.progress.len()
+    }
+    
+    /// 计算完成百分比
+    pub fn completion_percentage(&self) -> f64 {
+        if self.total_count() == 0 {
+            0.0
+        } else {
+            (self.completed_count() as f64 / self.total_count() as f64) * 100.0
+        }
+    }
+    
+    /// 显示进度报告
+    pub fn show_progress(&self) {
+        let stdout = std::io::stdout();
+        // 写入标准输出失败时没有更合适的地方报告错误，忽略即可
+        let _ = TerminalReport { emoji: true }.render(self, &mut stdout.lock());
+    }
+}
+
+impl Default for ProgressTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,213 @@
+//! 主题注册表
+//!
+//! 所有学习主题的元数据集中在这里：所属阶段、中英文名称、对应的二进制程序、
+//! 源文件路径和预计学习时长。进度跟踪、报告和主程序的课程列表都从这里读取，
+//! 新增主题时只需要在 `Topic` 中加一个变体并在 `TOPICS` 中登记。
+
+use super::Topic;
+
+/// 学习阶段
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StageInfo {
+    pub id: &'static str,
+    pub name_zh: &'static str,
+    pub name_en: &'static str,
+}
+
+/// 主题的元数据
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicInfo {
+    pub topic: Topic,
+    /// 命令行等场景使用的标识符
+    pub id: &'static str,
+    /// 所属阶段的 `StageInfo::id`
+    pub stage: &'static str,
+    pub name_zh: &'static str,
+    pub name_en: &'static str,
+    /// `Cargo.toml` 中的 `[[bin]]` 名称，还没有编写的主题为 `None`
+    pub binary: Option<&'static str>,
+    /// 源文件路径（相对于项目根目录）
+    pub source: &'static str,
+    pub estimated_hours: u32,
+}
+
+/// 所有学习阶段，按学习顺序排列
+pub const STAGES: &[StageInfo] = &[
+    StageInfo { id: "basics", name_zh: "基础语法", name_en: "Basics" },
+    StageInfo { id: "ownership", name_zh: "所有权系统", name_en: "Ownership" },
+    StageInfo { id: "structs_enums", name_zh: "结构体和枚举", name_en: "Structs and Enums" },
+    StageInfo { id: "error_handling", name_zh: "错误处理", name_en: "Error Handling" },
+    StageInfo { id: "generics_traits", name_zh: "泛型和特征", name_en: "Generics and Traits" },
+    StageInfo { id: "collections", name_zh: "集合类型", name_en: "Collections" },
+    StageInfo { id: "functional", name_zh: "函数式编程", name_en: "Functional Programming" },
+    StageInfo { id: "concurrency", name_zh: "并发编程", name_en: "Concurrency" },
+    StageInfo { id: "projects", name_zh: "实践项目", name_en: "Projects" },
+];
+
+macro_rules! topic {
+    ($topic:ident, $id:literal, $stage:literal, $zh:literal, $en:literal, $source:literal, $hours:literal) => {
+        topic!(@info $topic, $id, $stage, $zh, $en, Some($id), $source, $hours)
+    };
+    // 已经规划但还没有编写源文件的主题
+    ($topic:ident, $id:literal, $stage:literal, $zh:literal, $en:literal, $source:literal, $hours:literal, planned) => {
+        topic!(@info $topic, $id, $stage, $zh, $en, None, $source, $hours)
+    };
+    (@info $topic:ident, $id:literal, $stage:literal, $zh:literal, $en:literal, $binary:expr, $source:literal, $hours:literal) => {
+        TopicInfo {
+            topic: Topic::$topic,
+            id: $id,
+            stage: $stage,
+            name_zh: $zh,
+            name_en: $en,
+            binary: $binary,
+            source: $source,
+            estimated_hours: $hours,
+        }
+    };
+}
+
+/// 所有学习主题，按学习顺序排列
+pub const TOPICS: &[TopicInfo] = &[
+    topic!(Variables, "variables", "basics", "变量与可变性", "Variables", "src/basics/variables.rs", 2),
+    topic!(Functions, "functions", "basics", "函数", "Functions", "src/basics/functions.rs", 2),
+    topic!(ControlFlow, "control_flow", "basics", "控制流", "Control Flow", "src/basics/control_flow.rs", 2),
+    topic!(DataTypes, "data_types", "basics", "数据类型", "Data Types", "src/basics/data_types.rs", 3),
+    topic!(Ownership, "ownership", "ownership", "所有权", "Ownership", "src/ownership/ownership.rs", 4),
+    topic!(References, "references", "ownership", "引用与借用", "References and Borrowing", "src/ownership/references.rs", 4),
+    topic!(Slices, "slices", "ownership", "切片", "Slices", "src/ownership/slices.rs", 2),
+    topic!(Structs, "structs", "structs_enums", "结构体", "Structs", "src/structs_enums/structs.rs", 3),
+    topic!(Enums, "enums", "structs_enums", "枚举", "Enums", "src/structs_enums/enums.rs", 3),
+    topic!(PatternMatching, "pattern_matching", "structs_enums", "模式匹配", "Pattern Matching", "src/structs_enums/pattern_matching.rs", 3),
+    topic!(ErrorHandling, "error_handling", "error_handling", "错误处理", "Error Handling", "src/error_handling/error_handling.rs", 3),
+    topic!(ResultOption, "result_option", "error_handling", "Result 与 Option", "Result and Option", "src/error_handling/result_option.rs", 3),
+    topic!(Generics, "generics", "generics_traits", "泛型", "Generics", "src/generics_traits/generics.rs", 4),
+    topic!(Traits, "traits", "generics_traits", "特征", "Traits", "src/generics_traits/traits.rs", 5, planned),
+    topic!(Lifetimes, "lifetimes", "generics_traits", "生命周期", "Lifetimes", "src/generics_traits/lifetimes.rs", 5, planned),
+    topic!(Collections, "collections", "collections", "集合概览", "Collections Overview", "src/collections/collections.rs", 2),
+    topic!(Vectors, "vectors", "collections", "Vector", "Vectors", "src/collections/vectors.rs", 2, planned),
+    topic!(Strings, "strings", "collections", "字符串", "Strings", "src/collections/strings.rs", 3, planned),
+    topic!(HashMaps, "hashmaps", "collections", "HashMap", "Hash Maps", "src/collections/hashmaps.rs", 2, planned),
+    topic!(Closures, "closures", "functional", "闭包", "Closures", "src/functional/closures.rs", 3, planned),
+    topic!(Iterators, "iterators", "functional", "迭代器", "Iterators", "src/functional/iterators.rs", 3, planned),
+    topic!(Threads, "threads", "concurrency", "线程", "Threads", "src/concurrency/threads.rs", 3, planned),
+    topic!(Channels, "channels", "concurrency", "消息传递", "Channels", "src/concurrency/channels.rs", 3, planned),
+    topic!(SharedState, "shared_state", "concurrency", "共享状态", "Shared State", "src/concurrency/shared_state.rs", 4, planned),
+    topic!(Calculator, "calculator", "projects", "计算器", "Calculator", "src/projects/calculator.rs", 6),
+    topic!(TodoApp, "todo_app", "projects", "待办事项管理器", "Todo App", "src/projects/todo_app.rs", 10),
+    topic!(FileSearch, "file_search", "projects", "文件搜索工具", "File Search", "src/projects/file_search.rs", 10, planned),
+];
+
+impl StageInfo {
+    /// 属于这个阶段的主题，按学习顺序排列
+    pub fn topics(&self) -> Vec<Topic> {
+        TOPICS.iter()
+            .filter(|info| info.stage == self.id)
+            .map(|info| info.topic.clone())
+            .collect()
+    }
+}
+
+impl Topic {
+    /// 所有已登记的主题，按学习顺序排列
+    pub fn all() -> Vec<Topic> {
+        TOPICS.iter().map(|info| info.topic.clone()).collect()
+    }
+
+    /// 主题的元数据
+    pub fn info(&self) -> &'static TopicInfo {
+        TOPICS.iter()
+            .find(|info| info.topic == *self)
+            .unwrap_or_else(|| panic!("主题 {:?} 没有在 TOPICS 中登记", self))
+    }
+
+    /// 根据标识符查找主题
+    pub fn from_id(id: &str) -> Option<Topic> {
+        TOPICS.iter()
+            .find(|info| info.id == id)
+            .map(|info| info.topic.clone())
+    }
+
+    /// 主题所属的阶段
+    pub fn stage(&self) -> &'static StageInfo {
+        let stage_id = self.info().stage;
+        STAGES.iter()
+            .find(|stage| stage.id == stage_id)
+            .unwrap_or_else(|| panic!("阶段 {} 没有在 STAGES 中登记", stage_id))
+    }
+
+    /// 主题所属阶段的序号
+    pub fn stage_index(&self) -> usize {
+        let stage_id = self.info().stage;
+        STAGES.iter().position(|stage| stage.id == stage_id).unwrap_or(STAGES.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::path::Path;
+
+    /// 解析 Cargo.toml 中的 `[[bin]]`，返回 (name, path) 列表
+    fn cargo_binaries() -> Vec<(String, String)> {
+        let manifest = include_str!("../../Cargo.toml");
+        let mut binaries = Vec::new();
+        let mut in_bin = false;
+        let mut name = None;
+
+        for line in manifest.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_bin = line == "[[bin]]";
+                name = None;
+            } else if in_bin {
+                let value = |line: &str| line.split('"').nth(1).unwrap_or_default().to_string();
+                if line.starts_with("name") {
+                    name = Some(value(line));
+                } else if line.starts_with("path") {
+                    if let Some(name) = name.take() {
+                        binaries.push((name, value(line)));
+                    }
+                }
+            }
+        }
+        binaries
+    }
+
+    #[test]
+    fn test_registry_is_consistent() {
+        let ids: HashSet<_> = TOPICS.iter().map(|info| info.id).collect();
+        assert_eq!(ids.len(), TOPICS.len(), "主题标识符重复");
+
+        let topics: HashSet<_> = TOPICS.iter().map(|info| &info.topic).collect();
+        assert_eq!(topics.len(), TOPICS.len(), "主题重复登记");
+
+        for info in TOPICS {
+            assert!(STAGES.iter().any(|stage| stage.id == info.stage), "{} 的阶段未登记", info.id);
+            assert_eq!(Topic::from_id(info.id), Some(info.topic.clone()));
+        }
+        // 主题按阶段顺序登记
+        let indexes: Vec<usize> = TOPICS.iter().map(|info| info.topic.stage_index()).collect();
+        assert!(indexes.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_every_topic_has_a_binary() {
+        let binaries = cargo_binaries();
+
+        for info in TOPICS {
+            let declared = binaries.iter().find(|(name, _)| name == info.id);
+            let Some(binary) = info.binary else {
+                assert!(declared.is_none(), "{} 已经声明了 [[bin]]，需要在 TOPICS 中去掉 planned", info.id);
+                continue;
+            };
+            assert!(declared.is_some(), "Cargo.toml 中没有声明 [[bin]] {}", binary);
+            assert_eq!(declared.unwrap().1, info.source, "{} 的源文件路径不一致", binary);
+            assert!(
+                Path::new(env!("CARGO_MANIFEST_DIR")).join(info.source).exists(),
+                "{} 的源文件 {} 不存在",
+                binary,
+                info.source
+            );
+        }
+    }
+}
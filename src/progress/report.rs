@@ -0,0 +1,372 @@
+//! 进度报告渲染器
+//!
+//! 同一份进度可以渲染成终端文本、Markdown 表格、独立的 HTML 页面或 JSON，
+//! 所有渲染器都写入任意的 `io::Write`。
+
+use super::{ProgressTracker, StageInfo, Status, Topic, STAGES};
+use serde::Serialize;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// 进度报告渲染器
+pub trait ReportRenderer {
+    fn render(&self, tracker: &ProgressTracker, out: &mut dyn Write) -> io::Result<()>;
+}
+
+/// 报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Terminal,
+    Markdown,
+    Html,
+    Json,
+}
+
+impl ReportFormat {
+    /// 创建对应格式的渲染器
+    ///
+    /// `emoji` 为 `false` 时用纯文本标记代替表情符号，JSON 格式忽略这个选项。
+    pub fn renderer(self, emoji: bool) -> Box<dyn ReportRenderer> {
+        match self {
+            ReportFormat::Terminal => Box::new(TerminalReport { emoji }),
+            ReportFormat::Markdown => Box::new(MarkdownReport { emoji }),
+            ReportFormat::Html => Box::new(HtmlReport { emoji }),
+            ReportFormat::Json => Box::new(JsonReport),
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "terminal" | "text" | "txt" => Ok(ReportFormat::Terminal),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "html" => Ok(ReportFormat::Html),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(format!("未知的报告格式：'{}'（可选：terminal, md, html, json）", s)),
+        }
+    }
+}
+
+impl fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ReportFormat::Terminal => "terminal",
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+            ReportFormat::Json => "json",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Status {
+    /// 状态的中文名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Status::NotStarted => "未开始",
+            Status::InProgress => "进行中",
+            Status::Completed => "已完成",
+            Status::NeedsReview => "待复习",
+        }
+    }
+
+    /// 状态图标，`emoji` 为 `false` 时返回纯文本标记
+    pub fn icon(&self, emoji: bool) -> &'static str {
+        match (self, emoji) {
+            (Status::NotStarted, true) => "⭕",
+            (Status::InProgress, true) => "🔄",
+            (Status::Completed, true) => "✅",
+            (Status::NeedsReview, true) => "🔍",
+            (Status::NotStarted, false) => "[ ]",
+            (Status::InProgress, false) => "[~]",
+            (Status::Completed, false) => "[x]",
+            (Status::NeedsReview, false) => "[?]",
+        }
+    }
+}
+
+/// 一个阶段的完成情况
+struct StageSummary {
+    stage: &'static StageInfo,
+    topics: Vec<(Topic, Status)>,
+}
+
+impl StageSummary {
+    fn completed(&self) -> usize {
+        self.topics.iter().filter(|(_, status)| *status == Status::Completed).count()
+    }
+
+    fn percentage(&self) -> f64 {
+        if self.topics.is_empty() {
+            0.0
+        } else {
+            self.completed() as f64 / self.topics.len() as f64 * 100.0
+        }
+    }
+}
+
+fn stage_summaries(tracker: &ProgressTracker) -> Vec<StageSummary> {
+    STAGES.iter()
+        .map(|stage| StageSummary {
+            stage,
+            topics: stage.topics()
+                .into_iter()
+                .map(|topic| {
+                    let status = tracker.get_status(&topic).cloned().unwrap_or(Status::NotStarted);
+                    (topic, status)
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// 终端文本报告（`show_progress` 使用的格式）
+pub struct TerminalReport {
+    pub emoji: bool,
+}
+
+impl ReportRenderer for TerminalReport {
+    fn render(&self, tracker: &ProgressTracker, out: &mut dyn Write) -> io::Result<()> {
+        let (crab, book) = if self.emoji { ("🦀 ", "📚 ") } else { ("", "") };
+
+        writeln!(out, "{}Rust 学习进度报告", crab)?;
+        writeln!(out, "====================")?;
+        writeln!(out, "完成进度: {:.1}% ({}/{})",
+                 tracker.completion_percentage(),
+                 tracker.completed_count(),
+                 tracker.total_count())?;
+        writeln!(out)?;
+
+        for summary in stage_summaries(tracker) {
+            writeln!(out, "{}{}", book, summary.stage.name_zh)?;
+            for (topic, status) in &summary.topics {
+                writeln!(out, "   {} {}", status.icon(self.emoji), topic.info().name_zh)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+/// Markdown 表格报告
+pub struct MarkdownReport {
+    pub emoji: bool,
+}
+
+impl ReportRenderer for MarkdownReport {
+    fn render(&self, tracker: &ProgressTracker, out: &mut dyn Write) -> io::Result<()> {
+        let summaries = stage_summaries(tracker);
+
+        writeln!(out, "# Rust 学习进度报告")?;
+        writeln!(out)?;
+        writeln!(out, "完成进度：**{:.1}%**（{}/{}）",
+                 tracker.completion_percentage(),
+                 tracker.completed_count(),
+                 tracker.total_count())?;
+        writeln!(out)?;
+
+        writeln!(out, "| 阶段 | 完成 | 进度 |")?;
+        writeln!(out, "|------|------|------|")?;
+        for summary in &summaries {
+            writeln!(out, "| {} | {}/{} | {:.0}% |",
+                     summary.stage.name_zh,
+                     summary.completed(),
+                     summary.topics.len(),
+                     summary.percentage())?;
+        }
+        writeln!(out)?;
+
+        writeln!(out, "| 阶段 | 主题 | 状态 |")?;
+        writeln!(out, "|------|------|------|")?;
+        for summary in &summaries {
+            for (topic, status) in &summary.topics {
+                writeln!(out, "| {} | {} | {} {} |",
+                         summary.stage.name_zh,
+                         topic.info().name_zh,
+                         status.icon(self.emoji).replace('[', "\\[").replace(']', "\\]"),
+                         status.label())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 独立的 HTML 页面，每个阶段带一个进度条
+pub struct HtmlReport {
+    pub emoji: bool,
+}
+
+impl ReportRenderer for HtmlReport {
+    fn render(&self, tracker: &ProgressTracker, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html lang=\"zh-CN\">")?;
+        writeln!(out, "<head>")?;
+        writeln!(out, "<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>Rust 学习进度报告</title>")?;
+        writeln!(out, "<style>")?;
+        writeln!(out, "body {{ font-family: sans-serif; max-width: 48rem; margin: 2rem auto; }}")?;
+        writeln!(out, ".bar {{ background: #eee; border-radius: 4px; height: 0.75rem; }}")?;
+        writeln!(out, ".fill {{ background: #dea584; border-radius: 4px; height: 100%; }}")?;
+        writeln!(out, "ul {{ list-style: none; padding-left: 1rem; }}")?;
+        writeln!(out, "</style>")?;
+        writeln!(out, "</head>")?;
+        writeln!(out, "<body>")?;
+        writeln!(out, "<h1>Rust 学习进度报告</h1>")?;
+        writeln!(out, "<p>完成进度：{:.1}%（{}/{}）</p>",
+                 tracker.completion_percentage(),
+                 tracker.completed_count(),
+                 tracker.total_count())?;
+
+        for summary in stage_summaries(tracker) {
+            writeln!(out, "<section>")?;
+            writeln!(out, "<h2>{}（{}/{}）</h2>",
+                     escape_html(summary.stage.name_zh),
+                     summary.completed(),
+                     summary.topics.len())?;
+            writeln!(out, "<div class=\"bar\"><div class=\"fill\" style=\"width: {:.0}%\"></div></div>",
+                     summary.percentage())?;
+            writeln!(out, "<ul>")?;
+            for (topic, status) in &summary.topics {
+                writeln!(out, "<li>{} {} <small>{}</small></li>",
+                         escape_html(status.icon(self.emoji)),
+                         escape_html(topic.info().name_zh),
+                         status.label())?;
+            }
+            writeln!(out, "</ul>")?;
+            writeln!(out, "</section>")?;
+        }
+
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// JSON 报告，便于其他工具读取
+pub struct JsonReport;
+
+#[derive(Serialize)]
+struct JsonTopic {
+    id: &'static str,
+    name_zh: &'static str,
+    name_en: &'static str,
+    status: Status,
+}
+
+#[derive(Serialize)]
+struct JsonStage {
+    id: &'static str,
+    name_zh: &'static str,
+    name_en: &'static str,
+    completed: usize,
+    total: usize,
+    topics: Vec<JsonTopic>,
+}
+
+#[derive(Serialize)]
+struct JsonDocument {
+    completed: usize,
+    total: usize,
+    percentage: f64,
+    stages: Vec<JsonStage>,
+}
+
+impl ReportRenderer for JsonReport {
+    fn render(&self, tracker: &ProgressTracker, out: &mut dyn Write) -> io::Result<()> {
+        let document = JsonDocument {
+            completed: tracker.completed_count(),
+            total: tracker.total_count(),
+            percentage: tracker.completion_percentage(),
+            stages: stage_summaries(tracker)
+                .into_iter()
+                .map(|summary| JsonStage {
+                    id: summary.stage.id,
+                    name_zh: summary.stage.name_zh,
+                    name_en: summary.stage.name_en,
+                    completed: summary.completed(),
+                    total: summary.topics.len(),
+                    topics: summary.topics
+                        .into_iter()
+                        .map(|(topic, status)| {
+                            let info = topic.info();
+                            JsonTopic { id: info.id, name_zh: info.name_zh, name_en: info.name_en, status }
+                        })
+                        .collect(),
+                })
+                .collect(),
+        };
+        serde_json::to_writer_pretty(&mut *out, &document)?;
+        writeln!(out)
+    }
+}
+
+impl ProgressTracker {
+    /// 用指定的渲染器生成报告
+    pub fn render_report(&self, renderer: &dyn ReportRenderer, out: &mut dyn Write) -> io::Result<()> {
+        renderer.render(self, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(format: ReportFormat, emoji: bool) -> String {
+        let mut tracker = ProgressTracker::new();
+        tracker.force_update_status(Topic::Variables, Status::Completed);
+        tracker.force_update_status(Topic::Functions, Status::InProgress);
+
+        let mut out = Vec::new();
+        tracker.render_report(format.renderer(emoji).as_ref(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_terminal_report() {
+        let report = render(ReportFormat::Terminal, true);
+        assert!(report.contains("📚 基础语法"));
+        assert!(report.contains("   ✅ 变量与可变性"));
+
+        let plain = render(ReportFormat::Terminal, false);
+        assert!(plain.contains("   [x] 变量与可变性"));
+        assert!(plain.contains("   [~] 函数"));
+        assert!(!plain.contains('✅'));
+    }
+
+    #[test]
+    fn test_markdown_and_html_reports() {
+        let markdown = render(ReportFormat::Markdown, true);
+        assert!(markdown.contains("| 基础语法 | 1/4 | 25% |"));
+        assert!(markdown.contains("| 基础语法 | 变量与可变性 | ✅ 已完成 |"));
+
+        let html = render(ReportFormat::Html, false);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("style=\"width: 25%\""));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn test_json_report() {
+        let json: serde_json::Value = serde_json::from_str(&render(ReportFormat::Json, true)).unwrap();
+        assert_eq!(json["completed"], 1);
+        assert_eq!(json["stages"][0]["id"], "basics");
+        assert_eq!(json["stages"][0]["topics"][1]["status"], "InProgress");
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("md".parse(), Ok(ReportFormat::Markdown));
+        assert_eq!("HTML".parse(), Ok(ReportFormat::Html));
+        assert!("pdf".parse::<ReportFormat>().is_err());
+    }
+}
@@ -0,0 +1,248 @@
+//! 间隔重复复习计划（SM-2 算法）
+//!
+//! 主题第一次标记为已完成时会自动建立复习卡片。每次复习根据回忆质量调整
+//! 难度系数（ease factor）和复习间隔；到期的主题会被切换为 `NeedsReview`。
+
+use super::{ProgressTracker, Status, Topic};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 初始难度系数
+const INITIAL_EASE: f64 = 2.5;
+/// 难度系数下限
+const MIN_EASE: f64 = 1.3;
+
+/// 一次复习的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewOutcome {
+    /// 完全想不起来，需要重新学习
+    Again,
+    /// 想起来了，但很吃力
+    Hard,
+    /// 正常想起
+    Good,
+    /// 毫不费力
+    Easy,
+}
+
+impl ReviewOutcome {
+    /// 对应 SM-2 中 0~5 分的回忆质量
+    fn quality(self) -> f64 {
+        match self {
+            ReviewOutcome::Again => 1.0,
+            ReviewOutcome::Hard => 3.0,
+            ReviewOutcome::Good => 4.0,
+            ReviewOutcome::Easy => 5.0,
+        }
+    }
+}
+
+impl FromStr for ReviewOutcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "again" => Ok(ReviewOutcome::Again),
+            "hard" => Ok(ReviewOutcome::Hard),
+            "good" => Ok(ReviewOutcome::Good),
+            "easy" => Ok(ReviewOutcome::Easy),
+            _ => Err(format!("未知的复习结果：'{}'（可选：again, hard, good, easy）", s)),
+        }
+    }
+}
+
+impl fmt::Display for ReviewOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ReviewOutcome::Again => "again",
+            ReviewOutcome::Hard => "hard",
+            ReviewOutcome::Good => "good",
+            ReviewOutcome::Easy => "easy",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 一个主题的复习卡片
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewCard {
+    pub ease_factor: f64,
+    /// 当前复习间隔（天）
+    pub interval_days: u32,
+    /// 连续成功复习的次数
+    pub repetitions: u32,
+    pub due: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_reviewed: Option<DateTime<Utc>>,
+}
+
+impl ReviewCard {
+    /// 新卡片，第一次复习安排在一天后
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            ease_factor: INITIAL_EASE,
+            interval_days: 1,
+            repetitions: 0,
+            due: now + Duration::days(1),
+            last_reviewed: None,
+        }
+    }
+
+    /// 按 SM-2 规则记录一次复习
+    pub fn review(&mut self, outcome: ReviewOutcome, now: DateTime<Utc>) {
+        let q = outcome.quality();
+        self.ease_factor = (self.ease_factor + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)).max(MIN_EASE);
+
+        if outcome == ReviewOutcome::Again {
+            self.repetitions = 0;
+            self.interval_days = 1;
+        } else {
+            self.repetitions += 1;
+            self.interval_days = match self.repetitions {
+                1 => 1,
+                2 => 6,
+                _ => (self.interval_days as f64 * self.ease_factor).round() as u32,
+            };
+        }
+
+        self.last_reviewed = Some(now);
+        self.due = now + Duration::days(self.interval_days as i64);
+    }
+
+    /// 到 `now` 所在的这一天结束时是否到期
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.due.date_naive() <= now.date_naive()
+    }
+}
+
+impl ProgressTracker {
+    /// 主题的复习卡片
+    pub fn review_card(&self, topic: &Topic) -> Option<&ReviewCard> {
+        self.reviews.get(topic)
+    }
+
+    /// 把已到期的已完成主题切换为待复习，返回被切换的主题
+    pub fn refresh_reviews(&mut self, now: DateTime<Utc>) -> Vec<Topic> {
+        let due: Vec<Topic> = self.reviews.iter()
+            .filter(|(topic, card)| {
+                card.is_due(now) && self.get_status(topic) == Some(&Status::Completed)
+            })
+            .map(|(topic, _)| topic.clone())
+            .collect();
+
+        for topic in &due {
+            self.force_update_status_at(topic.clone(), Status::NeedsReview, now, Some("复习到期".to_string()));
+        }
+        due
+    }
+
+    /// 今天需要复习的主题，按到期时间排序
+    ///
+    /// 手动标记为待复习但还没有卡片的主题排在最前面。
+    pub fn review_queue(&self, now: DateTime<Utc>) -> Vec<Topic> {
+        let mut queue: Vec<(Option<DateTime<Utc>>, Topic)> = self.progress.iter()
+            .filter_map(|(topic, status)| match self.reviews.get(topic) {
+                Some(card) if card.is_due(now) && matches!(status, Status::Completed | Status::NeedsReview) => {
+                    Some((Some(card.due), topic.clone()))
+                }
+                None if *status == Status::NeedsReview => Some((None, topic.clone())),
+                _ => None,
+            })
+            .collect();
+        queue.sort();
+        queue.into_iter().map(|(_, topic)| topic).collect()
+    }
+
+    /// 记录一次复习结果，返回更新后的卡片
+    ///
+    /// 除了 `Again` 以外的结果都会把主题重新标记为已完成。
+    pub fn record_review(&mut self, topic: &Topic, outcome: ReviewOutcome, now: DateTime<Utc>) -> &ReviewCard {
+        let mut card = self.reviews.get(topic).cloned().unwrap_or_else(|| ReviewCard::new(now));
+        card.review(outcome, now);
+
+        let status = if outcome == ReviewOutcome::Again {
+            Status::NeedsReview
+        } else {
+            Status::Completed
+        };
+        // 复习的主题已经学过，不再检查前置主题
+        self.force_update_status_at(topic.clone(), status, now, Some(format!("复习：{}", outcome)));
+
+        self.reviews.insert(topic.clone(), card);
+        &self.reviews[topic]
+    }
+
+    /// 第一次完成主题时建立复习卡片
+    pub(super) fn schedule_first_review(&mut self, topic: &Topic, now: DateTime<Utc>) {
+        self.reviews.entry(topic.clone()).or_insert_with(|| ReviewCard::new(now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 4, d, 8, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_sm2_intervals() {
+        let mut card = ReviewCard::new(day(1));
+        card.review(ReviewOutcome::Good, day(2));
+        assert_eq!(card.interval_days, 1);
+        card.review(ReviewOutcome::Good, day(3));
+        assert_eq!(card.interval_days, 6);
+        card.review(ReviewOutcome::Easy, day(9));
+        assert!(card.ease_factor > INITIAL_EASE);
+        assert_eq!(card.interval_days, 16);
+
+        card.review(ReviewOutcome::Again, day(25));
+        assert_eq!(card.repetitions, 0);
+        assert_eq!(card.interval_days, 1);
+        assert_eq!(card.due, day(26));
+
+        for _ in 0..10 {
+            card.review(ReviewOutcome::Hard, day(26));
+        }
+        assert!(card.ease_factor >= MIN_EASE);
+    }
+
+    #[test]
+    fn test_completed_topic_becomes_due() {
+        let mut tracker = ProgressTracker::new();
+        tracker.force_update_status_at(Topic::Variables, Status::Completed, day(1), None);
+        assert!(tracker.review_card(&Topic::Variables).is_some());
+
+        assert!(tracker.refresh_reviews(day(1)).is_empty());
+        assert_eq!(tracker.refresh_reviews(day(2)), vec![Topic::Variables]);
+        assert_eq!(tracker.get_status(&Topic::Variables), Some(&Status::NeedsReview));
+        assert_eq!(tracker.review_queue(day(2)), vec![Topic::Variables]);
+
+        tracker.record_review(&Topic::Variables, ReviewOutcome::Good, day(2));
+        assert_eq!(tracker.get_status(&Topic::Variables), Some(&Status::Completed));
+        assert!(tracker.review_queue(day(2)).is_empty());
+    }
+
+    #[test]
+    fn test_review_queue_order() {
+        let mut tracker = ProgressTracker::new();
+        tracker.force_update_status_at(Topic::Functions, Status::Completed, day(3), None);
+        tracker.force_update_status_at(Topic::Variables, Status::Completed, day(1), None);
+        tracker.force_update_status(Topic::Slices, Status::NeedsReview);
+
+        assert_eq!(
+            tracker.review_queue(day(5)),
+            vec![Topic::Slices, Topic::Variables, Topic::Functions]
+        );
+    }
+
+    #[test]
+    fn test_outcome_from_str() {
+        assert_eq!("Easy".parse(), Ok(ReviewOutcome::Easy));
+        assert!("perfect".parse::<ReviewOutcome>().is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! 进度数据的 JSON 持久化
+//!
+//! 文件格式：
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "topics": { "Variables": "Completed", "Functions": "InProgress" },
+//!   "history": [
+//!     { "topic": "Variables", "from": "NotStarted", "to": "Completed", "at": "2026-03-02T09:00:00Z" }
+//!   ],
+//!   "reviews": {
+//!     "Variables": { "ease_factor": 2.5, "interval_days": 1, "repetitions": 0, "due": "2026-03-03T09:00:00Z" }
+//!   }
+//! }
+//! ```
+//!
+//! `history` 和 `reviews` 是后来加入的可选字段，缺少时按空处理，所以早期的文件
+//! 仍然可以正常读取。只有旧版本无法正确读取的改动才需要提升版本号，新增可选
+//! 字段不算。
+//!
+//! 和 `topics` 一样，`history` 中无法解析的记录（例如由更新版本写入、涉及新主题的）
+//! 不会导致加载失败，而是原样保留，保存时写在历史的末尾。
+
+use super::{ProgressTracker, ReviewCard, Status, StatusChange, Topic};
+use crate::utils::fs::write_atomic;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 当前的文件格式版本
+const FORMAT_VERSION: u32 = 1;
+
+/// 磁盘上的文件结构
+#[derive(Serialize, Deserialize)]
+struct ProgressFile {
+    version: u32,
+    topics: BTreeMap<String, Value>,
+    #[serde(default)]
+    history: Vec<Value>,
+    #[serde(default)]
+    reviews: BTreeMap<String, ReviewCard>,
+}
+
+impl ProgressTracker {
+    /// 从 JSON 文件加载进度
+    ///
+    /// 文件中缺少的主题保持未开始状态；不认识的主题（例如由更新版本写入的）
+    /// 会被保留下来，并在下次 `save` 时原样写回。
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::from_json(&content)
+    }
+
+    /// 加载进度，文件不存在时返回全新的跟踪器
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        match Self::load(path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            result => result,
+        }
+    }
+
+    /// 把进度保存为 JSON 文件
+    ///
+    /// 通过 `write_atomic` 写入，写入过程中崩溃也不会留下损坏的进度文件。
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        write_atomic(path.as_ref(), &self.to_json()?)
+    }
+
+    /// 序列化为 JSON 字符串
+    pub fn to_json(&self) -> io::Result<String> {
+        Ok(serde_json::to_string_pretty(&self.to_value()?)?)
+    }
+
+    /// 从 JSON 字符串解析
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        Self::from_value(serde_json::from_str(json)?)
+    }
+
+    /// 序列化为 JSON 值，供嵌入其他文件（如团队进度）使用
+    pub(super) fn to_value(&self) -> io::Result<Value> {
+        let mut topics = self.unknown_topics.clone();
+        for (topic, status) in &self.progress {
+            topics.insert(topic_key(topic)?, serde_json::to_value(status)?);
+        }
+        let mut reviews = self.unknown_reviews.clone();
+        for (topic, card) in &self.reviews {
+            reviews.insert(topic_key(topic)?, card.clone());
+        }
+        let mut history = Vec::with_capacity(self.history.len() + self.unknown_history.len());
+        for change in &self.history {
+            history.push(serde_json::to_value(change)?);
+        }
+        history.extend(self.unknown_history.iter().cloned());
+
+        let file = ProgressFile {
+            version: FORMAT_VERSION,
+            topics,
+            history,
+            reviews,
+        };
+        Ok(serde_json::to_value(file)?)
+    }
+
+    /// 从 JSON 值解析
+    pub(super) fn from_value(value: Value) -> io::Result<Self> {
+        let file: ProgressFile = serde_json::from_value(value)?;
+        if file.version > FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("不支持的进度文件版本：{}", file.version),
+            ));
+        }
+
+        let mut tracker = Self::new();
+        for (key, value) in file.topics {
+            match serde_json::from_value::<Topic>(Value::String(key.clone())) {
+                Ok(topic) => {
+                    let status: Status = serde_json::from_value(value)?;
+                    tracker.progress.insert(topic, status);
+                }
+                Err(_) => {
+                    tracker.unknown_topics.insert(key, value);
+                }
+            }
+        }
+        for (key, card) in file.reviews {
+            match serde_json::from_value::<Topic>(Value::String(key.clone())) {
+                Ok(topic) => {
+                    tracker.reviews.insert(topic, card);
+                }
+                Err(_) => {
+                    tracker.unknown_reviews.insert(key, card);
+                }
+            }
+        }
+        for value in file.history {
+            match serde_json::from_value::<StatusChange>(value.clone()) {
+                Ok(change) => tracker.history.push(change),
+                Err(_) => tracker.unknown_history.push(value),
+            }
+        }
+        Ok(tracker)
+    }
+}
+
+/// 主题在文件中使用的键名
+fn topic_key(topic: &Topic) -> io::Result<String> {
+    match serde_json::to_value(topic)? {
+        Value::String(key) => Ok(key),
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("无法作为主题键：{}", other),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("learn_rust_{}_{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = temp_path("roundtrip");
+        let mut tracker = ProgressTracker::new();
+        tracker.force_update_status(Topic::Ownership, Status::Completed);
+        tracker.force_update_status(Topic::Traits, Status::NeedsReview);
+        tracker.save(&path).unwrap();
+
+        let loaded = ProgressTracker::load(&path).unwrap();
+        assert_eq!(loaded.get_status(&Topic::Ownership), Some(&Status::Completed));
+        assert_eq!(loaded.get_status(&Topic::Traits), Some(&Status::NeedsReview));
+        assert_eq!(loaded.get_status(&Topic::Variables), Some(&Status::NotStarted));
+        assert_eq!(loaded.total_count(), tracker.total_count());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_topics_are_preserved() {
+        let json = r#"{
+            "version": 1,
+            "topics": { "Variables": "Completed", "AsyncAwait": "InProgress" }
+        }"#;
+        let tracker = ProgressTracker::from_json(json).unwrap();
+        assert_eq!(tracker.get_status(&Topic::Variables), Some(&Status::Completed));
+        assert_eq!(tracker.completed_count(), 1);
+
+        let saved = tracker.to_json().unwrap();
+        assert!(saved.contains("\"AsyncAwait\": \"InProgress\""));
+    }
+
+    #[test]
+    fn test_load_or_default_missing_file() {
+        let tracker = ProgressTracker::load_or_default(temp_path("missing")).unwrap();
+        assert_eq!(tracker.completed_count(), 0);
+    }
+
+    #[test]
+    fn test_history_roundtrip_and_v1_files() {
+        let mut tracker = ProgressTracker::new();
+        tracker.update_status_with_note(Topic::Slices, Status::InProgress, "先看 &str").unwrap();
+        let loaded = ProgressTracker::from_json(&tracker.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.history(), tracker.history());
+
+        let v1 = r#"{ "version": 1, "topics": { "Slices": "Completed" } }"#;
+        let loaded = ProgressTracker::from_json(v1).unwrap();
+        assert_eq!(loaded.get_status(&Topic::Slices), Some(&Status::Completed));
+        assert!(loaded.history().is_empty());
+        assert!(loaded.review_card(&Topic::Slices).is_none());
+    }
+
+    #[test]
+    fn test_unknown_history_is_preserved() {
+        let json = r#"{
+            "version": 1,
+            "topics": { "Variables": "Completed" },
+            "history": [
+                { "topic": "Variables", "from": "NotStarted", "to": "Completed", "at": "2026-03-02T09:00:00Z" },
+                { "topic": "AsyncAwait", "from": "NotStarted", "to": "InProgress", "at": "2026-03-03T09:00:00Z" }
+            ]
+        }"#;
+        let tracker = ProgressTracker::from_json(json).unwrap();
+        assert_eq!(tracker.history().len(), 1);
+        assert_eq!(tracker.history()[0].topic, Topic::Variables);
+
+        let saved: Value = serde_json::from_str(&tracker.to_json().unwrap()).unwrap();
+        let history = saved["history"].as_array().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1]["topic"], "AsyncAwait");
+    }
+
+    #[test]
+    fn test_additive_fields_keep_version() {
+        let mut tracker = ProgressTracker::new();
+        tracker.force_update_status(Topic::Enums, Status::Completed);
+        let value: Value = serde_json::from_str(&tracker.to_json().unwrap()).unwrap();
+        assert_eq!(value["version"], 1);
+    }
+
+    #[test]
+    fn test_review_cards_roundtrip() {
+        let mut tracker = ProgressTracker::new();
+        tracker.force_update_status(Topic::Enums, Status::Completed);
+        let loaded = ProgressTracker::from_json(&tracker.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.review_card(&Topic::Enums), tracker.review_card(&Topic::Enums));
+        assert!(loaded.review_card(&Topic::Enums).is_some());
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let json = r#"{ "version": 99, "topics": {} }"#;
+        assert!(ProgressTracker::from_json(json).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+//! 多人学习进度与团队排行榜
+//!
+//! 团队共用一个文件，按学习者标识保存每个人的 `ProgressTracker`：
+//!
+//! ```json
+//! { "version": 1, "learners": { "alice": { "version": 1, "topics": { ... } } } }
+//! ```
+
+use super::{ProgressTracker, Status, Topic, TOPICS};
+use crate::utils::fs::write_atomic;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// 当前的团队文件格式版本
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct TeamFile {
+    version: u32,
+    learners: BTreeMap<String, Value>,
+}
+
+/// 某个主题在团队中的完成情况
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicCompletion {
+    pub topic: Topic,
+    pub completed: usize,
+    pub learners: usize,
+}
+
+/// 长时间停留在进行中状态的主题
+#[derive(Debug, Clone, PartialEq)]
+pub struct StuckTopic {
+    pub learner: String,
+    pub topic: Topic,
+    pub since: DateTime<Utc>,
+    pub duration: Duration,
+}
+
+/// 排行榜中的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub learner: String,
+    pub completed: usize,
+    pub total: usize,
+    pub percentage: f64,
+}
+
+/// 按学习者保存进度的团队存储
+#[derive(Debug, Default)]
+pub struct TeamStore {
+    learners: BTreeMap<String, ProgressTracker>,
+}
+
+impl TeamStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从 JSON 文件加载
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::from_json(&content)
+    }
+
+    /// 加载团队进度，文件不存在时返回空的存储
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        match Self::load(path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            result => result,
+        }
+    }
+
+    /// 原子地保存为 JSON 文件
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        write_atomic(path.as_ref(), &self.to_json()?)
+    }
+
+    pub fn to_json(&self) -> io::Result<String> {
+        let mut learners = BTreeMap::new();
+        for (id, tracker) in &self.learners {
+            learners.insert(id.clone(), tracker.to_value()?);
+        }
+        let file = TeamFile { version: FORMAT_VERSION, learners };
+        Ok(serde_json::to_string_pretty(&file)?)
+    }
+
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let file: TeamFile = serde_json::from_str(json)?;
+        if file.version > FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("不支持的团队进度文件版本：{}", file.version),
+            ));
+        }
+
+        let mut store = Self::new();
+        for (id, value) in file.learners {
+            store.learners.insert(id, ProgressTracker::from_value(value)?);
+        }
+        Ok(store)
+    }
+
+    /// 某个学习者的进度
+    pub fn learner(&self, id: &str) -> Option<&ProgressTracker> {
+        self.learners.get(id)
+    }
+
+    /// 某个学习者的进度，不存在时新建
+    pub fn learner_mut(&mut self, id: &str) -> &mut ProgressTracker {
+        self.learners.entry(id.to_string()).or_default()
+    }
+
+    /// 移除学习者，返回其进度
+    pub fn remove_learner(&mut self, id: &str) -> Option<ProgressTracker> {
+        self.learners.remove(id)
+    }
+
+    /// 所有学习者，按标识排序
+    pub fn learners(&self) -> impl Iterator<Item = (&str, &ProgressTracker)> {
+        self.learners.iter().map(|(id, tracker)| (id.as_str(), tracker))
+    }
+
+    /// 每个主题有多少人完成，按学习顺序排列
+    pub fn topic_completion(&self) -> Vec<TopicCompletion> {
+        TOPICS.iter()
+            .map(|info| TopicCompletion {
+                topic: info.topic.clone(),
+                completed: self.learners.values()
+                    .filter(|tracker| tracker.get_status(&info.topic) == Some(&Status::Completed))
+                    .count(),
+                learners: self.learners.len(),
+            })
+            .collect()
+    }
+
+    /// 当前处于进行中状态的主题，停留时间最长的排在前面
+    ///
+    /// 开始时间取最近一次切换到进行中的记录，没有历史记录的主题不计入。
+    pub fn stuck_in_progress(&self, now: DateTime<Utc>) -> Vec<StuckTopic> {
+        let mut stuck = Vec::new();
+        for (id, tracker) in &self.learners {
+            for info in TOPICS {
+                if tracker.get_status(&info.topic) != Some(&Status::InProgress) {
+                    continue;
+                }
+                let since = tracker.topic_history(&info.topic)
+                    .iter()
+                    .rev()
+                    .find(|change| change.to == Status::InProgress)
+                    .map(|change| change.at);
+                if let Some(since) = since {
+                    stuck.push(StuckTopic {
+                        learner: id.clone(),
+                        topic: info.topic.clone(),
+                        since,
+                        duration: now - since,
+                    });
+                }
+            }
+        }
+        stuck.sort_by(|a, b| b.duration.cmp(&a.duration).then_with(|| a.learner.cmp(&b.learner)));
+        stuck
+    }
+
+    /// 按完成百分比排序的排行榜，百分比相同的名次相同
+    pub fn leaderboard(&self) -> Vec<LeaderboardEntry> {
+        let mut entries: Vec<LeaderboardEntry> = self.learners.iter()
+            .map(|(id, tracker)| LeaderboardEntry {
+                rank: 0,
+                learner: id.clone(),
+                completed: tracker.completed_count(),
+                total: tracker.total_count(),
+                percentage: tracker.completion_percentage(),
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            b.percentage.total_cmp(&a.percentage).then_with(|| a.learner.cmp(&b.learner))
+        });
+
+        for i in 0..entries.len() {
+            entries[i].rank = if i > 0 && entries[i].percentage == entries[i - 1].percentage {
+                entries[i - 1].rank
+            } else {
+                i + 1
+            };
+        }
+        entries
+    }
+
+    /// 把排行榜导出为 CSV
+    pub fn write_leaderboard_csv(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "rank,learner,completed,total,percentage")?;
+        for entry in self.leaderboard() {
+            writeln!(out, "{},{},{},{},{:.1}",
+                     entry.rank,
+                     csv_field(&entry.learner),
+                     entry.completed,
+                     entry.total,
+                     entry.percentage)?;
+        }
+        Ok(())
+    }
+
+    /// 把每个主题的团队完成情况导出为 CSV
+    pub fn write_topic_completion_csv(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "topic,stage,completed,learners")?;
+        for row in self.topic_completion() {
+            let info = row.topic.info();
+            writeln!(out, "{},{},{},{}", info.id, info.stage, row.completed, row.learners)?;
+        }
+        Ok(())
+    }
+}
+
+/// 按 CSV 规则转义字段
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 5, d, 10, 0, 0).unwrap()
+    }
+
+    fn sample_team() -> TeamStore {
+        let mut team = TeamStore::new();
+        let alice = team.learner_mut("alice");
+        alice.force_update_status_at(Topic::Variables, Status::Completed, day(1), None);
+        alice.force_update_status_at(Topic::Functions, Status::Completed, day(2), None);
+        alice.force_update_status_at(Topic::Ownership, Status::InProgress, day(3), None);
+
+        let bob = team.learner_mut("bob");
+        bob.force_update_status_at(Topic::Variables, Status::Completed, day(1), None);
+        bob.force_update_status_at(Topic::Functions, Status::InProgress, day(1), None);
+
+        team.learner_mut("carol, jr");
+        team
+    }
+
+    #[test]
+    fn test_topic_completion() {
+        let completion = sample_team().topic_completion();
+        assert_eq!(completion[0], TopicCompletion { topic: Topic::Variables, completed: 2, learners: 3 });
+        assert_eq!(completion[1].completed, 1);
+    }
+
+    #[test]
+    fn test_stuck_in_progress() {
+        let stuck = sample_team().stuck_in_progress(day(10));
+        assert_eq!(stuck.len(), 2);
+        assert_eq!((stuck[0].learner.as_str(), &stuck[0].topic), ("bob", &Topic::Functions));
+        assert_eq!(stuck[0].duration, Duration::days(9));
+        assert_eq!(stuck[1].learner, "alice");
+    }
+
+    #[test]
+    fn test_leaderboard_and_csv() {
+        let team = sample_team();
+        let board = team.leaderboard();
+        let order: Vec<(usize, &str)> = board.iter().map(|e| (e.rank, e.learner.as_str())).collect();
+        assert_eq!(order, vec![(1, "alice"), (2, "bob"), (3, "carol, jr")]);
+
+        let mut csv = Vec::new();
+        team.write_leaderboard_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "rank,learner,completed,total,percentage");
+        assert_eq!(lines[1], "1,alice,2,27,7.4");
+        assert_eq!(lines[3], "3,\"carol, jr\",0,27,0.0");
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let team = sample_team();
+        let loaded = TeamStore::from_json(&team.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.learners().count(), 3);
+        assert_eq!(
+            loaded.learner("alice").unwrap().get_status(&Topic::Ownership),
+            Some(&Status::InProgress)
+        );
+        assert_eq!(loaded.learner("bob").unwrap().history().len(), 2);
+    }
+}
@@ -2,145 +2,639 @@
 //! 
 //! 这是第一个实践项目，用于巩固基础语法学习。
 //! 
+//! 计算引擎在库的 `learn_rust::projects::calculator` 模块中，这里只负责读取输入、
+//! 显示结果和保存历史记录。
+//! 
 //! 功能：
-//! - 基本四则运算
+//! - 基本四则运算、取余和乘方，支持括号、负号和科学计数法
 //! - 用户输入处理
-//! - 错误处理基础
+//! - 错误处理基础，解析错误会标出出错的列
+//! - 变量、`ans`、内置常量和用户定义的函数
+//! - 内置数学函数（sqrt、ln、log、三角函数、fact、gcd 等），三角函数支持角度和弧度
+//! - 浮点数、精确分数和任意精度整数三种数值模式，结果可以显示为分数或定点小数
+//! - 程序员模式：十六/八/二进制字面量、位运算、可选的字长和溢出策略
+//! - 带单位的计算：长度、质量、时间、数据量和温度，用 `in` 换算，可以自定义单位
+//! - 复数（`3 + 4i`）和矩阵（`[1, 2; 3, 4]`），支持转置、行列式和求逆
+//! - 符号运算：`diff` 求导、`simplify` 化简、`eval` 代入求值
+//! - 数值方法：`solve` 解方程、`integrate` 数值积分、`roots` 多项式求根
+//! - `:plot` 在终端里画函数图像，可以同时画多条曲线
+//! - 历史记录保存在 `~/.calculator_history`，可以搜索、重新执行和引用之前的结果
+//! - 批处理模式：计算命令行上的表达式、执行脚本文件或者读取管道输入
 //! - 循环和控制流
+//!
+//! ```text
+//! calculator                         交互模式
+//! calculator -e "2 + 2"              计算表达式后退出，-e 可以重复
+//! calculator script.calc             逐行执行脚本，# 之后是注释
+//! echo "sqrt(2)" | calculator        从标准输入读取
+//! calculator --json script.calc      每个结果输出一行 JSON
+//! ```
+//!
+//! 批处理模式遇到第一个错误时停止，退出状态为 1。
+
+use advanced::CalculatorHistory;
+use learn_rust::projects::calculator::{
+    describe, parse, AngleMode, CalcError, Engine, Number, NumberMode, OutputFormat, Outcome, Overflow, Value,
+    WordSize, BUILTINS, FORMS,
+};
+use clap::Parser;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process;
+
+/// Rust 计算器
+#[derive(Parser)]
+#[command(name = "calculator", about)]
+struct Cli {
+    /// 计算表达式后退出，可以重复使用
+    #[arg(short = 'e', long = "expr", value_name = "表达式", allow_hyphen_values = true, conflicts_with = "script")]
+    expressions: Vec<String>,
 
-use std::io;
+    /// 逐行执行的脚本文件，'-' 表示标准输入
+    script: Option<PathBuf>,
+
+    /// 每个结果输出一行 JSON
+    #[arg(long)]
+    json: bool,
+}
 
 fn main() {
+    let cli = Cli::parse();
+    // 有表达式或脚本时不进入交互模式，标准输入不是终端时同样按批处理执行
+    if !cli.expressions.is_empty() || cli.script.is_some() || cli.json || !io::stdin().is_terminal() {
+        process::exit(batch(&cli));
+    }
+    repl();
+}
+
+/// 交互模式
+fn repl() {
     println!("🧮 欢迎使用 Rust 计算器！");
-    println!("支持的操作：+, -, *, /");
+    println!("支持的操作：+, -, *, /, %, ^ (或 **)，以及括号");
+    println!("变量：x = 3，函数：f(x) = x^2 + 1，上一次的结果：ans，常量：pi, e");
+    println!("内置函数：sqrt, ln, log, sin, cos, tan, abs, round, min, max, fact, gcd ...（':funcs' 查看全部）");
+    println!("命令：':vars' 查看变量和函数，':del 名称' 删除，':angle deg|rad' 切换角度单位");
+    println!("数值：':mode float|rational|integer|programmer' 切换数值模式，':format auto|fraction|decimal [位数]' 设置结果格式");
+    println!("程序员模式：0xff、0o17、0b1010，位运算 & | ^ ~ << >>（乘方用 **），':word i8..u128' 字长，':overflow wrapping|checked|saturating'");
+    println!("单位：3 km + 200 m in ft，5 MiB / 2 s，20 degC in degF；':units' 查看单位，':unit 名称 [= 表达式]' 定义新单位");
+    println!("复数和矩阵：3 + 4i，abs, conj, arg；[1, 2; 3, 4]，transpose, det, inv；':complex on|off' 切换复数模式");
+    println!("符号运算：f = diff(x^2 * sin(x), x)，simplify(x + x)，eval(f, x = 2)");
+    println!("数值方法：solve(x^3 - 2x - 5 = 0, x, 2)，integrate(sin(x), 0, pi)，roots(x^2 - 3x + 2)");
+    println!("画图：':plot sin(x), cos(x) from -pi to pi [ascii|braille]'");
+    println!("历史：':history [关键词]' 查看或搜索，':clear' 清空，'!n' 重新执行第 n 条，'$n' 引用第 n 条的结果");
     println!("输入 'quit' 或 'q' 退出程序\n");
     
+    let mut engine = Engine::new();
+    let path = history_path();
+    let mut history = CalculatorHistory::open(&path).unwrap_or_else(|e| {
+        eprintln!("警告：无法读取历史文件 {}：{}，本次的计算不会保存", path.display(), e);
+        CalculatorHistory::new()
+    });
+    engine.environment_mut().set_results(history.results());
+    
     loop {
-        println!("请输入计算表达式（例如：5 + 3）：");
+        println!("请输入计算表达式（例如：2 * (3 + 4)）：");
         
         let mut input = String::new();
-        io::stdin()
+        let bytes = io::stdin()
             .read_line(&mut input)
             .expect("读取输入失败");
         
         let input = input.trim();
         
-        // 检查退出命令
-        if input == "quit" || input == "q" {
+        // 检查退出命令，输入结束（Ctrl-D）时同样退出
+        if bytes == 0 || input == "quit" || input == "q" {
             println!("再见！👋");
             break;
         }
+        if input.is_empty() {
+            continue;
+        }
         
-        // 解析并计算表达式
-        match parse_and_calculate(input) {
-            Ok(result) => println!("结果：{}", result),
-            Err(error) => println!("错误：{}", error),
+        if let Some(command) = input.strip_prefix(':') {
+            run_command(&mut engine, &mut history, command);
+        } else if let Some(n) = input.strip_prefix('!') {
+            // 重新执行历史记录中的表达式
+            match n.parse().ok().and_then(|n| history.get(n)) {
+                Some(entry) => {
+                    let expression = entry.expression.clone();
+                    println!("» {}", expression);
+                    execute_line(&mut engine, &mut history, &expression);
+                }
+                None => println!("错误：没有第 {} 条历史记录", n),
+            }
+        } else {
+            execute_line(&mut engine, &mut history, input);
         }
         
         println!(); // 空行分隔
     }
 }
 
-/// 解析输入并计算结果
-fn parse_and_calculate(input: &str) -> Result<f64, String> {
-    let parts: Vec<&str> = input.split_whitespace().collect();
-    
-    if parts.len() != 3 {
-        return Err("请输入格式：数字 操作符 数字（例如：5 + 3）".to_string());
+/// 执行批处理，返回进程的退出状态
+fn batch(cli: &Cli) -> i32 {
+    let mut engine = Engine::new();
+    let mut out = io::stdout().lock();
+    let (source, result) = match &cli.script {
+        _ if !cli.expressions.is_empty() => {
+            let script = cli.expressions.join("\n");
+            ("命令行".to_string(), run_batch(&mut engine, script.as_bytes(), cli.json, &mut out))
+        }
+        Some(path) if path.as_os_str() != "-" => match File::open(path) {
+            Ok(file) => (path.display().to_string(), run_batch(&mut engine, BufReader::new(file), cli.json, &mut out)),
+            Err(error) => {
+                eprintln!("错误：无法读取脚本 {}：{}", path.display(), error);
+                return 1;
+            }
+        },
+        _ => ("标准输入".to_string(), run_batch(&mut engine, io::stdin().lock(), cli.json, &mut out)),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            if let BatchError::Statement { input, error: CalcError::Parse(parse), .. } = &error {
+                eprintln!("  {}", input);
+                eprintln!("  {}", parse.pointer());
+            }
+            eprintln!("错误：{} {}", source, error);
+            1
+        }
+    }
+}
+
+/// 批处理中止的原因
+#[derive(Debug)]
+enum BatchError {
+    /// 读取输入或者写出结果失败
+    Io(io::Error),
+    /// 第 `line` 行（从 1 开始）的语句出错
+    Statement { line: usize, input: String, error: CalcError },
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BatchError::Io(error) => write!(f, "读写失败：{}", error),
+            BatchError::Statement { line, error, .. } => write!(f, "第 {} 行：{}", line, error),
+        }
     }
-    
-    // 解析第一个数字
-    let num1 = parts[0].parse::<f64>()
-        .map_err(|_| format!("'{}' 不是有效的数字", parts[0]))?;
-    
-    // 解析操作符
-    let operator = parts[1];
-    
-    // 解析第二个数字
-    let num2 = parts[2].parse::<f64>()
-        .map_err(|_| format!("'{}' 不是有效的数字", parts[2]))?;
-    
-    // 执行计算
-    calculate(num1, operator, num2)
 }
 
-/// 执行具体的计算操作
-fn calculate(num1: f64, operator: &str, num2: f64) -> Result<f64, String> {
-    match operator {
-        "+" => Ok(num1 + num2),
-        "-" => Ok(num1 - num2),
-        "*" => Ok(num1 * num2),
-        "/" => {
-            if num2 == 0.0 {
-                Err("除数不能为零".to_string())
+impl From<io::Error> for BatchError {
+    fn from(error: io::Error) -> Self {
+        BatchError::Io(error)
+    }
+}
+
+/// `--json` 输出的一条记录，每条占一行
+#[derive(Serialize)]
+struct Record<'a> {
+    line: usize,
+    input: &'a str,
+    /// value、assignment、definition 或 error
+    kind: &'static str,
+    /// 赋值的变量名
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    /// 按输出格式显示的结果，函数定义时是函数的签名
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    /// 不带单位的实数结果，方便脚本直接使用
+    #[serde(skip_serializing_if = "Option::is_none")]
+    number: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    notes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// 语法错误所在的列
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+}
+
+impl<'a> Record<'a> {
+    fn new(line: usize, input: &'a str, kind: &'static str) -> Self {
+        Self { line, input, kind, name: None, value: None, number: None, notes: Vec::new(), error: None, column: None }
+    }
+}
+
+/// 逐行执行语句，遇到第一个错误时停止
+///
+/// 空行和 `#` 之后的注释会被跳过。结果写到 `out`：默认每行一个结果，附注（如
+/// `roots` 的复根）跟在结果后面，放在括号里；`json` 时每行一个 JSON 对象，
+/// 出错的语句也会输出一条 `error` 记录。
+/// 命令（`:mode` 等）只能在交互模式中使用。
+fn run_batch(engine: &mut Engine, input: impl BufRead, json: bool, out: &mut impl Write) -> Result<(), BatchError> {
+    for (index, line) in input.lines().enumerate() {
+        let line = line?;
+        let statement = line.split_once('#').map_or(line.as_str(), |(code, _)| code).trim_end();
+        if statement.trim().is_empty() {
+            continue;
+        }
+        let result = match statement.trim_start().strip_prefix(':') {
+            Some(command) => Err(CalcError::Unsupported(format!("批处理模式不支持命令 ':{}'", command))),
+            None => engine.execute(statement),
+        };
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(error) => {
+                if json {
+                    let mut record = Record::new(index + 1, statement, "error");
+                    record.error = Some(error.to_string());
+                    if let CalcError::Parse(parse) = &error {
+                        record.column = Some(parse.column);
+                    }
+                    write_record(out, &record)?;
+                }
+                return Err(BatchError::Statement { line: index + 1, input: statement.to_string(), error });
+            }
+        };
+
+        let env = engine.environment();
+        if !json {
+            writeln!(out, "{}", outcome.format(env.output_format()))?;
+            for note in env.take_notes() {
+                writeln!(out, "（{}）", note)?;
+            }
+            continue;
+        }
+        let mut record = match &outcome {
+            Outcome::Value(_) => Record::new(index + 1, statement, "value"),
+            Outcome::Assigned(name, _) => Record { name: Some(name), ..Record::new(index + 1, statement, "assignment") },
+            Outcome::Defined(signature) => {
+                Record { value: Some(signature.clone()), ..Record::new(index + 1, statement, "definition") }
+            }
+        };
+        if let Outcome::Value(value) | Outcome::Assigned(_, value) = &outcome {
+            record.value = Some(value.format(env.output_format()));
+            if let Value::Scalar(quantity) = value {
+                record.number = quantity.unit.is_none().then(|| quantity.number.to_f64());
+            }
+        }
+        record.notes = env.take_notes();
+        write_record(out, &record)?;
+    }
+    Ok(())
+}
+
+fn write_record(out: &mut impl Write, record: &Record) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, record)?;
+    writeln!(out)
+}
+
+/// 历史文件的位置：主目录下的 `.calculator_history`
+fn history_path() -> PathBuf {
+    env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".calculator_history"))
+        .unwrap_or_else(|| PathBuf::from(".calculator_history"))
+}
+
+/// 解析并执行一行语句，有结果时记入历史
+fn execute_line(engine: &mut Engine, history: &mut CalculatorHistory, input: &str) {
+    match engine.execute(input) {
+        Ok(outcome) => {
+            let env = engine.environment();
+            let mut line = format!("结果：{}", outcome.format(env.output_format()));
+            if let Outcome::Value(value) | Outcome::Assigned(_, value) = &outcome {
+                history.add_calculation(input, value.clone());
+                // 程序员模式下同时显示其他进制
+                if let (NumberMode::Programmer { word, .. }, Value::Scalar(quantity)) = (env.mode(), value) {
+                    if let (Number::Integer(number), true) = (&quantity.number, quantity.unit.is_none()) {
+                        line = format!("{}    {}", line, word.bases(number));
+                    }
+                }
+            }
+            println!("{}", line);
+            for note in env.take_notes() {
+                println!("（{}）", note);
+            }
+        }
+        Err(CalcError::Parse(error)) => {
+            println!("{}", error.pointer());
+            println!("错误：{}", error);
+        }
+        Err(error) => println!("错误：{}", error),
+    }
+}
+
+/// 处理以 ':' 开头的命令
+fn run_command(engine: &mut Engine, history: &mut CalculatorHistory, command: &str) {
+    let env = engine.environment_mut();
+    let (name, argument) = match command.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, Some(argument.trim())),
+        None => (command, None),
+    };
+    match (name, argument) {
+        ("vars", None) => {
+            let mut empty = true;
+            for (name, value) in env.variables() {
+                println!("{} = {}", name, value.format(env.output_format()));
+                empty = false;
+            }
+            for (name, function) in env.functions() {
+                println!("{}({}) = {}", name, function.params.join(", "), function.body);
+                empty = false;
+            }
+            if empty {
+                println!("暂无变量和函数");
+            }
+        }
+        ("funcs", None) => {
+            for builtin in BUILTINS {
+                println!("  {}", builtin.usage);
+            }
+            for (_, usage) in FORMS {
+                println!("  {}", usage);
+            }
+        }
+        ("angle", mode) => {
+            match mode {
+                Some("deg") => env.set_angle_mode(AngleMode::Degrees),
+                Some("rad") => env.set_angle_mode(AngleMode::Radians),
+                None => {}
+                Some(other) => {
+                    println!("错误：未知的角度单位 '{}'（可选：deg, rad）", other);
+                    return;
+                }
+            }
+            println!("三角函数使用{}", env.angle_mode());
+        }
+        ("mode", mode) => {
+            if let Some(mode) = mode {
+                match mode.parse::<NumberMode>() {
+                    Ok(mode) => env.set_mode(mode),
+                    Err(error) => {
+                        println!("错误：{}", error);
+                        return;
+                    }
+                }
+            }
+            println!("数值模式：{}", env.mode());
+        }
+        ("complex", complex) => {
+            match complex {
+                Some("on") => env.set_complex(true),
+                Some("off") => env.set_complex(false),
+                None => {}
+                Some(other) => {
+                    println!("错误：未知的选项 '{}'（可选：on, off）", other);
+                    return;
+                }
+            }
+            if env.complex() {
+                println!("复数模式：开启，sqrt(-1) = i");
             } else {
-                Ok(num1 / num2)
+                println!("复数模式：关闭，负数的平方根和对数会报错");
             }
         }
-        _ => Err(format!("不支持的操作符：'{}'", operator)),
+        ("word", Some(word)) => match word.parse::<WordSize>() {
+            Ok(word) => {
+                let overflow = match env.mode() {
+                    NumberMode::Programmer { overflow, .. } => overflow,
+                    _ => Overflow::Wrapping,
+                };
+                env.set_mode(NumberMode::Programmer { word, overflow });
+                println!("数值模式：{}", env.mode());
+            }
+            Err(error) => println!("错误：{}", error),
+        },
+        ("overflow", Some(overflow)) => match (overflow.parse::<Overflow>(), env.mode()) {
+            (Ok(overflow), NumberMode::Programmer { word, .. }) => {
+                env.set_mode(NumberMode::Programmer { word, overflow });
+                println!("数值模式：{}", env.mode());
+            }
+            (Ok(_), _) => println!("错误：溢出策略只在程序员模式下有效，先使用 ':mode programmer'"),
+            (Err(error), _) => println!("错误：{}", error),
+        },
+        ("format", format) => {
+            if let Some(format) = format {
+                match parse_format(format) {
+                    Some(format) => env.set_output_format(format),
+                    None => {
+                        println!("错误：未知的格式 '{}'（可选：auto, fraction, decimal [位数]）", format);
+                        return;
+                    }
+                }
+            }
+            println!("结果格式：{}", env.output_format());
+        }
+        ("units", None) => {
+            // 按量纲分组，如 m：cm, ft, inch, km ...
+            let mut groups: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+            for (name, unit) in env.units().iter() {
+                groups.entry(describe(&unit.dimension)).or_default().push(name);
+            }
+            for (dimension, names) in groups {
+                println!("  {}：{}", dimension, names.join(", "));
+            }
+        }
+        ("unit", Some(definition)) => {
+            let (name, expr) = match definition.split_once('=') {
+                Some((name, expr)) => match parse(expr) {
+                    Ok(expr) => (name.trim(), Some(expr)),
+                    Err(error) => {
+                        println!("错误：{}", error);
+                        return;
+                    }
+                },
+                None => (definition, None),
+            };
+            match env.define_unit(name, expr.as_ref()) {
+                Ok(()) => println!("已定义单位 {}", name),
+                Err(error) => println!("错误：{}", error),
+            }
+        }
+        ("plot", Some(arguments)) => match engine.plot(arguments) {
+            Ok(chart) => println!("{}", chart),
+            Err(error) => println!("错误：{}", error),
+        },
+        ("del", Some(name)) => {
+            if env.remove(name) {
+                println!("已删除 '{}'", name);
+            } else {
+                println!("错误：没有名为 '{}' 的变量或函数", name);
+            }
+        }
+        ("history", None) => history.show_history(),
+        ("history", Some(keyword)) => {
+            let found = history.search(keyword);
+            if found.is_empty() {
+                println!("没有包含 '{}' 的历史记录", keyword);
+            }
+            for (n, entry) in found {
+                println!("{}. {} = {}", n, entry.expression, entry.result);
+            }
+        }
+        ("clear", None) => {
+            history.clear_history();
+            env.set_results([]);
+        }
+        _ => println!("错误：未知的命令 ':{}'", command),
     }
 }
 
+/// 解析 `:format` 的参数，`decimal` 默认保留 6 位小数
+fn parse_format(text: &str) -> Option<OutputFormat> {
+    let mut words = text.split_whitespace();
+    let format = match (words.next()?, words.next()) {
+        ("auto", None) => OutputFormat::Auto,
+        ("fraction", None) => OutputFormat::Fraction,
+        ("decimal", None) => OutputFormat::Decimal(6),
+        ("decimal", Some(digits)) => OutputFormat::Decimal(digits.parse().ok()?),
+        _ => return None,
+    };
+    words.next().is_none().then_some(format)
+}
+
 /// 高级计算器功能（可选扩展）
 #[allow(dead_code)]
 mod advanced {
-    use super::*;
+    use learn_rust::projects::calculator::{NumberMode, Value};
+    use serde::{Deserialize, Serialize};
+    use std::fs;
+    use std::io::{self, Write};
+    use std::path::{Path, PathBuf};
     
-    /// 支持更多操作的计算器
-    pub fn advanced_calculate(num1: f64, operator: &str, num2: f64) -> Result<f64, String> {
-        match operator {
-            "+" => Ok(num1 + num2),
-            "-" => Ok(num1 - num2),
-            "*" => Ok(num1 * num2),
-            "/" => {
-                if num2 == 0.0 {
-                    Err("除数不能为零".to_string())
-                } else {
-                    Ok(num1 / num2)
-                }
+    /// 一条历史记录
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct HistoryEntry {
+        pub expression: String,
+        pub result: Value,
+    }
+    
+    /// 历史文件中的一行
+    #[derive(Serialize, Deserialize)]
+    struct Record {
+        expression: String,
+        /// 结果的 `Display` 形式
+        result: String,
+        /// 结果是否是浮点数，读回来时据此换算，`0.1` 不会变成分数
+        float: bool,
+    }
+    
+    impl Record {
+        fn new(entry: &HistoryEntry) -> Self {
+            Self {
+                expression: entry.expression.clone(),
+                result: entry.result.to_string(),
+                float: entry.result.is_float(),
             }
-            "%" => {
-                if num2 == 0.0 {
-                    Err("除数不能为零".to_string())
-                } else {
-                    Ok(num1 % num2)
-                }
+        }
+        
+        fn into_entry(self) -> Result<HistoryEntry, String> {
+            let mut result: Value = self.result.parse()?;
+            if self.float {
+                result = result.convert(NumberMode::Float).map_err(|e| e.to_string())?;
             }
-            "**" | "^" => Ok(num1.powf(num2)),
-            _ => Err(format!("不支持的操作符：'{}'", operator)),
+            Ok(HistoryEntry {
+                expression: self.expression,
+                result,
+            })
         }
     }
     
     /// 计算器历史记录
+    ///
+    /// 打开文件后每次计算都会追加到文件末尾，下次启动时重新加载。
+    /// 文件每行是一条记录的 JSON。
     pub struct CalculatorHistory {
-        history: Vec<String>,
+        history: Vec<HistoryEntry>,
+        file: Option<PathBuf>,
     }
     
     impl CalculatorHistory {
         pub fn new() -> Self {
             Self {
                 history: Vec::new(),
+                file: None,
             }
         }
         
-        pub fn add_calculation(&mut self, expression: &str, result: f64) {
-            let entry = format!("{} = {}", expression, result);
+        /// 从文件加载历史记录，之后的计算会追加到该文件；文件不存在时从空记录开始
+        ///
+        /// 有一行无法解析就报错：跳过它会让后面记录的 `!n`、`$n` 编号错位。
+        pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+            let path = path.as_ref();
+            let mut history = Vec::new();
+            match fs::read_to_string(path) {
+                Ok(content) => {
+                    for (i, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+                        let entry = serde_json::from_str::<Record>(line)
+                            .map_err(|e| e.to_string())
+                            .and_then(Record::into_entry)
+                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("第 {} 行：{}", i + 1, e)))?;
+                        history.push(entry);
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+            Ok(Self {
+                history,
+                file: Some(path.to_path_buf()),
+            })
+        }
+        
+        pub fn add_calculation(&mut self, expression: &str, result: impl Into<Value>) {
+            let entry = HistoryEntry {
+                expression: expression.to_string(),
+                result: result.into(),
+            };
+            if let Some(path) = &self.file {
+                let line = serde_json::to_string(&Record::new(&entry)).expect("记录总能序列化") + "\n";
+                let written = fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| file.write_all(line.as_bytes()));
+                if let Err(e) = written {
+                    eprintln!("警告：无法写入历史文件 {}：{}", path.display(), e);
+                }
+            }
             self.history.push(entry);
         }
         
+        /// 第 n 条记录（从 1 开始）
+        pub fn get(&self, n: usize) -> Option<&HistoryEntry> {
+            n.checked_sub(1).and_then(|index| self.history.get(index))
+        }
+        
+        /// 所有结果，按记录顺序排列
+        pub fn results(&self) -> impl Iterator<Item = Value> + '_ {
+            self.history.iter().map(|entry| entry.result.clone())
+        }
+        
+        /// 表达式中包含 `keyword` 的记录及其编号
+        pub fn search(&self, keyword: &str) -> Vec<(usize, &HistoryEntry)> {
+            self.history.iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.expression.contains(keyword))
+                .map(|(i, entry)| (i + 1, entry))
+                .collect()
+        }
+        
         pub fn show_history(&self) {
             if self.history.is_empty() {
                 println!("暂无计算历史");
             } else {
                 println!("计算历史：");
                 for (i, entry) in self.history.iter().enumerate() {
-                    println!("{}. {}", i + 1, entry);
+                    println!("{}. {} = {}", i + 1, entry.expression, entry.result);
                 }
             }
         }
         
         pub fn clear_history(&mut self) {
             self.history.clear();
+            if let Some(path) = &self.file {
+                if let Err(e) = fs::write(path, "") {
+                    eprintln!("警告：无法清空历史文件 {}：{}", path.display(), e);
+                }
+            }
             println!("历史记录已清空");
         }
     }
@@ -151,53 +645,48 @@ mod tests {
     use super::*;
     
     #[test]
-    fn test_addition() {
-        assert_eq!(calculate(2.0, "+", 3.0).unwrap(), 5.0);
-        assert_eq!(calculate(-1.0, "+", 1.0).unwrap(), 0.0);
+    fn test_parse_format() {
+        assert_eq!(parse_format("decimal 3"), Some(OutputFormat::Decimal(3)));
+        assert_eq!(parse_format("fraction"), Some(OutputFormat::Fraction));
+        assert_eq!(parse_format("decimal x"), None);
     }
     
-    #[test]
-    fn test_subtraction() {
-        assert_eq!(calculate(5.0, "-", 3.0).unwrap(), 2.0);
-        assert_eq!(calculate(0.0, "-", 5.0).unwrap(), -5.0);
+    /// 执行脚本，返回输出和错误
+    fn batch(script: &str, json: bool) -> (String, Option<BatchError>) {
+        let mut out = Vec::new();
+        let result = run_batch(&mut Engine::new(), script.as_bytes(), json, &mut out);
+        (String::from_utf8(out).unwrap(), result.err())
     }
     
     #[test]
-    fn test_multiplication() {
-        assert_eq!(calculate(4.0, "*", 3.0).unwrap(), 12.0);
-        assert_eq!(calculate(-2.0, "*", 3.0).unwrap(), -6.0);
-    }
-    
-    #[test]
-    fn test_division() {
-        assert_eq!(calculate(6.0, "/", 2.0).unwrap(), 3.0);
-        assert_eq!(calculate(7.0, "/", 2.0).unwrap(), 3.5);
-    }
-    
-    #[test]
-    fn test_division_by_zero() {
-        assert!(calculate(5.0, "/", 0.0).is_err());
-    }
-    
-    #[test]
-    fn test_invalid_operator() {
-        assert!(calculate(5.0, "&", 3.0).is_err());
-    }
-    
-    #[test]
-    fn test_parse_and_calculate() {
-        assert_eq!(parse_and_calculate("5 + 3").unwrap(), 8.0);
-        assert_eq!(parse_and_calculate("10 / 2").unwrap(), 5.0);
-        assert!(parse_and_calculate("5 +").is_err());
-        assert!(parse_and_calculate("abc + 3").is_err());
+    fn test_batch() {
+        let script = "# 圆的面积\nr = 2\n\narea(r) = pi * r^2  # 函数\nround(area(r), 2)\n";
+        let (out, error) = batch(script, false);
+        assert_eq!(out, "r = 2\n已定义函数 area(r) = pi * r^2\n12.57\n");
+        assert!(error.is_none());
+        
+        let (out, _) = batch("roots(x^3 - 1)", false);
+        assert_eq!(out, "[1]\n（另有复根 -0.5 + 0.8660254037844386i, -0.5 - 0.8660254037844386i）\n");
+
+        let (out, error) = batch("1 + 1\n2 / 0\nx = 3", false);
+        assert_eq!(out, "2\n");
+        assert_eq!(error.unwrap().to_string(), "第 2 行：除数不能为零");
+        let (_, error) = batch(":mode rational", false);
+        assert_eq!(error.unwrap().to_string(), "第 1 行：批处理模式不支持命令 ':mode rational'");
     }
     
     #[test]
-    fn test_advanced_calculator() {
-        use advanced::*;
-        
-        assert_eq!(advanced_calculate(2.0, "**", 3.0).unwrap(), 8.0);
-        assert_eq!(advanced_calculate(10.0, "%", 3.0).unwrap(), 1.0);
+    fn test_batch_json() {
+        let (out, error) = batch("x = 3\n2 km in m\nsolve(x^2 = 4, x, 1)\n(1 +", true);
+        let records: Vec<serde_json::Value> = out.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(records[0], serde_json::json!({"line": 1, "input": "x = 3", "kind": "assignment", "name": "x", "value": "3", "number": 3.0}));
+        assert_eq!(records[1]["value"], "2000 m");
+        assert!(records[1].get("number").is_none());
+        assert_eq!(records[2]["number"], 2.0);
+        assert!(records[2]["notes"][0].as_str().unwrap().starts_with("牛顿法"), "{}", records[2]);
+        assert_eq!(records[3]["kind"], "error");
+        assert_eq!(records[3]["column"], 5);
+        assert!(matches!(error, Some(BatchError::Statement { line: 4, error: CalcError::Parse(_), .. })));
     }
     
     #[test]
@@ -213,52 +702,109 @@ mod tests {
         history.clear_history();
         history.show_history();
     }
+    
+    #[test]
+    fn test_history_file() {
+        use advanced::*;
+        
+        let path = std::env::temp_dir().join(format!("calculator_history_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        
+        let mut history = CalculatorHistory::open(&path).unwrap();
+        history.add_calculation("x = 2", 2.0);
+        history.add_calculation("sqrt(2)\t* 2", 2.0f64.sqrt() * 2.0);
+        
+        let mut reloaded = CalculatorHistory::open(&path).unwrap();
+        assert_eq!(reloaded.get(2).unwrap().expression, "sqrt(2)\t* 2");
+        assert_eq!(reloaded.results().collect::<Vec<_>>(), history.results().collect::<Vec<_>>());
+        assert_eq!(reloaded.search("x").len(), 1);
+        assert!(reloaded.get(0).is_none());
+        
+        reloaded.clear_history();
+        assert!(CalculatorHistory::open(&path).unwrap().get(1).is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn test_history_reload_keeps_number_kind() {
+        use advanced::*;
+        
+        let path = std::env::temp_dir().join(format!("calculator_history_kind_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        
+        let distance = "2.001 km".parse::<Value>().unwrap().convert(NumberMode::Float).unwrap();
+        let mut history = CalculatorHistory::open(&path).unwrap();
+        history.add_calculation("1 / 3", 1.0 / 3.0);
+        history.add_calculation("2001 m in km", distance.clone());
+        history.add_calculation("1/3 + 1/6", "1/2".parse::<Value>().unwrap().convert(NumberMode::Rational).unwrap());
+        
+        let reloaded = CalculatorHistory::open(&path).unwrap();
+        let shown: Vec<String> = reloaded.results().map(|value| value.to_string()).collect();
+        assert_eq!(shown, ["0.3333333333333333", "2.001 km", "1/2"]);
+        assert_eq!(reloaded.get(1).unwrap().result, Value::from(1.0 / 3.0));
+        assert!(reloaded.get(2).unwrap().result.is_float());
+        assert!(!reloaded.get(3).unwrap().result.is_float());
+        
+        // 损坏的行不能跳过，否则后面记录的编号会变
+        let mut content = std::fs::read_to_string(&path).unwrap();
+        content.insert_str(0, "2\tx = 2\n");
+        std::fs::write(&path, content).unwrap();
+        let error = CalculatorHistory::open(&path).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with("第 1 行："), "{}", error);
+        std::fs::remove_file(&path).unwrap();
+    }
 }
 
-/// 使用示例和学习要点
-/// 
-/// 这个计算器项目演示了以下 Rust 概念：
-/// 
-/// 1. **函数定义和调用**：
-///    - main() 函数作为程序入口
-///    - 自定义函数 parse_and_calculate() 和 calculate()
-/// 
-/// 2. **变量和可变性**：
-///    - let mut input 声明可变变量
-///    - 字符串的创建和修改
-/// 
-/// 3. **控制流**：
-///    - loop 无限循环
-///    - match 模式匹配
-///    - if 条件判断
-/// 
-/// 4. **错误处理基础**：
-///    - Result<T, E> 类型
-///    - ? 操作符用于错误传播
-///    - expect() 方法处理 panic
-/// 
-/// 5. **字符串处理**：
-///    - String 和 &str 的区别
-///    - split_whitespace() 分割字符串
-///    - trim() 去除空白字符
-/// 
-/// 6. **集合类型**：
-///    - Vec<T> 动态数组
-///    - collect() 收集迭代器结果
-/// 
-/// 7. **类型转换**：
-///    - parse() 方法将字符串转换为数字
-///    - to_string() 将其他类型转换为字符串
-/// 
-/// 8. **模块系统**：
-///    - mod advanced 子模块
-///    - pub 关键字控制可见性
-/// 
-/// 9. **结构体和方法**：
-///    - CalculatorHistory 结构体
-///    - impl 块定义方法
-/// 
-/// 10. **测试**：
-///     - #[cfg(test)] 测试模块
-///     - #[test] 测试函数
-///     - assert_eq! 和 assert! 宏
\ No newline at end of file
+// 使用示例和学习要点
+// 
+// 这个计算器项目演示了以下 Rust 概念：
+// 
+// 1. **函数定义和调用**：
+//    - main() 函数作为程序入口
+//    - 自定义函数 parse_and_calculate()、evaluate() 和 calculate()
+// 
+// 2. **变量和可变性**：
+//    - let mut input 声明可变变量
+//    - 字符串的创建和修改
+// 
+// 3. **控制流**：
+//    - loop 无限循环
+//    - match 模式匹配
+//    - if 条件判断
+// 
+// 4. **错误处理基础**：
+//    - Result<T, E> 类型
+//    - ? 操作符用于错误传播
+//    - expect() 方法处理 panic
+// 
+// 5. **字符串处理**：
+//    - String 和 &str 的区别
+//    - chars() 逐字符扫描输入（lexer 模块）
+//    - trim() 去除空白字符
+// 
+// 6. **集合类型**：
+//    - Vec<T> 动态数组
+//    - collect() 收集迭代器结果
+// 
+// 7. **类型转换**：
+//    - parse() 方法将字符串转换为数字
+//    - to_string() 将其他类型转换为字符串
+// 
+// 8. **模块系统**：
+//    - mod advanced 子模块
+//    - 计算引擎是库中的模块，lexer、parser 等子模块放在 calculator/ 目录下
+//    - pub 关键字控制可见性，pub use 导出引擎的公共接口
+// 
+// 9. **结构体和方法**：
+//    - CalculatorHistory 结构体
+//    - impl 块定义方法
+// 
+// 10. **测试**：
+//     - #[cfg(test)] 测试模块
+//     - #[test] 测试函数
+//     - assert_eq! 和 assert! 宏
+// 
+// 11. **递归数据结构**：
+//     - Expr 枚举用 Box 表示递归的表达式树
+//     - evaluate() 通过 match 递归求值
@@ -0,0 +1,98 @@
+//! 计算器的错误类型
+
+use super::functions::Arity;
+use super::number::WordSize;
+use super::parser::ParseError;
+use num_bigint::BigInt;
+use std::fmt;
+
+/// 解析或求值时的错误
+///
+/// 调用方可以按种类处理错误，如未定义的变量、超出定义域；同一种类的错误
+/// 各不相同时带一条说明。`Display` 输出的就是 REPL 里显示的错误信息。
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalcError {
+    /// 语法错误，带出错的列
+    Parse(ParseError),
+    /// 除数为零
+    DivisionByZero,
+    /// 未定义的变量
+    UndefinedVariable(String),
+    /// 未定义的函数
+    UndefinedFunction(String),
+    /// 函数的参数个数不对
+    Arity { name: String, arity: Arity, found: usize },
+    /// 程序员模式下检查溢出时，结果超出了字长的范围
+    Overflow { value: BigInt, word: WordSize },
+    /// 函数调用嵌套太深，`function` 是最后调用的函数
+    RecursionLimit { depth: usize, function: Option<String> },
+    /// 解方程、积分等迭代方法没有收敛
+    NoConvergence(String),
+    /// 参数超出定义域或结果不是实数，如 `sqrt(-1)`、`(-8)^(1/3)`、奇异矩阵求逆
+    Domain(String),
+    /// 结果太大，超出了浮点数的范围或无法精确计算，如 `10^400`
+    OutOfRange(String),
+    /// 量纲或矩阵形状不匹配，如 `1 m + 1 s`
+    DimensionMismatch(String),
+    /// 参数或操作数的形式不对，如要求整数却得到小数、函数的用法不对
+    InvalidArgument(String),
+    /// 不支持的运算，如对位运算求导、复数的位运算
+    Unsupported(String),
+    /// 未知的单位
+    UnknownUnit(String),
+    /// 没有第 n 条历史记录；`None` 表示还没有上一次的结果（`ans`）
+    NoResult(Option<usize>),
+    /// 内置的常量、函数、单位和 `ans` 不能重新定义
+    Reserved(String),
+    /// 带说明的错误，如画图时出错的是哪一条曲线
+    Context { context: String, error: Box<CalcError> },
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CalcError::Parse(error) => write!(f, "{}", error),
+            CalcError::DivisionByZero => write!(f, "除数不能为零"),
+            CalcError::UndefinedVariable(name) => write!(f, "未定义的变量 '{}'", name),
+            CalcError::UndefinedFunction(name) => write!(f, "未定义的函数 '{}'", name),
+            CalcError::Arity { name, arity, found } => write!(f, "函数 '{}' {}，但提供了 {} 个", name, arity, found),
+            CalcError::Overflow { value, word } => {
+                write!(f, "结果 {} 超出了 {} 的范围（{} 到 {}）", value, word, word.min(), word.max())
+            }
+            CalcError::RecursionLimit { depth, function } => {
+                write!(f, "函数调用嵌套超过 {} 层，可能存在无限递归", depth)?;
+                match function {
+                    Some(name) => write!(f, "（'{}'）", name),
+                    None => Ok(()),
+                }
+            }
+            CalcError::UnknownUnit(name) => write!(f, "未知的单位 '{}'", name),
+            CalcError::NoResult(Some(n)) => write!(f, "没有第 {} 条历史记录", n),
+            CalcError::NoResult(None) => write!(f, "还没有上一次的结果（ans）"),
+            CalcError::Context { context, error } => write!(f, "{}：{}", context, error),
+            CalcError::NoConvergence(message)
+            | CalcError::Domain(message)
+            | CalcError::OutOfRange(message)
+            | CalcError::DimensionMismatch(message)
+            | CalcError::InvalidArgument(message)
+            | CalcError::Unsupported(message)
+            | CalcError::Reserved(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for CalcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CalcError::Parse(error) => Some(error),
+            CalcError::Context { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for CalcError {
+    fn from(error: ParseError) -> Self {
+        CalcError::Parse(error)
+    }
+}
@@ -0,0 +1,1178 @@
+//! 求值：变量、内置常量、内置函数、用户定义的函数、单位、复数、矩阵、符号表达式
+//! 以及解方程、数值积分和多项式求根
+
+use super::calculate;
+use super::error::CalcError;
+use super::functions::{builtin, AngleMode, Arity, Builtin};
+use super::number::{self, Number, NumberMode, OutputFormat};
+use super::numeric;
+use super::parser::{BinaryOp, Expr, Statement, Syntax};
+use super::symbolic;
+use super::units::{describe, Quantity, Unit, UnitTable};
+use super::value::{Complex, Matrix, Value, I};
+use num_rational::BigRational;
+use num_traits::{One, ToPrimitive, Zero};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// 函数调用的最大嵌套深度
+const MAX_CALL_DEPTH: usize = 100;
+
+/// 内置常量，不能被重新赋值
+const CONSTANTS: &[(&str, f64)] = &[
+    ("pi", std::f64::consts::PI),
+    ("e", std::f64::consts::E),
+];
+
+/// 保存上一次结果的变量名
+const ANS: &str = "ans";
+
+/// 虚数单位的名称，可以被同名的变量遮住
+const IMAGINARY: &str = "i";
+
+/// 用户定义的函数
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub params: Vec<String>,
+    pub body: Expr,
+}
+
+/// 执行一行输入的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// 表达式的值
+    Value(Value),
+    /// 给变量赋值
+    Assigned(String, Value),
+    /// 定义了函数
+    Defined(String),
+}
+
+impl Outcome {
+    /// 按输出格式显示
+    pub fn format(&self, format: OutputFormat) -> String {
+        match self {
+            Outcome::Value(value) => value.format(format),
+            Outcome::Assigned(name, value) => format!("{} = {}", name, value.format(format)),
+            Outcome::Defined(name) => format!("已定义函数 {}", name),
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(OutputFormat::Auto))
+    }
+}
+
+/// 变量和函数环境
+///
+/// 每次求值成功后结果会保存到 `ans`，并追加到历史结果中供 `$n` 引用。
+/// 变量保存求值时的表示，切换数值模式后在使用时再换算。名称依次查找
+/// 函数参数、变量、常量、单位和虚数单位 `i`，所以变量 `m` 会遮住单位米。
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    variables: BTreeMap<String, Value>,
+    functions: BTreeMap<String, Function>,
+    units: UnitTable,
+    angle_mode: AngleMode,
+    mode: NumberMode,
+    /// 复数模式下 `sqrt(-1)`、`ln(-1)` 等返回复数而不是报错
+    complex: bool,
+    output_format: OutputFormat,
+    results: Vec<Value>,
+    /// 最近一次执行中解方程、积分等的收敛情况
+    notes: RefCell<Vec<String>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 执行一条语句
+    pub fn execute(&mut self, statement: Statement) -> Result<Outcome, CalcError> {
+        self.notes.get_mut().clear();
+        let outcome = match statement {
+            Statement::Expr(expr) => Outcome::Value(self.evaluate(&expr)?),
+            Statement::Assign { name, expr } => {
+                check_name(&name)?;
+                let value = self.evaluate(&expr)?;
+                self.variables.insert(name.clone(), value.clone());
+                Outcome::Assigned(name, value)
+            }
+            Statement::Define { name, params, body } => {
+                if builtin(&name).is_some() || symbolic::form(&name).is_some() {
+                    return Err(CalcError::Reserved(format!("'{}' 是内置函数，不能重新定义", name)));
+                }
+                let function = Function { params, body };
+                let signature = format!("{}({}) = {}", name, function.params.join(", "), function.body);
+                self.functions.insert(name, function);
+                return Ok(Outcome::Defined(signature));
+            }
+        };
+
+        if let Outcome::Value(value) | Outcome::Assigned(_, value) = &outcome {
+            self.variables.insert(ANS.to_string(), value.clone());
+            self.results.push(value.clone());
+        }
+        Ok(outcome)
+    }
+
+    /// 计算表达式的值
+    pub fn evaluate(&self, expr: &Expr) -> Result<Value, CalcError> {
+        self.eval(expr, &[], 0)
+    }
+
+    /// 以浮点数计算表达式的值，不论当前是哪种数值模式；结果必须是不带单位的实数
+    pub fn evaluate_float(&self, expr: &Expr, what: &str) -> Result<f64, CalcError> {
+        self.floating().float_arg(expr, what, &[], 0)
+    }
+
+    /// 把表达式看作一元函数，以浮点数求值，用于画图
+    ///
+    /// 变量的确定方式和 `integrate` 相同：只有一个参数的用户函数名，或者表达式中
+    /// 唯一一个未定义的变量。
+    pub fn function<'a>(&'a self, expr: &Expr) -> Result<impl Fn(f64) -> Result<f64, CalcError> + 'a, CalcError> {
+        let (expr, var) = self.unknown(expr, None, &[], 0)?;
+        let env = self.floating();
+        Ok(move |x| env.at(&expr, &var, x, &[], 0))
+    }
+
+    /// 取走最近一次执行时记录的收敛情况，如牛顿法的迭代次数
+    pub fn take_notes(&self) -> Vec<String> {
+        self.notes.take()
+    }
+
+    /// 三角函数使用的角度单位
+    pub fn angle_mode(&self) -> AngleMode {
+        self.angle_mode
+    }
+
+    pub fn set_angle_mode(&mut self, mode: AngleMode) {
+        self.angle_mode = mode;
+    }
+
+    /// 计算时使用的数值表示
+    pub fn mode(&self) -> NumberMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: NumberMode) {
+        self.mode = mode;
+    }
+
+    /// 当前模式下解析输入使用的语法，程序员模式中 `^` 是按位异或
+    pub fn syntax(&self) -> Syntax {
+        match self.mode {
+            NumberMode::Programmer { .. } => Syntax::Programmer,
+            _ => Syntax::Standard,
+        }
+    }
+
+    pub fn complex(&self) -> bool {
+        self.complex
+    }
+
+    pub fn set_complex(&mut self, complex: bool) {
+        self.complex = complex;
+    }
+
+    /// 结果的显示格式
+    pub fn output_format(&self) -> OutputFormat {
+        self.output_format
+    }
+
+    pub fn set_output_format(&mut self, format: OutputFormat) {
+        self.output_format = format;
+    }
+
+    /// 替换历史结果，例如从历史文件加载之后
+    pub fn set_results(&mut self, results: impl IntoIterator<Item = Value>) {
+        self.results = results.into_iter().collect();
+    }
+
+    pub fn units(&self) -> &UnitTable {
+        &self.units
+    }
+
+    /// 定义新单位：有表达式时用它的值定义，如 `furlong = 220 yd`，
+    /// 否则定义一个自成量纲的基本单位
+    pub fn define_unit(&mut self, name: &str, expr: Option<&Expr>) -> Result<(), CalcError> {
+        check_unit_name(name)?;
+        match expr {
+            Some(expr) => {
+                let quantity = self.evaluate(expr)?.into_quantity("单位的定义")?;
+                self.units.define(name, &quantity)
+            }
+            None => self.units.define_base(name),
+        }
+    }
+
+    /// 按名称排序的变量（包括 `ans`）
+    pub fn variables(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.variables.iter().map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// 按名称排序的函数
+    pub fn functions(&self) -> impl Iterator<Item = (&str, &Function)> {
+        self.functions.iter().map(|(name, function)| (name.as_str(), function))
+    }
+
+    /// 删除同名的变量和函数，返回是否删除了任何内容
+    pub fn remove(&mut self, name: &str) -> bool {
+        let variable = self.variables.remove(name).is_some();
+        let function = self.functions.remove(name).is_some();
+        variable || function
+    }
+
+    /// `locals` 是当前函数调用的参数绑定，`depth` 是调用嵌套深度
+    ///
+    /// 返回值总是当前数值模式的表示。
+    fn eval(&self, expr: &Expr, locals: &[(&str, Value)], depth: usize) -> Result<Value, CalcError> {
+        match expr {
+            Expr::Number(value) => value.convert(self.mode).map(Value::from),
+            Expr::Var(name) => self.lookup(name, locals)?.convert(self.mode),
+            Expr::HistoryRef(n) => n.checked_sub(1)
+                .and_then(|index| self.results.get(index))
+                .ok_or(CalcError::NoResult(Some(*n)))?
+                .convert(self.mode),
+            // 程序员模式下取负也可能溢出，如 u8 的 -1
+            Expr::Neg(inner) => match self.eval(inner, locals, depth)? {
+                Value::Scalar(quantity) => {
+                    Ok(Value::Scalar(Quantity::new((-quantity.number).convert(self.mode)?, quantity.unit)))
+                }
+                Value::Complex(z) => Ok(Value::Complex(-z)),
+                Value::Matrix(matrix) => matrix.map(|value| (-value.clone()).convert(self.mode)).map(Value::Matrix),
+                Value::Symbolic(expr) => self.symbolic_value(symbolic::simplify(&Expr::Neg(Box::new(expr)))),
+            },
+            Expr::Not(inner) => {
+                let value = real(self.eval(inner, locals, depth)?, "位运算的操作数")?;
+                let value = value.to_integer().map_err(|_| CalcError::InvalidArgument(format!("位运算只能用于整数，得到 {}", value)))?;
+                Number::Integer(self.mode.fit(!value, true)?).convert(self.mode).map(Value::from)
+            }
+            Expr::Binary { .. } => {
+                // 沿左侧操作数逐个求值，`1 + 2 + ... + n` 这样的长链不会随长度递归
+                let mut operands = Vec::new();
+                let mut leftmost = expr;
+                while let Expr::Binary { op, lhs, rhs } = leftmost {
+                    operands.push((*op, rhs));
+                    leftmost = lhs;
+                }
+                let mut value = self.eval(leftmost, locals, depth)?;
+                for (op, rhs) in operands.into_iter().rev() {
+                    value = self.binary(op, value, self.eval(rhs, locals, depth)?)?;
+                }
+                Ok(value)
+            }
+            Expr::Convert { expr, unit } => {
+                let target = self.unit_of(unit)?;
+                let quantity = self.eval(expr, locals, depth)?.into_quantity("换算的值")?;
+                self.convert_to(quantity, &target).map(Value::Scalar)
+            }
+            Expr::Matrix(rows) => {
+                let rows = rows.iter()
+                    .map(|row| row.iter()
+                        .map(|element| real(self.eval(element, locals, depth)?, "矩阵元素"))
+                        .collect())
+                    .collect::<Result<Vec<Vec<Number>>, CalcError>>()?;
+                Matrix::new(rows).map(Value::Matrix)
+            }
+            Expr::Equation { .. } => Err(CalcError::InvalidArgument("'=' 只能用在 eval 等函数的参数中".to_string())),
+            Expr::Call { name, args } if symbolic::form(name).is_some() => self.form(name, args, locals, depth),
+            Expr::Call { name, args } => {
+                let Some(function) = self.functions.get(name) else {
+                    let builtin = builtin(name).ok_or_else(|| CalcError::UndefinedFunction(name.clone()))?;
+                    let args = args.iter()
+                        .map(|arg| self.eval(arg, locals, depth))
+                        .collect::<Result<Vec<Value>, CalcError>>()?;
+                    return self.call_builtin(builtin, args);
+                };
+                if args.len() != function.params.len() {
+                    return Err(CalcError::Arity {
+                        name: name.clone(),
+                        arity: Arity::Exact(function.params.len()),
+                        found: args.len(),
+                    });
+                }
+                if depth >= MAX_CALL_DEPTH {
+                    return Err(CalcError::RecursionLimit { depth: MAX_CALL_DEPTH, function: Some(name.clone()) });
+                }
+
+                let mut frame = Vec::with_capacity(args.len());
+                for (param, arg) in function.params.iter().zip(args) {
+                    frame.push((param.as_str(), self.eval(arg, locals, depth)?));
+                }
+                self.eval(&function.body, &frame, depth + 1)
+            }
+        }
+    }
+
+    /// 按操作数的表示选择浮点数、整数或分数运算
+    fn arithmetic(&self, op: BinaryOp, lhs: Number, rhs: Number) -> Result<Number, CalcError> {
+        match (lhs, rhs) {
+            (Number::Float(a), Number::Float(b)) if !op.is_bitwise() => {
+                calculate(a, op.symbol(), b).map(Number::Float)
+            }
+            (Number::Integer(a), Number::Integer(b)) => {
+                let value = number::integer_op(op, &a, &b)?;
+                self.mode.fit(value, op.is_bitwise()).map(Number::Integer)
+            }
+            (a, b) => {
+                let value = number::rational_op(op, &a.to_rational()?, &b.to_rational()?)?;
+                Number::Rational(value).convert(self.mode)
+            }
+        }
+    }
+
+    /// 参数按表达式传递的函数：求导、化简、代入求值、解方程、积分和求根
+    fn form(&self, name: &str, args: &[Expr], locals: &[(&str, Value)], depth: usize) -> Result<Value, CalcError> {
+        let usage = || CalcError::InvalidArgument(format!("用法：{}", symbolic::form(name).unwrap_or_default()));
+        match (name, args) {
+            ("diff", [expr, Expr::Var(var)]) => {
+                let expr = self.expand(expr, Some(var), locals, depth)?;
+                self.symbolic_value(symbolic::simplify(&symbolic::diff(&expr, var)?))
+            }
+            ("simplify", [expr]) => {
+                let expr = self.expand(expr, None, locals, depth)?;
+                symbolic::check_depth(&expr)?;
+                self.symbolic_value(symbolic::simplify(&expr))
+            }
+            ("eval", [expr, bindings @ ..]) => {
+                let mut frame = Vec::with_capacity(bindings.len() + locals.len());
+                for binding in bindings {
+                    let Expr::Equation { lhs, rhs } = binding else {
+                        return Err(CalcError::InvalidArgument(format!("eval 的绑定应写成 x = 值，得到 {}", binding)));
+                    };
+                    let Expr::Var(name) = &**lhs else {
+                        return Err(CalcError::InvalidArgument(format!("'=' 左边只能是变量名，得到 {}", lhs)));
+                    };
+                    frame.push((name.as_str(), self.eval(rhs, locals, depth)?));
+                }
+                frame.extend(locals.iter().cloned());
+                let expr = self.expand(expr, None, &frame, depth)?;
+                self.eval(&expr, &frame, depth)
+            }
+            ("solve", [equation, Expr::Var(var), start @ ..]) if matches!(start.len(), 1 | 2) => {
+                // f = g 化成 f - g = 0
+                let expr = match self.expand(equation, Some(var), locals, depth)? {
+                    Expr::Equation { lhs, rhs } => Expr::Binary { op: BinaryOp::Sub, lhs, rhs },
+                    expr => expr,
+                };
+                let start = start.iter()
+                    .map(|arg| self.float_arg(arg, "solve 的初值或区间", locals, depth))
+                    .collect::<Result<Vec<f64>, CalcError>>()?;
+                let env = self.floating();
+                let f = |x: f64| env.at(&expr, var, x, locals, depth);
+                let root = if let [a, b] = start[..] {
+                    numeric::bisection(&mut |x| f(x), a, b)?
+                } else {
+                    // 符号导数按弧度计算，角度模式下改用差商
+                    let derivative = symbolic::diff(&expr, var)
+                        .ok()
+                        .filter(|_| self.angle_mode == AngleMode::Radians)
+                        .map(|derivative| symbolic::simplify(&derivative));
+                    let mut df = |x: f64| match &derivative {
+                        Some(derivative) => env.at(derivative, var, x, locals, depth),
+                        None => {
+                            let h = 1e-6 * x.abs().max(1.0);
+                            Ok((f(x + h)? - f(x - h)?) / (2.0 * h))
+                        }
+                    };
+                    numeric::newton(&mut |x| f(x), &mut df, start[0])?
+                };
+                self.note(root.to_string());
+                Number::Float(root.x).convert(self.mode).map(Value::from)
+            }
+            ("integrate", [integrand, rest @ ..]) if matches!(rest.len(), 2 | 3) => {
+                let (var, a, b) = match rest {
+                    [Expr::Var(var), a, b] => (Some(var.as_str()), a, b),
+                    [a, b] => (None, a, b),
+                    _ => return Err(usage()),
+                };
+                let (expr, var) = self.unknown(integrand, var, locals, depth)?;
+                let a = self.float_arg(a, "积分下限", locals, depth)?;
+                let b = self.float_arg(b, "积分上限", locals, depth)?;
+                let env = self.floating();
+                let integral = numeric::integrate(&mut |x| env.at(&expr, &var, x, locals, depth), a, b)?;
+                self.note(integral.to_string());
+                Number::Float(integral.value).convert(self.mode).map(Value::from)
+            }
+            ("roots", [polynomial, rest @ ..]) if rest.len() <= 1 => {
+                let var = match rest {
+                    [Expr::Var(var)] => Some(var.as_str()),
+                    [] => None,
+                    _ => return Err(usage()),
+                };
+                let (expr, var) = self.unknown(polynomial, var, locals, depth)?;
+                let coefficients = symbolic::polynomial(&expr, &var, &mut |coefficient| {
+                    self.float_arg(coefficient, "多项式的系数", locals, depth)
+                })?;
+                let (real, complex): (Vec<Complex>, Vec<Complex>) = numeric::roots(&coefficients)?
+                    .into_iter()
+                    .partition(|z| z.im == 0.0);
+                let list = |roots: &[Complex]| {
+                    roots.iter().map(|z| z.format(self.output_format)).collect::<Vec<_>>().join(", ")
+                };
+                if real.is_empty() {
+                    return Err(CalcError::Domain(format!("多项式没有实根，复根为 {}", list(&complex))));
+                }
+                if !complex.is_empty() {
+                    self.note(format!("另有复根 {}", list(&complex)));
+                }
+                let row = real.iter()
+                    .map(|z| Number::Float(z.re).convert(self.mode))
+                    .collect::<Result<Vec<Number>, CalcError>>()?;
+                Matrix::new(vec![row]).map(Value::Matrix)
+            }
+            _ => Err(usage()),
+        }
+    }
+
+    /// 确定被积函数或多项式的变量，返回展开后的表达式和变量名
+    ///
+    /// 没有指定变量时，只有一个参数的用户函数名（如 `integrate(f, 0, 1)` 中的 `f`）
+    /// 用它的参数，否则用表达式中唯一一个未定义的变量。
+    fn unknown(&self, expr: &Expr, var: Option<&str>, locals: &[(&str, Value)], depth: usize) -> Result<(Expr, String), CalcError> {
+        if let Some(var) = var {
+            return Ok((self.expand(expr, Some(var), locals, depth)?, var.to_string()));
+        }
+        if let Expr::Var(name) = expr {
+            if let (Some(function), Err(_)) = (self.functions.get(name), self.lookup(name, locals)) {
+                if let [param] = function.params.as_slice() {
+                    let call = Expr::Call { name: name.clone(), args: vec![Expr::Var(param.clone())] };
+                    return Ok((self.expand(&call, Some(param), locals, depth)?, param.clone()));
+                }
+            }
+        }
+        let expr = self.expand(expr, None, locals, depth)?;
+        let mut free: Vec<String> = symbolic::variables(&expr)
+            .into_iter()
+            .filter(|name| self.lookup(name, locals).is_err())
+            .collect();
+        match free.len() {
+            0 => Ok((expr, "x".to_string())),
+            1 => Ok((expr, free.remove(0))),
+            _ => Err(CalcError::InvalidArgument(format!("无法确定变量（{}），请在参数中指定", free.join("、")))),
+        }
+    }
+
+    /// 以浮点数求 `var = x` 时表达式的值
+    fn at(&self, expr: &Expr, var: &str, x: f64, locals: &[(&str, Value)], depth: usize) -> Result<f64, CalcError> {
+        let mut frame = Vec::with_capacity(locals.len() + 1);
+        frame.push((var, Value::from(x)));
+        frame.extend(locals.iter().cloned());
+        Ok(real(self.eval(expr, &frame, depth)?, "函数值")?.to_f64())
+    }
+
+    /// 求值后按浮点数使用的参数，如积分上下限
+    fn float_arg(&self, arg: &Expr, what: &str, locals: &[(&str, Value)], depth: usize) -> Result<f64, CalcError> {
+        Ok(real(self.eval(arg, locals, depth)?, what)?.to_f64())
+    }
+
+    /// 数值方法总是按浮点数计算，其他模式下用一份浮点模式的副本
+    fn floating(&self) -> Cow<'_, Environment> {
+        if self.mode == NumberMode::Float {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(Environment { mode: NumberMode::Float, ..self.clone() })
+        }
+    }
+
+    /// 记录收敛情况
+    fn note(&self, note: String) {
+        self.notes.borrow_mut().push(note);
+    }
+
+    /// 展开成纯粹的符号表达式
+    ///
+    /// 值是符号表达式的变量和历史结果换成对应的表达式，用户定义的函数换成
+    /// 代入参数后的函数体，其余名称保持为符号。`keep` 是求导的变量，不展开。
+    fn expand(&self, expr: &Expr, keep: Option<&str>, locals: &[(&str, Value)], depth: usize) -> Result<Expr, CalcError> {
+        if depth >= MAX_CALL_DEPTH {
+            return Err(CalcError::RecursionLimit { depth: MAX_CALL_DEPTH, function: None });
+        }
+        symbolic::check_depth(expr)?;
+        symbolic::rewrite(expr, &mut |node| match node {
+            Expr::Var(name) if Some(name.as_str()) != keep => match self.lookup(name, locals) {
+                Ok(Value::Symbolic(expr)) => Ok(Some(expr)),
+                _ => Ok(None),
+            },
+            Expr::HistoryRef(n) => match n.checked_sub(1).and_then(|index| self.results.get(index)) {
+                Some(Value::Symbolic(expr)) => Ok(Some(expr.clone())),
+                _ => Ok(None),
+            },
+            Expr::Call { name, args } => match self.functions.get(name) {
+                Some(function) if function.params.len() == args.len() => {
+                    let args = args.iter()
+                        .map(|arg| self.expand(arg, keep, locals, depth))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    symbolic::check_depth(&function.body)?;
+                    let bindings: Vec<(&str, Expr)> = function.params.iter().map(String::as_str).zip(args).collect();
+                    let body = symbolic::substitute(&function.body, &bindings);
+                    self.expand(&body, keep, locals, depth + 1).map(Some)
+                }
+                _ => Ok(None),
+            },
+            _ => Ok(None),
+        })
+    }
+
+    /// 化简后只剩数字时换回当前模式的实数
+    fn symbolic_value(&self, expr: Expr) -> Result<Value, CalcError> {
+        match expr {
+            Expr::Number(value) => value.convert(self.mode).map(Value::from),
+            expr => Ok(Value::Symbolic(expr)),
+        }
+    }
+
+    /// 调用内置函数，复数、矩阵和符号参数在这里处理，实数参数交给函数表
+    fn call_builtin(&self, builtin: &Builtin, args: Vec<Value>) -> Result<Value, CalcError> {
+        builtin.check_arity(args.len())?;
+        if args.iter().any(|arg| matches!(arg, Value::Symbolic(_))) {
+            let args = args.into_iter().map(symbolic_expr).collect::<Result<_, _>>()?;
+            let call = Expr::Call { name: builtin.name.to_string(), args };
+            return self.symbolic_value(symbolic::simplify(&call));
+        }
+        match (builtin.name, args.as_slice()) {
+            ("transpose", [Value::Matrix(matrix)]) => return Ok(Value::Matrix(matrix.transpose())),
+            ("det", [Value::Matrix(matrix)]) => return matrix.determinant()?.convert(self.mode).map(Value::from),
+            ("inv", [Value::Matrix(matrix)]) => {
+                return matrix.inverse()?.map(|value| value.convert(self.mode)).map(Value::Matrix);
+            }
+            (name, [Value::Complex(z)]) => return self.complex_function(name, *z),
+            (name @ ("sqrt" | "ln"), [Value::Scalar(x)]) if self.complex && x.unit.is_none() && x.number.is_negative() => {
+                return self.complex_function(name, x.number.to_f64().into());
+            }
+            _ => {}
+        }
+        let what = format!("函数 '{}' 的参数", builtin.name);
+        let args = args.into_iter()
+            .map(|arg| real(arg, &what))
+            .collect::<Result<Vec<Number>, CalcError>>()?;
+        builtin.evaluate(&args, self.angle_mode, self.mode).map(Value::from)
+    }
+
+    /// 参数是复数的函数
+    fn complex_function(&self, name: &str, z: Complex) -> Result<Value, CalcError> {
+        let real = |x: f64| Number::Float(x).convert(self.mode).map(Value::from);
+        match name {
+            "abs" => real(z.abs()),
+            "arg" => real(self.angle_mode.angle(z.arg())),
+            "re" => real(z.re),
+            "im" => real(z.im),
+            "conj" => self.complex_value(z.conj()),
+            "sqrt" => self.complex_value(z.sqrt()),
+            "exp" => self.complex_value(z.exp()),
+            "ln" => self.complex_value(z.ln()?),
+            _ => Err(CalcError::Unsupported(format!("函数 '{}' 不支持复数参数", name))),
+        }
+    }
+
+    /// 虚部为零的复数换回当前模式的实数
+    fn complex_value(&self, z: Complex) -> Result<Value, CalcError> {
+        if z.im == 0.0 {
+            Number::Float(z.re).convert(self.mode).map(Value::from)
+        } else {
+            Ok(Value::Complex(z))
+        }
+    }
+
+    /// 按操作数的种类分派二元运算
+    fn binary(&self, op: BinaryOp, lhs: Value, rhs: Value) -> Result<Value, CalcError> {
+        match (lhs, rhs) {
+            (lhs @ Value::Symbolic(_), rhs) | (lhs, rhs @ Value::Symbolic(_)) => {
+                let expr = Expr::Binary { op, lhs: Box::new(symbolic_expr(lhs)?), rhs: Box::new(symbolic_expr(rhs)?) };
+                self.symbolic_value(symbolic::simplify(&expr))
+            }
+            (Value::Scalar(lhs), Value::Scalar(rhs)) => {
+                // 复数模式下负数的非整数次幂是复数，如 (-8)^(1/3)
+                let complex_power = self.complex
+                    && op == BinaryOp::Pow
+                    && lhs.unit.is_none()
+                    && lhs.number.is_negative()
+                    && rhs.number.to_integer().is_err();
+                if complex_power {
+                    return self.complex_op(op, Value::Scalar(lhs), Value::Scalar(rhs));
+                }
+                self.combine(op, lhs, rhs).map(Value::Scalar)
+            }
+            (lhs @ Value::Matrix(_), rhs) | (lhs, rhs @ Value::Matrix(_)) => self.matrix_op(op, lhs, rhs),
+            (lhs, rhs) => self.complex_op(op, lhs, rhs),
+        }
+    }
+
+    /// 至少一边是复数的运算
+    fn complex_op(&self, op: BinaryOp, lhs: Value, rhs: Value) -> Result<Value, CalcError> {
+        let complex = |value: Value| match value {
+            Value::Complex(z) => Ok(z),
+            value => real(value, "复数运算的操作数").map(|x| Complex::from(x.to_f64())),
+        };
+        self.complex_value(Complex::apply(op, complex(lhs)?, complex(rhs)?)?)
+    }
+
+    /// 至少一边是矩阵的运算：同形矩阵加减、矩阵乘法、矩阵与数的乘除和方阵的整数次幂
+    fn matrix_op(&self, op: BinaryOp, lhs: Value, rhs: Value) -> Result<Value, CalcError> {
+        const SCALAR: &str = "与矩阵运算的数";
+        let kind = |value: &Value| match value {
+            Value::Matrix(matrix) => matrix.shape(),
+            _ => "数".to_string(),
+        };
+        match (op, lhs, rhs) {
+            (BinaryOp::Add | BinaryOp::Sub, Value::Matrix(a), Value::Matrix(b)) => {
+                a.elementwise(op, &b, |x, y| self.arithmetic(op, x, y)).map(Value::Matrix)
+            }
+            (BinaryOp::Mul, Value::Matrix(a), Value::Matrix(b)) => self.multiply(&a, &b).map(Value::Matrix),
+            (BinaryOp::Mul, Value::Matrix(matrix), k) | (BinaryOp::Mul, k, Value::Matrix(matrix)) => {
+                let k = real(k, SCALAR)?;
+                matrix.map(|x| self.arithmetic(op, x.clone(), k.clone())).map(Value::Matrix)
+            }
+            (BinaryOp::Div, Value::Matrix(matrix), k) if !matches!(k, Value::Matrix(_)) => {
+                let k = real(k, SCALAR)?;
+                matrix.map(|x| self.arithmetic(op, x.clone(), k.clone())).map(Value::Matrix)
+            }
+            (BinaryOp::Pow, Value::Matrix(matrix), n) if !matches!(n, Value::Matrix(_)) => {
+                self.matrix_power(&matrix, real(n, "矩阵的指数")?).map(Value::Matrix)
+            }
+            (BinaryOp::Add | BinaryOp::Sub, lhs, rhs) => {
+                Err(CalcError::DimensionMismatch(format!("矩阵形状不匹配：{} {} {}", kind(&lhs), op.symbol(), kind(&rhs))))
+            }
+            (BinaryOp::Div, _, Value::Matrix(_)) => Err(CalcError::Unsupported("不能除以矩阵，可以乘以它的逆矩阵 inv(...)".to_string())),
+            (BinaryOp::Pow, _, Value::Matrix(_)) => Err(CalcError::Unsupported("矩阵不能作为指数".to_string())),
+            (op, _, _) => Err(CalcError::Unsupported(format!("矩阵不支持运算符 {}", op.symbol()))),
+        }
+    }
+
+    fn multiply(&self, a: &Matrix, b: &Matrix) -> Result<Matrix, CalcError> {
+        a.product(
+            b,
+            |x, y| self.arithmetic(BinaryOp::Add, x, y),
+            |x, y| self.arithmetic(BinaryOp::Mul, x, y),
+        )
+    }
+
+    /// 方阵的整数次幂，负数次幂先求逆
+    fn matrix_power(&self, matrix: &Matrix, exponent: Number) -> Result<Matrix, CalcError> {
+        let n = exponent.to_integer().ok()
+            .and_then(|n| n.to_i32())
+            .ok_or_else(|| CalcError::InvalidArgument(format!("矩阵只能取整数次幂，得到 {}", exponent)))?;
+        if !matrix.is_square() {
+            return Err(CalcError::DimensionMismatch(format!("只有方阵才能取幂，得到 {} 矩阵", matrix.shape())));
+        }
+        let convert = |value: &Number| value.convert(self.mode);
+        let mut base = if n < 0 { matrix.inverse()?.map(convert)? } else { matrix.clone() };
+        let mut result = Matrix::identity(matrix.rows()).map(convert)?;
+        let mut n = n.unsigned_abs();
+        while n > 0 {
+            if n & 1 == 1 {
+                result = self.multiply(&result, &base)?;
+            }
+            n >>= 1;
+            if n > 0 {
+                base = self.multiply(&base, &base)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// 带单位的二元运算
+    ///
+    /// 加减和取余要求量纲相同，右边先换算成左边的单位；乘除合并单位；
+    /// 乘方要求带单位的底数配整数指数；位运算不接受单位。
+    fn combine(&self, op: BinaryOp, lhs: Quantity, rhs: Quantity) -> Result<Quantity, CalcError> {
+        match op {
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Rem => {
+                if self.units.dimension(&lhs.unit)? != self.units.dimension(&rhs.unit)? {
+                    return Err(CalcError::DimensionMismatch(format!(
+                        "量纲不匹配：{} {} {}",
+                        unit_name(&lhs.unit), op.symbol(), unit_name(&rhs.unit)
+                    )));
+                }
+                let rhs = self.rescale(rhs, &lhs.unit)?;
+                Ok(Quantity::new(self.arithmetic(op, lhs.number, rhs)?, lhs.unit))
+            }
+            BinaryOp::Mul | BinaryOp::Div => {
+                let (unit, factor) = self.units.align(&rhs.unit, &lhs.unit)?;
+                let rhs = self.affine(rhs.number, factor, BigRational::zero(), BigRational::zero())?;
+                let unit = lhs.unit.combine(&unit, if op == BinaryOp::Mul { 1 } else { -1 });
+                let number = self.arithmetic(op, lhs.number, rhs)?;
+                self.simplify(Quantity::new(number, unit))
+            }
+            BinaryOp::Pow => {
+                let exponent = plain(rhs, "指数")?;
+                if lhs.unit.is_none() {
+                    return self.arithmetic(op, lhs.number, exponent).map(Quantity::from);
+                }
+                let n = exponent.to_integer().ok()
+                    .and_then(|n| n.to_i32())
+                    .ok_or_else(|| CalcError::InvalidArgument(format!("带单位的量只能取整数次幂，得到 {}", exponent)))?;
+                Ok(Quantity::new(self.arithmetic(op, lhs.number, exponent)?, lhs.unit.powi(n)))
+            }
+            BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor | BinaryOp::Shl | BinaryOp::Shr => {
+                let (lhs, rhs) = (plain(lhs, "位运算的操作数")?, plain(rhs, "位运算的操作数")?);
+                self.arithmetic(op, lhs, rhs).map(Quantity::from)
+            }
+        }
+    }
+
+    /// 把量的数值换算成以 `unit` 表示，调用前已经确认量纲相同
+    fn rescale(&self, quantity: Quantity, unit: &Unit) -> Result<Number, CalcError> {
+        if quantity.unit == *unit {
+            return Ok(quantity.number);
+        }
+        let factor = self.units.scale(&quantity.unit)? / self.units.scale(unit)?;
+        self.affine(quantity.number, factor, BigRational::zero(), BigRational::zero())
+    }
+
+    /// 单位相互抵消后（如 `MiB/KiB`）换算成纯数值
+    fn simplify(&self, quantity: Quantity) -> Result<Quantity, CalcError> {
+        if quantity.unit.is_none() || !self.units.dimension(&quantity.unit)?.is_empty() {
+            return Ok(quantity);
+        }
+        self.rescale(quantity, &Unit::default()).map(Quantity::from)
+    }
+
+    /// `expr in unit`
+    ///
+    /// 单独的温度换算要考虑零点：基本单位的值 = (值 + offset) * factor。
+    fn convert_to(&self, quantity: Quantity, target: &Unit) -> Result<Quantity, CalcError> {
+        let (from, to) = (self.units.dimension(&quantity.unit)?, self.units.dimension(target)?);
+        if from != to {
+            return Err(CalcError::DimensionMismatch(format!(
+                "无法把 {} 换算成 {}：量纲不同（{} 和 {}）",
+                unit_name(&quantity.unit), target, describe(&from), describe(&to)
+            )));
+        }
+        let factor = self.units.scale(&quantity.unit)? / self.units.scale(target)?;
+        let (before, after) = (self.units.offset(&quantity.unit), self.units.offset(target));
+        let number = self.affine(quantity.number, factor, before, after)?;
+        Ok(Quantity::new(number, target.clone()))
+    }
+
+    /// `in` 后面的单位表达式，如 `km/h`、`m^2`
+    ///
+    /// 这里的名称只当作单位，即使有同名的变量。
+    fn unit_of(&self, expr: &Expr) -> Result<Unit, CalcError> {
+        match expr {
+            Expr::Var(name) if self.units.get(name).is_some() => Ok(Unit::named(name)),
+            Expr::Var(name) => Err(CalcError::UnknownUnit(name.clone())),
+            Expr::Binary { op: op @ (BinaryOp::Mul | BinaryOp::Div), lhs, rhs } => {
+                let sign = if *op == BinaryOp::Mul { 1 } else { -1 };
+                Ok(self.unit_of(lhs)?.combine(&self.unit_of(rhs)?, sign))
+            }
+            Expr::Binary { op: BinaryOp::Pow, lhs, rhs } => {
+                let n = match &**rhs {
+                    Expr::Number(n) => n.to_integer().ok().and_then(|n| n.to_i32()),
+                    _ => None,
+                };
+                let n = n.ok_or_else(|| CalcError::InvalidArgument(format!("单位只能取整数次幂，得到 {}", rhs)))?;
+                Ok(self.unit_of(lhs)?.powi(n))
+            }
+            _ => Err(CalcError::InvalidArgument(format!("'in' 后面只能是单位，如 km/h，得到 {}", expr))),
+        }
+    }
+
+    /// 计算 `(number + before) * factor - after`
+    ///
+    /// 浮点数也先按精确值计算，最后只舍入一次，
+    /// 否则 `20 degC in degF` 会得到 67.99999999999994。
+    fn affine(&self, number: Number, factor: BigRational, before: BigRational, after: BigRational) -> Result<Number, CalcError> {
+        if factor.is_one() && before.is_zero() && after.is_zero() {
+            return Ok(number);
+        }
+        if let Number::Float(value) = number {
+            if !value.is_finite() {
+                return Ok(Number::Float(value * factor.to_f64().unwrap_or(f64::NAN)));
+            }
+        }
+        Number::Rational((number.to_rational()? + before) * factor - after).convert(self.mode)
+    }
+
+    fn lookup(&self, name: &str, locals: &[(&str, Value)]) -> Result<Value, CalcError> {
+        if let Some((_, value)) = locals.iter().find(|(param, _)| *param == name) {
+            return Ok(value.clone());
+        }
+        if let Some(value) = self.variables.get(name) {
+            return Ok(value.clone());
+        }
+        if let Some((_, value)) = CONSTANTS.iter().find(|(constant, _)| *constant == name) {
+            return Ok(Number::Float(*value).into());
+        }
+        if self.units.get(name).is_some() {
+            return Ok(Value::Scalar(Quantity::new(Number::from(1), Unit::named(name))));
+        }
+        if name == IMAGINARY {
+            return Ok(Value::Complex(I));
+        }
+        if name == ANS {
+            return Err(CalcError::NoResult(None));
+        }
+        Err(CalcError::UndefinedVariable(name.to_string()))
+    }
+}
+
+/// 参与符号运算的值：符号表达式本身或不带单位的实数
+fn symbolic_expr(value: Value) -> Result<Expr, CalcError> {
+    match value {
+        Value::Symbolic(expr) => Ok(expr),
+        value => real(value, "与符号表达式运算的值").map(Expr::Number),
+    }
+}
+
+/// 要求值是不带单位的实数
+fn real(value: Value, what: &str) -> Result<Number, CalcError> {
+    plain(value.into_quantity(what)?, what)
+}
+
+/// 要求量不带单位
+fn plain(quantity: Quantity, what: &str) -> Result<Number, CalcError> {
+    quantity.into_number().map_err(|quantity| CalcError::DimensionMismatch(format!("{}不能带单位，得到 {}", what, quantity)))
+}
+
+/// 错误信息中显示的单位
+fn unit_name(unit: &Unit) -> String {
+    if unit.is_none() {
+        "无单位".to_string()
+    } else {
+        unit.to_string()
+    }
+}
+
+/// 检查变量名是否可以赋值
+fn check_name(name: &str) -> Result<(), CalcError> {
+    if CONSTANTS.iter().any(|(constant, _)| *constant == name) {
+        return Err(CalcError::Reserved(format!("'{}' 是内置常量，不能重新赋值", name)));
+    }
+    if name == ANS {
+        return Err(CalcError::Reserved("'ans' 保存上一次的结果，不能手动赋值".to_string()));
+    }
+    Ok(())
+}
+
+/// 检查名称能否定义成单位
+///
+/// 常量、`ans`、虚数单位和内置函数都先于单位解析，同名的单位永远用不上。
+fn check_unit_name(name: &str) -> Result<(), CalcError> {
+    let kind = if CONSTANTS.iter().any(|(constant, _)| *constant == name) {
+        "内置常量"
+    } else if name == ANS {
+        "上一次的结果"
+    } else if name == IMAGINARY {
+        "虚数单位"
+    } else if builtin(name).is_some() || symbolic::form(name).is_some() {
+        "内置函数"
+    } else {
+        return Ok(());
+    };
+    Err(CalcError::Reserved(format!("'{}' 是{}，不能用作单位名称", name, kind)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projects::calculator::number::Overflow;
+    use crate::projects::calculator::parser::{parse, parse_statement, parse_statement_with};
+
+    fn run(env: &mut Environment, input: &str) -> Result<Outcome, CalcError> {
+        env.execute(parse_statement(input).unwrap())
+    }
+
+    #[test]
+    fn test_variables_and_ans() {
+        let mut env = Environment::new();
+        assert_eq!(run(&mut env, "ans").unwrap_err().to_string(), "还没有上一次的结果（ans）");
+        assert_eq!(run(&mut env, "x = 3").unwrap(), Outcome::Assigned("x".to_string(), 3.0.into()));
+        assert_eq!(run(&mut env, "2x + 1").unwrap(), Outcome::Value(7.0.into()));
+        assert_eq!(run(&mut env, "ans * 2").unwrap(), Outcome::Value(14.0.into()));
+        assert_eq!(run(&mut env, "y + 1").unwrap_err(), CalcError::UndefinedVariable("y".to_string()));
+        assert!(run(&mut env, "pi = 3").is_err());
+        assert!(run(&mut env, "ans = 3").is_err());
+
+        assert!(env.remove("x"));
+        assert!(!env.remove("x"));
+        assert!(run(&mut env, "x").is_err());
+    }
+
+    #[test]
+    fn test_history_refs() {
+        let mut env = Environment::new();
+        env.set_results([10.0.into()]);
+        run(&mut env, "f(x) = x").unwrap();
+        run(&mut env, "$1 + 1").unwrap();
+        assert_eq!(run(&mut env, "$1 * $2").unwrap(), Outcome::Value(110.0.into()));
+        assert_eq!(run(&mut env, "$9").unwrap_err().to_string(), "没有第 9 条历史记录");
+        assert!(run(&mut env, "$0").is_err());
+    }
+
+    #[test]
+    fn test_constants() {
+        let mut env = Environment::new();
+        assert_eq!(run(&mut env, "2pi").unwrap(), Outcome::Value((2.0 * std::f64::consts::PI).into()));
+        assert_eq!(run(&mut env, "e").unwrap(), Outcome::Value(std::f64::consts::E.into()));
+    }
+
+    #[test]
+    fn test_user_functions() {
+        let mut env = Environment::new();
+        assert_eq!(run(&mut env, "f(x) = x^2 + 1").unwrap(), Outcome::Defined("f(x) = x^2 + 1".to_string()));
+        run(&mut env, "g(x, y) = f(x) * y").unwrap();
+        run(&mut env, "x = 100").unwrap();
+        assert_eq!(run(&mut env, "g(2, 3)").unwrap(), Outcome::Value(15.0.into()));
+        assert_eq!(run(&mut env, "f(1, 2)").unwrap_err().to_string(), "函数 'f' 需要 1 个参数，但提供了 2 个");
+        assert_eq!(run(&mut env, "h(1)").unwrap_err().to_string(), "未定义的函数 'h'");
+        assert_eq!(env.functions().count(), 2);
+    }
+
+    #[test]
+    fn test_builtin_functions() {
+        let mut env = Environment::new();
+        assert_eq!(run(&mut env, "sqrt(16) + max(1, 5, 3)").unwrap(), Outcome::Value(9.0.into()));
+        assert_eq!(run(&mut env, "sqrt(-1)").unwrap_err().to_string(), "sqrt 的定义域不包含 -1");
+        assert!(run(&mut env, "sqrt(x) = x").is_err());
+
+        env.set_angle_mode(AngleMode::Degrees);
+        assert_eq!(run(&mut env, "sin(90) + cos(180)").unwrap(), Outcome::Value(0.0.into()));
+    }
+
+    #[test]
+    fn test_number_modes() {
+        let mut env = Environment::new();
+        env.set_mode(NumberMode::Rational);
+        assert_eq!(run(&mut env, "x = 0.1 + 0.2").unwrap().to_string(), "x = 3/10");
+        assert_eq!(run(&mut env, "x / 3 + pi - pi").unwrap().to_string(), "1/10");
+
+        env.set_mode(NumberMode::Integer);
+        assert_eq!(run(&mut env, "x").unwrap_err().to_string(), "整数模式下不能表示非整数 3/10");
+        assert_eq!(run(&mut env, "2^64 / 3").unwrap().to_string(), "6148914691236517205");
+
+        env.set_mode(NumberMode::Float);
+        assert_eq!(run(&mut env, "x").unwrap(), Outcome::Value(0.3.into()));
+        assert_eq!(run(&mut env, "$2 * 2").unwrap(), Outcome::Value(0.2.into()));
+    }
+
+    #[test]
+    fn test_programmer_mode() {
+        let mut env = Environment::new();
+        env.set_mode(NumberMode::Programmer { word: "u8".parse().unwrap(), overflow: Overflow::Wrapping });
+        let mut run = |input: &str| {
+            env.execute(parse_statement_with(input, env.syntax()).unwrap()).map(|outcome| outcome.to_string())
+        };
+        assert_eq!(run("255 + 1").unwrap(), "0");
+        assert_eq!(run("0b1100 ^ 0b1010").unwrap(), "6");
+        assert_eq!(run("~0x0f").unwrap(), "240");
+        assert_eq!(run("1 << 9").unwrap(), "0");
+        assert_eq!(run("-1").unwrap(), "255");
+        assert_eq!(run("2 ** 3").unwrap(), "8");
+
+        env.set_mode(NumberMode::Programmer { word: "i8".parse().unwrap(), overflow: Overflow::Checked });
+        assert!(env.execute(parse_statement("100 + 100").unwrap()).is_err());
+        assert_eq!(env.evaluate(&parse("~0 >> 1 | 0x40").unwrap()).unwrap().to_string(), "-1");
+
+        // 其他模式下位运算要求操作数是整数
+        env.set_mode(NumberMode::Float);
+        assert_eq!(env.evaluate(&parse("6 & 3").unwrap()).unwrap(), 2.0.into());
+        assert_eq!(env.evaluate(&parse("~1.5").unwrap()).unwrap_err().to_string(), "位运算只能用于整数，得到 1.5");
+    }
+
+    #[test]
+    fn test_units() {
+        let mut env = Environment::new();
+        let mut run = |input: &str| run(&mut env, input).map(|outcome| outcome.to_string());
+        assert_eq!(run("3 km + 200 m").unwrap(), "3.2 km");
+        assert_eq!(run("3 km + 200 m in m").unwrap(), "3200 m");
+        assert_eq!(run("5 MiB / 2 s in KiB/s").unwrap(), "2560 KiB/s");
+        assert_eq!(run("(2 m)^2 * 3 m").unwrap(), "12 m^3");
+        assert_eq!(run("1 MiB / 1 KiB").unwrap(), "1024");
+        assert_eq!(run("20 degC in degF").unwrap(), "68 degF");
+        assert_eq!(run("20 degC + 5 K").unwrap(), "25 degC");
+        assert_eq!(run("3 m/s * 2 min").unwrap(), "360 m");
+
+        assert_eq!(run("3 m + 2 s").unwrap_err().to_string(), "量纲不匹配：m + s");
+        assert_eq!(run("3 m + 2").unwrap_err().to_string(), "量纲不匹配：m + 无单位");
+        assert_eq!(run("1 h in m").unwrap_err().to_string(), "无法把 h 换算成 m：量纲不同（s 和 m）");
+        assert_eq!(run("sqrt(4 m)").unwrap_err().to_string(), "函数 'sqrt' 的参数不能带单位，得到 4 m");
+        assert!(run("2 m ^ 0.5").is_err());
+        assert!(run("2 in x").is_err());
+    }
+
+    #[test]
+    fn test_user_units() {
+        let mut env = Environment::new();
+        env.define_unit("furlong", Some(&parse("220 yd").unwrap())).unwrap();
+        env.define_unit("coin", None).unwrap();
+        assert_eq!(run(&mut env, "1 mi in furlong").unwrap().to_string(), "8 furlong");
+        assert_eq!(run(&mut env, "30 coin / 2 h in coin/min").unwrap().to_string(), "0.25 coin/min");
+        assert!(run(&mut env, "1 coin + 1 m").is_err());
+        for name in ["pi", "ans", "i", "sin", "diff"] {
+            let error = env.define_unit(name, Some(&parse("1 m").unwrap())).unwrap_err();
+            assert!(matches!(error, CalcError::Reserved(_)), "{}: {:?}", name, error);
+        }
+        assert_eq!(env.define_unit("sin", None).unwrap_err().to_string(), "'sin' 是内置函数，不能用作单位名称");
+        assert!(env.units().get("pi").is_none());
+
+        // 变量遮住同名的单位，但 `in` 后面总是单位
+        run(&mut env, "m = 5").unwrap();
+        assert_eq!(run(&mut env, "2 m").unwrap(), Outcome::Value(10.0.into()));
+        assert_eq!(run(&mut env, "2 km in m").unwrap().to_string(), "2000 m");
+    }
+
+    #[test]
+    fn test_complex_numbers() {
+        let mut env = Environment::new();
+        let mut run = |input: &str| run(&mut env, input).map(|outcome| outcome.to_string());
+        assert_eq!(run("3 + 4i").unwrap(), "3 + 4i");
+        assert_eq!(run("abs(3 + 4i)").unwrap(), "5");
+        assert_eq!(run("conj(3 + 4i)").unwrap(), "3 - 4i");
+        assert_eq!(run("(1 + 2i) * (3 - i)").unwrap(), "5 + 5i");
+        assert_eq!(run("i^2").unwrap(), "-1");
+        assert_eq!(run("(3 + 4i) / i").unwrap(), "4 - 3i");
+        assert_eq!(run("re(ans) + im(ans)").unwrap(), "1");
+        assert_eq!(run("sqrt(-4)").unwrap_err().to_string(), "sqrt 的定义域不包含 -4");
+        assert_eq!(run("sin(i)").unwrap_err().to_string(), "函数 'sin' 不支持复数参数");
+        assert_eq!(run("(1 + i) % 2").unwrap_err().to_string(), "复数不支持运算符 %");
+        assert_eq!(run("(2 + i) m").unwrap_err().to_string(), "复数运算的操作数不能带单位，得到 1 m");
+    }
+
+    #[test]
+    fn test_complex_mode() {
+        let mut env = Environment::new();
+        env.set_complex(true);
+        let mut run = |input: &str| run(&mut env, input).map(|outcome| outcome.to_string());
+        assert_eq!(run("sqrt(-1)").unwrap(), "i");
+        assert_eq!(run("sqrt(-4) + 1").unwrap(), "1 + 2i");
+        assert_eq!(run("sqrt(4)").unwrap(), "2");
+        assert_eq!(run("ln(-1)").unwrap(), format!("{}i", std::f64::consts::PI));
+        assert_eq!(run("round(re((-8)^(1/3)), 10)").unwrap(), "1");
+        assert_eq!(run("(-8)^2").unwrap(), "64");
+    }
+
+    #[test]
+    fn test_matrices() {
+        let mut env = Environment::new();
+        let mut run = |input: &str| run(&mut env, input).map(|outcome| outcome.to_string());
+        assert_eq!(run("a = [1, 2; 3, 4]").unwrap(), "a = [1, 2; 3, 4]");
+        assert_eq!(run("a + [1, 1; 1, 1]").unwrap(), "[2, 3; 4, 5]");
+        assert_eq!(run("a * a").unwrap(), "[7, 10; 15, 22]");
+        assert_eq!(run("a * [1; 1]").unwrap(), "[3; 7]");
+        assert_eq!(run("2a - a / 2").unwrap(), "[1.5, 3; 4.5, 6]");
+        assert_eq!(run("transpose(a)").unwrap(), "[1, 3; 2, 4]");
+        assert_eq!(run("det(a)").unwrap(), "-2");
+        assert_eq!(run("inv(a)").unwrap(), "[-2, 1; 1.5, -0.5]");
+        assert_eq!(run("a^-1 * a").unwrap(), "[1, 0; 0, 1]");
+        assert_eq!(run("a^3").unwrap(), "[37, 54; 81, 118]");
+        assert_eq!(run("-[1, 2]").unwrap(), "[-1, -2]");
+
+        assert_eq!(run("a + [1, 2]").unwrap_err().to_string(), "矩阵形状不匹配：2×2 + 1×2");
+        assert_eq!(run("[1, 2] * a").unwrap(), "[7, 10]");
+        assert_eq!(run("a * [1, 2]").unwrap_err().to_string(), "矩阵形状不匹配：2×2 * 1×2");
+        assert_eq!(run("a + 1").unwrap_err().to_string(), "矩阵形状不匹配：2×2 + 数");
+        assert_eq!(run("1 / a").unwrap_err().to_string(), "不能除以矩阵，可以乘以它的逆矩阵 inv(...)");
+        assert_eq!(run("inv([1, 2; 2, 4])").unwrap_err().to_string(), "矩阵不可逆（行列式为 0）");
+        assert_eq!(run("det([1, 2, 3])").unwrap_err().to_string(), "det 只能用于方阵，得到 1×3 矩阵");
+        assert_eq!(run("[1, i]").unwrap_err().to_string(), "矩阵元素不能是复数，得到 i");
+        assert_eq!(run("sqrt(a)").unwrap_err().to_string(), "函数 'sqrt' 的参数不能是矩阵，得到 2×2 矩阵");
+
+
+        env.set_mode(NumberMode::Rational);
+        let inverse = env.evaluate(&parse("inv([1, 2; 3, 4])").unwrap()).unwrap();
+        assert_eq!(inverse.to_string(), "[-2, 1; 3/2, -1/2]");
+    }
+
+    #[test]
+    fn test_symbolic() {
+        let mut env = Environment::new();
+        let mut run = |input: &str| run(&mut env, input).map(|outcome| outcome.to_string());
+        assert_eq!(run("diff(x^2 * sin(x), x)").unwrap(), "2 * x * sin(x) + x^2 * cos(x)");
+        assert_eq!(run("eval(ans, x = 0)").unwrap(), "0");
+        assert_eq!(run("f = diff(x^3 - 2x, x)").unwrap(), "f = 3 * x^2 - 2");
+        assert_eq!(run("eval(f, x = 2)").unwrap(), "10");
+        assert_eq!(run("diff(f, x)").unwrap(), "6 * x");
+        assert_eq!(run("2f + 4").unwrap(), "2 * (3 * x^2 - 2) + 4");
+        assert_eq!(run("simplify(x * 2 + 3x - 0)").unwrap(), "5 * x");
+        assert_eq!(run("simplify(1/3 + 1/6)").unwrap(), "0.5");
+        assert_eq!(run("eval(a * x + b, a = 2, x = 3, b = 1)").unwrap(), "7");
+
+        // 用户定义的函数在求导前展开，其他变量当作常量
+        run("g(t) = t^2 + c").unwrap();
+        assert_eq!(run("diff(g(2x), x)").unwrap(), "8 * x");
+        assert_eq!(run("diff(x, y)").unwrap(), "0");
+
+        assert_eq!(run("eval(f, 2)").unwrap_err().to_string(), "eval 的绑定应写成 x = 值，得到 2");
+        assert_eq!(run("diff(x^2, 2)").unwrap_err().to_string(), "用法：diff(f, x)  对 x 求导，得到符号表达式");
+        assert_eq!(run("eval(f)").unwrap_err().to_string(), "未定义的变量 'x'");
+        assert_eq!(run("x = 1").unwrap(), "x = 1");
+        assert_eq!(run("diff(x^2, x)").unwrap(), "2 * x");
+        assert_eq!(run("max(1, x = 2)").unwrap_err().to_string(), "'=' 只能用在 eval 等函数的参数中");
+        assert_eq!(run("diff(x) = 1").unwrap_err().to_string(), "'diff' 是内置函数，不能重新定义");
+    }
+
+    #[test]
+    fn test_numeric_methods() {
+        let mut env = Environment::new();
+        let mut run = |input: &str| run(&mut env, input).map(|outcome| outcome.to_string());
+        assert_eq!(run("solve(x^3 - 2x - 5 = 0, x, 2)").unwrap(), "2.0945514815423265");
+        assert_eq!(run("solve(cos(x) = x, x, 0, 1)").unwrap(), "0.7390851332151607");
+        assert_eq!(run("round(integrate(sin(x), 0, pi), 10)").unwrap(), "2");
+        assert_eq!(run("roots(x^2 - 3x + 2)").unwrap(), "[1, 2]");
+        assert_eq!(run("roots(y^3 - y)").unwrap(), "[-1, 0, 1]");
+
+        // 用户函数、符号变量和其他变量都可以出现在表达式中
+        run("f(t) = t^2").unwrap();
+        assert_eq!(run("round(integrate(f, 0, 3), 10)").unwrap(), "9");
+        run("a = 4").unwrap();
+        assert_eq!(run("solve(f(x) = a, x, 1)").unwrap(), "2");
+        assert_eq!(run("roots(x^2 - a)").unwrap(), "[-2, 2]");
+        run("g = diff(x^3, x) - 12").unwrap();
+        assert_eq!(run("roots(g)").unwrap(), "[-2, 2]");
+
+        assert_eq!(run("solve(x^2 + 1, x, 0)").unwrap_err().to_string(), "牛顿法在 x = 0 处导数为 0，请换一个初值或给出区间");
+        assert_eq!(run("solve(x^2 + 1, x, -1, 1)").unwrap_err().to_string(), "f(-1) 和 f(1) 同号，区间内不一定有根");
+        assert_eq!(run("integrate(1 / x, 0, 1)").unwrap_err().to_string(), "除数不能为零");
+        assert_eq!(run("integrate(x * y, 0, 1)").unwrap_err().to_string(), "无法确定变量（x、y），请在参数中指定");
+        assert_eq!(run("roots(x^2 + 1)").unwrap_err().to_string(), "多项式没有实根，复根为 i, -i");
+        assert_eq!(run("roots(sin(x))").unwrap_err().to_string(), "sin(x) 不是 x 的多项式");
+        assert_eq!(run("solve(x = 1, 2)").unwrap_err().to_string(), "用法：solve(f = g, x, x0)  从 x0 开始用牛顿法解方程；solve(f = g, x, a, b) 在区间内二分");
+    }
+
+    #[test]
+    fn test_notes() {
+        let mut env = Environment::new();
+        run(&mut env, "roots(x^3 - 1)").unwrap();
+        assert_eq!(env.take_notes(), vec!["另有复根 -0.5 + 0.8660254037844386i, -0.5 - 0.8660254037844386i"]);
+        run(&mut env, "solve(x^2 = 2, x, 1)").unwrap();
+        let notes = env.take_notes();
+        assert!(notes[0].starts_with("牛顿法迭代"), "{:?}", notes);
+        assert!(env.take_notes().is_empty());
+    }
+
+    #[test]
+    fn test_recursion_depth() {
+        let mut env = Environment::new();
+        run(&mut env, "loop(n) = loop(n - 1) + 1").unwrap();
+        let err = run(&mut env, "loop(3)").unwrap_err();
+        assert_eq!(err, CalcError::RecursionLimit { depth: MAX_CALL_DEPTH, function: Some("loop".to_string()) });
+        assert!(err.to_string().contains("无限递归（'loop'）"), "{}", err);
+    }
+
+    #[test]
+    fn test_deep_expressions() {
+        // 解析器允许的最深表达式在测试线程的栈上也能求值
+        let mut env = Environment::new();
+        assert_eq!(run(&mut env, &format!("{}1", "-".repeat(98))).unwrap(), Outcome::Value(1.0.into()));
+        let sines = |inner: &str| format!("{}{}{}", "sin(".repeat(48), inner, ")".repeat(48));
+        assert_eq!(run(&mut env, &sines("0")).unwrap(), Outcome::Value(0.0.into()));
+        assert!(run(&mut env, &format!("diff({}, x)", sines("x"))).is_ok());
+
+        // 左结合的长链不算嵌套，能解析和求值，但符号运算仍然受层数限制
+        let sum = vec!["1"; 1000].join(" + ");
+        assert_eq!(run(&mut env, &sum).unwrap(), Outcome::Value(1000.0.into()));
+        assert_eq!(run(&mut env, &vec!["1"; 5000].join(" - ")).unwrap(), Outcome::Value((-4998.0).into()));
+        assert!(run(&mut env, &format!("f(x) = {}", vec!["x"; 4000].join(" + "))).is_ok());
+        assert_eq!(run(&mut env, "f(2)").unwrap(), Outcome::Value(8000.0.into()));
+        for input in [format!("simplify({})", sum), "diff(f(x), x)".to_string()] {
+            assert!(matches!(run(&mut env, &input), Err(CalcError::Unsupported(_))), "{}", input);
+        }
+    }
+}
@@ -0,0 +1,420 @@
+//! 内置数学函数
+//!
+//! 每个函数登记在 `BUILTINS` 中，包括参数个数和说明；求值前统一检查参数个数，
+//! 定义域错误（如 `sqrt(-1)`）由各个函数自己返回。取整、阶乘、gcd 这类函数
+//! 另有精确版本，在分数和整数模式下使用。
+//!
+//! 复数和矩阵的函数（`conj`、`det`、`inv` 等）在求值器中处理，这里登记的是
+//! 它们对实数的版本：实数是虚部为零的复数，也是 1×1 的矩阵。
+
+use super::error::CalcError;
+use super::number::{Number, NumberMode};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive, Zero};
+use std::fmt;
+
+/// 精确计算阶乘时允许的最大参数
+const MAX_EXACT_FACT: u32 = 10_000;
+
+/// 三角函数使用的角度单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AngleMode {
+    #[default]
+    Radians,
+    Degrees,
+}
+
+impl AngleMode {
+    /// 把以当前单位表示的角度换算成弧度
+    fn to_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            AngleMode::Degrees => angle.to_radians(),
+        }
+    }
+
+    /// 把弧度换算成当前单位
+    pub fn angle(self, radians: f64) -> f64 {
+        match self {
+            AngleMode::Radians => radians,
+            AngleMode::Degrees => radians.to_degrees(),
+        }
+    }
+}
+
+impl fmt::Display for AngleMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AngleMode::Radians => write!(f, "弧度"),
+            AngleMode::Degrees => write!(f, "角度"),
+        }
+    }
+}
+
+/// 函数接受的参数个数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exact(usize),
+    Range(usize, usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    fn accepts(self, count: usize) -> bool {
+        match self {
+            Arity::Exact(n) => count == n,
+            Arity::Range(min, max) => (min..=max).contains(&count),
+            Arity::AtLeast(min) => count >= min,
+        }
+    }
+}
+
+/// 如 "需要 1 个参数"、"需要至少 1 个参数"
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arity::Exact(n) => write!(f, "需要 {} 个参数", n),
+            Arity::Range(min, max) => write!(f, "需要 {} 到 {} 个参数", min, max),
+            Arity::AtLeast(min) => write!(f, "需要至少 {} 个参数", min),
+        }
+    }
+}
+
+/// 函数的精确版本
+type Exact = fn(&[BigRational]) -> Result<BigRational, CalcError>;
+
+/// 一个内置函数
+pub struct Builtin {
+    pub name: &'static str,
+    pub arity: Arity,
+    /// 用法说明，如 `log(b, x)  以 b 为底的对数`
+    pub usage: &'static str,
+    eval: fn(&[f64], AngleMode) -> Result<f64, CalcError>,
+    exact: Option<Exact>,
+}
+
+impl Builtin {
+    /// 检查参数个数后调用函数
+    pub fn call(&self, args: &[f64], mode: AngleMode) -> Result<f64, CalcError> {
+        self.check_arity(args.len())?;
+        (self.eval)(args, mode)
+    }
+
+    /// 按数值模式调用函数
+    ///
+    /// 分数和整数模式下优先使用精确版本，没有精确版本时用浮点数计算，
+    /// 再把结果换算回当前模式。
+    pub fn evaluate(&self, args: &[Number], angle: AngleMode, mode: NumberMode) -> Result<Number, CalcError> {
+        match self.exact {
+            Some(exact) if mode != NumberMode::Float => {
+                self.check_arity(args.len())?;
+                let args = args.iter().map(Number::to_rational).collect::<Result<Vec<_>, _>>()?;
+                Number::Rational(exact(&args)?).convert(mode)
+            }
+            _ => {
+                let args: Vec<f64> = args.iter().map(Number::to_f64).collect();
+                Number::Float(self.call(&args, angle)?).convert(mode)
+            }
+        }
+    }
+
+    pub fn check_arity(&self, count: usize) -> Result<(), CalcError> {
+        if self.arity.accepts(count) {
+            Ok(())
+        } else {
+            Err(CalcError::Arity { name: self.name.to_string(), arity: self.arity, found: count })
+        }
+    }
+}
+
+/// 按名称查找内置函数
+pub fn builtin(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|builtin| builtin.name == name)
+}
+
+macro_rules! builtin {
+    ($name:literal, $arity:expr, $usage:literal, $eval:expr) => {
+        Builtin { name: $name, arity: $arity, usage: $usage, eval: $eval, exact: None }
+    };
+    ($name:literal, $arity:expr, $usage:literal, $eval:expr, exact: $exact:expr) => {
+        Builtin {
+            name: $name,
+            arity: $arity,
+            usage: $usage,
+            eval: $eval,
+            exact: {
+                let exact: Exact = $exact;
+                Some(exact)
+            },
+        }
+    };
+}
+
+/// 所有内置函数
+pub static BUILTINS: &[Builtin] = &[
+    builtin!("sqrt", Arity::Exact(1), "sqrt(x)  平方根", |a, _| {
+        domain(a[0] >= 0.0, "sqrt", a[0])?;
+        Ok(a[0].sqrt())
+    }),
+    builtin!("cbrt", Arity::Exact(1), "cbrt(x)  立方根", |a, _| Ok(a[0].cbrt())),
+    builtin!("exp", Arity::Exact(1), "exp(x)  e 的 x 次方", |a, _| Ok(a[0].exp())),
+    builtin!("ln", Arity::Exact(1), "ln(x)  自然对数", |a, _| {
+        domain(a[0] > 0.0, "ln", a[0])?;
+        Ok(a[0].ln())
+    }),
+    builtin!("log", Arity::Range(1, 2), "log(x) 或 log(b, x)  以 10 或 b 为底的对数", |a, _| {
+        let (base, x) = match a {
+            [x] => (10.0, *x),
+            [base, x] => (*base, *x),
+            _ => unreachable!(),
+        };
+        if base <= 0.0 || base == 1.0 {
+            return Err(CalcError::Domain(format!("对数的底必须是不等于 1 的正数，得到 {}", base)));
+        }
+        domain(x > 0.0, "log", x)?;
+        Ok(if base == 10.0 { x.log10() } else { x.log(base) })
+    }),
+    builtin!("sin", Arity::Exact(1), "sin(x)  正弦", |a, mode| Ok(snap(mode.to_radians(a[0]).sin()))),
+    builtin!("cos", Arity::Exact(1), "cos(x)  余弦", |a, mode| Ok(snap(mode.to_radians(a[0]).cos()))),
+    builtin!("tan", Arity::Exact(1), "tan(x)  正切", |a, mode| {
+        // 角度模式下可以精确判断 90° 的奇数倍
+        if mode == AngleMode::Degrees && (a[0] - 90.0).rem_euclid(180.0) == 0.0 {
+            return Err(CalcError::Domain(format!("tan 在 {}° 处没有定义", a[0])));
+        }
+        Ok(snap(mode.to_radians(a[0]).tan()))
+    }),
+    builtin!("asin", Arity::Exact(1), "asin(x)  反正弦", |a, mode| {
+        domain((-1.0..=1.0).contains(&a[0]), "asin", a[0])?;
+        Ok(mode.angle(a[0].asin()))
+    }),
+    builtin!("acos", Arity::Exact(1), "acos(x)  反余弦", |a, mode| {
+        domain((-1.0..=1.0).contains(&a[0]), "acos", a[0])?;
+        Ok(mode.angle(a[0].acos()))
+    }),
+    builtin!("atan", Arity::Range(1, 2), "atan(x) 或 atan(y, x)  反正切", |a, mode| {
+        let angle = match a {
+            [x] => x.atan(),
+            [y, x] => y.atan2(*x),
+            _ => unreachable!(),
+        };
+        Ok(mode.angle(angle))
+    }),
+    builtin!("abs", Arity::Exact(1), "abs(x)  绝对值", |a, _| Ok(a[0].abs()), exact: |a| Ok(a[0].abs())),
+    builtin!("conj", Arity::Exact(1), "conj(z)  共轭复数", |a, _| Ok(a[0]), exact: |a| Ok(a[0].clone())),
+    builtin!("re", Arity::Exact(1), "re(z)  实部", |a, _| Ok(a[0]), exact: |a| Ok(a[0].clone())),
+    builtin!("im", Arity::Exact(1), "im(z)  虚部", |_, _| Ok(0.0), exact: |_| Ok(BigRational::zero())),
+    builtin!("arg", Arity::Exact(1), "arg(z)  辐角", |a, mode| {
+        Ok(mode.angle(if a[0] < 0.0 { std::f64::consts::PI } else { 0.0 }))
+    }),
+    builtin!("transpose", Arity::Exact(1), "transpose(m)  转置矩阵", |a, _| Ok(a[0]), exact: |a| Ok(a[0].clone())),
+    builtin!("det", Arity::Exact(1), "det(m)  行列式", |a, _| Ok(a[0]), exact: |a| Ok(a[0].clone())),
+    builtin!("inv", Arity::Exact(1), "inv(m)  逆矩阵", |a, _| {
+        if a[0] == 0.0 {
+            return Err(CalcError::Domain("矩阵不可逆（行列式为 0）".to_string()));
+        }
+        Ok(1.0 / a[0])
+    }, exact: |a| {
+        if a[0].is_zero() {
+            return Err(CalcError::Domain("矩阵不可逆（行列式为 0）".to_string()));
+        }
+        Ok(a[0].recip())
+    }),
+    builtin!("floor", Arity::Exact(1), "floor(x)  向下取整", |a, _| Ok(a[0].floor()), exact: |a| Ok(a[0].floor())),
+    builtin!("ceil", Arity::Exact(1), "ceil(x)  向上取整", |a, _| Ok(a[0].ceil()), exact: |a| Ok(a[0].ceil())),
+    builtin!("round", Arity::Range(1, 2), "round(x) 或 round(x, n)  四舍五入到 n 位小数", |a, _| {
+        match a {
+            [x] => Ok(x.round()),
+            [x, digits] => {
+                let digits = integer("round", *digits)?;
+                let scale = 10f64.powi(digits as i32);
+                Ok((x * scale).round() / scale)
+            }
+            _ => unreachable!(),
+        }
+    }, exact: |a| {
+        match a {
+            [x] => Ok(x.round()),
+            [x, digits] => {
+                let digits = exact_integer("round", digits)?.to_i32()
+                    .filter(|digits| digits.abs() <= 1000)
+                    .ok_or_else(|| CalcError::OutOfRange("round 的小数位数太大".to_string()))?;
+                let scale = BigRational::from_integer(BigInt::from(10)).pow(digits);
+                Ok((x * &scale).round() / scale)
+            }
+            _ => unreachable!(),
+        }
+    }),
+    builtin!("min", Arity::AtLeast(1), "min(a, b, ...)  最小值", |a, _| {
+        Ok(a.iter().copied().fold(f64::INFINITY, f64::min))
+    }, exact: |a| Ok(a.iter().min().cloned().expect("参数个数已经检查过"))),
+    builtin!("max", Arity::AtLeast(1), "max(a, b, ...)  最大值", |a, _| {
+        Ok(a.iter().copied().fold(f64::NEG_INFINITY, f64::max))
+    }, exact: |a| Ok(a.iter().max().cloned().expect("参数个数已经检查过"))),
+    builtin!("fact", Arity::Exact(1), "fact(n)  阶乘", |a, _| {
+        let n = integer("fact", a[0])?;
+        if n < 0 {
+            return Err(CalcError::Domain(format!("fact 的参数不能是负数，得到 {}", n)));
+        }
+        if n > 170 {
+            return Err(CalcError::OutOfRange(format!("fact({}) 超出了浮点数的表示范围", n)));
+        }
+        Ok((1..=n).map(|k| k as f64).product())
+    }, exact: |a| {
+        let n = exact_integer("fact", &a[0])?;
+        if n.is_negative() {
+            return Err(CalcError::Domain(format!("fact 的参数不能是负数，得到 {}", n)));
+        }
+        let n = n.to_u32()
+            .filter(|n| *n <= MAX_EXACT_FACT)
+            .ok_or_else(|| CalcError::OutOfRange(format!("fact({}) 太大，无法精确计算", n)))?;
+        Ok(BigRational::from_integer((1..=n).map(BigInt::from).product()))
+    }),
+    builtin!("gcd", Arity::AtLeast(1), "gcd(a, b, ...)  最大公约数", |a, _| {
+        let mut result = 0;
+        for &x in a {
+            result = gcd(result, integer("gcd", x)?.unsigned_abs());
+        }
+        Ok(result as f64)
+    }, exact: |a| {
+        let mut result = BigInt::zero();
+        for x in a {
+            result = result.gcd(&exact_integer("gcd", x)?);
+        }
+        Ok(BigRational::from_integer(result))
+    }),
+    builtin!("lcm", Arity::AtLeast(1), "lcm(a, b, ...)  最小公倍数", |a, _| {
+        let mut result: u64 = 1;
+        for &x in a {
+            let x = integer("lcm", x)?.unsigned_abs();
+            if x == 0 {
+                return Ok(0.0);
+            }
+            result = (result / gcd(result, x)).checked_mul(x)
+                .ok_or_else(|| CalcError::OutOfRange("lcm 的结果太大".to_string()))?;
+        }
+        Ok(result as f64)
+    }, exact: |a| {
+        let mut result = BigInt::from(1);
+        for x in a {
+            result = result.lcm(&exact_integer("lcm", x)?);
+        }
+        Ok(BigRational::from_integer(result))
+    }),
+];
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// 定义域检查
+fn domain(ok: bool, name: &str, x: f64) -> Result<(), CalcError> {
+    if ok {
+        Ok(())
+    } else {
+        Err(CalcError::Domain(format!("{} 的定义域不包含 {}", name, x)))
+    }
+}
+
+/// 要求参数是整数
+fn integer(name: &str, x: f64) -> Result<i64, CalcError> {
+    if x.fract() == 0.0 && x.abs() < 2f64.powi(53) {
+        Ok(x as i64)
+    } else {
+        Err(CalcError::InvalidArgument(format!("{} 的参数必须是整数，得到 {}", name, x)))
+    }
+}
+
+/// 要求精确值是整数
+fn exact_integer(name: &str, x: &BigRational) -> Result<BigInt, CalcError> {
+    if x.is_integer() {
+        Ok(x.to_integer())
+    } else {
+        Err(CalcError::InvalidArgument(format!("{} 的参数必须是整数，得到 {}", name, x)))
+    }
+}
+
+/// 把三角函数结果中的舍入误差（如 sin(π) ≈ 1.2e-16）归零
+fn snap(x: f64) -> f64 {
+    if x.abs() < 1e-15 {
+        0.0
+    } else {
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, args: &[f64]) -> Result<f64, CalcError> {
+        builtin(name).unwrap().call(args, AngleMode::Radians)
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn test_basic_functions() {
+        assert_eq!(call("sqrt", &[16.0]), Ok(4.0));
+        assert_eq!(call("log", &[1000.0]), Ok(3.0));
+        assert!(close(call("log", &[2.0, 8.0]).unwrap(), 3.0));
+        assert_eq!(call("ln", &[1.0]), Ok(0.0));
+        assert_eq!(call("round", &[2.345, 2.0]), Ok(2.35));
+        assert_eq!(call("min", &[3.0, -1.0, 2.0]), Ok(-1.0));
+        assert_eq!(call("max", &[3.0]), Ok(3.0));
+        assert_eq!(call("fact", &[5.0]), Ok(120.0));
+        assert_eq!(call("gcd", &[12.0, 18.0, -30.0]), Ok(6.0));
+        assert_eq!(call("lcm", &[4.0, 6.0]), Ok(12.0));
+    }
+
+    #[test]
+    fn test_angle_modes() {
+        let sin = builtin("sin").unwrap();
+        assert_eq!(sin.call(&[std::f64::consts::PI], AngleMode::Radians), Ok(0.0));
+        assert!(close(sin.call(&[30.0], AngleMode::Degrees).unwrap(), 0.5));
+        assert!(close(builtin("acos").unwrap().call(&[0.0], AngleMode::Degrees).unwrap(), 90.0));
+        assert!(builtin("tan").unwrap().call(&[270.0], AngleMode::Degrees).is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(call("sqrt", &[-1.0]).unwrap_err().to_string(), "sqrt 的定义域不包含 -1");
+        assert!(call("ln", &[0.0]).is_err());
+        assert!(call("log", &[1.0, 5.0]).is_err());
+        assert!(call("asin", &[2.0]).is_err());
+        assert!(call("fact", &[2.5]).is_err());
+        assert!(call("fact", &[-1.0]).is_err());
+        assert!(call("fact", &[171.0]).is_err());
+        assert_eq!(
+            call("sqrt", &[1.0, 2.0]).unwrap_err(),
+            CalcError::Arity { name: "sqrt".to_string(), arity: Arity::Exact(1), found: 2 }
+        );
+        assert_eq!(call("max", &[]).unwrap_err().to_string(), "函数 'max' 需要至少 1 个参数，但提供了 0 个");
+    }
+
+    #[test]
+    fn test_exact_versions() {
+        let evaluate = |name: &str, args: &[&str], mode| {
+            let args: Vec<Number> = args.iter().map(|arg| arg.parse().unwrap()).collect();
+            builtin(name).unwrap().evaluate(&args, AngleMode::Radians, mode).map(|value| value.to_string())
+        };
+        assert_eq!(evaluate("fact", &["30"], NumberMode::Integer).unwrap(), "265252859812191058636308480000000");
+        assert_eq!(evaluate("round", &["2/3", "2"], NumberMode::Rational).unwrap(), "67/100");
+        assert_eq!(evaluate("gcd", &["12", "-18"], NumberMode::Integer).unwrap(), "6");
+        assert_eq!(evaluate("lcm", &["4", "6", "0"], NumberMode::Rational).unwrap(), "0");
+        assert_eq!(evaluate("max", &["1/3", "0.3"], NumberMode::Rational).unwrap(), "1/3");
+        assert!(evaluate("fact", &["1/2"], NumberMode::Rational).is_err());
+
+        // 没有精确版本的函数借助浮点数计算，整数模式下结果必须是整数
+        assert_eq!(evaluate("sqrt", &["1/4"], NumberMode::Rational).unwrap(), "1/2");
+        assert_eq!(evaluate("sqrt", &["16"], NumberMode::Integer).unwrap(), "4");
+        assert!(evaluate("sqrt", &["2"], NumberMode::Integer).is_err());
+        assert_eq!(evaluate("fact", &["5"], NumberMode::Float).unwrap(), "120");
+    }
+}
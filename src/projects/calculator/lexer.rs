@@ -0,0 +1,297 @@
+//! 词法分析：把输入切分成记号
+
+use super::number::Number;
+use super::parser::ParseError;
+use num_bigint::BigInt;
+
+/// 记号的种类
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    /// 数字字面量，按精确值保存
+    Number(Number),
+    /// 变量名或函数名
+    Ident(String),
+    /// `$n`，引用第 n 条历史记录的结果
+    HistoryRef(usize),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    /// `^`
+    Caret,
+    /// `**`
+    StarStar,
+    /// `&`
+    Amp,
+    /// `|`
+    Pipe,
+    /// `~`
+    Tilde,
+    /// `<<`
+    Shl,
+    /// `>>`
+    Shr,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    /// `;`，分隔矩阵的行
+    Semicolon,
+    /// `=`
+    Equals,
+    /// 输入结束
+    End,
+}
+
+/// 带位置的记号
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// 记号起始位置，从 1 开始按字符计数
+    pub column: usize,
+}
+
+/// 把输入切分成记号，最后总是以 `End` 结尾
+pub fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if let Some(radix) = radix_prefix(&chars[i..]) {
+            let (value, len) = scan_radix(&chars[i..], radix).map_err(|message| ParseError::new(column, message))?;
+            tokens.push(Token { kind: TokenKind::Number(Number::Integer(value)), column });
+            i += len;
+            continue;
+        }
+
+        if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+            let (value, len) = scan_number(&chars[i..]).map_err(|message| ParseError::new(column, message))?;
+            tokens.push(Token { kind: TokenKind::Number(value), column });
+            i += len;
+            continue;
+        }
+
+        // `°` 只用于温度单位 `°C`、`°F`
+        if c.is_alphabetic() || c == '_' || c == '°' {
+            let len = 1 + chars[i + 1..].iter().take_while(|c| c.is_alphanumeric() || **c == '_').count();
+            tokens.push(Token { kind: TokenKind::Ident(chars[i..i + len].iter().collect()), column });
+            i += len;
+            continue;
+        }
+
+        if c == '$' {
+            let len = chars[i + 1..].iter().take_while(|c| c.is_ascii_digit()).count();
+            let digits: String = chars[i + 1..i + 1 + len].iter().collect();
+            let n = digits.parse().map_err(|_| ParseError::new(column, "'$' 后面需要历史记录编号"))?;
+            tokens.push(Token { kind: TokenKind::HistoryRef(n), column });
+            i += 1 + len;
+            continue;
+        }
+
+        let (kind, len) = match c {
+            '+' => (TokenKind::Plus, 1),
+            '-' => (TokenKind::Minus, 1),
+            '*' if chars.get(i + 1) == Some(&'*') => (TokenKind::StarStar, 2),
+            '*' => (TokenKind::Star, 1),
+            '/' => (TokenKind::Slash, 1),
+            '%' => (TokenKind::Percent, 1),
+            '^' => (TokenKind::Caret, 1),
+            '&' => (TokenKind::Amp, 1),
+            '|' => (TokenKind::Pipe, 1),
+            '~' => (TokenKind::Tilde, 1),
+            '<' if chars.get(i + 1) == Some(&'<') => (TokenKind::Shl, 2),
+            '>' if chars.get(i + 1) == Some(&'>') => (TokenKind::Shr, 2),
+            '(' => (TokenKind::LParen, 1),
+            ')' => (TokenKind::RParen, 1),
+            '[' => (TokenKind::LBracket, 1),
+            ']' => (TokenKind::RBracket, 1),
+            ',' => (TokenKind::Comma, 1),
+            ';' => (TokenKind::Semicolon, 1),
+            '=' => (TokenKind::Equals, 1),
+            _ => return Err(ParseError::new(column, format!("无法识别的字符 '{}'", c))),
+        };
+        tokens.push(Token { kind, column });
+        i += len;
+    }
+
+    tokens.push(Token { kind: TokenKind::End, column: chars.len() + 1 });
+    Ok(tokens)
+}
+
+/// 读取一个数字字面量，支持小数和科学计数法（`1.5e-3`），返回数值和长度
+///
+/// `e` 后面紧跟字母时是名称的开头（`2exp(1)` 即 `2 * exp(1)`）；
+/// 单独的 `e`、`e+` 后面没有数字时是不完整的指数（`1e`、`1e+`），报错而不是当作 `1 * e`。
+fn scan_number(chars: &[char]) -> Result<(Number, usize), String> {
+    let digits = |from: usize| chars[from..].iter().take_while(|c| c.is_ascii_digit()).count();
+
+    let mut len = digits(0);
+    if chars.get(len) == Some(&'.') {
+        len += 1 + digits(len + 1);
+    }
+    if matches!(chars.get(len), Some('e' | 'E')) {
+        let sign = usize::from(matches!(chars.get(len + 1), Some('+' | '-')));
+        let exponent = digits(len + 1 + sign);
+        if exponent > 0 {
+            len += 1 + sign + exponent;
+        } else if sign > 0 || !chars.get(len + 1).is_some_and(|c| c.is_alphanumeric() || *c == '_') {
+            let text: String = chars[..len + 1 + sign].iter().collect();
+            return Err(format!("'{}' 的指数不完整", text));
+        }
+    }
+
+    let text: String = chars[..len].iter().collect();
+    text.parse::<Number>().map(|value| (value, len))
+}
+
+/// `0x`、`0o`、`0b` 前缀，后面必须紧跟对应进制的数字，否则 `0b` 仍是 `0 * b`
+fn radix_prefix(chars: &[char]) -> Option<u32> {
+    let radix = match chars {
+        ['0', 'x', ..] => 16,
+        ['0', 'o', ..] => 8,
+        ['0', 'b', ..] => 2,
+        _ => return None,
+    };
+    chars.get(2).filter(|c| c.is_digit(radix)).map(|_| radix)
+}
+
+/// 读取带前缀的整数字面量，数字之间可以用 `_` 分隔，如 `0b1111_0000`
+fn scan_radix(chars: &[char], radix: u32) -> Result<(BigInt, usize), String> {
+    let len = 2 + chars[2..].iter().take_while(|c| c.is_alphanumeric() || **c == '_').count();
+    let text: String = chars[..len].iter().collect();
+    let digits: String = text[2..].chars().filter(|c| *c != '_').collect();
+    BigInt::parse_bytes(digits.as_bytes(), radix)
+        .map(|value| (value, len))
+        .ok_or_else(|| format!("'{}' 不是有效的{}进制数", text, match radix {
+            16 => "十六",
+            8 => "八",
+            _ => "二",
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<TokenKind> {
+        tokenize(input).unwrap().into_iter().map(|token| token.kind).collect()
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(kinds("42 .5 1.5e3 2E-2"), vec![
+            TokenKind::Number(42.0.into()),
+            TokenKind::Number(0.5.into()),
+            TokenKind::Number(1500.0.into()),
+            TokenKind::Number(0.02.into()),
+            TokenKind::End,
+        ]);
+    }
+
+    #[test]
+    fn test_operators_and_columns() {
+        let tokens = tokenize("2**(3 ^ 1)").unwrap();
+        let columns: Vec<usize> = tokens.iter().map(|token| token.column).collect();
+        assert_eq!(tokens[1].kind, TokenKind::StarStar);
+        assert_eq!(tokens[4].kind, TokenKind::Caret);
+        assert_eq!(columns, vec![1, 2, 4, 5, 7, 9, 10, 11]);
+    }
+
+    #[test]
+    fn test_radix_literals() {
+        assert_eq!(kinds("0xff 0o17 0b1111_0000 0b"), vec![
+            TokenKind::Number(255.0.into()),
+            TokenKind::Number(15.0.into()),
+            TokenKind::Number(240.0.into()),
+            TokenKind::Number(0.0.into()),
+            TokenKind::Ident("b".to_string()),
+            TokenKind::End,
+        ]);
+        let err = tokenize("1 + 0b102").unwrap_err();
+        assert_eq!((err.column, err.message.as_str()), (5, "'0b102' 不是有效的二进制数"));
+    }
+
+    #[test]
+    fn test_bitwise_operators() {
+        assert_eq!(kinds("~a & b | c << 1 >> 2"), vec![
+            TokenKind::Tilde,
+            TokenKind::Ident("a".to_string()),
+            TokenKind::Amp,
+            TokenKind::Ident("b".to_string()),
+            TokenKind::Pipe,
+            TokenKind::Ident("c".to_string()),
+            TokenKind::Shl,
+            TokenKind::Number(1.0.into()),
+            TokenKind::Shr,
+            TokenKind::Number(2.0.into()),
+            TokenKind::End,
+        ]);
+        assert!(tokenize("1 < 2").is_err());
+    }
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(kinds("f(x_1, 2exp) = 2e3"), vec![
+            TokenKind::Ident("f".to_string()),
+            TokenKind::LParen,
+            TokenKind::Ident("x_1".to_string()),
+            TokenKind::Comma,
+            TokenKind::Number(2.0.into()),
+            TokenKind::Ident("exp".to_string()),
+            TokenKind::RParen,
+            TokenKind::Equals,
+            TokenKind::Number(2000.0.into()),
+            TokenKind::End,
+        ]);
+    }
+
+    #[test]
+    fn test_incomplete_exponent() {
+        for (input, column, message) in [
+            ("1e", 1, "'1e' 的指数不完整"),
+            ("2 * 1.5E+", 5, "'1.5E+' 的指数不完整"),
+            ("3e-x", 1, "'3e-' 的指数不完整"),
+            ("(4e)", 2, "'4e' 的指数不完整"),
+        ] {
+            let err = tokenize(input).unwrap_err();
+            assert_eq!((err.column, err.message.as_str()), (column, message), "{}", input);
+        }
+        assert_eq!(kinds("2 e"), vec![
+            TokenKind::Number(2.0.into()),
+            TokenKind::Ident("e".to_string()),
+            TokenKind::End,
+        ]);
+    }
+
+    #[test]
+    fn test_unit_names() {
+        assert_eq!(kinds("20°C"), vec![
+            TokenKind::Number(20.0.into()),
+            TokenKind::Ident("°C".to_string()),
+            TokenKind::End,
+        ]);
+    }
+
+    #[test]
+    fn test_history_refs() {
+        assert_eq!(kinds("$12"), vec![TokenKind::HistoryRef(12), TokenKind::End]);
+        assert_eq!(tokenize("1 + $").unwrap_err().column, 5);
+    }
+
+    #[test]
+    fn test_unknown_character() {
+        let err = tokenize("1 + #").unwrap_err();
+        assert_eq!(err.column, 5);
+    }
+}
@@ -0,0 +1,390 @@
+//! 计算器引擎
+//!
+//! 解析和求值都在这里，`calculator` 二进制只是读取输入、显示结果的前端。
+//! 其他模块可以直接用 [`Engine`] 计算表达式：
+//!
+//! - 基本四则运算、取余和乘方，支持括号、负号和科学计数法，解析错误会标出出错的列
+//! - 变量、`ans`、内置常量和用户定义的函数
+//! - 内置数学函数（sqrt、ln、log、三角函数、fact、gcd 等），三角函数支持角度和弧度
+//! - 浮点数、精确分数和任意精度整数三种数值模式，结果可以显示为分数或定点小数
+//! - 程序员模式：十六/八/二进制字面量、位运算、可选的字长和溢出策略
+//! - 带单位的计算：长度、质量、时间、数据量和温度，用 `in` 换算，可以自定义单位
+//! - 复数（`3 + 4i`）和矩阵（`[1, 2; 3, 4]`），支持转置、行列式和求逆
+//! - 符号运算：`diff` 求导、`simplify` 化简、`eval` 代入求值
+//! - 数值方法：`solve` 解方程、`integrate` 数值积分、`roots` 多项式求根
+//! - 在终端里画函数图像，可以同时画多条曲线
+//!
+//! 出错时返回 [`CalcError`]，`Display` 输出的是给用户看的错误信息。
+
+mod error;
+mod eval;
+mod functions;
+mod lexer;
+mod number;
+mod numeric;
+mod parser;
+mod plot;
+mod symbolic;
+mod units;
+mod value;
+
+pub use error::CalcError;
+pub use eval::{Environment, Function, Outcome};
+pub use functions::{AngleMode, Arity, Builtin, BUILTINS};
+pub use number::{Number, NumberMode, OutputFormat, Overflow, WordSize};
+pub use parser::{parse, Expr, ParseError, Syntax};
+pub use plot::Style;
+pub use symbolic::FORMS;
+pub use units::{describe, Quantity, Unit, UnitTable};
+pub use value::{Complex, Matrix, Value};
+
+use parser::{parse_statement_with, Statement};
+use plot::{Chart, Series};
+
+/// 计算器引擎：解析并执行输入，保存变量、函数、单位和历史结果
+///
+/// 输入按当前数值模式的语法解析，所以程序员模式下 `^` 是按位异或。
+#[derive(Debug, Clone, Default)]
+pub struct Engine {
+    env: Environment,
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 执行一行输入：表达式、赋值或函数定义
+    pub fn execute(&mut self, input: &str) -> Result<Outcome, CalcError> {
+        let statement = parse_statement_with(input, self.env.syntax())?;
+        self.env.execute(statement)
+    }
+
+    /// 计算一行输入的值，赋值语句的值是赋给变量的值
+    ///
+    /// 函数定义没有值，要用 [`Engine::execute`]。结果之外的附注，如 `roots` 找到的
+    /// 复根，用 [`Environment::take_notes`] 取得。
+    pub fn eval(&mut self, input: &str) -> Result<Value, CalcError> {
+        let statement = parse_statement_with(input, self.env.syntax())?;
+        if let Statement::Define { name, .. } = &statement {
+            return Err(CalcError::Unsupported(format!("定义函数 '{}' 没有值，请使用 execute", name)));
+        }
+        match self.env.execute(statement)? {
+            Outcome::Value(value) | Outcome::Assigned(_, value) => Ok(value),
+            Outcome::Defined(_) => unreachable!("函数定义已经在上面排除"),
+        }
+    }
+
+    /// 变量、函数和各种模式
+    pub fn environment(&self) -> &Environment {
+        &self.env
+    }
+
+    pub fn environment_mut(&mut self) -> &mut Environment {
+        &mut self.env
+    }
+
+    /// 画出 `f, g from a to b [ascii|braille]`
+    ///
+    /// 省略区间时画 -10 到 10。只有一条曲线时默认用盲文点阵，多条曲线时默认用
+    /// ASCII 字符区分。区间端点和采样点总是按浮点数计算，与当前的数值模式无关。
+    pub fn plot(&self, arguments: &str) -> Result<String, CalcError> {
+        let mut arguments = arguments.trim();
+        let mut style = None;
+        if let Some((rest, word)) = arguments.rsplit_once(char::is_whitespace) {
+            if let Ok(parsed) = word.parse::<Style>() {
+                style = Some(parsed);
+                arguments = rest.trim_end();
+            }
+        }
+        let (functions, range) = match arguments.split_once(" from ") {
+            Some((functions, range)) => (functions, Some(range)),
+            None => (arguments, None),
+        };
+        let (from, to) = match range {
+            Some(range) => {
+                let (from, to) = range.split_once(" to ").ok_or_else(|| CalcError::InvalidArgument("区间应写成 from a to b".to_string()))?;
+                (self.plot_bound(from)?, self.plot_bound(to)?)
+            }
+            None => (-10.0, 10.0),
+        };
+        if from >= to {
+            return Err(CalcError::InvalidArgument(format!("区间的起点 {} 必须小于终点 {}", from, to)));
+        }
+
+        let functions = split_arguments(functions);
+        let series = functions.iter()
+            .map(|text| {
+                let expr = parse(text).map_err(|error| {
+                    CalcError::Context { context: text.to_string(), error: Box::new(error.into()) }
+                })?;
+                Ok(Series { label: text.to_string(), f: Box::new(self.env.function(&expr)?) })
+            })
+            .collect::<Result<Vec<Series>, CalcError>>()?;
+        let style = style.unwrap_or(if series.len() == 1 { Style::Braille } else { Style::Ascii });
+        Chart::new(style).render(&series, from, to)
+    }
+
+    /// 画图区间的端点，可以是表达式，如 `-pi`、`2 * pi`
+    fn plot_bound(&self, text: &str) -> Result<f64, CalcError> {
+        let expr = parse(text).map_err(|error| {
+            CalcError::Context { context: text.trim().to_string(), error: Box::new(error.into()) }
+        })?;
+        self.env.evaluate_float(&expr, "区间端点")
+    }
+}
+
+/// 按不在括号里的逗号分开，`max(x, 1), sin(x)` 是两个函数
+fn split_arguments(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(text[start..].trim());
+    parts
+}
+
+/// 执行具体的计算操作
+///
+/// 结果溢出成无穷大时报错，例如 `1e308 * 10`。
+fn calculate(num1: f64, operator: &str, num2: f64) -> Result<f64, CalcError> {
+    let value = match operator {
+        "+" => num1 + num2,
+        "-" => num1 - num2,
+        "*" => num1 * num2,
+        "/" | "%" if num2 == 0.0 => return Err(CalcError::DivisionByZero),
+        "/" => num1 / num2,
+        "%" => num1 % num2,
+        "**" | "^" => return number::float_pow(num1, num2),
+        _ => return Err(CalcError::Unsupported(format!("不支持的操作符：'{}'", operator))),
+    };
+    // 很大或很小的数用 Display 会写出几百位数字
+    let show = |x: f64| if x != 0.0 && !(1e-6..1e16).contains(&x.abs()) { format!("{:e}", x) } else { x.to_string() };
+    number::finite(value, || format!("{} {} {}", show(num1), operator, show(num2)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 解析输入并计算结果
+    ///
+    /// 在每种数值模式下分别计算，确认结果相同后返回。
+    fn parse_and_calculate(input: &str) -> Result<f64, CalcError> {
+        let results: Vec<Result<f64, CalcError>> = NumberMode::ALL.iter()
+            .map(|&mode| {
+                let mut engine = Engine::new();
+                engine.environment_mut().set_mode(mode);
+                let expr = parse(input)?;
+                engine.environment()
+                    .evaluate(&expr)
+                    .and_then(|value| value.into_quantity("结果"))
+                    .map(|quantity| quantity.number.to_f64())
+            })
+            .collect();
+        for (mode, result) in NumberMode::ALL.iter().zip(&results) {
+            assert_eq!(result, &results[0], "'{}' 在{}模式下的结果不同", input, mode);
+        }
+        results[0].clone()
+    }
+
+    #[test]
+    fn test_addition() {
+        assert_eq!(calculate(2.0, "+", 3.0).unwrap(), 5.0);
+        assert_eq!(calculate(-1.0, "+", 1.0).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_subtraction() {
+        assert_eq!(calculate(5.0, "-", 3.0).unwrap(), 2.0);
+        assert_eq!(calculate(0.0, "-", 5.0).unwrap(), -5.0);
+    }
+
+    #[test]
+    fn test_multiplication() {
+        assert_eq!(calculate(4.0, "*", 3.0).unwrap(), 12.0);
+        assert_eq!(calculate(-2.0, "*", 3.0).unwrap(), -6.0);
+    }
+
+    #[test]
+    fn test_division() {
+        assert_eq!(calculate(6.0, "/", 2.0).unwrap(), 3.0);
+        assert_eq!(calculate(7.0, "/", 2.0).unwrap(), 3.5);
+        assert_eq!(calculate(10.0, "%", 3.0).unwrap(), 1.0);
+        assert_eq!(calculate(2.0, "**", 3.0).unwrap(), 8.0);
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        assert_eq!(calculate(5.0, "/", 0.0).unwrap_err(), CalcError::DivisionByZero);
+    }
+
+    #[test]
+    fn test_invalid_operator() {
+        assert!(calculate(5.0, "&", 3.0).is_err());
+    }
+
+    #[test]
+    fn test_parse_and_calculate() {
+        assert_eq!(parse_and_calculate("5 + 3").unwrap(), 8.0);
+        assert_eq!(parse_and_calculate("10 / 2").unwrap(), 5.0);
+        assert!(matches!(parse_and_calculate("5 +"), Err(CalcError::Parse(_))));
+        assert_eq!(parse_and_calculate("abc + 3").unwrap_err(), CalcError::UndefinedVariable("abc".to_string()));
+    }
+
+    #[test]
+    fn test_expressions() {
+        assert_eq!(parse_and_calculate("2*(3+4)").unwrap(), 14.0);
+        assert_eq!(parse_and_calculate("-1.5e3 / 2").unwrap(), -750.0);
+        assert_eq!(parse_and_calculate("2 + 3 * 4 ^ 2").unwrap(), 50.0);
+        assert_eq!(parse_and_calculate("2 ** 3 ** 2").unwrap(), 512.0);
+        assert_eq!(parse_and_calculate("-2^2").unwrap(), -4.0);
+        assert_eq!(parse_and_calculate("(-2)^2").unwrap(), 4.0);
+        assert_eq!(parse_and_calculate("10 % 4 - -1").unwrap(), 3.0);
+        assert_eq!(parse_and_calculate("1 / (2 - 2)").unwrap_err(), CalcError::DivisionByZero);
+        assert_eq!(
+            parse_and_calculate("2 * (3 + 4").unwrap_err().to_string(),
+            "第 11 列：缺少与第 5 列匹配的右括号"
+        );
+    }
+
+    #[test]
+    fn test_power_errors() {
+        let mut engine = Engine::new();
+        assert_eq!(engine.eval("(-8)^(1/3)").unwrap_err().to_string(), "(-8)^0.3333333333333333 不是实数");
+        assert_eq!(engine.eval("10^400").unwrap_err().to_string(), "10^400 超出了浮点数的范围");
+        assert_eq!(engine.eval("0^-1").unwrap_err(), CalcError::DivisionByZero);
+        assert!(matches!(engine.eval("(-2)^0.5"), Err(CalcError::Domain(_))));
+        assert!(matches!(engine.eval("2^2000"), Err(CalcError::OutOfRange(_))));
+        assert_eq!(engine.eval("(-8)^3").unwrap().to_string(), "-512");
+
+        engine.environment_mut().set_mode(NumberMode::Rational);
+        assert_eq!(engine.eval("(-8)^(1/3)").unwrap_err().to_string(), "(-8)^0.3333333333333333 不是实数");
+        assert_eq!(engine.eval("10^400.5").unwrap_err().to_string(), "10^400.5 超出了浮点数的范围");
+    }
+
+    #[test]
+    fn test_float_overflow() {
+        let mut engine = Engine::new();
+        assert_eq!(engine.eval("1e308 * 10").unwrap_err().to_string(), "1e308 * 10 超出了浮点数的范围");
+        assert_eq!(engine.eval("1.7e308 + 1e308").unwrap_err().to_string(), "1.7e308 + 1e308 超出了浮点数的范围");
+        assert_eq!(engine.eval("1e308 / 1e-10").unwrap_err().to_string(), "1e308 / 1e-10 超出了浮点数的范围");
+        assert_eq!(engine.eval("-1e308 - 1e308").unwrap_err().to_string(), "-1e308 - 1e308 超出了浮点数的范围");
+        assert_eq!(engine.eval("1e400").unwrap_err().to_string(), "1e400 超出了浮点数的范围");
+        assert_eq!(engine.eval("-2.5e400").unwrap_err().to_string(), "2.5e400 超出了浮点数的范围");
+        assert!(matches!(engine.eval("1e5000"), Err(CalcError::OutOfRange(_))));
+        assert!(matches!(engine.eval("exp(1000)"), Err(CalcError::OutOfRange(_))));
+        assert_eq!(engine.eval("1e308 * 1").unwrap().to_string(), 1e308.to_string());
+
+        // 精确模式下大数本身没有问题
+        engine.environment_mut().set_mode(NumberMode::Rational);
+        assert_eq!(engine.eval("1e400 / 1e399").unwrap().to_string(), "10");
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        let mut engine = Engine::new();
+        assert_eq!(engine.eval("ans").unwrap_err(), CalcError::NoResult(None));
+        assert_eq!(engine.eval("$3").unwrap_err(), CalcError::NoResult(Some(3)));
+        assert!(matches!(engine.eval("sqrt(-1)"), Err(CalcError::Domain(_))));
+        assert!(matches!(engine.eval("fact(200)"), Err(CalcError::OutOfRange(_))));
+        assert!(matches!(engine.eval("1 m + 1 s"), Err(CalcError::DimensionMismatch(_))));
+        assert!(matches!(engine.eval("[1, 2] + [1; 2]"), Err(CalcError::DimensionMismatch(_))));
+        assert!(matches!(engine.eval("gcd(1.5, 2)"), Err(CalcError::InvalidArgument(_))));
+        assert!(matches!(engine.eval("diff(~x, x)"), Err(CalcError::Unsupported(_))));
+        assert!(matches!(engine.eval("pi = 3"), Err(CalcError::Reserved(_))));
+        assert_eq!(engine.eval("2 in parsec").unwrap_err(), CalcError::UnknownUnit("parsec".to_string()));
+
+        let error = engine.plot("sin(x), sqrt(-1 - x^2) from 0 to 1").unwrap_err();
+        let CalcError::Context { context, error } = error else { panic!("{:?}", error) };
+        assert_eq!(context, "sqrt(-1 - x^2)");
+        assert!(matches!(*error, CalcError::Domain(_)), "{:?}", error);
+        let error = engine.plot("x from 0 to (1").unwrap_err();
+        assert_eq!(error.to_string(), "(1：第 3 列：缺少与第 1 列匹配的右括号");
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[test]
+    fn test_number_modes() {
+        let run = |mode, input: &str| {
+            let mut engine = Engine::new();
+            engine.environment_mut().set_mode(mode);
+            engine.execute(input).map(|outcome| outcome.to_string())
+        };
+        assert_eq!(run(NumberMode::Float, "0.1 + 0.2").unwrap(), "0.30000000000000004");
+        assert_eq!(run(NumberMode::Rational, "0.1 + 0.2").unwrap(), "3/10");
+        assert_eq!(run(NumberMode::Rational, "1/3 + 1/6").unwrap(), "1/2");
+        assert_eq!(run(NumberMode::Integer, "7 / 2").unwrap(), "3");
+        assert_eq!(run(NumberMode::Integer, "fact(25)").unwrap(), "15511210043330985984000000");
+        assert!(run(NumberMode::Integer, "0.5 + 1").is_err());
+    }
+
+    #[test]
+    fn test_engine() {
+        let mut engine = Engine::new();
+        assert_eq!(engine.eval("x = 3").unwrap(), Value::from(3.0));
+        assert_eq!(engine.eval("2x + 1").unwrap().to_string(), "7");
+        assert_eq!(engine.execute("f(t) = t^2").unwrap(), Outcome::Defined("f(t) = t^2".to_string()));
+        assert_eq!(engine.eval("f(ans)").unwrap().to_string(), "49");
+        assert_eq!(engine.eval("g(t) = t").unwrap_err().to_string(), "定义函数 'g' 没有值，请使用 execute");
+        assert_eq!(engine.eval("g(1)").unwrap_err(), CalcError::UndefinedFunction("g".to_string()));
+        assert_eq!(
+            engine.eval("max()").unwrap_err(),
+            CalcError::Arity { name: "max".to_string(), arity: Arity::AtLeast(1), found: 0 }
+        );
+        let error = engine.eval("1 +").unwrap_err();
+        assert!(matches!(&error, CalcError::Parse(parse) if parse.column == 4), "{:?}", error);
+        assert_eq!(engine.eval("roots(x^3 - 1)").unwrap().to_string(), "[1]");
+        assert_eq!(engine.environment().take_notes(), vec!["另有复根 -0.5 + 0.8660254037844386i, -0.5 - 0.8660254037844386i"]);
+
+        engine.environment_mut().set_mode(NumberMode::Programmer {
+            word: "u8".parse().unwrap(),
+            overflow: Overflow::Checked,
+        });
+        assert_eq!(engine.eval("0xff ^ 0x0f").unwrap().to_string(), "240");
+        assert!(matches!(engine.eval("200 + 100"), Err(CalcError::Overflow { .. })));
+        assert_eq!(engine.eval("200 + 100").unwrap_err().to_string(), "结果 300 超出了 u8 的范围（0 到 255）");
+    }
+
+    #[test]
+    fn test_plot() {
+        let mut engine = Engine::new();
+        engine.execute("f(t) = t^2").unwrap();
+        let chart = engine.plot("sin(x), max(x, 0), f from -pi to pi").unwrap();
+        assert!(chart.ends_with("* sin(x)   + max(x, 0)   o f"), "{}", chart);
+        assert!(chart.contains("-3.142"), "{}", chart);
+        assert!(engine.plot("ln(x) from 0 to 2 braille").unwrap().ends_with("⣿ ln(x)（1 个点没有定义）"));
+
+        let plot = |arguments: &str| engine.plot(arguments).unwrap_err().to_string();
+        assert_eq!(plot("x from 1 to 0"), "区间的起点 1 必须小于终点 0");
+        assert_eq!(plot("x from 1"), "区间应写成 from a to b");
+        assert_eq!(plot("x * y"), "无法确定变量（x、y），请在参数中指定");
+        assert_eq!(plot("x from 0 to 1 m"), "区间端点不能带单位，得到 1 m");
+        assert_eq!(split_arguments("max(x, 1), [1, 2]"), vec!["max(x, 1)", "[1, 2]"]);
+    }
+
+    #[test]
+    fn test_plot_in_exact_modes() {
+        // 整数模式下 pi 和 1/2 都不是整数，画图仍按浮点数计算，与浮点模式的结果相同
+        let plot = |mode, arguments: &str| {
+            let mut engine = Engine::new();
+            engine.environment_mut().set_mode(mode);
+            engine.plot(arguments)
+        };
+        for arguments in ["sin(x) from -pi to pi", "x / 3 from -1/2 to 1/2", "x^0.5 from 0 to 2"] {
+            let expected = plot(NumberMode::Float, arguments).unwrap();
+            for mode in [NumberMode::Rational, NumberMode::Integer, NumberMode::PROGRAMMER] {
+                assert_eq!(plot(mode, arguments).unwrap(), expected, "{} {}", mode, arguments);
+            }
+        }
+    }
+}
@@ -0,0 +1,715 @@
+//! 数值：浮点数、精确分数、任意精度整数和固定字长的整数
+//!
+//! 表达式中的数字字面量总是按精确值保存（`0.1` 就是 1/10），求值时再换算成
+//! 当前数值模式的表示，因此切换模式不需要重新解析输入。程序员模式同样用
+//! `BigInt` 计算，每一步之后再按字长和溢出策略截断，与 Rust 的
+//! `wrapping_*`、`checked_*`、`saturating_*` 方法对应。
+
+use super::error::CalcError;
+use super::parser::BinaryOp;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::fmt;
+use std::ops::Neg;
+use std::str::FromStr;
+
+/// 精确计算乘方时允许的最大指数，避免结果大到算不完
+const MAX_EXPONENT: u32 = 100_000;
+
+/// 数值模式，决定计算时使用哪种表示
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberMode {
+    /// 64 位浮点数，速度快但有舍入误差
+    #[default]
+    Float,
+    /// 任意精度的分数，四则运算没有误差
+    Rational,
+    /// 任意精度的整数，除法向零取整
+    Integer,
+    /// 固定字长的整数，`^` 表示按位异或
+    Programmer { word: WordSize, overflow: Overflow },
+}
+
+impl NumberMode {
+    pub const ALL: [NumberMode; 4] = [
+        NumberMode::Float,
+        NumberMode::Rational,
+        NumberMode::Integer,
+        NumberMode::PROGRAMMER,
+    ];
+
+    /// 程序员模式的默认设置：i64，溢出时回绕
+    pub const PROGRAMMER: NumberMode = NumberMode::Programmer {
+        word: WordSize { bits: 64, signed: true },
+        overflow: Overflow::Wrapping,
+    };
+
+    /// 命令中使用的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            NumberMode::Float => "float",
+            NumberMode::Rational => "rational",
+            NumberMode::Integer => "integer",
+            NumberMode::Programmer { .. } => "programmer",
+        }
+    }
+
+    /// 把整数运算的结果限制在程序员模式的字长内，其他模式原样返回
+    ///
+    /// 位运算（`bitwise` 为真）总是按补码截断，只有算术运算遵循溢出策略，
+    /// 所以 u8 下 `~5` 得到 250 而不是溢出错误。
+    pub fn fit(self, value: BigInt, bitwise: bool) -> Result<BigInt, CalcError> {
+        match self {
+            NumberMode::Programmer { word, .. } if bitwise => Ok(word.wrap(&value)),
+            NumberMode::Programmer { word, overflow } => overflow.apply(word, value),
+            _ => Ok(value),
+        }
+    }
+}
+
+impl FromStr for NumberMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NumberMode::ALL.into_iter()
+            .find(|mode| mode.name() == s)
+            .ok_or_else(|| format!("未知的数值模式 '{}'（可选：float, rational, integer, programmer）", s))
+    }
+}
+
+impl fmt::Display for NumberMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NumberMode::Float => write!(f, "浮点数"),
+            NumberMode::Rational => write!(f, "精确分数"),
+            NumberMode::Integer => write!(f, "任意精度整数"),
+            NumberMode::Programmer { word, overflow } => write!(f, "程序员模式（{}，溢出时{}）", word, overflow),
+        }
+    }
+}
+
+/// 程序员模式的字长，如 `i32`、`u8`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordSize {
+    pub bits: u32,
+    pub signed: bool,
+}
+
+impl WordSize {
+    pub fn min(self) -> BigInt {
+        if self.signed {
+            -(BigInt::one() << (self.bits - 1))
+        } else {
+            BigInt::zero()
+        }
+    }
+
+    pub fn max(self) -> BigInt {
+        let magnitude = if self.signed { self.bits - 1 } else { self.bits };
+        (BigInt::one() << magnitude) - 1
+    }
+
+    /// 按补码截断，相当于 Rust 的 `wrapping_*`
+    pub fn wrap(self, value: &BigInt) -> BigInt {
+        let bits = self.twos_complement(value);
+        if self.signed && bits > self.max() {
+            bits - (BigInt::one() << self.bits)
+        } else {
+            bits
+        }
+    }
+
+    /// 补码表示对应的无符号值，负数 -1 在 i8 下得到 255
+    fn twos_complement(self, value: &BigInt) -> BigInt {
+        value.mod_floor(&(BigInt::one() << self.bits))
+    }
+
+    /// 十六进制、八进制和二进制形式，负数按补码显示
+    pub fn bases(self, value: &BigInt) -> String {
+        let bits = self.twos_complement(&self.wrap(value));
+        let binary = format!("{:b}", bits);
+        // 补齐到 4 的倍数后每 4 位加一个下划线
+        let binary = format!("{}{}", "0".repeat((4 - binary.len() % 4) % 4), binary);
+        let groups: Vec<&str> = binary.as_bytes()
+            .chunks(4)
+            .map(|chunk| std::str::from_utf8(chunk).expect("二进制数字"))
+            .collect();
+        format!("十六进制 0x{:x}  八进制 0o{:o}  二进制 0b{}", bits, bits, groups.join("_"))
+    }
+}
+
+impl FromStr for WordSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("未知的字长 '{}'（可选：i8 到 i128、u8 到 u128）", s);
+        let signed = match s.chars().next() {
+            Some('i') => true,
+            Some('u') => false,
+            _ => return Err(invalid()),
+        };
+        match s[1..].parse() {
+            Ok(bits @ (8 | 16 | 32 | 64 | 128)) => Ok(WordSize { bits, signed }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for WordSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", if self.signed { "i" } else { "u" }, self.bits)
+    }
+}
+
+/// 程序员模式下算术运算溢出时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// 按补码回绕，`255u8 + 1 = 0`
+    Wrapping,
+    /// 报告错误
+    Checked,
+    /// 停在最大值或最小值，`255u8 + 1 = 255`
+    Saturating,
+}
+
+impl Overflow {
+    pub fn apply(self, word: WordSize, value: BigInt) -> Result<BigInt, CalcError> {
+        let (min, max) = (word.min(), word.max());
+        if min <= value && value <= max {
+            return Ok(value);
+        }
+        match self {
+            Overflow::Wrapping => Ok(word.wrap(&value)),
+            Overflow::Checked => Err(CalcError::Overflow { value, word }),
+            Overflow::Saturating => Ok(if value < min { min } else { max }),
+        }
+    }
+}
+
+impl FromStr for Overflow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrapping" => Ok(Overflow::Wrapping),
+            "checked" => Ok(Overflow::Checked),
+            "saturating" => Ok(Overflow::Saturating),
+            _ => Err(format!("未知的溢出策略 '{}'（可选：wrapping, checked, saturating）", s)),
+        }
+    }
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Overflow::Wrapping => write!(f, "回绕"),
+            Overflow::Checked => write!(f, "报错"),
+            Overflow::Saturating => write!(f, "饱和"),
+        }
+    }
+}
+
+/// 结果的显示格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// 浮点数按最短形式，分数显示为 `a/b`
+    #[default]
+    Auto,
+    /// 总是显示为分数
+    Fraction,
+    /// 四舍五入到固定的小数位数
+    Decimal(usize),
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutputFormat::Auto => write!(f, "自动"),
+            OutputFormat::Fraction => write!(f, "分数"),
+            OutputFormat::Decimal(digits) => write!(f, "{} 位小数", digits),
+        }
+    }
+}
+
+/// 一个数值
+///
+/// 比较时按数值比较，不区分表示方式：`Float(0.5) == Rational(1/2)`。
+#[derive(Debug, Clone)]
+pub enum Number {
+    Float(f64),
+    Rational(BigRational),
+    Integer(BigInt),
+}
+
+impl Number {
+    /// 换算成 `mode` 对应的表示
+    ///
+    /// 浮点数按最短的十进制形式换算成分数，所以 `0.1` 得到 1/10；
+    /// 超出浮点数范围的值（如字面量 `1e400`）换算成浮点数时报错；
+    /// 整数模式下遇到非整数时报错，程序员模式下再按字长处理溢出。
+    pub fn convert(&self, mode: NumberMode) -> Result<Number, CalcError> {
+        match (self, mode) {
+            (Number::Float(value), NumberMode::Float) => finite(*value, || value.to_string()).map(Number::Float),
+            (Number::Rational(_), NumberMode::Rational)
+            | (Number::Integer(_), NumberMode::Integer) => Ok(self.clone()),
+            (_, NumberMode::Float) => finite(self.to_f64(), || match self.to_rational() {
+                Ok(value) => scientific(&value),
+                Err(_) => self.to_string(),
+            }).map(Number::Float),
+            (_, NumberMode::Rational) => self.to_rational().map(Number::Rational),
+            (_, NumberMode::Integer | NumberMode::Programmer { .. }) => {
+                let value = self.to_integer().map_err(|_| CalcError::InvalidArgument(format!("整数模式下不能表示非整数 {}", self)))?;
+                mode.fit(value, false).map(Number::Integer)
+            }
+        }
+    }
+
+    /// 精确的整数值，不是整数时报错
+    pub fn to_integer(&self) -> Result<BigInt, CalcError> {
+        if let Number::Integer(value) = self {
+            return Ok(value.clone());
+        }
+        let value = self.to_rational()?;
+        if value.is_integer() {
+            Ok(value.to_integer())
+        } else {
+            Err(CalcError::InvalidArgument(format!("{} 不是整数", self)))
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Float(value) => *value,
+            Number::Rational(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Integer(value) => value.to_f64().unwrap_or(f64::NAN),
+        }
+    }
+
+    /// 精确值；无穷大和 NaN 没有精确值
+    pub fn to_rational(&self) -> Result<BigRational, CalcError> {
+        match self {
+            Number::Float(value) => {
+                if !value.is_finite() {
+                    return Err(CalcError::OutOfRange(format!("{} 无法转换为精确值", value)));
+                }
+                // f64 的 Display 给出能还原出同一个浮点数的最短十进制形式
+                Ok(parse_decimal(&value.to_string()).expect("浮点数的十进制形式"))
+            }
+            Number::Rational(value) => Ok(value.clone()),
+            Number::Integer(value) => Ok(BigRational::from_integer(value.clone())),
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Number::Float(value) => *value < 0.0,
+            Number::Rational(value) => value.is_negative(),
+            Number::Integer(value) => value.is_negative(),
+        }
+    }
+
+    /// 能写成有限小数时返回小数形式，如 3/2 得到 `1.5`，1/3 没有
+    pub fn to_decimal(&self) -> Option<String> {
+        let Number::Rational(value) = self else {
+            return Some(self.to_string());
+        };
+        let mut denominator = value.denom().clone();
+        // 分母只含因子 2 和 5 时才是有限小数，小数位数是两者次数中较大的一个
+        let (mut twos, mut fives) = (0, 0);
+        let (two, five) = (BigInt::from(2), BigInt::from(5));
+        while denominator.is_multiple_of(&two) {
+            denominator /= &two;
+            twos += 1;
+        }
+        while denominator.is_multiple_of(&five) {
+            denominator /= &five;
+            fives += 1;
+        }
+        if denominator.is_one() {
+            Some(fixed(value, twos.max(fives)))
+        } else {
+            None
+        }
+    }
+
+    /// 按输出格式显示
+    pub fn format(&self, format: OutputFormat) -> String {
+        match (self, format) {
+            (Number::Float(value), OutputFormat::Decimal(digits)) => format!("{:.*}", digits, value),
+            (Number::Float(_), OutputFormat::Fraction) => match self.to_rational() {
+                Ok(value) => fraction(&value),
+                Err(_) => self.to_string(),
+            },
+            (Number::Rational(value), OutputFormat::Decimal(digits)) => fixed(value, digits),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Number::Float(value) => write!(f, "{}", value),
+            Number::Rational(value) => write!(f, "{}", fraction(value)),
+            Number::Integer(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// 解析字面量或历史文件中保存的结果
+///
+/// 支持整数、小数、科学计数法和 `a/b` 形式的分数，整数得到 `Integer`，
+/// 其余精确值得到 `Rational`；`inf`、`NaN` 以及指数过大的数得到 `Float`。
+impl FromStr for Number {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{}' 不是有效的数字", s);
+
+        if let Some((numerator, denominator)) = s.split_once('/') {
+            let numerator: BigInt = numerator.parse().map_err(|_| invalid())?;
+            let denominator: BigInt = denominator.parse().map_err(|_| invalid())?;
+            if denominator.is_zero() {
+                return Err(invalid());
+            }
+            return Ok(Number::Rational(BigRational::new(numerator, denominator)));
+        }
+        if let Some(value) = parse_decimal(s) {
+            return Ok(if value.is_integer() {
+                Number::Integer(value.to_integer())
+            } else {
+                Number::Rational(value)
+            });
+        }
+        s.parse().map(Number::Float).map_err(|_| invalid())
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Number::Float(_), _) | (_, Number::Float(_)) => self.to_f64() == other.to_f64(),
+            _ => self.to_rational() == other.to_rational(),
+        }
+    }
+}
+
+impl Neg for Number {
+    type Output = Number;
+
+    fn neg(self) -> Number {
+        match self {
+            Number::Float(value) => Number::Float(-value),
+            Number::Rational(value) => Number::Rational(-value),
+            Number::Integer(value) => Number::Integer(-value),
+        }
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Number::Float(value)
+    }
+}
+
+impl From<i64> for Number {
+    fn from(value: i64) -> Self {
+        Number::Integer(BigInt::from(value))
+    }
+}
+
+/// 分数的二元运算，除了非整数次幂之外都没有误差
+///
+/// 位运算要求两边都是整数。
+pub fn rational_op(op: BinaryOp, a: &BigRational, b: &BigRational) -> Result<BigRational, CalcError> {
+    match op {
+        BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor | BinaryOp::Shl | BinaryOp::Shr => {
+            let integer = |value: &BigRational| if value.is_integer() {
+                Ok(value.to_integer())
+            } else {
+                Err(CalcError::InvalidArgument(format!("位运算只能用于整数，得到 {}", fraction(value))))
+            };
+            integer_op(op, &integer(a)?, &integer(b)?).map(BigRational::from_integer)
+        }
+        BinaryOp::Add => Ok(a + b),
+        BinaryOp::Sub => Ok(a - b),
+        BinaryOp::Mul => Ok(a * b),
+        BinaryOp::Div => {
+            nonzero(b)?;
+            Ok(a / b)
+        }
+        // 与浮点数的 % 一样，余数的符号和被除数相同
+        BinaryOp::Rem => {
+            nonzero(b)?;
+            Ok(a - b * (a / b).trunc())
+        }
+        BinaryOp::Pow if b.is_integer() => {
+            let exponent = b.to_integer();
+            if exponent.is_negative() && a.is_zero() {
+                return Err(CalcError::DivisionByZero);
+            }
+            if a.abs().is_one() || a.is_zero() {
+                // 0、1、-1 的任意次幂只取决于指数的奇偶
+                let odd = exponent.is_odd();
+                return Ok(if exponent.is_zero() { BigRational::one() } else if odd { a.clone() } else { a.abs() });
+            }
+            let exponent = exponent.to_i32()
+                .filter(|exponent| exponent.unsigned_abs() <= MAX_EXPONENT)
+                .ok_or_else(|| CalcError::OutOfRange(format!("指数 {} 太大，无法精确计算", exponent)))?;
+            Ok(a.pow(exponent))
+        }
+        // 非整数次幂一般是无理数，只能借助浮点数近似
+        BinaryOp::Pow => {
+            let value = float_pow(a.to_f64().unwrap_or(f64::NAN), b.to_f64().unwrap_or(f64::NAN))?;
+            Number::Float(value).to_rational()
+        }
+    }
+}
+
+/// 浮点数的乘方
+///
+/// `powf` 对负数的非整数次幂返回 NaN，结果超出 f64 的范围时返回无穷大，这里都报错。
+pub fn float_pow(base: f64, exponent: f64) -> Result<f64, CalcError> {
+    if base == 0.0 && exponent < 0.0 {
+        return Err(CalcError::DivisionByZero);
+    }
+    let value = base.powf(exponent);
+    let power = || if base < 0.0 { format!("({})^{}", base, exponent) } else { format!("{}^{}", base, exponent) };
+    if value.is_nan() && !base.is_nan() && !exponent.is_nan() {
+        return Err(CalcError::Domain(format!("{} 不是实数", power())));
+    }
+    if value.is_infinite() && base.is_finite() && exponent.is_finite() {
+        return Err(CalcError::OutOfRange(format!("{} 超出了浮点数的范围", power())));
+    }
+    Ok(value)
+}
+
+/// 检查浮点运算的结果，溢出成无穷大时报错而不是继续用 `inf` 计算
+///
+/// `expression` 给出这次运算的写法，用于错误信息。
+pub fn finite(value: f64, expression: impl FnOnce() -> String) -> Result<f64, CalcError> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(CalcError::OutOfRange(format!("{} 超出了浮点数的范围", expression())))
+    }
+}
+
+/// 用科学计数法简短地显示很大的精确值，如 `1e400`，只保留四位有效数字
+fn scientific(value: &BigRational) -> String {
+    let digits = value.abs().to_integer().to_string();
+    let mantissa = format!("{}.{}", &digits[..1], &digits[1..digits.len().min(4)]);
+    let sign = if value.is_negative() { "-" } else { "" };
+    format!("{}{}e{}", sign, mantissa.trim_end_matches('0').trim_end_matches('.'), digits.len() - 1)
+}
+
+/// 整数的二元运算，除法和取余都向零取整
+///
+/// 位运算按补码计算，右移是算术右移（向负无穷取整），与 Rust 的有符号整数一致。
+pub fn integer_op(op: BinaryOp, a: &BigInt, b: &BigInt) -> Result<BigInt, CalcError> {
+    match op {
+        BinaryOp::BitAnd => Ok(a & b),
+        BinaryOp::BitOr => Ok(a | b),
+        BinaryOp::BitXor => Ok(a ^ b),
+        BinaryOp::Shl | BinaryOp::Shr => {
+            if b.is_negative() {
+                return Err(CalcError::InvalidArgument("移位的位数不能是负数".to_string()));
+            }
+            let shift = b.to_u32()
+                .filter(|shift| *shift <= MAX_EXPONENT)
+                .ok_or_else(|| CalcError::OutOfRange(format!("移位的位数 {} 太大", b)))?;
+            Ok(if op == BinaryOp::Shl { a << shift } else { a >> shift })
+        }
+        BinaryOp::Add => Ok(a + b),
+        BinaryOp::Sub => Ok(a - b),
+        BinaryOp::Mul => Ok(a * b),
+        BinaryOp::Div | BinaryOp::Rem if b.is_zero() => Err(CalcError::DivisionByZero),
+        BinaryOp::Div => Ok(a / b),
+        BinaryOp::Rem => Ok(a % b),
+        BinaryOp::Pow => {
+            if b.is_negative() {
+                return Err(CalcError::InvalidArgument("整数模式下指数不能是负数".to_string()));
+            }
+            if a.abs() <= BigInt::one() {
+                return Ok(if b.is_zero() { BigInt::one() } else if b.is_odd() { a.clone() } else { a.abs() });
+            }
+            let exponent = b.to_u32()
+                .filter(|exponent| *exponent <= MAX_EXPONENT)
+                .ok_or_else(|| CalcError::OutOfRange(format!("指数 {} 太大，无法精确计算", b)))?;
+            Ok(a.pow(exponent))
+        }
+    }
+}
+
+fn nonzero(value: &BigRational) -> Result<(), CalcError> {
+    if value.is_zero() {
+        Err(CalcError::DivisionByZero)
+    } else {
+        Ok(())
+    }
+}
+
+/// 解析十进制数，如 `-1.5e3`；不是十进制数或指数超过 ±1000 时返回 `None`
+fn parse_decimal(text: &str) -> Option<BigRational> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()?),
+        None => (text, 0),
+    };
+    if exponent.abs() > 1000 {
+        return None;
+    }
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if whole.len() + fraction.len() == 0 || !all_digits(whole) || !all_digits(fraction) {
+        return None;
+    }
+
+    let digits: BigInt = format!("{}{}", whole, fraction).parse().ok()?;
+    let scale = exponent - fraction.len() as i32;
+    let power = BigInt::from(10).pow(scale.unsigned_abs());
+    let value = if scale >= 0 {
+        BigRational::from_integer(digits * power)
+    } else {
+        BigRational::new(digits, power)
+    };
+    Some(if negative { -value } else { value })
+}
+
+/// `a/b` 形式，分母为 1 时只显示整数
+fn fraction(value: &BigRational) -> String {
+    if value.is_integer() {
+        value.to_integer().to_string()
+    } else {
+        format!("{}/{}", value.numer(), value.denom())
+    }
+}
+
+/// 四舍五入到 `digits` 位小数
+fn fixed(value: &BigRational, digits: usize) -> String {
+    let scale = BigInt::from(10).pow(digits as u32);
+    let scaled = (value * BigRational::from_integer(scale)).round().to_integer();
+    let mut text = scaled.abs().to_string();
+    if digits > 0 {
+        if text.len() <= digits {
+            text = format!("{}{}", "0".repeat(digits + 1 - text.len()), text);
+        }
+        text.insert(text.len() - digits, '.');
+    }
+    if scaled.is_negative() {
+        text.insert(0, '-');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(text: &str) -> Number {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(matches!(number("42"), Number::Integer(_)));
+        assert!(matches!(number("1.5e3"), Number::Integer(_)));
+        assert_eq!(number("0.1").to_string(), "1/10");
+        assert_eq!(number("-2.5E-1").to_string(), "-1/4");
+        assert_eq!(number("6/4").to_string(), "3/2");
+        assert!(matches!(number("inf"), Number::Float(_)));
+        assert!(matches!(number("1e5000"), Number::Float(_)));
+        assert!("1/0".parse::<Number>().is_err());
+        assert!("abc".parse::<Number>().is_err());
+    }
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(Number::Float(0.1).convert(NumberMode::Rational).unwrap().to_string(), "1/10");
+        assert_eq!(number("1/3").convert(NumberMode::Float).unwrap(), Number::Float(1.0 / 3.0));
+        assert_eq!(Number::Float(4.0).convert(NumberMode::Integer).unwrap().to_string(), "4");
+        assert_eq!(
+            number("0.5").convert(NumberMode::Integer).unwrap_err().to_string(),
+            "整数模式下不能表示非整数 1/2"
+        );
+        assert!(Number::Float(f64::NAN).convert(NumberMode::Rational).is_err());
+        assert_eq!(Number::Float(0.5), number("1/2"));
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(number("1/3").format(OutputFormat::Decimal(5)), "0.33333");
+        assert_eq!(number("-2/3").format(OutputFormat::Decimal(2)), "-0.67");
+        assert_eq!(number("1/200").format(OutputFormat::Decimal(1)), "0.0");
+        assert_eq!(Number::Float(0.125).format(OutputFormat::Fraction), "1/8");
+        assert_eq!(Number::Float(2.0 / 3.0).format(OutputFormat::Decimal(3)), "0.667");
+        assert_eq!(number("3/8").to_decimal().as_deref(), Some("0.375"));
+        assert_eq!(number("1/3").to_decimal(), None);
+    }
+
+    #[test]
+    fn test_rational_ops() {
+        let op = |op, a: &str, b: &str| {
+            rational_op(op, &number(a).to_rational().unwrap(), &number(b).to_rational().unwrap())
+                .map(|value| fraction(&value))
+        };
+        assert_eq!(op(BinaryOp::Add, "0.1", "0.2").unwrap(), "3/10");
+        assert_eq!(op(BinaryOp::Rem, "-7/2", "1").unwrap(), "-1/2");
+        assert_eq!(op(BinaryOp::Pow, "2/3", "-2").unwrap(), "9/4");
+        assert_eq!(op(BinaryOp::Pow, "-1", "1000000001").unwrap(), "-1");
+        assert_eq!(op(BinaryOp::Pow, "4", "0.5").unwrap(), "2");
+        assert_eq!(op(BinaryOp::Div, "1", "0").unwrap_err(), CalcError::DivisionByZero);
+        assert!(op(BinaryOp::Pow, "-8", "1/3").is_err());
+        assert!(op(BinaryOp::Pow, "2", "1000000").is_err());
+    }
+
+    #[test]
+    fn test_float_pow() {
+        assert_eq!(float_pow(2.0, 0.5), Ok(std::f64::consts::SQRT_2));
+        assert_eq!(float_pow(-2.0, 3.0), Ok(-8.0));
+        assert_eq!(float_pow(0.0, -1.0), Err(CalcError::DivisionByZero));
+        assert_eq!(float_pow(-8.0, 0.5).unwrap_err().to_string(), "(-8)^0.5 不是实数");
+        assert_eq!(float_pow(10.0, 400.0).unwrap_err().to_string(), "10^400 超出了浮点数的范围");
+        assert_eq!(float_pow(f64::INFINITY, 2.0), Ok(f64::INFINITY));
+    }
+
+    #[test]
+    fn test_integer_ops() {
+        let op = |op, a: i64, b: i64| integer_op(op, &BigInt::from(a), &BigInt::from(b)).map(|value| value.to_string());
+        assert_eq!(op(BinaryOp::Div, -7, 2).unwrap(), "-3");
+        assert_eq!(op(BinaryOp::Rem, -7, 2).unwrap(), "-1");
+        assert_eq!(op(BinaryOp::Pow, 2, 100).unwrap(), "1267650600228229401496703205376");
+        assert!(op(BinaryOp::Pow, 2, -1).is_err());
+        assert!(op(BinaryOp::Rem, 1, 0).is_err());
+        assert_eq!(op(BinaryOp::BitXor, 0b1100, 0b1010).unwrap(), "6");
+        assert_eq!(op(BinaryOp::Shr, -7, 1).unwrap(), "-4");
+        assert!(op(BinaryOp::Shl, 1, -1).is_err());
+    }
+
+    #[test]
+    fn test_word_sizes() {
+        let u8: WordSize = "u8".parse().unwrap();
+        let i8: WordSize = "i8".parse().unwrap();
+        assert!("i7".parse::<WordSize>().is_err());
+        assert_eq!((i8.min(), i8.max()), (BigInt::from(-128), BigInt::from(127)));
+
+        let apply = |overflow: Overflow, word, value: i64| overflow.apply(word, BigInt::from(value)).map(|v| v.to_string());
+        assert_eq!(apply(Overflow::Wrapping, u8, 256).unwrap(), "0");
+        assert_eq!(apply(Overflow::Wrapping, i8, 128).unwrap(), "-128");
+        assert_eq!(apply(Overflow::Saturating, u8, -5).unwrap(), "0");
+        assert_eq!(apply(Overflow::Checked, u8, 255).unwrap(), "255");
+        assert_eq!(apply(Overflow::Checked, u8, 256).unwrap_err().to_string(), "结果 256 超出了 u8 的范围（0 到 255）");
+
+        let checked = NumberMode::Programmer { word: u8, overflow: Overflow::Checked };
+        assert_eq!(checked.fit(BigInt::from(-6), true).unwrap(), BigInt::from(250));
+        assert!(Number::from(300).convert(checked).is_err());
+
+        assert_eq!(i8.bases(&BigInt::from(-1)), "十六进制 0xff  八进制 0o377  二进制 0b1111_1111");
+        assert_eq!(u8.bases(&BigInt::from(10)), "十六进制 0xa  八进制 0o12  二进制 0b1010");
+    }
+}
@@ -0,0 +1,352 @@
+//! 数值方法：解方程、数值积分和多项式求根
+//!
+//! 这里只和 `f64` 打交道，要求解或积分的表达式由调用方包装成闭包。求值出错
+//! （如除以零）时错误原样传出；得到 `inf` 或 `NaN` 时也当作错误，不会把
+//! `NaN` 当作结果返回。
+
+use super::error::CalcError;
+use super::parser::BinaryOp;
+use super::value::Complex;
+use std::fmt;
+
+/// 牛顿法的最大迭代次数
+const MAX_ITERATIONS: usize = 100;
+
+/// 二分法的最大迭代次数，足够把任何区间缩小到相邻的两个 `f64`
+const MAX_BISECTIONS: usize = 2100;
+
+/// 迭代步长小于 `TOLERANCE * max(|x|, 1)` 时认为已经收敛
+const TOLERANCE: f64 = 1e-12;
+
+/// 收敛后 `|f(x)|` 仍大于这个值时，认为收敛到的是间断点而不是根
+const MAX_RESIDUAL: f64 = 1e-6;
+
+/// 自适应 Simpson 积分的最大求值次数
+const MAX_EVALUATIONS: usize = 200_000;
+
+/// 多项式求根的最大迭代次数
+const MAX_ROOT_ITERATIONS: usize = 1000;
+
+/// 被求解或积分的函数
+pub type Function<'a> = dyn FnMut(f64) -> Result<f64, CalcError> + 'a;
+
+/// 方程的根和收敛情况
+#[derive(Debug, Clone, PartialEq)]
+pub struct Root {
+    pub x: f64,
+    /// 使用的方法
+    pub method: &'static str,
+    pub iterations: usize,
+    /// 根处的函数值
+    pub residual: f64,
+}
+
+impl fmt::Display for Root {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}迭代 {} 次收敛，f(x) = {}", self.method, self.iterations, scientific(self.residual))
+    }
+}
+
+/// 积分值和误差估计
+#[derive(Debug, Clone, PartialEq)]
+pub struct Integral {
+    pub value: f64,
+    /// 各个子区间误差估计之和
+    pub error: f64,
+    /// 被积函数的求值次数
+    pub evaluations: usize,
+}
+
+impl fmt::Display for Integral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "自适应 Simpson 求值 {} 次，误差估计 {}", self.evaluations, scientific(self.error))
+    }
+}
+
+/// 用科学计数法显示误差，保留 3 位有效数字
+fn scientific(x: f64) -> String {
+    if x == 0.0 {
+        "0".to_string()
+    } else {
+        format!("{:.2e}", x)
+    }
+}
+
+/// 求函数值，结果不是有限的数时报错
+fn value(f: &mut Function, x: f64) -> Result<f64, CalcError> {
+    let y = f(x)?;
+    if y.is_finite() {
+        Ok(y)
+    } else {
+        Err(CalcError::Domain(format!("函数在 x = {} 处没有定义（得到 {}）", x, y)))
+    }
+}
+
+/// 检查收敛到的点确实是根
+fn check_root(root: Root) -> Result<Root, CalcError> {
+    if root.residual.abs() > MAX_RESIDUAL {
+        return Err(CalcError::NoConvergence(format!(
+            "{}收敛到 x = {}，但 f(x) = {}，这里可能是间断点而不是根",
+            root.method, root.x, root.residual
+        )));
+    }
+    Ok(root)
+}
+
+/// 从 `x0` 开始用牛顿法求 `f(x) = 0` 的根，`df` 是导函数
+pub fn newton(f: &mut Function, df: &mut Function, x0: f64) -> Result<Root, CalcError> {
+    let mut x = x0;
+    for iteration in 1..=MAX_ITERATIONS {
+        let fx = value(f, x)?;
+        if fx == 0.0 {
+            return Ok(Root { x, method: "牛顿法", iterations: iteration - 1, residual: 0.0 });
+        }
+        let slope = value(df, x)?;
+        if slope == 0.0 {
+            return Err(CalcError::NoConvergence(format!("牛顿法在 x = {} 处导数为 0，请换一个初值或给出区间", x)));
+        }
+        let next = x - fx / slope;
+        if !next.is_finite() {
+            return Err(CalcError::NoConvergence(format!("牛顿法在 x = {} 处发散，请换一个初值或给出区间", x)));
+        }
+        if (next - x).abs() <= TOLERANCE * next.abs().max(1.0) {
+            let residual = value(f, next)?;
+            return check_root(Root { x: next, method: "牛顿法", iterations: iteration, residual });
+        }
+        x = next;
+    }
+    Err(CalcError::NoConvergence(format!(
+        "牛顿法迭代 {} 次没有收敛（最后 x = {}），请换一个初值或给出区间",
+        MAX_ITERATIONS, x
+    )))
+}
+
+/// 在区间 `[a, b]` 内用二分法求根，`f(a)` 和 `f(b)` 必须异号
+///
+/// 二分法每次只缩小一半，但总能收敛，所以一直二分到区间不能再分为止。
+pub fn bisection(f: &mut Function, a: f64, b: f64) -> Result<Root, CalcError> {
+    let (mut a, mut b) = if a <= b { (a, b) } else { (b, a) };
+    let (mut fa, fb) = (value(f, a)?, value(f, b)?);
+    for (x, fx) in [(a, fa), (b, fb)] {
+        if fx == 0.0 {
+            return Ok(Root { x, method: "二分法", iterations: 0, residual: 0.0 });
+        }
+    }
+    if fa.signum() == fb.signum() {
+        return Err(CalcError::InvalidArgument(format!("f({}) 和 f({}) 同号，区间内不一定有根", a, b)));
+    }
+    for iteration in 1..=MAX_BISECTIONS {
+        let mid = a + (b - a) / 2.0;
+        let fm = value(f, mid)?;
+        if fm == 0.0 || mid == a || mid == b {
+            return check_root(Root { x: mid, method: "二分法", iterations: iteration, residual: fm });
+        }
+        if fm.signum() == fa.signum() {
+            a = mid;
+            fa = fm;
+        } else {
+            b = mid;
+        }
+    }
+    Err(CalcError::NoConvergence(format!("二分法迭代 {} 次没有收敛", MAX_BISECTIONS)))
+}
+
+/// 用自适应 Simpson 公式计算 `f` 在 `[a, b]` 上的积分，`a > b` 时结果取反
+pub fn integrate(f: &mut Function, a: f64, b: f64) -> Result<Integral, CalcError> {
+    if !a.is_finite() || !b.is_finite() {
+        return Err(CalcError::InvalidArgument("积分区间必须是有限的".to_string()));
+    }
+    let mut simpson = Simpson { f, evaluations: 0, error: 0.0 };
+    if a == b {
+        return Ok(Integral { value: 0.0, error: 0.0, evaluations: 0 });
+    }
+    let m = (a + b) / 2.0;
+    let (fa, fm, fb) = (simpson.value(a)?, simpson.value(m)?, simpson.value(b)?);
+    let whole = (b - a) / 6.0 * (fa + 4.0 * fm + fb);
+    let tolerance = 1e-10 * whole.abs().max(1.0);
+    let value = simpson.refine([a, m, b], [fa, fm, fb], whole, tolerance)?;
+    Ok(Integral { value, error: simpson.error, evaluations: simpson.evaluations })
+}
+
+/// 自适应 Simpson 积分的状态
+struct Simpson<'a, 'f> {
+    f: &'a mut Function<'f>,
+    evaluations: usize,
+    error: f64,
+}
+
+impl Simpson<'_, '_> {
+    fn value(&mut self, x: f64) -> Result<f64, CalcError> {
+        self.evaluations += 1;
+        if self.evaluations > MAX_EVALUATIONS {
+            return Err(CalcError::NoConvergence(format!("积分求值超过 {} 次仍没有收敛，被积函数可能发散或剧烈振荡", MAX_EVALUATIONS)));
+        }
+        value(self.f, x)
+    }
+
+    /// 把区间二等分，两半的和与整体相差太大时继续细分
+    fn refine(&mut self, [a, m, b]: [f64; 3], [fa, fm, fb]: [f64; 3], whole: f64, tolerance: f64) -> Result<f64, CalcError> {
+        let (lm, rm) = ((a + m) / 2.0, (m + b) / 2.0);
+        if lm == a || lm == m || rm == m || rm == b {
+            return Err(CalcError::NoConvergence(format!("积分在 x = {} 附近没有收敛，被积函数可能发散", m)));
+        }
+        let (flm, frm) = (self.value(lm)?, self.value(rm)?);
+        let left = (m - a) / 6.0 * (fa + 4.0 * flm + fm);
+        let right = (b - m) / 6.0 * (fm + 4.0 * frm + fb);
+        let delta = left + right - whole;
+        if delta.abs() <= 15.0 * tolerance {
+            self.error += delta.abs() / 15.0;
+            return Ok(left + right + delta / 15.0);
+        }
+        Ok(self.refine([a, lm, m], [fa, flm, fm], left, tolerance / 2.0)?
+            + self.refine([m, rm, b], [fm, frm, fb], right, tolerance / 2.0)?)
+    }
+}
+
+/// 多项式的全部根，`coefficients` 从常数项开始
+///
+/// 用 Durand–Kerner 方法同时迭代所有根。重根只能算到大约一半的有效数字，
+/// 所以虚部可以忽略的根换成实根后再用牛顿法修正，代入后恰好为 0 的近似整数根
+/// 换成整数。实根在前，按从小到大排列。
+pub fn roots(coefficients: &[f64]) -> Result<Vec<Complex>, CalcError> {
+    let Some(degree) = coefficients.iter().rposition(|c| *c != 0.0) else {
+        return Err(CalcError::InvalidArgument("零多项式的根不确定".to_string()));
+    };
+    if degree == 0 {
+        return Err(CalcError::InvalidArgument("常数多项式没有根".to_string()));
+    }
+    // 常数项为 0 时先提出因子 x
+    let zeros = coefficients.iter().position(|c| *c != 0.0).unwrap_or(0);
+    let monic: Vec<f64> = coefficients[zeros..=degree].iter().map(|c| c / coefficients[degree]).collect();
+    let mut roots = vec![Complex::from(0.0); zeros];
+    roots.extend(durand_kerner(&monic)?);
+
+    // 同时求多项式和导数的值
+    let polynomial = |x: f64| {
+        coefficients.iter().rev().fold((0.0, 0.0), |(p, dp), c| (p * x + c, dp * x + p))
+    };
+    for root in &mut roots {
+        if root.im.abs() > 1e-7 * root.re.abs().max(1.0) {
+            continue;
+        }
+        let mut x = root.re;
+        for _ in 0..MAX_ITERATIONS {
+            let (p, dp) = polynomial(x);
+            let next = x - p / dp;
+            if p == 0.0 || !next.is_finite() || (next - x).abs() <= f64::EPSILON * x.abs() {
+                break;
+            }
+            x = next;
+        }
+        *root = Complex::from(if polynomial(x.round()).0 == 0.0 { x.round() } else { x });
+    }
+    roots.sort_by(|a, b| {
+        (a.im != 0.0).cmp(&(b.im != 0.0))
+            .then(a.re.total_cmp(&b.re))
+            .then(b.im.total_cmp(&a.im))
+    });
+    Ok(roots)
+}
+
+/// 首一多项式（最高次系数为 1）的根
+fn durand_kerner(monic: &[f64]) -> Result<Vec<Complex>, CalcError> {
+    let degree = monic.len() - 1;
+    let evaluate = |z: Complex| monic.iter().rev().fold(Complex::from(0.0), |acc, c| acc * z + Complex::from(*c));
+    // 初值取一个既不是实数也不是单位根的数的各次幂
+    let seed = Complex::new(0.4, 0.9);
+    let mut roots: Vec<Complex> = (0..degree)
+        .scan(Complex::from(1.0), |z, _| {
+            *z = *z * seed;
+            Some(*z)
+        })
+        .collect();
+
+    for _ in 0..MAX_ROOT_ITERATIONS {
+        let mut step: f64 = 0.0;
+        for k in 0..degree {
+            let denominator = (0..degree)
+                .filter(|j| *j != k)
+                .fold(Complex::from(1.0), |acc, j| acc * (roots[k] - roots[j]));
+            let Ok(delta) = Complex::apply(BinaryOp::Div, evaluate(roots[k]), denominator) else {
+                // 两个近似根重合，稍微错开再继续
+                roots[k] = roots[k] + Complex::new(1e-8, 1e-8);
+                step = f64::INFINITY;
+                continue;
+            };
+            roots[k] = roots[k] - delta;
+            step = step.max(delta.abs() / roots[k].abs().max(1.0));
+        }
+        if roots.iter().any(|z| !z.re.is_finite() || !z.im.is_finite()) {
+            return Err(CalcError::NoConvergence("多项式求根发散".to_string()));
+        }
+        if step <= 1e-15 {
+            return Ok(roots);
+        }
+    }
+    Err(CalcError::NoConvergence(format!("多项式求根迭代 {} 次没有收敛", MAX_ROOT_ITERATIONS)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn test_newton() {
+        let root = newton(&mut |x| Ok(x * x * x - 2.0 * x - 5.0), &mut |x| Ok(3.0 * x * x - 2.0), 2.0).unwrap();
+        assert!(close(root.x, 2.0945514815423265), "{}", root.x);
+        assert!(root.iterations < 10);
+        assert_eq!(
+            newton(&mut |x| Ok(x * x + 1.0), &mut |x| Ok(2.0 * x), 0.0).unwrap_err().to_string(),
+            "牛顿法在 x = 0 处导数为 0，请换一个初值或给出区间"
+        );
+        let err = newton(&mut |x| Ok(x * x + 1.0), &mut |x| Ok(2.0 * x), 0.5).unwrap_err().to_string();
+        assert!(err.starts_with("牛顿法迭代 100 次没有收敛"), "{}", err);
+        assert_eq!(
+            newton(&mut |x| Ok(x.ln()), &mut |x| Ok(1.0 / x), -1.0).unwrap_err().to_string(),
+            "函数在 x = -1 处没有定义（得到 NaN）"
+        );
+    }
+
+    #[test]
+    fn test_bisection() {
+        let root = bisection(&mut |x| Ok(x.cos() - x), 0.0, 1.0).unwrap();
+        assert!(close(root.x, 0.7390851332151607), "{}", root.x);
+        assert_eq!(bisection(&mut |x| Ok(x * x + 1.0), -1.0, 2.0).unwrap_err().to_string(), "f(-1) 和 f(2) 同号，区间内不一定有根");
+        let err = bisection(&mut |x| Ok(if x < 0.3 { -1.0 } else { 1.0 }), 0.0, 1.0).unwrap_err().to_string();
+        assert!(err.contains("间断点"), "{}", err);
+    }
+
+    #[test]
+    fn test_integrate() {
+        let integral = integrate(&mut |x| Ok(x.sin()), 0.0, std::f64::consts::PI).unwrap();
+        assert!(close(integral.value, 2.0), "{}", integral.value);
+        assert!(close(integrate(&mut |x| Ok(x * x), 3.0, 0.0).unwrap().value, -9.0));
+        assert!(close(integrate(&mut |x| Ok(x.sqrt()), 0.0, 1.0).unwrap().value, 2.0 / 3.0));
+        assert_eq!(integrate(&mut |_| Ok(1.0), 2.0, 2.0).unwrap().value, 0.0);
+        assert_eq!(
+            integrate(&mut |x| Ok(1.0 / x), 0.0, 1.0).unwrap_err().to_string(),
+            "函数在 x = 0 处没有定义（得到 inf）"
+        );
+        assert!(integrate(&mut |x| Ok(1.0 / (x - 0.3)), 0.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_roots() {
+        let show = |coefficients: &[f64]| {
+            let show = |z: &Complex| if z.im == 0.0 { z.re.to_string() } else { z.to_string() };
+            roots(coefficients).map(|roots| roots.iter().map(show).collect::<Vec<_>>().join(", "))
+        };
+        assert_eq!(show(&[6.0, -5.0, 1.0]).unwrap(), "2, 3");
+        assert_eq!(show(&[0.0, -1.0, 0.0, 1.0]).unwrap(), "-1, 0, 1");
+        assert_eq!(show(&[1.0, 0.0, 1.0]).unwrap(), "i, -i");
+        let sqrt2 = roots(&[-2.0, 0.0, 1.0]).unwrap();
+        assert!(close(sqrt2[0].re, -2f64.sqrt()) && close(sqrt2[1].re, 2f64.sqrt()), "{:?}", sqrt2);
+        assert_eq!(show(&[1.0, 2.0, 1.0]).unwrap(), "-1, -1");
+        assert_eq!(show(&[5.0]).unwrap_err().to_string(), "常数多项式没有根");
+        assert_eq!(show(&[0.0, 0.0]).unwrap_err().to_string(), "零多项式的根不确定");
+    }
+}
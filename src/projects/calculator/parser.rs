@@ -0,0 +1,757 @@
+//! 语法分析：用 Pratt 解析法把记号构造成表达式树
+//!
+//! 优先级从低到高，位运算的顺序与 Rust 相同：
+//!
+//! | 运算符           | 结合性 |
+//! |------------------|--------|
+//! | `\|`             | 左结合 |
+//! | `^`（程序员模式） | 左结合 |
+//! | `&`              | 左结合 |
+//! | `<<` `>>`        | 左结合 |
+//! | `+` `-`          | 左结合 |
+//! | `*` `/` `%`      | 左结合 |
+//! | 一元 `-` `+` `~` | 前缀   |
+//! | `^` `**`         | 右结合 |
+//!
+//! 因此 `-2^2 = -4`，`2^3^2 = 2^9`。数字或右括号后面紧跟名称或左括号时
+//! 视为省略了乘号：`2x^2 = 2*(x^2)`，`3(1+2) = 9`。省略乘号的乘法比 `*` `/`
+//! 结合得更紧，所以 `5 MiB / 2 s` 是 `(5 MiB) / (2 s)`。程序员模式下 `^` 是
+//! 按位异或，乘方只能写成 `**`。
+//!
+//! 优先级最低的是单位换算 `表达式 in 单位`（也可以写作 `to`），如
+//! `3 km + 200 m in ft`。矩阵写在方括号里，逗号分隔元素，分号分隔行：
+//! `[1, 2; 3, 4]`。函数参数中可以写等式，如 `eval(f, x = 2)`。
+
+use super::lexer::{tokenize, Token, TokenKind};
+use super::number::Number;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// 带列号的解析错误
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// 出错位置，从 1 开始按字符计数
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(column: usize, message: impl Into<String>) -> Self {
+        Self { column, message: message.into() }
+    }
+
+    /// 在输入下方用 `^` 标出出错位置
+    pub fn pointer(&self) -> String {
+        format!("{}^", " ".repeat(self.column - 1))
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "第 {} 列：{}", self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 二元运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+}
+
+impl BinaryOp {
+    /// 运算符的书写形式
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Pow => "^",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+        }
+    }
+
+    /// 是否是位运算，位运算只能用于整数
+    pub fn is_bitwise(self) -> bool {
+        matches!(self, BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor | BinaryOp::Shl | BinaryOp::Shr)
+    }
+
+    /// 打印时使用的优先级，与解析时的绑定强度一致
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::BitOr => 1,
+            BinaryOp::BitXor => 2,
+            BinaryOp::BitAnd => 3,
+            BinaryOp::Shl | BinaryOp::Shr => 4,
+            BinaryOp::Add | BinaryOp::Sub => 5,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 6,
+            BinaryOp::Pow => 8,
+        }
+    }
+}
+
+/// 表达式树
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(Number),
+    Var(String),
+    /// `$n`
+    HistoryRef(usize),
+    Neg(Box<Expr>),
+    /// 按位取反 `~x`
+    Not(Box<Expr>),
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    Call {
+        name: String,
+        args: Vec<Expr>,
+    },
+    /// `expr in unit`，`unit` 只由单位名称、乘除和整数次幂组成
+    Convert {
+        expr: Box<Expr>,
+        unit: Box<Expr>,
+    },
+    /// `[1, 2; 3, 4]`，按行保存，每行的元素个数相同
+    Matrix(Vec<Vec<Expr>>),
+    /// `lhs = rhs`，只出现在函数参数中
+    Equation {
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+}
+
+impl Expr {
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Convert { .. } | Expr::Equation { .. } => 0,
+            Expr::Binary { op, .. } => op.precedence(),
+            Expr::Neg(_) | Expr::Not(_) => 7,
+            Expr::Number(value) if value.is_negative() => 7,
+            // 不能写成有限小数的分数显示为 `a/b`，与除法同级
+            Expr::Number(value) if value.to_decimal().is_none() => 6,
+            _ => 9,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        /// 优先级低于 `min` 时加括号
+        fn operand(f: &mut fmt::Formatter, expr: &Expr, min: u8) -> fmt::Result {
+            if expr.precedence() < min {
+                write!(f, "({})", expr)
+            } else {
+                write!(f, "{}", expr)
+            }
+        }
+
+        match self {
+            Expr::Number(value) => match value.to_decimal() {
+                Some(decimal) => write!(f, "{}", decimal),
+                None => write!(f, "{}", value),
+            },
+            Expr::Var(name) => write!(f, "{}", name),
+            Expr::HistoryRef(n) => write!(f, "${}", n),
+            Expr::Neg(inner) => {
+                write!(f, "-")?;
+                operand(f, inner, 7)
+            }
+            Expr::Not(inner) => {
+                write!(f, "~")?;
+                operand(f, inner, 7)
+            }
+            // 乘方右结合
+            Expr::Binary { op: BinaryOp::Pow, lhs, rhs } => {
+                let prec = BinaryOp::Pow.precedence();
+                operand(f, lhs, prec + 1)?;
+                write!(f, "^")?;
+                operand(f, rhs, prec)
+            }
+            // 其余左结合：同级的长链沿左侧操作数逐个写出，不随长度递归
+            Expr::Binary { op, .. } => {
+                let prec = op.precedence();
+                let mut operands = Vec::new();
+                let mut leftmost = self;
+                while let Expr::Binary { op, lhs, rhs } = leftmost {
+                    if *op == BinaryOp::Pow || op.precedence() != prec {
+                        break;
+                    }
+                    operands.push((op, rhs));
+                    leftmost = lhs;
+                }
+                operand(f, leftmost, prec)?;
+                for (op, rhs) in operands.into_iter().rev() {
+                    write!(f, " {} ", op.symbol())?;
+                    operand(f, rhs, prec + 1)?;
+                }
+                Ok(())
+            }
+            Expr::Call { name, args } => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "{}({})", name, args.join(", "))
+            }
+            Expr::Convert { expr, unit } => {
+                operand(f, expr, 0)?;
+                write!(f, " in ")?;
+                operand(f, unit, BinaryOp::Mul.precedence())
+            }
+            Expr::Matrix(rows) => {
+                let rows: Vec<String> = rows.iter()
+                    .map(|row| row.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(", "))
+                    .collect();
+                write!(f, "[{}]", rows.join("; "))
+            }
+            Expr::Equation { lhs, rhs } => write!(f, "{} = {}", lhs, rhs),
+        }
+    }
+}
+
+/// 一行输入：表达式、变量赋值或函数定义
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Expr(Expr),
+    /// `x = 3`
+    Assign { name: String, expr: Expr },
+    /// `f(x, y) = x^2 + y`
+    Define { name: String, params: Vec<String>, body: Expr },
+}
+
+/// 语法变体，只影响 `^` 的含义
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Syntax {
+    /// `^` 是乘方
+    #[default]
+    Standard,
+    /// `^` 是按位异或
+    Programmer,
+}
+
+/// 一元运算符的绑定强度：低于乘方，高于乘除
+const PREFIX_BP: u8 = 13;
+
+/// 乘法的左右绑定强度
+const MUL_BP: (u8, u8) = (11, 12);
+
+/// 省略乘号的乘法：比 `*` `/` 强，比一元运算符弱，所以 `-2pi = (-2)*pi`
+const IMPLICIT_MUL_BP: (u8, u8) = (12, 13);
+
+/// `in` 和 `to` 是单位换算关键字
+fn is_keyword(name: &str) -> bool {
+    matches!(name, "in" | "to")
+}
+
+/// 二元运算符及其左右绑定强度
+///
+/// 左结合运算符右边更强，右结合运算符左边更强。
+fn infix(kind: &TokenKind, syntax: Syntax) -> Option<(BinaryOp, u8, u8)> {
+    match kind {
+        TokenKind::Pipe => Some((BinaryOp::BitOr, 1, 2)),
+        TokenKind::Caret if syntax == Syntax::Programmer => Some((BinaryOp::BitXor, 3, 4)),
+        TokenKind::Amp => Some((BinaryOp::BitAnd, 5, 6)),
+        TokenKind::Shl => Some((BinaryOp::Shl, 7, 8)),
+        TokenKind::Shr => Some((BinaryOp::Shr, 7, 8)),
+        TokenKind::Plus => Some((BinaryOp::Add, 9, 10)),
+        TokenKind::Minus => Some((BinaryOp::Sub, 9, 10)),
+        TokenKind::Star => Some((BinaryOp::Mul, MUL_BP.0, MUL_BP.1)),
+        TokenKind::Slash => Some((BinaryOp::Div, MUL_BP.0, MUL_BP.1)),
+        TokenKind::Percent => Some((BinaryOp::Rem, MUL_BP.0, MUL_BP.1)),
+        TokenKind::Caret | TokenKind::StarStar => Some((BinaryOp::Pow, 16, 15)),
+        _ => None,
+    }
+}
+
+/// 解析完整的表达式
+pub fn parse(input: &str) -> Result<Expr, ParseError> {
+    let mut parser = Parser::new(input, Syntax::Standard)?;
+    let expr = parser.expression(0)?;
+    parser.finish()?;
+    Ok(expr)
+}
+
+/// 按标准语法解析一行输入，测试中使用
+#[cfg(test)]
+pub fn parse_statement(input: &str) -> Result<Statement, ParseError> {
+    parse_statement_with(input, Syntax::Standard)
+}
+
+/// 按指定的语法变体解析一行输入
+pub fn parse_statement_with(input: &str, syntax: Syntax) -> Result<Statement, ParseError> {
+    let mut parser = Parser::new(input, syntax)?;
+
+    let statement = if let Some(name) = parser.assignment_target() {
+        Statement::Assign { name, expr: parser.expression(0)? }
+    } else if let Some((name, params)) = parser.definition_head()? {
+        Statement::Define { name, params, body: parser.expression(0)? }
+    } else {
+        Statement::Expr(parser.expression(0)?)
+    };
+
+    parser.finish()?;
+    Ok(statement)
+}
+
+/// 表达式树最多嵌套的层数
+///
+/// 解析和求值都是递归的，不加限制时 `------1` 或成千上万层括号这样的输入会耗尽栈空间。
+const MAX_DEPTH: usize = 100;
+
+/// 一条输入最多的记号数
+///
+/// 左结合的长链不受 `MAX_DEPTH` 限制，但显示、复制和释放表达式树仍然是递归的，
+/// 所以另外限制输入的总长度。
+const MAX_TOKENS: usize = 10_000;
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    syntax: Syntax,
+    /// 当前位置在表达式树中的深度
+    depth: usize,
+}
+
+impl Parser {
+    fn new(input: &str, syntax: Syntax) -> Result<Self, ParseError> {
+        let tokens = tokenize(input)?;
+        // 最后一个记号是 End
+        if tokens.len() > MAX_TOKENS + 1 {
+            return Err(ParseError::new(tokens[MAX_TOKENS].column, format!("表达式过长（最多 {} 个记号）", MAX_TOKENS)));
+        }
+        Ok(Self { tokens, pos: 0, syntax, depth: 0 })
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn peek_kind(&self, offset: usize) -> &TokenKind {
+        let index = (self.pos + offset).min(self.tokens.len() - 1);
+        &self.tokens[index].kind
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// 确认已经读完所有记号
+    fn finish(&self) -> Result<(), ParseError> {
+        let token = self.peek();
+        match token.kind {
+            TokenKind::End => Ok(()),
+            TokenKind::RParen => Err(ParseError::new(token.column, "多余的右括号")),
+            TokenKind::RBracket => Err(ParseError::new(token.column, "多余的右方括号")),
+            TokenKind::Equals => Err(ParseError::new(token.column, "'=' 左边只能是变量名或函数定义")),
+            _ => Err(ParseError::new(token.column, "缺少运算符")),
+        }
+    }
+
+    /// 识别 `名称 =` 开头的赋值，成功时跳过这两个记号
+    fn assignment_target(&mut self) -> Option<String> {
+        match (self.peek_kind(0), self.peek_kind(1)) {
+            (TokenKind::Ident(name), TokenKind::Equals) => {
+                let name = name.clone();
+                self.pos += 2;
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    /// 识别 `名称(参数, ...) =` 开头的函数定义，成功时跳过这些记号
+    ///
+    /// 形式不符合时不消耗记号，按普通表达式（函数调用）解析。
+    fn definition_head(&mut self) -> Result<Option<(String, Vec<String>)>, ParseError> {
+        let (TokenKind::Ident(name), TokenKind::LParen) = (self.peek_kind(0), self.peek_kind(1)) else {
+            return Ok(None);
+        };
+
+        let mut params: Vec<(String, usize)> = Vec::new();
+        let mut offset = 2;
+        loop {
+            match self.peek_kind(offset) {
+                TokenKind::Ident(param) => params.push((param.clone(), self.tokens[self.pos + offset].column)),
+                TokenKind::RParen if params.is_empty() => break,
+                _ => return Ok(None),
+            }
+            match self.peek_kind(offset + 1) {
+                TokenKind::Comma => offset += 2,
+                TokenKind::RParen => {
+                    offset += 1;
+                    break;
+                }
+                _ => return Ok(None),
+            }
+        }
+        if *self.peek_kind(offset + 1) != TokenKind::Equals {
+            return Ok(None);
+        }
+
+        let mut seen = BTreeSet::new();
+        for (param, column) in &params {
+            if !seen.insert(param) {
+                return Err(ParseError::new(*column, format!("参数 '{}' 重复", param)));
+            }
+        }
+
+        let name = name.clone();
+        self.pos += offset + 2;
+        Ok(Some((name, params.into_iter().map(|(param, _)| param).collect())))
+    }
+
+    /// 进入表达式树的下一层，超过 `MAX_DEPTH` 时报错
+    fn descend(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(ParseError::new(self.peek().column, format!("表达式嵌套过深（最多 {} 层）", MAX_DEPTH)));
+        }
+        Ok(())
+    }
+
+    /// 解析绑定强度不低于 `min_bp` 的表达式
+    ///
+    /// 只有递归调用才会加深一层：括号、前缀运算符、函数参数和右结合的 `^`。
+    /// `1 + 2 + ... + n` 这样的左结合长链在循环中逐个拼接，不受深度限制。
+    fn expression(&mut self, min_bp: u8) -> Result<Expr, ParseError> {
+        let outer = self.depth;
+        self.descend()?;
+        let mut lhs = self.prefix()?;
+
+        loop {
+            if min_bp == 0 && matches!(&self.peek().kind, TokenKind::Ident(name) if is_keyword(name)) {
+                self.next();
+                let unit = self.expression(MUL_BP.0)?;
+                lhs = Expr::Convert { expr: Box::new(lhs), unit: Box::new(unit) };
+                continue;
+            }
+
+            let (op, left_bp, right_bp, explicit) = match infix(&self.peek().kind, self.syntax) {
+                Some((op, left_bp, right_bp)) => (op, left_bp, right_bp, true),
+                None if self.implicit_multiplication() => {
+                    (BinaryOp::Mul, IMPLICIT_MUL_BP.0, IMPLICIT_MUL_BP.1, false)
+                }
+                None => break,
+            };
+            if left_bp < min_bp {
+                break;
+            }
+            if explicit {
+                self.next();
+            }
+            let rhs = self.expression(right_bp)?;
+            lhs = Expr::Binary { op, lhs: Box::new(lhs), rhs: Box::new(rhs) };
+        }
+        self.depth = outer;
+        Ok(lhs)
+    }
+
+    /// 数字或右括号后面紧跟名称或左括号，如 `2x`、`3(x+1)`、`(a)(b)`
+    fn implicit_multiplication(&self) -> bool {
+        let previous = self.pos.checked_sub(1).map(|index| &self.tokens[index].kind);
+        matches!(previous, Some(TokenKind::Number(_) | TokenKind::RParen))
+            && match &self.peek().kind {
+                TokenKind::Ident(name) => !is_keyword(name),
+                kind => *kind == TokenKind::LParen,
+            }
+    }
+
+    fn prefix(&mut self) -> Result<Expr, ParseError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::Number(value) => Ok(Expr::Number(value)),
+            TokenKind::HistoryRef(n) => Ok(Expr::HistoryRef(n)),
+            TokenKind::Ident(name) => {
+                if self.peek().kind == TokenKind::LParen {
+                    let open = self.next();
+                    let args = self.arguments(open.column)?;
+                    Ok(Expr::Call { name, args })
+                } else {
+                    Ok(Expr::Var(name))
+                }
+            }
+            TokenKind::Minus => Ok(Expr::Neg(Box::new(self.expression(PREFIX_BP)?))),
+            TokenKind::Tilde => Ok(Expr::Not(Box::new(self.expression(PREFIX_BP)?))),
+            TokenKind::Plus => self.expression(PREFIX_BP),
+            TokenKind::LParen => {
+                let expr = self.expression(0)?;
+                self.close(token.column)?;
+                Ok(expr)
+            }
+            TokenKind::LBracket => self.matrix(token.column),
+            TokenKind::End => Err(ParseError::new(token.column, "表达式不完整")),
+            TokenKind::RParen => Err(ParseError::new(token.column, "括号中缺少表达式")),
+            _ => Err(ParseError::new(token.column, "此处需要数字或表达式")),
+        }
+    }
+
+    /// 解析函数调用的参数列表，左括号已经读过；参数可以是等式
+    fn arguments(&mut self, open: usize) -> Result<Vec<Expr>, ParseError> {
+        let mut args = Vec::new();
+        if self.peek().kind == TokenKind::RParen {
+            self.next();
+            return Ok(args);
+        }
+        loop {
+            let arg = self.expression(0)?;
+            if self.peek().kind == TokenKind::Equals {
+                self.next();
+                let rhs = self.expression(0)?;
+                args.push(Expr::Equation { lhs: Box::new(arg), rhs: Box::new(rhs) });
+            } else {
+                args.push(arg);
+            }
+            if self.peek().kind == TokenKind::Comma {
+                self.next();
+            } else {
+                self.close(open)?;
+                return Ok(args);
+            }
+        }
+    }
+
+    /// 解析矩阵的各行，左方括号已经读过
+    fn matrix(&mut self, open: usize) -> Result<Expr, ParseError> {
+        if self.peek().kind == TokenKind::RBracket {
+            return Err(ParseError::new(self.peek().column, "矩阵不能为空"));
+        }
+        let mut rows: Vec<Vec<Expr>> = vec![Vec::new()];
+        let mut row_start = self.peek().column;
+        loop {
+            let row = rows.last_mut().expect("至少有一行");
+            row.push(self.expression(0)?);
+            let count = row.len();
+            let token = self.next();
+            match token.kind {
+                TokenKind::Comma => continue,
+                TokenKind::Semicolon | TokenKind::RBracket if rows.len() > 1 && count != rows[0].len() => {
+                    return Err(ParseError::new(row_start, format!(
+                        "矩阵第 {} 行有 {} 个元素，第 1 行有 {} 个",
+                        rows.len(), count, rows[0].len()
+                    )));
+                }
+                TokenKind::Semicolon => {
+                    rows.push(Vec::new());
+                    row_start = self.peek().column;
+                }
+                TokenKind::RBracket => return Ok(Expr::Matrix(rows)),
+                TokenKind::End => {
+                    return Err(ParseError::new(token.column, format!("缺少与第 {} 列匹配的右方括号", open)));
+                }
+                _ => return Err(ParseError::new(token.column, "缺少运算符")),
+            }
+        }
+    }
+
+    /// 读取与第 `open` 列的左括号匹配的右括号
+    fn close(&mut self, open: usize) -> Result<(), ParseError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::RParen => Ok(()),
+            TokenKind::End => Err(ParseError::new(
+                token.column,
+                format!("缺少与第 {} 列匹配的右括号", open),
+            )),
+            _ => Err(ParseError::new(token.column, "缺少运算符")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(value: f64) -> Box<Expr> {
+        Box::new(Expr::Number(value.into()))
+    }
+
+    #[test]
+    fn test_precedence_and_associativity() {
+        assert_eq!(parse("1 - 2 - 3").unwrap(), Expr::Binary {
+            op: BinaryOp::Sub,
+            lhs: Box::new(Expr::Binary { op: BinaryOp::Sub, lhs: num(1.0), rhs: num(2.0) }),
+            rhs: num(3.0),
+        });
+        assert_eq!(parse("2 ^ 3 ** 2").unwrap(), Expr::Binary {
+            op: BinaryOp::Pow,
+            lhs: num(2.0),
+            rhs: Box::new(Expr::Binary { op: BinaryOp::Pow, lhs: num(3.0), rhs: num(2.0) }),
+        });
+        assert_eq!(parse("-2^2").unwrap(), Expr::Neg(Box::new(Expr::Binary {
+            op: BinaryOp::Pow,
+            lhs: num(2.0),
+            rhs: num(2.0),
+        })));
+    }
+
+    #[test]
+    fn test_parentheses() {
+        assert_eq!(parse("2*(3+4)").unwrap(), Expr::Binary {
+            op: BinaryOp::Mul,
+            lhs: num(2.0),
+            rhs: Box::new(Expr::Binary { op: BinaryOp::Add, lhs: num(3.0), rhs: num(4.0) }),
+        });
+    }
+
+    #[test]
+    fn test_implicit_multiplication() {
+        assert_eq!(parse("2x^2").unwrap(), parse("2 * x^2").unwrap());
+        assert_eq!(parse("3(1 + 2)").unwrap(), parse("3 * (1 + 2)").unwrap());
+        assert_eq!(parse("-2pi").unwrap(), parse("(-2) * pi").unwrap());
+        assert_eq!(parse("5 MiB / 2 s").unwrap(), parse("(5 * MiB) / (2 * s)").unwrap());
+        assert_eq!(parse("1 / 2x^2").unwrap().to_string(), "1 / (2 * x^2)");
+        assert!(parse("x y").is_err());
+    }
+
+    #[test]
+    fn test_unit_conversion() {
+        assert_eq!(parse("3 km + 200 m in ft").unwrap(), Expr::Convert {
+            expr: Box::new(parse("3 km + 200 m").unwrap()),
+            unit: Box::new(Expr::Var("ft".to_string())),
+        });
+        assert_eq!(parse("x to km/h").unwrap(), parse("x in km / h").unwrap());
+        assert_eq!(parse("f(2 in m) in m^2 + 1").unwrap().to_string(), "(f(2 in m) in m^2) + 1");
+        assert_eq!(parse("2 in").unwrap_err(), ParseError::new(5, "表达式不完整"));
+    }
+
+    #[test]
+    fn test_matrices() {
+        let expr = parse("[1, 2; 3, x^2]").unwrap();
+        assert_eq!(expr, Expr::Matrix(vec![
+            vec![Expr::Number(1.0.into()), Expr::Number(2.0.into())],
+            vec![Expr::Number(3.0.into()), parse("x^2").unwrap()],
+        ]));
+        assert_eq!(expr.to_string(), "[1, 2; 3, x^2]");
+        assert_eq!(parse("2 * [1, 2]").unwrap().to_string(), "2 * [1, 2]");
+        assert_eq!(parse("3 + 4i").unwrap(), parse("3 + 4 * i").unwrap());
+
+        for (input, column, message) in [
+            ("[]", 2, "矩阵不能为空"),
+            ("[1, 2; 3]", 8, "矩阵第 2 行有 1 个元素，第 1 行有 2 个"),
+            ("[1, 2", 6, "缺少与第 1 列匹配的右方括号"),
+            ("[1 2]", 4, "缺少运算符"),
+            ("[1]]", 4, "多余的右方括号"),
+        ] {
+            assert_eq!(parse(input).unwrap_err(), ParseError::new(column, message), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_display_roundtrip() {
+        let chain = vec!["x - 1"; 2500].join(" + ");
+        for input in ["x^2 + 1", "0.25 * 1500", "-(a + b) * c", "2 - 3 - (4 - 5)", "(x^y)^z", "(-2)^2", "f(x, y + 1) / 2", "$1 * 2", "a * b / c + d - e * f", &chain] {
+            let expr = parse(input).unwrap();
+            assert_eq!(expr.to_string(), input);
+            assert_eq!(parse(&expr.to_string()).unwrap(), expr);
+        }
+    }
+
+    #[test]
+    fn test_bitwise_operators() {
+        // 与 Rust 相同：移位低于加减，& 低于移位，| 最低
+        assert_eq!(parse("1 | 2 & 3 << 1 + 1").unwrap(), parse("1 | (2 & (3 << (1 + 1)))").unwrap());
+        assert_eq!(parse("~x & 0xff").unwrap(), parse("(~x) & 255").unwrap());
+        assert_eq!(parse("a & b | c << 2").unwrap().to_string(), "a & b | c << 2");
+        assert_eq!(parse("(a | b) & c").unwrap().to_string(), "(a | b) & c");
+    }
+
+    #[test]
+    fn test_programmer_syntax() {
+        let programmer = |input| match parse_statement_with(input, Syntax::Programmer).unwrap() {
+            Statement::Expr(expr) => expr,
+            statement => panic!("{:?}", statement),
+        };
+        let var = |name: &str| Box::new(Expr::Var(name.to_string()));
+        assert_eq!(programmer("a ^ b & c"), Expr::Binary {
+            op: BinaryOp::BitXor,
+            lhs: var("a"),
+            rhs: Box::new(Expr::Binary { op: BinaryOp::BitAnd, lhs: var("b"), rhs: var("c") }),
+        });
+        assert_eq!(programmer("2 ** 3"), parse("2 ^ 3").unwrap());
+    }
+
+    #[test]
+    fn test_statements() {
+        assert_eq!(parse_statement("x = 3").unwrap(), Statement::Assign {
+            name: "x".to_string(),
+            expr: Expr::Number(3.0.into()),
+        });
+        assert_eq!(parse_statement("f(x, y) = x").unwrap(), Statement::Define {
+            name: "f".to_string(),
+            params: vec!["x".to_string(), "y".to_string()],
+            body: Expr::Var("x".to_string()),
+        });
+        assert!(matches!(parse_statement("f(2)").unwrap(), Statement::Expr(Expr::Call { .. })));
+        assert_eq!(parse_statement("eval(f, x = 2)").unwrap(), Statement::Expr(Expr::Call {
+            name: "eval".to_string(),
+            args: vec![
+                Expr::Var("f".to_string()),
+                Expr::Equation { lhs: Box::new(Expr::Var("x".to_string())), rhs: Box::new(Expr::Number(2.0.into())) },
+            ],
+        }));
+        assert_eq!(parse("solve(x^2 = 2x + 1, x)").unwrap().to_string(), "solve(x^2 = 2 * x + 1, x)");
+        assert_eq!(parse("f(x = 1 = 2)").unwrap_err(), ParseError::new(9, "缺少运算符"));
+
+        assert_eq!(parse_statement("f(x, x) = 1").unwrap_err(), ParseError::new(6, "参数 'x' 重复"));
+        assert_eq!(parse_statement("1 + x = 2").unwrap_err().column, 7);
+    }
+
+    #[test]
+    fn test_error_columns() {
+        let cases = [
+            ("5 +", 4, "表达式不完整"),
+            ("(1 + 2", 7, "缺少与第 1 列匹配的右括号"),
+            ("1 + 2)", 6, "多余的右括号"),
+            ("2 3", 3, "缺少运算符"),
+            ("()", 2, "括号中缺少表达式"),
+            ("1 * / 2", 5, "此处需要数字或表达式"),
+            ("max(1, 2", 9, "缺少与第 4 列匹配的右括号"),
+        ];
+        for (input, column, message) in cases {
+            assert_eq!(parse(input).unwrap_err(), ParseError::new(column, message), "{}", input);
+        }
+        assert_eq!(ParseError::new(4, "").pointer(), "   ^");
+    }
+
+    #[test]
+    fn test_nesting_depth() {
+        let nested = |n: usize| format!("{}1{}", "(".repeat(n), ")".repeat(n));
+        assert!(parse(&nested(MAX_DEPTH - 1)).is_ok());
+        assert!(parse(&format!("{}1", "-".repeat(MAX_DEPTH - 1))).is_ok());
+
+        let message = format!("表达式嵌套过深（最多 {} 层）", MAX_DEPTH);
+        assert_eq!(parse(&nested(3000)).unwrap_err(), ParseError::new(MAX_DEPTH + 1, message.clone()));
+        assert_eq!(parse(&format!("{}1", "-".repeat(1000))).unwrap_err(), ParseError::new(MAX_DEPTH + 1, message.clone()));
+        assert_eq!(parse(&format!("{}1", "2^".repeat(3000))).unwrap_err().message, message);
+        assert_eq!(parse(&format!("{}1{}", "f(".repeat(3000), ")".repeat(3000))).unwrap_err().message, message);
+        assert_eq!(parse(&format!("1{}", " + (1".repeat(200))).unwrap_err().message, message);
+
+        // 左结合的长链没有嵌套，只受总长度限制
+        assert!(parse(&vec!["1"; 5000].join(" + ")).is_ok());
+        assert!(parse(&vec!["2"; 5000].join(" * ")).is_ok());
+        assert!(parse(&format!("{}1", "2 * 3 + ".repeat(2000))).is_ok());
+        let message = format!("表达式过长（最多 {} 个记号）", MAX_TOKENS);
+        assert_eq!(parse(&vec!["1"; 5001].join("+")).unwrap_err(), ParseError::new(MAX_TOKENS + 1, message));
+    }
+}
@@ -0,0 +1,339 @@
+//! 在终端里画函数图像
+//!
+//! 盲文字符（U+2800 起）每个有 2×4 个点，一行文字就能画出比 ASCII 细四倍的曲线；
+//! 多条曲线画在一起时盲文点分不清是哪一条，改用每条曲线一个字符的 ASCII 画法。
+//! 相邻两个采样点之间用竖线连起来，跨过极点（如 `tan(x)` 在 π/2 处）的跳变和
+//! 没有定义的点（如 `ln(x)` 在 x ≤ 0 处）都会断开。纵轴的范围按函数值自动确定。
+
+use super::error::CalcError;
+use std::fmt;
+use std::str::FromStr;
+
+/// 默认的图像宽度和高度，按字符计
+const WIDTH: usize = 64;
+const HEIGHT: usize = 16;
+
+/// ASCII 画法中各条曲线使用的字符
+const MARKERS: [char; 5] = ['*', '+', 'o', 'x', '#'];
+
+/// 盲文字符中第 (行, 列) 个点对应的位
+const BRAILLE: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// 画法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// 盲文点阵，每个字符 2×4 个点
+    Braille,
+    /// 每个字符一个点，不同曲线用不同字符
+    Ascii,
+}
+
+impl Style {
+    /// 每个字符横向和纵向的点数
+    fn resolution(self) -> (usize, usize) {
+        match self {
+            Style::Braille => (2, 4),
+            Style::Ascii => (1, 1),
+        }
+    }
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "braille" => Ok(Style::Braille),
+            "ascii" => Ok(Style::Ascii),
+            _ => Err(format!("未知的画法 '{}'（可选：braille, ascii）", s)),
+        }
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Style::Braille => write!(f, "braille"),
+            Style::Ascii => write!(f, "ascii"),
+        }
+    }
+}
+
+/// 一条曲线：图例中的名称和函数，求值出错的点当作没有定义
+pub struct Series<'a> {
+    pub label: String,
+    pub f: Box<dyn Fn(f64) -> Result<f64, CalcError> + 'a>,
+}
+
+/// 图像的画法和大小
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Chart {
+    pub style: Style,
+    /// 绘图区的宽度和高度，按字符计，不含坐标标签
+    pub width: usize,
+    pub height: usize,
+}
+
+/// 绘图区中的一个字符
+#[derive(Debug, Clone, Copy, Default)]
+struct Cell {
+    /// 盲文点
+    dots: u8,
+    /// 最后画在这里的曲线
+    series: Option<usize>,
+}
+
+impl Chart {
+    pub fn new(style: Style) -> Self {
+        Self { style, width: WIDTH, height: HEIGHT }
+    }
+
+    /// 在 `[from, to]` 上画出各条曲线，每一列点采样一次
+    pub fn render(&self, series: &[Series], from: f64, to: f64) -> Result<String, CalcError> {
+        let (rx, ry) = self.style.resolution();
+        let (columns, rows) = (self.width * rx, self.height * ry);
+        let xs: Vec<f64> = (0..columns)
+            .map(|i| from + (to - from) * i as f64 / (columns - 1).max(1) as f64)
+            .collect();
+        let points = series.iter()
+            .map(|series| sample(series, &xs))
+            .collect::<Result<Vec<_>, CalcError>>()?;
+        let mut values: Vec<f64> = points.iter().flatten().flatten().copied().collect();
+        if values.is_empty() {
+            return Err(CalcError::Domain("函数在整个区间内都没有定义".to_string()));
+        }
+        let (lo, hi) = y_range(&mut values);
+
+        let mut cells = vec![vec![Cell::default(); self.width]; self.height];
+        // 函数值对应的点的行号，可能在绘图区外面
+        let row_of = |y: f64| (hi - y) / (hi - lo) * (rows - 1) as f64;
+        for (index, (series, points)) in series.iter().zip(&points).enumerate() {
+            for (column, y) in points.iter().enumerate() {
+                let Some(y) = *y else { continue };
+                let row = row_of(y);
+                // 要画的竖线：(列, 起点行, 终点行)
+                let mut spans = vec![(column, row, row)];
+                if let Some(Some(before)) = column.checked_sub(1).map(|c| points[c]) {
+                    let gap = (row - row_of(before)).abs() > 1.0;
+                    if gap && connected(&*series.f, (xs[column - 1], before), (xs[column], y), (hi - lo) / rows as f64) {
+                        // 相差超过一行时，前一列画到两点的中间，这一列从中间画起
+                        let (before, middle) = (row_of(before), (row + row_of(before)) / 2.0);
+                        spans = vec![(column - 1, before, middle), (column, middle, row)];
+                    }
+                }
+                for (column, a, b) in spans {
+                    let (top, bottom) = (a.min(b).round().max(0.0), a.max(b).round().min((rows - 1) as f64));
+                    if top > bottom {
+                        continue;
+                    }
+                    for r in top as usize..=bottom as usize {
+                        let cell = &mut cells[r / ry][column / rx];
+                        cell.dots |= BRAILLE[r % ry][column % rx];
+                        cell.series = Some(index);
+                    }
+                }
+            }
+        }
+
+        // 坐标轴所在的行和列
+        let axis_row = (lo <= 0.0 && 0.0 <= hi).then(|| row_of(0.0).round() as usize / ry);
+        let axis_column = (from <= 0.0 && 0.0 <= to)
+            .then(|| ((0.0 - from) / (to - from) * (columns - 1) as f64).round() as usize / rx);
+
+        let labels: Vec<(usize, String)> = [Some((0, hi)), axis_row.map(|row| (row, 0.0)), Some((self.height - 1, lo))]
+            .into_iter()
+            .flatten()
+            .map(|(row, y)| (row, label(y)))
+            .collect();
+        let margin = labels.iter().map(|(_, text)| text.chars().count()).max().unwrap_or(0);
+
+        let mut lines = Vec::with_capacity(self.height + 3);
+        for (r, row) in cells.iter().enumerate() {
+            let text: String = row.iter()
+                .enumerate()
+                .map(|(c, cell)| match (cell.series, self.style) {
+                    (Some(_), Style::Braille) => char::from_u32(0x2800 + u32::from(cell.dots)).unwrap_or(' '),
+                    (Some(index), Style::Ascii) => MARKERS[index % MARKERS.len()],
+                    (None, _) => match (Some(r) == axis_row, Some(c) == axis_column) {
+                        (true, true) => '┼',
+                        (true, false) => '─',
+                        (false, true) => '│',
+                        (false, false) => ' ',
+                    },
+                })
+                .collect();
+            let line = match labels.iter().find(|(row, _)| *row == r) {
+                Some((_, y)) => format!("{:>margin$} ┤{}", y, text),
+                None => format!("{:>margin$} │{}", "", text),
+            };
+            lines.push(line.trim_end().to_string());
+        }
+        lines.push(format!("{:>margin$} └{}", "", "─".repeat(self.width)));
+        lines.push(format!("{:>margin$}  {}", "", x_labels(from, to, self.width)).trim_end().to_string());
+
+        let legend: Vec<String> = series.iter()
+            .zip(&points)
+            .enumerate()
+            .map(|(index, (series, points))| {
+                let marker = match self.style {
+                    Style::Braille => '⣿',
+                    Style::Ascii => MARKERS[index % MARKERS.len()],
+                };
+                let undefined = points.iter().filter(|y| y.is_none()).count();
+                if undefined == 0 {
+                    format!("{} {}", marker, series.label)
+                } else {
+                    format!("{} {}（{} 个点没有定义）", marker, series.label, undefined)
+                }
+            })
+            .collect();
+        lines.push(format!("{:>margin$}  {}", "", legend.join("   ")));
+        Ok(lines.join("\n"))
+    }
+}
+
+/// 在每个采样点求值；所有点都出错时报告第一个错误，如未定义的变量
+fn sample(series: &Series, xs: &[f64]) -> Result<Vec<Option<f64>>, CalcError> {
+    let mut error = None;
+    let points: Vec<Option<f64>> = xs.iter()
+        .map(|x| match (series.f)(*x) {
+            Ok(y) => y.is_finite().then_some(y),
+            Err(e) => {
+                error.get_or_insert(e);
+                None
+            }
+        })
+        .collect();
+    match error {
+        Some(error) if points.iter().all(Option::is_none) => Err(CalcError::Context { context: series.label.clone(), error: Box::new(error) }),
+        _ => Ok(points),
+    }
+}
+
+/// 相邻两个采样点之间是否连续
+///
+/// 相差不到两个点时直接连线；否则在中点再求一次值，中点没有定义或者不在
+/// 两个值之间（如 `1/x` 在 0 处、`tan(x)` 在 π/2 处）就是间断点。
+fn connected(f: &dyn Fn(f64) -> Result<f64, CalcError>, (x0, y0): (f64, f64), (x1, y1): (f64, f64), step: f64) -> bool {
+    if (y1 - y0).abs() <= 2.0 * step {
+        return true;
+    }
+    let (low, high) = (y0.min(y1), y0.max(y1));
+    let slack = (high - low) * 0.01;
+    matches!(f((x0 + x1) / 2.0), Ok(y) if low - slack <= y && y <= high + slack)
+}
+
+/// 纵轴的范围
+///
+/// 有极点时（如 `tan(x)`）少数几个点的值会大得离谱，这时按中间 96% 的点
+/// 定范围，否则画出全部的点。
+fn y_range(values: &mut [f64]) -> (f64, f64) {
+    values.sort_by(f64::total_cmp);
+    let (min, max) = (values[0], values[values.len() - 1]);
+    let quantile = |q: f64| values[((values.len() - 1) as f64 * q).round() as usize];
+    let (low, high) = (quantile(0.02), quantile(0.98));
+    let (lo, hi) = if max - min > 10.0 * (high - low) {
+        let margin = (high - low) * 0.1;
+        (low - margin, high + margin)
+    } else {
+        (min, max)
+    };
+    if lo == hi {
+        (lo - 1.0, hi + 1.0)
+    } else {
+        (lo, hi)
+    }
+}
+
+/// 横轴下方的标签：起点、中点和终点，放不下时省略中点
+fn x_labels(from: f64, to: f64, width: usize) -> String {
+    let (left, middle, right) = (label(from), label((from + to) / 2.0), label(to));
+    let gap = width.saturating_sub(left.len() + middle.len() + right.len());
+    if gap >= 2 {
+        let first = (width / 2).saturating_sub(left.len() + middle.len() / 2).clamp(1, gap - 1);
+        format!("{}{}{}{}{}", left, " ".repeat(first), middle, " ".repeat(gap - first), right)
+    } else {
+        format!("{}{}{}", left, " ".repeat(width.saturating_sub(left.len() + right.len()).max(1)), right)
+    }
+}
+
+/// 坐标标签，保留三位小数，很大或很小的数用科学计数法
+fn label(value: f64) -> String {
+    if value != 0.0 && (value.abs() >= 1e5 || value.abs() < 1e-3) {
+        return format!("{:.2e}", value);
+    }
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0" } else { text }.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plot(style: Style, width: usize, height: usize, f: impl Fn(f64) -> Option<f64>, from: f64, to: f64) -> String {
+        let chart = Chart { style, width, height };
+        let f = move |x| f(x).ok_or_else(|| CalcError::Domain("没有定义".to_string()));
+        chart.render(&[Series { label: "f".to_string(), f: Box::new(f) }], from, to).unwrap()
+    }
+
+    #[test]
+    fn test_ascii() {
+        let expected = [
+            " 1 ┤  │ *",
+            "   │  │*",
+            " 0 ┤──*──",
+            "   │ *│",
+            "-1 ┤* │",
+            "   └─────",
+            "    -1  1",
+            "    * f",
+        ];
+        assert_eq!(plot(Style::Ascii, 5, 5, Some, -1.0, 1.0), expected.join("\n"));
+    }
+
+    #[test]
+    fn test_braille() {
+        // 一个字符里的 2×4 个点：两列各画一个点，再连到两点中间
+        let text = plot(Style::Braille, 1, 1, Some, 0.0, 1.0);
+        assert_eq!(text.lines().next().unwrap(), "1 ┤⡼");
+        let text = plot(Style::Braille, 8, 4, |x| Some(x.sin()), -3.0, 3.0);
+        assert_eq!(text.lines().count(), 7);
+        assert!(text.ends_with("⣿ f"), "{}", text);
+    }
+
+    #[test]
+    fn test_discontinuities() {
+        // 1/x 在 0 两侧的两个点（第 4、5 列）之间不连线，横轴上只有两端的点
+        let text = plot(Style::Ascii, 10, 9, |x| Some(1.0 / x), -1.0, 1.0);
+        let axis = text.lines().find(|line| line.starts_with(" 0 ┤")).unwrap();
+        assert_eq!(axis, " 0 ┤*────┼───*", "{}", text);
+
+        let text = plot(Style::Ascii, 9, 5, |x| (x != 0.0).then(|| x.abs()), -1.0, 1.0);
+        assert!(text.ends_with("* f（1 个点没有定义）"), "{}", text);
+
+        let chart = Chart::new(Style::Braille);
+        let undefined = Series { label: "ln(x)".to_string(), f: Box::new(|x: f64| Ok(x.ln())) };
+        assert_eq!(chart.render(&[undefined], -2.0, -1.0).unwrap_err().to_string(), "函数在整个区间内都没有定义");
+        let failing = Series { label: "y".to_string(), f: Box::new(|_| Err(CalcError::UndefinedVariable("y".to_string()))) };
+        assert_eq!(chart.render(&[failing], 0.0, 1.0).unwrap_err().to_string(), "y：未定义的变量 'y'");
+    }
+
+    #[test]
+    fn test_y_range() {
+        // tan(x) 的极点附近只有少数几个很大的值，不会把其他部分压扁
+        let mut values: Vec<f64> = (0..100).map(|i| f64::from(i) / 100.0).chain([1e6, -1e6]).collect();
+        let (lo, hi) = y_range(&mut values);
+        assert!(lo > -1.0 && hi < 2.0, "{} {}", lo, hi);
+        assert_eq!(y_range(&mut [3.0, 3.0]), (2.0, 4.0));
+    }
+
+    #[test]
+    fn test_labels() {
+        assert_eq!(label(1.23456), "1.235");
+        assert_eq!(label(-0.0001), "-1.00e-4");
+        assert_eq!(label(-0.0), "0");
+        assert_eq!(label(2.0), "2");
+        assert_eq!(x_labels(-1.23456, 1.23456, 20), "-1.235    0    1.235");
+    }
+}
@@ -0,0 +1,585 @@
+//! 符号运算：求导、化简和代入
+//!
+//! 表达式树本身就是符号表达式，这里的每个函数都是对 `Expr` 各种节点的一个
+//! `match`。求导按求导法则逐个节点展开，结果往往很啰嗦（`2 * x^1 * 1`），
+//! 再交给 `simplify` 做常量折叠和代数恒等式化简。三角函数的导数按弧度计算。
+
+use super::error::CalcError;
+use super::number::{self, Number};
+use super::parser::{BinaryOp, Expr};
+use std::collections::BTreeSet;
+
+/// 参数按表达式而不是按值传递的函数及其用法
+pub static FORMS: &[(&str, &str)] = &[
+    ("diff", "diff(f, x)  对 x 求导，得到符号表达式"),
+    ("simplify", "simplify(f)  常量折叠和代数化简"),
+    ("eval", "eval(f, x = 1, ...)  代入变量后求值"),
+    ("solve", "solve(f = g, x, x0)  从 x0 开始用牛顿法解方程；solve(f = g, x, a, b) 在区间内二分"),
+    ("integrate", "integrate(f, a, b)  自适应 Simpson 数值积分；integrate(f, x, a, b) 指定积分变量"),
+    ("roots", "roots(p)  多项式的全部实根，复根在附注中列出；roots(p, x) 指定变量"),
+];
+
+/// 按名称查找按表达式传参的函数，返回用法
+pub fn form(name: &str) -> Option<&'static str> {
+    FORMS.iter().find(|(form, _)| *form == name).map(|(_, usage)| *usage)
+}
+
+/// 表达式中是否出现变量 `var`
+pub fn depends(expr: &Expr, var: &str) -> bool {
+    match expr {
+        Expr::Var(name) => name == var,
+        Expr::Number(_) | Expr::HistoryRef(_) => false,
+        Expr::Neg(inner) | Expr::Not(inner) => depends(inner, var),
+        Expr::Binary { lhs, rhs, .. } | Expr::Equation { lhs, rhs } => depends(lhs, var) || depends(rhs, var),
+        Expr::Convert { expr, .. } => depends(expr, var),
+        Expr::Call { args, .. } => args.iter().any(|arg| depends(arg, var)),
+        Expr::Matrix(rows) => rows.iter().flatten().any(|element| depends(element, var)),
+    }
+}
+
+/// 表达式中出现的所有变量，按名称排序
+pub fn variables(expr: &Expr) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    rewrite(expr, &mut |node| {
+        if let Expr::Var(name) = node {
+            names.insert(name.clone());
+        }
+        Ok(None)
+    }).expect("遍历表达式不会出错");
+    names
+}
+
+/// 自顶向下改写表达式：`f` 返回 `Some` 时替换整个节点，否则继续改写子节点
+pub fn rewrite(expr: &Expr, f: &mut dyn FnMut(&Expr) -> Result<Option<Expr>, CalcError>) -> Result<Expr, CalcError> {
+    if let Some(replacement) = f(expr)? {
+        return Ok(replacement);
+    }
+    let mut each = |exprs: &[Expr]| exprs.iter().map(|expr| rewrite(expr, f)).collect::<Result<Vec<_>, _>>();
+    Ok(match expr {
+        Expr::Number(_) | Expr::Var(_) | Expr::HistoryRef(_) => expr.clone(),
+        Expr::Neg(inner) => Expr::Neg(Box::new(rewrite(inner, f)?)),
+        Expr::Not(inner) => Expr::Not(Box::new(rewrite(inner, f)?)),
+        Expr::Binary { op, lhs, rhs } => binary(*op, rewrite(lhs, f)?, rewrite(rhs, f)?),
+        Expr::Call { name, args } => Expr::Call { name: name.clone(), args: each(args)? },
+        Expr::Convert { expr, unit } => Expr::Convert { expr: Box::new(rewrite(expr, f)?), unit: unit.clone() },
+        Expr::Matrix(rows) => Expr::Matrix(rows.iter().map(|row| each(row)).collect::<Result<_, _>>()?),
+        Expr::Equation { lhs, rhs } => {
+            Expr::Equation { lhs: Box::new(rewrite(lhs, f)?), rhs: Box::new(rewrite(rhs, f)?) }
+        }
+    })
+}
+
+/// 把变量替换为对应的表达式，用于展开用户定义的函数
+pub fn substitute(expr: &Expr, bindings: &[(&str, Expr)]) -> Expr {
+    let mut replace = |expr: &Expr| Ok(match expr {
+        Expr::Var(name) => bindings.iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.clone()),
+        _ => None,
+    });
+    rewrite(expr, &mut replace).expect("替换变量不会出错")
+}
+
+/// 符号运算支持的最大层数
+///
+/// 求导和化简都按节点递归，每层占用的栈空间很多；解析器不限制 `1 + 2 + ... + n`
+/// 这样的左结合长链，它们的层数和项数一样多，所以在这里另外检查。
+pub const MAX_DEPTH: usize = 100;
+
+/// 表达式树的层数
+///
+/// 用显式的栈遍历，左结合的长链再长也不会耗尽调用栈。
+pub fn depth(expr: &Expr) -> usize {
+    let mut deepest = 0;
+    let mut pending = vec![(expr, 1)];
+    while let Some((expr, level)) = pending.pop() {
+        deepest = deepest.max(level);
+        match expr {
+            Expr::Number(_) | Expr::Var(_) | Expr::HistoryRef(_) => {}
+            Expr::Neg(inner) | Expr::Not(inner) => pending.push((inner, level + 1)),
+            Expr::Binary { lhs, rhs, .. } | Expr::Equation { lhs, rhs } | Expr::Convert { expr: lhs, unit: rhs } => {
+                pending.push((lhs, level + 1));
+                pending.push((rhs, level + 1));
+            }
+            Expr::Call { args, .. } => pending.extend(args.iter().map(|arg| (arg, level + 1))),
+            Expr::Matrix(rows) => pending.extend(rows.iter().flatten().map(|element| (element, level + 1))),
+        }
+    }
+    deepest
+}
+
+/// 确认表达式不超过符号运算支持的层数
+pub fn check_depth(expr: &Expr) -> Result<(), CalcError> {
+    let depth = depth(expr);
+    if depth > MAX_DEPTH {
+        return Err(CalcError::Unsupported(format!("表达式有 {} 层，符号运算最多支持 {} 层", depth, MAX_DEPTH)));
+    }
+    Ok(())
+}
+
+/// 对 `var` 求导，结果没有化简
+pub fn diff(expr: &Expr, var: &str) -> Result<Expr, CalcError> {
+    check_depth(expr)?;
+    derive(expr, var)
+}
+
+fn derive(expr: &Expr, var: &str) -> Result<Expr, CalcError> {
+    if !depends(expr, var) {
+        return Ok(num(0));
+    }
+    let d = |expr: &Expr| derive(expr, var);
+    Ok(match expr {
+        Expr::Var(_) => num(1),
+        Expr::Neg(inner) => neg(d(inner)?),
+        Expr::Binary { op, lhs: u, rhs: v } => {
+            let (u, v) = (&**u, &**v);
+            match op {
+                BinaryOp::Add | BinaryOp::Sub => binary(*op, d(u)?, d(v)?),
+                // (uv)' = u'v + uv'
+                BinaryOp::Mul => add(mul(d(u)?, v.clone()), mul(u.clone(), d(v)?)),
+                // (u/v)' = (u'v - uv') / v^2
+                BinaryOp::Div => div(
+                    sub(mul(d(u)?, v.clone()), mul(u.clone(), d(v)?)),
+                    pow(v.clone(), num(2)),
+                ),
+                // (u^n)' = n u^(n-1) u'
+                BinaryOp::Pow if !depends(v, var) => {
+                    mul(mul(v.clone(), pow(u.clone(), sub(v.clone(), num(1)))), d(u)?)
+                }
+                // (a^v)' = a^v ln(a) v'
+                BinaryOp::Pow if !depends(u, var) => mul(mul(expr.clone(), call("ln", u.clone())), d(v)?),
+                // (u^v)' = u^v (v' ln(u) + v u' / u)
+                BinaryOp::Pow => mul(
+                    expr.clone(),
+                    add(mul(d(v)?, call("ln", u.clone())), div(mul(v.clone(), d(u)?), u.clone())),
+                ),
+                _ => return Err(CalcError::Unsupported(format!("无法对运算符 {} 求导", op.symbol()))),
+            }
+        }
+        // 链式法则
+        Expr::Call { name, args } => match args.as_slice() {
+            [u] => mul(derivative(name, u)?, d(u)?),
+            [base, u] if name == "log" && !depends(base, var) => {
+                mul(div(num(1), mul(u.clone(), call("ln", base.clone()))), d(u)?)
+            }
+            _ => return Err(CalcError::Unsupported(format!("无法对函数 '{}' 求导", name))),
+        },
+        Expr::Not(_) => return Err(CalcError::Unsupported("无法对位运算求导".to_string())),
+        _ => return Err(CalcError::Unsupported(format!("无法对 {} 求导", expr))),
+    })
+}
+
+/// 把 `var` 的多项式展开成系数，从常数项开始
+///
+/// 不含 `var` 的子表达式交给 `constant` 求值，所以 `a * x^2` 中的 `a` 可以是变量。
+pub fn polynomial(
+    expr: &Expr,
+    var: &str,
+    constant: &mut dyn FnMut(&Expr) -> Result<f64, CalcError>,
+) -> Result<Vec<f64>, CalcError> {
+    if !depends(expr, var) {
+        return Ok(vec![constant(expr)?]);
+    }
+    let not_polynomial = || CalcError::InvalidArgument(format!("{} 不是 {} 的多项式", expr, var));
+    Ok(match expr {
+        Expr::Var(_) => vec![0.0, 1.0],
+        Expr::Neg(inner) => polynomial(inner, var, constant)?.into_iter().map(|c| -c).collect(),
+        Expr::Binary { op, lhs, rhs } => match op {
+            BinaryOp::Add | BinaryOp::Sub => {
+                let (p, q) = (polynomial(lhs, var, constant)?, polynomial(rhs, var, constant)?);
+                let sign = if *op == BinaryOp::Add { 1.0 } else { -1.0 };
+                (0..p.len().max(q.len()))
+                    .map(|k| p.get(k).unwrap_or(&0.0) + sign * q.get(k).unwrap_or(&0.0))
+                    .collect()
+            }
+            BinaryOp::Mul => {
+                let (p, q) = (polynomial(lhs, var, constant)?, polynomial(rhs, var, constant)?);
+                multiply(&p, &q)
+            }
+            BinaryOp::Div if !depends(rhs, var) => {
+                let divisor = constant(rhs)?;
+                if divisor == 0.0 {
+                    return Err(CalcError::DivisionByZero);
+                }
+                polynomial(lhs, var, constant)?.into_iter().map(|c| c / divisor).collect()
+            }
+            BinaryOp::Pow if !depends(rhs, var) => {
+                let exponent = constant(rhs)?;
+                if exponent < 0.0 || exponent.fract() != 0.0 || exponent > 1000.0 {
+                    return Err(not_polynomial());
+                }
+                let base = polynomial(lhs, var, constant)?;
+                (0..exponent as usize).fold(vec![1.0], |acc, _| multiply(&acc, &base))
+            }
+            _ => return Err(not_polynomial()),
+        },
+        _ => return Err(not_polynomial()),
+    })
+}
+
+/// 两个多项式的系数相乘
+fn multiply(p: &[f64], q: &[f64]) -> Vec<f64> {
+    let mut product = vec![0.0; p.len() + q.len() - 1];
+    for (i, a) in p.iter().enumerate() {
+        for (j, b) in q.iter().enumerate() {
+            product[i + j] += a * b;
+        }
+    }
+    product
+}
+
+/// 一元函数在 `u` 处的导数
+fn derivative(name: &str, u: &Expr) -> Result<Expr, CalcError> {
+    let u = u.clone();
+    Ok(match name {
+        "sin" => call("cos", u),
+        "cos" => neg(call("sin", u)),
+        "tan" => div(num(1), pow(call("cos", u), num(2))),
+        "exp" => call("exp", u),
+        "ln" => div(num(1), u),
+        "log" => div(num(1), mul(u, call("ln", num(10)))),
+        "sqrt" => div(num(1), mul(num(2), call("sqrt", u))),
+        "cbrt" => div(num(1), mul(num(3), pow(call("cbrt", u), num(2)))),
+        "asin" => div(num(1), call("sqrt", sub(num(1), pow(u, num(2))))),
+        "acos" => neg(div(num(1), call("sqrt", sub(num(1), pow(u, num(2)))))),
+        "atan" => div(num(1), add(num(1), pow(u, num(2)))),
+        "abs" => div(u.clone(), call("abs", u)),
+        _ => return Err(CalcError::Unsupported(format!("无法对函数 '{}' 求导", name))),
+    })
+}
+
+/// 化简：常量折叠、去掉 0 和 1、合并同类项和同底数的幂
+pub fn simplify(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Number(_) | Expr::Var(_) | Expr::HistoryRef(_) => expr.clone(),
+        Expr::Neg(inner) => negate(simplify(inner)),
+        Expr::Not(inner) => Expr::Not(Box::new(simplify(inner))),
+        Expr::Binary { op, lhs, rhs } => simplify_binary(*op, simplify(lhs), simplify(rhs)),
+        Expr::Call { name, args } => simplify_call(name, args.iter().map(simplify).collect()),
+        Expr::Convert { expr, unit } => Expr::Convert { expr: Box::new(simplify(expr)), unit: unit.clone() },
+        Expr::Matrix(rows) => Expr::Matrix(rows.iter().map(|row| row.iter().map(simplify).collect()).collect()),
+        Expr::Equation { lhs, rhs } => Expr::Equation { lhs: Box::new(simplify(lhs)), rhs: Box::new(simplify(rhs)) },
+    }
+}
+
+fn simplify_binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+    if let (Expr::Number(a), Expr::Number(b)) = (&lhs, &rhs) {
+        if let Some(value) = fold(op, a, b) {
+            return Expr::Number(value);
+        }
+    }
+    match op {
+        BinaryOp::Add | BinaryOp::Sub => simplify_sum(op, lhs, rhs),
+        BinaryOp::Mul => simplify_product(lhs, rhs),
+        BinaryOp::Div => simplify_quotient(lhs, rhs),
+        BinaryOp::Pow => simplify_power(lhs, rhs),
+        _ => binary(op, lhs, rhs),
+    }
+}
+
+fn simplify_sum(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+    let flipped = if op == BinaryOp::Add { BinaryOp::Sub } else { BinaryOp::Add };
+    match (lhs, rhs) {
+        (x, zero) if is_number(&zero, 0) => x,
+        (zero, x) if is_number(&zero, 0) => if op == BinaryOp::Add { x } else { negate(x) },
+        // x + -y = x - y
+        (x, Expr::Neg(y)) => simplify_sum(flipped, x, *y),
+        (x, Expr::Number(n)) if n.is_negative() => simplify_sum(flipped, x, Expr::Number(-n)),
+        (lhs, rhs) => {
+            // 合并同类项：2x + 3x = 5x，x - x = 0
+            let ((a, x), (b, y)) = (coefficient(&lhs), coefficient(&rhs));
+            match fold(op, &a, &b) {
+                Some(c) if x == y => make_product(c, x),
+                _ => binary(op, lhs, rhs),
+            }
+        }
+    }
+}
+
+fn simplify_product(lhs: Expr, rhs: Expr) -> Expr {
+    match (lhs, rhs) {
+        (zero, _) | (_, zero) if is_number(&zero, 0) => num(0),
+        (one, x) | (x, one) if is_number(&one, 1) => x,
+        (Expr::Neg(x), y) => negate(simplify_product(*x, y)),
+        (x, Expr::Neg(y)) => negate(simplify_product(x, *y)),
+        // 乘以倒数写成除法：(1 / y) * x = x / y
+        (Expr::Binary { op: BinaryOp::Div, lhs: a, rhs: y }, x) | (x, Expr::Binary { op: BinaryOp::Div, lhs: a, rhs: y })
+            if matches!(*a, Expr::Number(_)) =>
+        {
+            div(simplify_product(*a, x), *y)
+        }
+        (lhs, rhs) => {
+            // 系数提到最前面相乘：x * 2 * (3 * x) = 6 * x^2
+            let ((a, x), (b, y)) = (coefficient(&lhs), coefficient(&rhs));
+            if is_one(&a) && is_one(&b) {
+                return match (power(&lhs), power(&rhs)) {
+                    ((x, m), (y, n)) if x == y => simplify_power(x, simplify_sum(BinaryOp::Add, m, n)),
+                    _ => mul(lhs, rhs),
+                };
+            }
+            match fold(BinaryOp::Mul, &a, &b) {
+                Some(c) => make_product(c, simplify_product(x, y)),
+                None => mul(lhs, rhs),
+            }
+        }
+    }
+}
+
+fn simplify_quotient(lhs: Expr, rhs: Expr) -> Expr {
+    match (lhs, rhs) {
+        (x, one) if is_number(&one, 1) => x,
+        (zero, y) if is_number(&zero, 0) && !is_number(&y, 0) => num(0),
+        (Expr::Neg(x), y) => negate(simplify_quotient(*x, y)),
+        (x, Expr::Neg(y)) => negate(simplify_quotient(x, *y)),
+        (x, y) if x == y => num(1),
+        (lhs, rhs) => {
+            // 分子的系数除以分母的系数：6x / 3 = 2x
+            let ((a, x), (b, y)) = (coefficient(&lhs), coefficient(&rhs));
+            if !is_one(&a) {
+                if let Some(c) = fold(BinaryOp::Div, &a, &b) {
+                    return make_product(c, simplify_quotient(x, y));
+                }
+            }
+            match (power(&lhs), power(&rhs)) {
+                ((x, m), (y, n)) if x == y => simplify_power(x, simplify_sum(BinaryOp::Sub, m, n)),
+                _ => div(lhs, rhs),
+            }
+        }
+    }
+}
+
+fn simplify_power(base: Expr, exponent: Expr) -> Expr {
+    match (base, exponent) {
+        (_, zero) if is_number(&zero, 0) => num(1),
+        (x, one) if is_number(&one, 1) => x,
+        (one, _) if is_number(&one, 1) => num(1),
+        // 整数次幂可以合并：(x^2)^3 = x^6
+        (Expr::Binary { op: BinaryOp::Pow, lhs, rhs }, Expr::Number(n)) if n.to_integer().is_ok() => {
+            simplify_power(*lhs, simplify_product(*rhs, Expr::Number(n)))
+        }
+        (base, exponent) => pow(base, exponent),
+    }
+}
+
+fn simplify_call(name: &str, args: Vec<Expr>) -> Expr {
+    match (name, args.as_slice()) {
+        ("ln", [one]) if is_number(one, 1) => num(0),
+        ("sin", [zero]) if is_number(zero, 0) => num(0),
+        ("exp" | "cos", [zero]) if is_number(zero, 0) => num(1),
+        ("ln", [Expr::Call { name, args }]) if name == "exp" && args.len() == 1 => args[0].clone(),
+        _ => Expr::Call { name: name.to_string(), args },
+    }
+}
+
+/// 取反并化简：-(-x) = x，-(a - b) = b - a，-(2x) = -2x，-(1/x) = -1/x
+fn negate(expr: Expr) -> Expr {
+    match expr {
+        Expr::Number(n) => Expr::Number(-n),
+        Expr::Neg(inner) => *inner,
+        Expr::Binary { op: BinaryOp::Sub, lhs, rhs } => Expr::Binary { op: BinaryOp::Sub, lhs: rhs, rhs: lhs },
+        Expr::Binary { op: BinaryOp::Mul, lhs, rhs } if matches!(*lhs, Expr::Number(_)) => mul(negate(*lhs), *rhs),
+        Expr::Binary { op: BinaryOp::Div, lhs, rhs } if matches!(*lhs, Expr::Number(_)) => div(negate(*lhs), *rhs),
+        expr => neg(expr),
+    }
+}
+
+/// 拆出最左边的数字系数：`2 * x * y` 是 (2, x * y)，`-x` 是 (-1, x)
+fn coefficient(expr: &Expr) -> (Number, Expr) {
+    match expr {
+        Expr::Number(n) => (n.clone(), num(1)),
+        Expr::Neg(inner) => {
+            let (c, rest) = coefficient(inner);
+            (-c, rest)
+        }
+        Expr::Binary { op: BinaryOp::Mul, lhs, rhs } => {
+            let (c, rest) = coefficient(lhs);
+            if is_number(&rest, 1) {
+                (c, (**rhs).clone())
+            } else {
+                (c, mul(rest, (**rhs).clone()))
+            }
+        }
+        _ => (Number::from(1), expr.clone()),
+    }
+}
+
+/// 拆成底数和指数：`x^2` 是 (x, 2)，`x` 是 (x, 1)
+fn power(expr: &Expr) -> (Expr, Expr) {
+    match expr {
+        Expr::Binary { op: BinaryOp::Pow, lhs, rhs } => ((**lhs).clone(), (**rhs).clone()),
+        _ => (expr.clone(), num(1)),
+    }
+}
+
+/// 系数乘以表达式，乘法保持左结合：2 * (x * y) 写成 2 * x * y
+fn make_product(c: Number, expr: Expr) -> Expr {
+    fn attach(c: Expr, expr: Expr) -> Expr {
+        match expr {
+            Expr::Binary { op: BinaryOp::Mul, lhs, rhs } => mul(attach(c, *lhs), *rhs),
+            expr => mul(c, expr),
+        }
+    }
+
+    if c == Number::from(0) {
+        num(0)
+    } else if is_one(&c) {
+        expr
+    } else if c == Number::from(-1) {
+        negate(expr)
+    } else if is_number(&expr, 1) {
+        Expr::Number(c)
+    } else {
+        attach(Expr::Number(c), expr)
+    }
+}
+
+/// 折叠两个数字的运算；浮点数按浮点数计算，其余按精确值计算，
+/// 结果不精确（如 `2^0.5`）或出错时不折叠
+fn fold(op: BinaryOp, a: &Number, b: &Number) -> Option<Number> {
+    if matches!(a, Number::Float(_)) || matches!(b, Number::Float(_)) {
+        let (a, b) = (a.to_f64(), b.to_f64());
+        let value = match op {
+            BinaryOp::Add => a + b,
+            BinaryOp::Sub => a - b,
+            BinaryOp::Mul => a * b,
+            BinaryOp::Div if b != 0.0 => a / b,
+            BinaryOp::Pow => a.powf(b),
+            _ => return None,
+        };
+        return value.is_finite().then_some(Number::Float(value));
+    }
+    let (a, b) = (a.to_rational().ok()?, b.to_rational().ok()?);
+    let exact = match op {
+        BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => true,
+        BinaryOp::Pow => b.is_integer(),
+        _ => false,
+    };
+    if !exact {
+        return None;
+    }
+    let value = number::rational_op(op, &a, &b).ok()?;
+    Some(if value.is_integer() { Number::Integer(value.to_integer()) } else { Number::Rational(value) })
+}
+
+fn is_number(expr: &Expr, n: i64) -> bool {
+    matches!(expr, Expr::Number(value) if *value == Number::from(n))
+}
+
+fn is_one(n: &Number) -> bool {
+    *n == Number::from(1)
+}
+
+fn num(n: i64) -> Expr {
+    Expr::Number(Number::from(n))
+}
+
+fn neg(expr: Expr) -> Expr {
+    Expr::Neg(Box::new(expr))
+}
+
+fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+    Expr::Binary { op, lhs: Box::new(lhs), rhs: Box::new(rhs) }
+}
+
+fn add(lhs: Expr, rhs: Expr) -> Expr {
+    binary(BinaryOp::Add, lhs, rhs)
+}
+
+fn sub(lhs: Expr, rhs: Expr) -> Expr {
+    binary(BinaryOp::Sub, lhs, rhs)
+}
+
+fn mul(lhs: Expr, rhs: Expr) -> Expr {
+    binary(BinaryOp::Mul, lhs, rhs)
+}
+
+fn div(lhs: Expr, rhs: Expr) -> Expr {
+    binary(BinaryOp::Div, lhs, rhs)
+}
+
+fn pow(base: Expr, exponent: Expr) -> Expr {
+    binary(BinaryOp::Pow, base, exponent)
+}
+
+fn call(name: &str, arg: Expr) -> Expr {
+    Expr::Call { name: name.to_string(), args: vec![arg] }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projects::calculator::parser::parse;
+
+    fn derive(input: &str) -> String {
+        simplify(&diff(&parse(input).unwrap(), "x").unwrap()).to_string()
+    }
+
+    fn simplified(input: &str) -> String {
+        simplify(&parse(input).unwrap()).to_string()
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(derive("x^2 * sin(x)"), "2 * x * sin(x) + x^2 * cos(x)");
+        assert_eq!(derive("3x^4 - 2x + 7"), "12 * x^3 - 2");
+        assert_eq!(derive("1 / x"), "-1 / x^2");
+        assert_eq!(derive("exp(2x)"), "2 * exp(2 * x)");
+        assert_eq!(derive("ln(x^2 + 1)"), "2 * x / (x^2 + 1)");
+        assert_eq!(derive("cos(x)"), "-sin(x)");
+        assert_eq!(derive("2^x"), "2^x * ln(2)");
+        assert_eq!(derive("x^x"), "x^x * (ln(x) + 1)");
+        assert_eq!(derive("a * y"), "0");
+        assert_eq!(diff(&parse("floor(x)").unwrap(), "x").unwrap_err().to_string(), "无法对函数 'floor' 求导");
+        assert_eq!(diff(&parse("x % 2").unwrap(), "x").unwrap_err().to_string(), "无法对运算符 % 求导");
+    }
+
+    #[test]
+    fn test_simplify() {
+        for (input, output) in [
+            ("1 + 2 * 3", "7"),
+            ("1/3 + 1/6", "0.5"),
+            ("1/3 + 1", "4/3"),
+            ("x * 1 + 0", "x"),
+            ("0 * sin(x) + y^1", "y"),
+            ("x + x", "2 * x"),
+            ("2x - 3x", "-x"),
+            ("x - x", "0"),
+            ("x * 3 * x", "3 * x^2"),
+            ("x^3 / x", "x^2"),
+            ("6x / 3", "2 * x"),
+            ("(x^2)^3", "x^6"),
+            ("x - -2", "x + 2"),
+            ("-(a - b)", "b - a"),
+            ("ln(exp(x + 1))", "x + 1"),
+            ("2^0.5", "2^0.5"),
+            ("x / 0", "x / 0"),
+        ] {
+            assert_eq!(simplified(input), output, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_polynomial() {
+        let coefficients = |input: &str| {
+            polynomial(&parse(input).unwrap(), "x", &mut |expr| match expr {
+                Expr::Number(n) => Ok(n.to_f64()),
+                _ => Err(CalcError::UndefinedVariable(expr.to_string())),
+            })
+        };
+        assert_eq!(coefficients("x^3 - 2x - 5").unwrap(), vec![-5.0, -2.0, 0.0, 1.0]);
+        assert_eq!(coefficients("(x - 1)(x + 1) / 2").unwrap(), vec![-0.5, 0.0, 0.5]);
+        assert_eq!(coefficients("-(x + 1)^2").unwrap(), vec![-1.0, -2.0, -1.0]);
+        assert_eq!(coefficients("a * x").unwrap_err().to_string(), "未定义的变量 'a'");
+        assert_eq!(coefficients("x / (x + 1)").unwrap_err().to_string(), "x / (x + 1) 不是 x 的多项式");
+        assert_eq!(coefficients("x^0.5").unwrap_err().to_string(), "x^0.5 不是 x 的多项式");
+        assert_eq!(coefficients("sin(x)").unwrap_err().to_string(), "sin(x) 不是 x 的多项式");
+    }
+
+    #[test]
+    fn test_substitute() {
+        let expr = parse("x^2 + y").unwrap();
+        let result = substitute(&expr, &[("x", parse("a + 1").unwrap()), ("y", parse("2").unwrap())]);
+        assert_eq!(result.to_string(), "(a + 1)^2 + 2");
+        assert!(depends(&expr, "y") && !depends(&result, "x"));
+    }
+}
@@ -0,0 +1,421 @@
+//! 单位：带量纲的数值、单位表和换算
+//!
+//! 每个单位记录它相对于基本单位的倍数，如 `km` 是 1000 `m`。复合单位（`km/h`）
+//! 按名称记录各个单位的指数，量纲由基本单位的指数组成。加减之前把右边换算成
+//! 左边的单位，量纲不同时报错；温度单位的零点不同，只有单独换算一个温度
+//! （`20 degC in degF`）时才考虑零点偏移，其余情况都当作温差处理。
+
+use super::error::CalcError;
+use super::number::{Number, NumberMode, OutputFormat};
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// 量纲：基本单位及其指数，如速度是 `{m: 1, s: -1}`
+pub type Dimension = BTreeMap<String, i32>;
+
+/// 单位表达式：单位名称及其指数，空表示没有单位
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Unit(BTreeMap<String, i32>);
+
+impl Unit {
+    pub fn named(name: &str) -> Self {
+        Unit(BTreeMap::from([(name.to_string(), 1)]))
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 只有一个指数为 1 的单位时返回它的名称
+    pub fn single(&self) -> Option<&str> {
+        match self.0.iter().next() {
+            Some((name, 1)) if self.0.len() == 1 => Some(name),
+            _ => None,
+        }
+    }
+
+    /// 相乘（`sign` 为 1）或相除（`sign` 为 -1）
+    pub fn combine(&self, other: &Unit, sign: i32) -> Unit {
+        let mut units = self.0.clone();
+        for (name, exponent) in &other.0 {
+            *units.entry(name.clone()).or_default() += sign * exponent;
+        }
+        units.retain(|_, exponent| *exponent != 0);
+        Unit(units)
+    }
+
+    pub fn powi(&self, n: i32) -> Unit {
+        if n == 0 {
+            return Unit::default();
+        }
+        Unit(self.0.iter().map(|(name, exponent)| (name.clone(), exponent * n)).collect())
+    }
+}
+
+/// 显示为 `kg·m^2/s^2` 的形式
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn join<'a>(units: impl Iterator<Item = (&'a String, i32)>) -> String {
+            units.map(|(name, exponent)| match exponent {
+                1 => name.clone(),
+                _ => format!("{}^{}", name, exponent),
+            })
+            .collect::<Vec<_>>()
+            .join("·")
+        }
+
+        let numerator = join(self.0.iter().filter(|(_, e)| **e > 0).map(|(n, e)| (n, *e)));
+        let denominator = join(self.0.iter().filter(|(_, e)| **e < 0).map(|(n, e)| (n, -*e)));
+        match (numerator.is_empty(), denominator.is_empty()) {
+            (_, true) => write!(f, "{}", numerator),
+            (true, false) => write!(f, "{}", join(self.0.iter().map(|(n, e)| (n, *e)))),
+            (false, false) => write!(f, "{}/{}", numerator, denominator),
+        }
+    }
+}
+
+/// 解析 `Display` 输出的形式，只检查格式，不检查单位是否存在
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (numerator, denominator) = s.split_once('/').unwrap_or((s, ""));
+        let mut unit = Unit::default();
+        for (part, sign) in [(numerator, 1), (denominator, -1)] {
+            for factor in part.split('·').filter(|factor| !factor.is_empty()) {
+                let (name, exponent) = match factor.split_once('^') {
+                    Some((name, exponent)) => (name, exponent.parse().map_err(|_| format!("'{}' 不是有效的单位", s))?),
+                    None => (factor, 1),
+                };
+                if !is_unit_name(name) {
+                    return Err(format!("'{}' 不是有效的单位", s));
+                }
+                unit = unit.combine(&Unit(BTreeMap::from([(name.to_string(), exponent)])), sign);
+            }
+        }
+        Ok(unit)
+    }
+}
+
+/// 单位名称和变量名的规则相同，另外可以以 `°` 开头
+fn is_unit_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '°')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// 带单位的数值
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    pub number: Number,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    pub fn new(number: Number, unit: Unit) -> Self {
+        Self { number, unit }
+    }
+
+    /// 不带单位时返回数值
+    pub fn into_number(self) -> Result<Number, Quantity> {
+        if self.unit.is_none() {
+            Ok(self.number)
+        } else {
+            Err(self)
+        }
+    }
+
+    /// 把数值换算成 `mode` 对应的表示，单位不变
+    pub fn convert(&self, mode: NumberMode) -> Result<Quantity, CalcError> {
+        Ok(Quantity::new(self.number.convert(mode)?, self.unit.clone()))
+    }
+
+    /// 按输出格式显示
+    pub fn format(&self, format: OutputFormat) -> String {
+        if self.unit.is_none() {
+            self.number.format(format)
+        } else {
+            format!("{} {}", self.number.format(format), self.unit)
+        }
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(OutputFormat::Auto))
+    }
+}
+
+/// 解析 `3/2 km/h` 这样的 `Display` 输出，用于读取历史文件
+impl FromStr for Quantity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (number, unit) = s.split_once(' ').unwrap_or((s, ""));
+        Ok(Quantity::new(number.parse()?, unit.parse()?))
+    }
+}
+
+impl From<Number> for Quantity {
+    fn from(number: Number) -> Self {
+        Quantity::new(number, Unit::default())
+    }
+}
+
+impl From<f64> for Quantity {
+    fn from(value: f64) -> Self {
+        Quantity::from(Number::Float(value))
+    }
+}
+
+/// 单位的定义
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitDef {
+    /// 相对于基本单位的倍数
+    pub factor: BigRational,
+    /// 零点偏移：基本单位的值 = (值 + offset) * factor，只有温度单位不为零
+    pub offset: BigRational,
+    pub dimension: Dimension,
+    /// 内置单位不能被重新定义
+    pub builtin: bool,
+}
+
+/// 内置单位：(名称, 相对基本单位的倍数, 零点偏移, 基本单位)
+///
+/// 英寸写作 `inch`，因为 `in` 是换算关键字。
+const BUILTIN_UNITS: &[(&str, &str, &str, &str)] = &[
+    // 长度
+    ("m", "1", "0", "m"),
+    ("km", "1000", "0", "m"),
+    ("cm", "0.01", "0", "m"),
+    ("mm", "0.001", "0", "m"),
+    ("inch", "0.0254", "0", "m"),
+    ("ft", "0.3048", "0", "m"),
+    ("yd", "0.9144", "0", "m"),
+    ("mi", "1609.344", "0", "m"),
+    // 质量
+    ("kg", "1", "0", "kg"),
+    ("g", "0.001", "0", "kg"),
+    ("mg", "0.000001", "0", "kg"),
+    ("t", "1000", "0", "kg"),
+    ("lb", "0.45359237", "0", "kg"),
+    ("oz", "0.028349523125", "0", "kg"),
+    // 时间
+    ("s", "1", "0", "s"),
+    ("ms", "0.001", "0", "s"),
+    ("min", "60", "0", "s"),
+    ("h", "3600", "0", "s"),
+    ("day", "86400", "0", "s"),
+    ("week", "604800", "0", "s"),
+    // 数据量
+    ("B", "1", "0", "B"),
+    ("bit", "1/8", "0", "B"),
+    ("KB", "1000", "0", "B"),
+    ("MB", "1000000", "0", "B"),
+    ("GB", "1000000000", "0", "B"),
+    ("TB", "1000000000000", "0", "B"),
+    ("KiB", "1024", "0", "B"),
+    ("MiB", "1048576", "0", "B"),
+    ("GiB", "1073741824", "0", "B"),
+    ("TiB", "1099511627776", "0", "B"),
+    // 温度
+    ("K", "1", "0", "K"),
+    ("degC", "1", "273.15", "K"),
+    ("°C", "1", "273.15", "K"),
+    ("degF", "5/9", "459.67", "K"),
+    ("°F", "5/9", "459.67", "K"),
+];
+
+/// 单位表，包括内置单位和用户定义的单位
+#[derive(Debug, Clone)]
+pub struct UnitTable {
+    units: BTreeMap<String, UnitDef>,
+}
+
+impl Default for UnitTable {
+    fn default() -> Self {
+        let exact = |text: &str| text.parse::<Number>()
+            .map_err(CalcError::InvalidArgument)
+            .and_then(|number| number.to_rational())
+            .expect("内置单位的倍数");
+        let units = BUILTIN_UNITS.iter()
+            .map(|&(name, factor, offset, base)| {
+                let definition = UnitDef {
+                    factor: exact(factor),
+                    offset: exact(offset),
+                    dimension: Dimension::from([(base.to_string(), 1)]),
+                    builtin: true,
+                };
+                (name.to_string(), definition)
+            })
+            .collect();
+        Self { units }
+    }
+}
+
+impl UnitTable {
+    pub fn get(&self, name: &str) -> Option<&UnitDef> {
+        self.units.get(name)
+    }
+
+    /// 按名称排序的所有单位
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &UnitDef)> {
+        self.units.iter().map(|(name, definition)| (name.as_str(), definition))
+    }
+
+    fn lookup(&self, name: &str) -> Result<&UnitDef, CalcError> {
+        self.get(name).ok_or_else(|| CalcError::UnknownUnit(name.to_string()))
+    }
+
+    pub fn dimension(&self, unit: &Unit) -> Result<Dimension, CalcError> {
+        let mut dimension = Dimension::new();
+        for (name, exponent) in &unit.0 {
+            for (base, power) in &self.lookup(name)?.dimension {
+                *dimension.entry(base.clone()).or_default() += exponent * power;
+            }
+        }
+        dimension.retain(|_, power| *power != 0);
+        Ok(dimension)
+    }
+
+    /// 单位相对于基本单位的倍数
+    pub fn scale(&self, unit: &Unit) -> Result<BigRational, CalcError> {
+        let mut scale = BigRational::one();
+        for (name, exponent) in &unit.0 {
+            scale *= self.lookup(name)?.factor.pow(*exponent);
+        }
+        Ok(scale)
+    }
+
+    /// 把 `unit` 中与 `like` 的某个单位量纲相同的单位换成那个单位，返回新单位和
+    /// 数值需要乘的倍数，这样 `coin/h * day` 能约掉时间
+    pub fn align(&self, unit: &Unit, like: &Unit) -> Result<(Unit, BigRational), CalcError> {
+        let mut aligned = Unit::default();
+        let mut factor = BigRational::one();
+        for (name, exponent) in &unit.0 {
+            let dimension = &self.lookup(name)?.dimension;
+            let target = like.0.keys()
+                .find(|other| self.get(other).is_some_and(|definition| definition.dimension == *dimension))
+                .unwrap_or(name);
+            if target != name {
+                factor *= (self.lookup(name)?.factor.clone() / self.lookup(target)?.factor.clone()).pow(*exponent);
+            }
+            aligned = aligned.combine(&Unit(BTreeMap::from([(target.clone(), *exponent)])), 1);
+        }
+        Ok((aligned, factor))
+    }
+
+    /// 零点偏移，只对单独的一个单位有意义
+    pub fn offset(&self, unit: &Unit) -> BigRational {
+        unit.single()
+            .and_then(|name| self.get(name))
+            .map(|definition| definition.offset.clone())
+            .unwrap_or_else(BigRational::zero)
+    }
+
+    /// 用已有单位表示的量定义新单位，如 `furlong = 201.168 m`
+    pub fn define(&mut self, name: &str, quantity: &Quantity) -> Result<(), CalcError> {
+        self.check_name(name)?;
+        let factor = quantity.number.to_rational()? * self.scale(&quantity.unit)?;
+        if factor.is_zero() {
+            return Err(CalcError::InvalidArgument("单位的大小不能为零".to_string()));
+        }
+        let dimension = self.dimension(&quantity.unit)?;
+        self.units.insert(name.to_string(), UnitDef { factor, offset: BigRational::zero(), dimension, builtin: false });
+        Ok(())
+    }
+
+    /// 定义新的基本单位，它自成一个量纲，如 `coin`
+    pub fn define_base(&mut self, name: &str) -> Result<(), CalcError> {
+        self.check_name(name)?;
+        let definition = UnitDef {
+            factor: BigRational::one(),
+            offset: BigRational::zero(),
+            dimension: Dimension::from([(name.to_string(), 1)]),
+            builtin: false,
+        };
+        self.units.insert(name.to_string(), definition);
+        Ok(())
+    }
+
+    fn check_name(&self, name: &str) -> Result<(), CalcError> {
+        let valid = name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !valid || name == "in" || name == "to" {
+            return Err(CalcError::InvalidArgument(format!("'{}' 不能用作单位名称", name)));
+        }
+        if self.get(name).is_some_and(|definition| definition.builtin) {
+            return Err(CalcError::Reserved(format!("'{}' 是内置单位，不能重新定义", name)));
+        }
+        Ok(())
+    }
+}
+
+/// 把量纲显示成基本单位的形式，如 `m/s`
+pub fn describe(dimension: &Dimension) -> String {
+    if dimension.is_empty() {
+        return "无单位".to_string();
+    }
+    Unit(dimension.clone()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(text: &str) -> Unit {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_unit_display_and_parse() {
+        let speed = Unit::named("km").combine(&Unit::named("h"), -1);
+        assert_eq!(speed.to_string(), "km/h");
+        let energy = unit("kg·m^2/s^2");
+        assert_eq!(energy.to_string(), "kg·m^2/s^2");
+        assert_eq!(unit("s^-1").to_string(), "s^-1");
+        assert_eq!(unit("m").combine(&unit("m"), -1), Unit::default());
+        assert_eq!(unit("km/h").powi(2).to_string(), "km^2/h^2");
+
+        let quantity: Quantity = "3/2 km/h".parse().unwrap();
+        assert_eq!(quantity.to_string(), "3/2 km/h");
+        assert_eq!("42".parse::<Quantity>().unwrap(), 42.0.into());
+        assert_eq!("20 °C".parse::<Quantity>().unwrap().to_string(), "20 °C");
+        assert!("2 * x".parse::<Quantity>().is_err());
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let table = UnitTable::default();
+        assert_eq!(describe(&table.dimension(&unit("km/h")).unwrap()), "m/s");
+        assert_eq!(describe(&table.dimension(&unit("MiB/KiB")).unwrap()), "无单位");
+        assert_eq!(table.scale(&unit("km/h")).unwrap(), BigRational::new(5.into(), 18.into()));
+        assert_eq!(table.offset(&unit("degC")).to_string(), "5463/20");
+        assert!(table.offset(&unit("degC/s")).is_zero());
+        assert_eq!(table.dimension(&unit("parsec")).unwrap_err().to_string(), "未知的单位 'parsec'");
+    }
+
+    #[test]
+    fn test_align() {
+        let table = UnitTable::default();
+        let (unit, factor) = table.align(&"day/m".parse().unwrap(), &"coin·km/h".parse().unwrap()).unwrap();
+        assert_eq!(unit.to_string(), "h/km");
+        assert_eq!(factor, BigRational::from_integer(24000.into()));
+    }
+
+    #[test]
+    fn test_user_units() {
+        let mut table = UnitTable::default();
+        table.define("furlong", &Quantity::new(Number::from(220), unit("yd"))).unwrap();
+        assert_eq!(table.scale(&unit("furlong")).unwrap().to_string(), "25146/125");
+        table.define_base("coin").unwrap();
+        assert_eq!(describe(&table.dimension(&unit("coin/h")).unwrap()), "coin/s");
+
+        assert_eq!(table.define_base("m").unwrap_err().to_string(), "'m' 是内置单位，不能重新定义");
+        assert!(table.define_base("in").is_err());
+        assert!(table.define("nothing", &Quantity::new(Number::from(0), unit("m"))).is_err());
+    }
+}
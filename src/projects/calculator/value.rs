@@ -0,0 +1,580 @@
+//! 计算结果的值：带单位的实数、复数、矩阵或符号表达式
+//!
+//! 复数只用浮点数表示，虚部为零的结果由求值器换回实数。矩阵的元素是
+//! `Number`，元素运算跟随当前数值模式；行列式和逆矩阵总是先按精确值消元，
+//! 所以 `inv([1,2;3,4])` 在浮点数模式下也能得到准确的 `1.5`。
+
+use super::error::CalcError;
+use super::number::{Number, NumberMode, OutputFormat};
+use super::parser::{self, BinaryOp, Expr};
+use super::units::Quantity;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+/// 复数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+/// 虚数单位
+pub const I: Complex = Complex { re: 0.0, im: 1.0 };
+
+/// 整数次幂用乘法计算的最大指数，更大的指数用 `exp(b ln a)`
+const MAX_INTEGER_POWER: f64 = 1024.0;
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    /// 模
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// 辐角，范围是 (-π, π]
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    pub fn exp(self) -> Self {
+        let r = self.re.exp();
+        Complex::new(r * self.im.cos(), r * self.im.sin())
+    }
+
+    /// 主值对数
+    pub fn ln(self) -> Result<Self, CalcError> {
+        if self.abs() == 0.0 {
+            return Err(CalcError::Domain("ln 的定义域不包含 0".to_string()));
+        }
+        Ok(Complex::new(self.abs().ln(), self.arg()))
+    }
+
+    /// 主值平方根，分别计算实部和虚部，所以 `sqrt(-1)` 正好是 `i`
+    pub fn sqrt(self) -> Self {
+        let r = self.abs();
+        let re = ((r + self.re) / 2.0).sqrt();
+        let im = ((r - self.re) / 2.0).sqrt();
+        Complex::new(re, if self.im < 0.0 { -im } else { im })
+    }
+
+    fn checked_div(self, other: Complex) -> Result<Self, CalcError> {
+        let norm = other.re * other.re + other.im * other.im;
+        if norm == 0.0 {
+            return Err(CalcError::DivisionByZero);
+        }
+        Ok(Complex::new(
+            (self.re * other.re + self.im * other.im) / norm,
+            (self.im * other.re - self.re * other.im) / norm,
+        ))
+    }
+
+    /// 主值乘方
+    ///
+    /// 不太大的整数次幂用乘法，避免 `i^2` 得到 `-1 + 1.2e-16i`。
+    fn pow(self, exponent: Complex) -> Result<Self, CalcError> {
+        if exponent.im == 0.0 && exponent.re.fract() == 0.0 && exponent.re.abs() <= MAX_INTEGER_POWER {
+            let mut result = Complex::new(1.0, 0.0);
+            let mut base = self;
+            let mut n = exponent.re.abs() as u32;
+            while n > 0 {
+                if n & 1 == 1 {
+                    result = result * base;
+                }
+                base = base * base;
+                n >>= 1;
+            }
+            return if exponent.re < 0.0 {
+                Complex::new(1.0, 0.0).checked_div(result)
+            } else {
+                Ok(result)
+            };
+        }
+        if self.abs() == 0.0 {
+            return if exponent.re > 0.0 {
+                Ok(self)
+            } else {
+                Err(CalcError::Domain(format!("0 不能取 {} 次幂", exponent)))
+            };
+        }
+        Ok((self.ln()? * exponent).exp())
+    }
+
+    /// 复数的四则运算和乘方
+    pub fn apply(op: BinaryOp, a: Complex, b: Complex) -> Result<Complex, CalcError> {
+        match op {
+            BinaryOp::Add => Ok(a + b),
+            BinaryOp::Sub => Ok(a - b),
+            BinaryOp::Mul => Ok(a * b),
+            BinaryOp::Div => a.checked_div(b),
+            BinaryOp::Pow => a.pow(b),
+            _ => Err(CalcError::Unsupported(format!("复数不支持运算符 {}", op.symbol()))),
+        }
+    }
+
+    /// 按输出格式显示，如 `3 + 4i`、`-i`
+    pub fn format(&self, format: OutputFormat) -> String {
+        let show = |x: f64| Number::Float(x).format(format);
+        let imaginary = if self.im.abs() == 1.0 {
+            "i".to_string()
+        } else {
+            format!("{}i", show(self.im.abs()))
+        };
+        let sign = if self.im < 0.0 { "-" } else { "+" };
+        if self.re == 0.0 {
+            format!("{}{}", if self.im < 0.0 { "-" } else { "" }, imaginary)
+        } else {
+            format!("{} {} {}", show(self.re), sign, imaginary)
+        }
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+
+    fn neg(self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Complex::new(re, 0.0)
+    }
+}
+
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(OutputFormat::Auto))
+    }
+}
+
+/// 解析 `Display` 输出的形式
+impl FromStr for Complex {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{}' 不是有效的复数", s);
+        let rest = s.strip_suffix('i').ok_or_else(invalid)?;
+        let coefficient = |text: &str| -> Result<f64, String> {
+            match text {
+                "" => Ok(1.0),
+                "-" => Ok(-1.0),
+                _ => text.parse::<Number>().map(|number| number.to_f64()).map_err(|_| invalid()),
+            }
+        };
+        if let Some(index) = rest.rfind(" + ").or_else(|| rest.rfind(" - ")) {
+            let re = coefficient(&rest[..index])?;
+            let im = coefficient(&rest[index + 3..])?;
+            return Ok(Complex::new(re, if &rest[index..index + 3] == " - " { -im } else { im }));
+        }
+        Ok(Complex::new(0.0, coefficient(rest)?))
+    }
+}
+
+/// 矩阵，按行保存元素；向量是只有一行或一列的矩阵
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    elements: Vec<Number>,
+}
+
+impl Matrix {
+    /// 由各行的元素构造，每行的元素个数必须相同
+    pub fn new(rows: Vec<Vec<Number>>) -> Result<Self, CalcError> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if cols == 0 {
+            return Err(CalcError::InvalidArgument("矩阵不能为空".to_string()));
+        }
+        if let Some(row) = rows.iter().position(|row| row.len() != cols) {
+            return Err(CalcError::DimensionMismatch(format!("矩阵第 {} 行有 {} 个元素，第 1 行有 {} 个", row + 1, rows[row].len(), cols)));
+        }
+        Ok(Self { rows: rows.len(), cols, elements: rows.into_iter().flatten().collect() })
+    }
+
+    /// n 阶单位矩阵
+    pub fn identity(n: usize) -> Self {
+        let elements = (0..n * n)
+            .map(|index| Number::from(i64::from(index % (n + 1) == 0)))
+            .collect();
+        Self { rows: n, cols: n, elements }
+    }
+
+    /// 形状，如 `2×3`
+    pub fn shape(&self) -> String {
+        format!("{}×{}", self.rows, self.cols)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> &Number {
+        &self.elements[row * self.cols + col]
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        let elements = (0..self.cols)
+            .flat_map(|col| (0..self.rows).map(move |row| (row, col)))
+            .map(|(row, col)| self.get(row, col).clone())
+            .collect();
+        Matrix { rows: self.cols, cols: self.rows, elements }
+    }
+
+    /// 对每个元素应用 `f`
+    pub fn map(&self, f: impl FnMut(&Number) -> Result<Number, CalcError>) -> Result<Matrix, CalcError> {
+        let elements = self.elements.iter().map(f).collect::<Result<_, _>>()?;
+        Ok(Matrix { rows: self.rows, cols: self.cols, elements })
+    }
+
+    /// 逐个元素运算（加减），形状必须相同
+    pub fn elementwise(
+        &self,
+        op: BinaryOp,
+        other: &Matrix,
+        mut f: impl FnMut(Number, Number) -> Result<Number, CalcError>,
+    ) -> Result<Matrix, CalcError> {
+        if (self.rows, self.cols) != (other.rows, other.cols) {
+            return Err(CalcError::DimensionMismatch(format!("矩阵形状不匹配：{} {} {}", self.shape(), op.symbol(), other.shape())));
+        }
+        let elements = self.elements.iter().cloned()
+            .zip(other.elements.iter().cloned())
+            .map(|(a, b)| f(a, b))
+            .collect::<Result<_, _>>()?;
+        Ok(Matrix { rows: self.rows, cols: self.cols, elements })
+    }
+
+    /// 矩阵乘法，`add` 和 `mul` 是元素的加法和乘法
+    pub fn product(
+        &self,
+        other: &Matrix,
+        add: impl Fn(Number, Number) -> Result<Number, CalcError>,
+        mul: impl Fn(Number, Number) -> Result<Number, CalcError>,
+    ) -> Result<Matrix, CalcError> {
+        if self.cols != other.rows {
+            return Err(CalcError::DimensionMismatch(format!("矩阵形状不匹配：{} * {}", self.shape(), other.shape())));
+        }
+        let mut elements = Vec::with_capacity(self.rows * other.cols);
+        for row in 0..self.rows {
+            for col in 0..other.cols {
+                let mut sum = mul(self.get(row, 0).clone(), other.get(0, col).clone())?;
+                for k in 1..self.cols {
+                    sum = add(sum, mul(self.get(row, k).clone(), other.get(k, col).clone())?)?;
+                }
+                elements.push(sum);
+            }
+        }
+        Ok(Matrix { rows: self.rows, cols: other.cols, elements })
+    }
+
+    /// 行列式，结果是精确值
+    pub fn determinant(&self) -> Result<Number, CalcError> {
+        let mut a = self.square_rationals("det")?;
+        let n = self.rows;
+        let mut det = BigRational::one();
+        for col in 0..n {
+            let Some(pivot) = (col..n).find(|&row| !a[row][col].is_zero()) else {
+                return Ok(Number::Rational(BigRational::zero()));
+            };
+            if pivot != col {
+                a.swap(pivot, col);
+                det = -det;
+            }
+            det *= &a[col][col];
+            let pivot_row = a[col].clone();
+            for row in a.iter_mut().skip(col + 1) {
+                let factor = &row[col] / &pivot_row[col];
+                for (value, pivot_value) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *value -= &factor * pivot_value;
+                }
+            }
+        }
+        Ok(Number::Rational(det))
+    }
+
+    /// 逆矩阵，用 Gauss-Jordan 消元精确计算
+    pub fn inverse(&self) -> Result<Matrix, CalcError> {
+        let n = self.rows;
+        let mut a = self.square_rationals("inv")?;
+        // 在右边拼上单位矩阵
+        for (i, row) in a.iter_mut().enumerate() {
+            row.extend((0..n).map(|j| if i == j { BigRational::one() } else { BigRational::zero() }));
+        }
+        for col in 0..n {
+            let pivot = (col..n)
+                .find(|&row| !a[row][col].is_zero())
+                .ok_or_else(|| CalcError::Domain("矩阵不可逆（行列式为 0）".to_string()))?;
+            a.swap(pivot, col);
+            let scale = a[col][col].clone();
+            for value in a[col].iter_mut() {
+                *value /= &scale;
+            }
+            let pivot_row = a[col].clone();
+            for (row, values) in a.iter_mut().enumerate() {
+                if row == col || values[col].is_zero() {
+                    continue;
+                }
+                let factor = values[col].clone();
+                for (value, pivot_value) in values.iter_mut().zip(&pivot_row) {
+                    *value -= &factor * pivot_value;
+                }
+            }
+        }
+        let elements = a.into_iter()
+            .flat_map(|row| row.into_iter().skip(n))
+            .map(Number::Rational)
+            .collect();
+        Ok(Matrix { rows: n, cols: n, elements })
+    }
+
+    /// 方阵的精确元素，按行排列
+    fn square_rationals(&self, name: &str) -> Result<Vec<Vec<BigRational>>, CalcError> {
+        if !self.is_square() {
+            return Err(CalcError::DimensionMismatch(format!("{} 只能用于方阵，得到 {} 矩阵", name, self.shape())));
+        }
+        self.elements
+            .chunks(self.cols)
+            .map(|row| row.iter().map(Number::to_rational).collect())
+            .collect()
+    }
+
+    /// 按输出格式显示，如 `[1, 2; 3, 4]`
+    pub fn format(&self, format: OutputFormat) -> String {
+        let rows: Vec<String> = self.elements
+            .chunks(self.cols)
+            .map(|row| row.iter().map(|value| value.format(format)).collect::<Vec<_>>().join(", "))
+            .collect();
+        format!("[{}]", rows.join("; "))
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(OutputFormat::Auto))
+    }
+}
+
+/// 解析 `Display` 输出的形式
+impl FromStr for Matrix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let inner = s.strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .ok_or_else(|| format!("'{}' 不是有效的矩阵", s))?;
+        let rows = inner.split(';')
+            .map(|row| row.split(',').map(|value| value.trim().parse()).collect())
+            .collect::<Result<_, _>>()?;
+        Matrix::new(rows).map_err(|error| error.to_string())
+    }
+}
+
+/// 表达式的值
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// 实数，可以带单位
+    Scalar(Quantity),
+    Complex(Complex),
+    Matrix(Matrix),
+    /// 符号表达式，如 `diff(x^2, x)` 的结果 `2 * x`
+    Symbolic(Expr),
+}
+
+impl Value {
+    /// 把数值换算成 `mode` 对应的表示；复数总是浮点数
+    pub fn convert(&self, mode: NumberMode) -> Result<Value, CalcError> {
+        match self {
+            Value::Scalar(quantity) => quantity.convert(mode).map(Value::Scalar),
+            Value::Complex(_) | Value::Symbolic(_) => Ok(self.clone()),
+            Value::Matrix(matrix) => matrix.map(|value| value.convert(mode)).map(Value::Matrix),
+        }
+    }
+
+    /// 是否包含浮点数
+    ///
+    /// `Display` 输出的小数解析回来是精确的分数，保存结果时需要同时记下这一点。
+    pub fn is_float(&self) -> bool {
+        match self {
+            Value::Scalar(quantity) => matches!(quantity.number, Number::Float(_)),
+            Value::Complex(_) => true,
+            Value::Matrix(matrix) => matrix.elements.iter().any(|value| matches!(value, Number::Float(_))),
+            Value::Symbolic(_) => false,
+        }
+    }
+
+    /// 要求是实数，`what` 说明这个值的用途，用于错误信息
+    pub fn into_quantity(self, what: &str) -> Result<Quantity, CalcError> {
+        match self {
+            Value::Scalar(quantity) => Ok(quantity),
+            Value::Complex(z) => Err(CalcError::InvalidArgument(format!("{}不能是复数，得到 {}", what, z))),
+            Value::Matrix(matrix) => Err(CalcError::InvalidArgument(format!("{}不能是矩阵，得到 {} 矩阵", what, matrix.shape()))),
+            Value::Symbolic(expr) => Err(CalcError::InvalidArgument(format!("{}不能是符号表达式，得到 {}", what, expr))),
+        }
+    }
+
+    /// 按输出格式显示
+    pub fn format(&self, format: OutputFormat) -> String {
+        match self {
+            Value::Scalar(quantity) => quantity.format(format),
+            Value::Complex(z) => z.format(format),
+            Value::Matrix(matrix) => matrix.format(format),
+            Value::Symbolic(expr) => expr.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(OutputFormat::Auto))
+    }
+}
+
+/// 解析 `Display` 输出的形式，用于读取历史文件
+impl FromStr for Value {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with('[') {
+            return s.parse().map(Value::Matrix);
+        }
+        // `3 mi` 也以 i 结尾，不是复数时按带单位的数解析，最后按表达式解析
+        if let Ok(z) = s.parse() {
+            return Ok(Value::Complex(z));
+        }
+        if let Ok(quantity) = s.parse() {
+            return Ok(Value::Scalar(quantity));
+        }
+        parser::parse(s).map(Value::Symbolic).map_err(|error| error.to_string())
+    }
+}
+
+impl From<Quantity> for Value {
+    fn from(quantity: Quantity) -> Self {
+        Value::Scalar(quantity)
+    }
+}
+
+impl From<Number> for Value {
+    fn from(number: Number) -> Self {
+        Value::Scalar(number.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Scalar(value.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(s: &str) -> Matrix {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_complex_arithmetic() {
+        let z = Complex::new(3.0, 4.0);
+        assert_eq!(z.abs(), 5.0);
+        assert_eq!(z.conj(), Complex::new(3.0, -4.0));
+        assert_eq!(Complex::new(-1.0, 0.0).sqrt(), I);
+        assert_eq!(Complex::apply(BinaryOp::Pow, I, 2.0.into()).unwrap(), (-1.0).into());
+        assert_eq!(Complex::apply(BinaryOp::Mul, z, z.conj()).unwrap(), 25.0.into());
+        assert_eq!(Complex::apply(BinaryOp::Div, z, I).unwrap(), Complex::new(4.0, -3.0));
+        assert_eq!(Complex::apply(BinaryOp::Div, z, 0.0.into()).unwrap_err(), CalcError::DivisionByZero);
+        assert!(Complex::apply(BinaryOp::Rem, z, I).is_err());
+        let e = Complex::apply(BinaryOp::Pow, I, I).unwrap();
+        assert!((e.re - (-std::f64::consts::FRAC_PI_2).exp()).abs() < 1e-15 && e.im.abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_complex_display() {
+        for (z, text) in [
+            (Complex::new(3.0, 4.0), "3 + 4i"),
+            (Complex::new(3.0, -1.0), "3 - i"),
+            (Complex::new(0.0, -2.5), "-2.5i"),
+            (I, "i"),
+        ] {
+            assert_eq!(z.to_string(), text);
+            assert_eq!(text.parse::<Complex>().unwrap(), z);
+        }
+        assert!("3 mi".parse::<Complex>().is_err());
+        assert_eq!("3 mi".parse::<Value>().unwrap().to_string(), "3 mi");
+        assert!(matches!("2 * x + 1".parse::<Value>().unwrap(), Value::Symbolic(_)));
+    }
+
+    #[test]
+    fn test_matrix() {
+        let m = matrix("[1, 2; 3, 4]");
+        assert_eq!(m.shape(), "2×2");
+        assert_eq!(m.transpose().to_string(), "[1, 3; 2, 4]");
+        assert_eq!(m.determinant().unwrap().to_string(), "-2");
+        assert_eq!(m.inverse().unwrap().to_string(), "[-2, 1; 3/2, -1/2]");
+        assert_eq!(matrix("[1, 2, 3]").transpose().to_string(), "[1; 2; 3]");
+        assert_eq!(Matrix::identity(2), matrix("[1, 0; 0, 1]"));
+
+        let add = |a: Number, b: Number| Ok(Number::Float(a.to_f64() + b.to_f64()));
+        let mul = |a: Number, b: Number| Ok(Number::Float(a.to_f64() * b.to_f64()));
+        assert_eq!(m.product(&m, add, mul).unwrap().to_string(), "[7, 10; 15, 22]");
+        assert_eq!(
+            m.product(&matrix("[1, 2, 3]"), add, mul).unwrap_err().to_string(),
+            "矩阵形状不匹配：2×2 * 1×3"
+        );
+        assert_eq!(
+            m.elementwise(BinaryOp::Add, &matrix("[1; 2]"), add).unwrap_err().to_string(),
+            "矩阵形状不匹配：2×2 + 2×1"
+        );
+    }
+
+    #[test]
+    fn test_singular_matrix() {
+        let m = matrix("[1, 2; 2, 4]");
+        assert_eq!(m.determinant().unwrap().to_string(), "0");
+        assert_eq!(m.inverse().unwrap_err().to_string(), "矩阵不可逆（行列式为 0）");
+        assert_eq!(matrix("[1, 2]").determinant().unwrap_err().to_string(), "det 只能用于方阵，得到 1×2 矩阵");
+        assert_eq!("[1, 2; 3]".parse::<Matrix>().unwrap_err().to_string(), "矩阵第 2 行有 1 个元素，第 1 行有 2 个");
+    }
+}
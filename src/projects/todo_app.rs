@@ -0,0 +1,357 @@
+//! 待办事项管理器
+//!
+//! 第二个实践项目：用 clap 解析子命令，用 serde 把任务保存为 JSON。
+//! 任务的数据和存储在库的 `learn_rust::projects::todo_app` 模块中，这里只负责
+//! 命令行界面。
+//!
+//! ```text
+//! todo_app add 写周报 -p high -d +3d -P work -t report     添加任务
+//! todo_app list                                 列出未完成的任务，--all 包括已完成的
+//! todo_app list tag:work priority>=high due<2026-11-01 -done
+//! todo_app list --sort due,-priority overdue    按截止日期、再按优先级从低到高排序
+//! todo_app done 3 4                             完成任务
+//! todo_app edit 3 写月报 --no-due --untag report  修改标题和属性
+//! todo_app remove 3                             删除任务
+//! ```
+//!
+//! 查询语法见 `learn_rust::projects::todo_app::Filter`。已经过了截止日期的任务
+//! 在终端里用红色显示，今天到期的用黄色显示。
+//!
+//! 任务默认保存在 `~/.todo.json`，可以用 `--file` 指定其他文件。
+
+use chrono::{Local, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand};
+use learn_rust::projects::todo_app::{
+    parse_date, sort_tasks, Filter, Priority, SortKey, Task, TaskId, TaskStore, TaskUpdate, TodoError,
+};
+use std::env;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// 逾期任务的颜色（红色）
+const OVERDUE_COLOR: &str = "\x1b[31m";
+/// 今天到期的任务的颜色（黄色）
+const DUE_TODAY_COLOR: &str = "\x1b[33m";
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// 待办事项管理器
+#[derive(Parser)]
+#[command(name = "todo_app", about)]
+struct Cli {
+    /// 任务文件路径，默认为 ~/.todo.json
+    #[arg(short, long, global = true)]
+    file: Option<PathBuf>,
+
+    /// 不使用颜色
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 添加任务
+    Add {
+        /// 任务标题，多个词用空格连接
+        #[arg(required = true)]
+        title: Vec<String>,
+        #[command(flatten)]
+        attributes: Attributes,
+    },
+    /// 列出任务
+    #[command(alias = "ls")]
+    List {
+        /// 包括已完成的任务
+        #[arg(short, long)]
+        all: bool,
+        /// 排序字段，用逗号分开，如 due,-priority
+        ///
+        /// 可选：id, title, priority, due, created, project, status；前缀 - 表示反向
+        #[arg(short, long, value_delimiter = ',', default_value = "id")]
+        sort: Vec<SortKey>,
+        /// 查询条件，如 tag:work priority>=high due<2026-11-01 -done
+        ///
+        /// 省略时只列出未完成的任务（--all 时列出全部）
+        #[arg(allow_hyphen_values = true, trailing_var_arg = true, conflicts_with = "all")]
+        query: Vec<String>,
+    },
+    /// 把任务标记为已完成
+    Done {
+        #[arg(required = true)]
+        ids: Vec<TaskId>,
+    },
+    /// 修改任务的标题和属性
+    Edit {
+        id: TaskId,
+        /// 新的标题，省略时保持不变
+        title: Vec<String>,
+        #[command(flatten)]
+        attributes: Attributes,
+        /// 清除截止日期
+        #[arg(long, conflicts_with = "due")]
+        no_due: bool,
+        /// 清除项目
+        #[arg(long, conflicts_with = "project")]
+        no_project: bool,
+        /// 去掉标签，可以重复使用
+        #[arg(long = "untag", value_name = "TAG")]
+        untags: Vec<String>,
+    },
+    /// 删除任务
+    #[command(alias = "rm")]
+    Remove {
+        #[arg(required = true)]
+        ids: Vec<TaskId>,
+    },
+}
+
+/// 添加和修改任务时可以设置的属性
+#[derive(Args)]
+struct Attributes {
+    /// 优先级：low, medium, high, urgent
+    #[arg(short, long)]
+    priority: Option<Priority>,
+    /// 截止日期，如 2026-11-01、today、tomorrow、+3d、+2w
+    #[arg(short, long, value_parser = parse_due)]
+    due: Option<NaiveDate>,
+    /// 所属项目
+    #[arg(short = 'P', long)]
+    project: Option<String>,
+    /// 标签，可以重复使用
+    #[arg(short, long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+}
+
+impl Attributes {
+    fn into_update(self) -> TaskUpdate {
+        TaskUpdate {
+            priority: self.priority,
+            due: self.due.map(Some),
+            project: self.project.map(Some),
+            add_tags: self.tags,
+            remove_tags: Vec::new(),
+        }
+    }
+}
+
+fn parse_due(text: &str) -> Result<NaiveDate, String> {
+    parse_date(text, today())
+        .ok_or_else(|| format!("无效的日期 '{}'（例如 2026-11-01、today、tomorrow、+3d）", text))
+}
+
+/// 本地时区的今天
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let path = cli.file.unwrap_or_else(default_path);
+    let color = !cli.no_color && env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal();
+
+    match run(cli.command, &path, today(), color) {
+        Ok(lines) => {
+            for line in lines {
+                println!("{}", line);
+            }
+        }
+        Err(error) => {
+            eprintln!("错误：{}", error);
+            process::exit(1);
+        }
+    }
+}
+
+/// 任务文件的默认位置：主目录下的 `.todo.json`
+fn default_path() -> PathBuf {
+    env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".todo.json"))
+        .unwrap_or_else(|| PathBuf::from(".todo.json"))
+}
+
+/// 执行命令，返回要显示的内容
+///
+/// 修改任务的命令全部成功后才保存，出错时文件保持原样。
+fn run(command: Command, path: &Path, today: NaiveDate, color: bool) -> Result<Vec<String>, TodoError> {
+    let mut store = TaskStore::load_or_default(path)?;
+    let now = Utc::now();
+
+    let lines = match command {
+        Command::List { all, sort, query } => {
+            let query = match (query.is_empty(), all) {
+                (true, false) => "-done".to_string(),
+                _ => query.join(" "),
+            };
+            return list(&store, &Filter::parse(&query, today)?, &sort, today, color);
+        }
+        Command::Add { title, attributes } => {
+            vec![format!("已添加 {}", store.add(&title.join(" "), attributes.into_update(), now)?)]
+        }
+        Command::Done { ids } => ids
+            .into_iter()
+            .map(|id| Ok(format!("已完成 {}", store.complete(id, now)?)))
+            .collect::<Result<_, TodoError>>()?,
+        Command::Edit { id, title, attributes, no_due, no_project, untags } => {
+            let title = (!title.is_empty()).then(|| title.join(" "));
+            let mut update = attributes.into_update();
+            if no_due {
+                update.due = Some(None);
+            }
+            if no_project {
+                update.project = Some(None);
+            }
+            update.remove_tags = untags;
+            vec![format!("已修改 {}", store.edit(id, title.as_deref(), update)?)]
+        }
+        Command::Remove { ids } => store.remove(&ids)?
+            .into_iter()
+            .map(|task| format!("已删除 {}", task))
+            .collect(),
+    };
+    store.save(path)?;
+    Ok(lines)
+}
+
+fn list(store: &TaskStore, filter: &Filter, sort: &[SortKey], today: NaiveDate, color: bool) -> Result<Vec<String>, TodoError> {
+    let mut tasks: Vec<&Task> = store.tasks().iter().filter(|task| filter.matches(task)).collect();
+    sort_tasks(&mut tasks, sort);
+    if tasks.is_empty() {
+        let message = if store.tasks().is_empty() { "暂无待办事项" } else { "没有符合条件的任务" };
+        return Ok(vec![message.to_string()]);
+    }
+    Ok(tasks.into_iter().map(|task| show(task, today, color)).collect())
+}
+
+/// 显示一个任务，逾期和今天到期的任务加上提示，需要时用颜色突出
+fn show(task: &Task, today: NaiveDate, color: bool) -> String {
+    let (line, highlight) = match task.due {
+        Some(due) if task.is_overdue(today) => {
+            let days = (today - due).num_days();
+            (format!("{}（逾期 {} 天）", task, days), OVERDUE_COLOR)
+        }
+        Some(due) if due == today && !task.is_done() => (format!("{}（今天到期）", task), DUE_TODAY_COLOR),
+        _ => return task.to_string(),
+    };
+    if color {
+        format!("{}{}{}", highlight, line, RESET_COLOR)
+    } else {
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn todo(path: &Path, args: &[&str]) -> Result<Vec<String>, TodoError> {
+        let cli = Cli::try_parse_from(std::iter::once("todo_app").chain(args.iter().copied())).unwrap();
+        run(cli.command, path, date("2026-10-18"), false)
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+        assert!(Cli::try_parse_from(["todo_app", "add"]).is_err());
+        assert!(Cli::try_parse_from(["todo_app", "done", "x"]).is_err());
+        assert!(Cli::try_parse_from(["todo_app", "add", "x", "-p", "p1"]).is_err());
+        assert!(Cli::try_parse_from(["todo_app", "add", "x", "-d", "下周"]).is_err());
+        assert!(Cli::try_parse_from(["todo_app", "edit", "1", "--due", "today", "--no-due"]).is_err());
+        assert!(Cli::try_parse_from(["todo_app", "list", "--sort", "size"]).is_err());
+        assert!(Cli::try_parse_from(["todo_app", "list", "--all", "tag:work"]).is_err());
+    }
+
+    #[test]
+    fn test_commands() {
+        let path = std::env::temp_dir().join(format!("todo_app_{}.json", process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(todo(&path, &["list"]).unwrap(), ["暂无待办事项"]);
+        assert_eq!(todo(&path, &["add", "买", "牛奶"]).unwrap(), ["已添加 #1 [ ] 买 牛奶"]);
+        todo(&path, &["add", "写周报"]).unwrap();
+        todo(&path, &["add", "读书"]).unwrap();
+        assert_eq!(todo(&path, &["done", "1", "3"]).unwrap(), ["已完成 #1 [x] 买 牛奶", "已完成 #3 [x] 读书"]);
+        assert_eq!(todo(&path, &["edit", "2", "写月报"]).unwrap(), ["已修改 #2 [ ] 写月报"]);
+        assert_eq!(todo(&path, &["ls"]).unwrap(), ["#2 [ ] 写月报"]);
+        assert_eq!(todo(&path, &["rm", "2"]).unwrap(), ["已删除 #2 [ ] 写月报"]);
+        assert_eq!(todo(&path, &["list"]).unwrap(), ["没有符合条件的任务"]);
+
+        // 出错时不保存，之前完成的任务也不会被记录
+        assert_eq!(todo(&path, &["remove", "1", "2"]).unwrap_err().to_string(), "没有编号为 2 的任务");
+        todo(&path, &["add", "跑步"]).unwrap();
+        assert!(todo(&path, &["done", "4", "9"]).is_err());
+        assert_eq!(todo(&path, &["list", "--all"]).unwrap(), ["#1 [x] 买 牛奶", "#3 [x] 读书", "#4 [ ] 跑步"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_query() {
+        let path = std::env::temp_dir().join(format!("todo_app_query_{}.json", process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(
+            todo(&path, &["add", "写周报", "-p", "high", "-d", "2026-10-20", "-P", "work", "-t", "report"]).unwrap(),
+            ["已添加 #1 [ ] 写周报 priority:high project:work tag:report due:2026-10-20"]
+        );
+        todo(&path, &["add", "交房租", "--due", "2026-10-15", "-p", "urgent"]).unwrap();
+        todo(&path, &["add", "买菜", "-d", "today", "-t", "home", "-t", "shopping"]).unwrap();
+        todo(&path, &["add", "读书", "-p", "low"]).unwrap();
+        assert_eq!(todo(&path, &["add", "x", "-t", "a b"]).unwrap_err().to_string(), "无效的名称 'a b'：标签和项目不能为空，也不能包含空白");
+
+        assert_eq!(
+            todo(&path, &["list"]).unwrap(),
+            [
+                "#1 [ ] 写周报 priority:high project:work tag:report due:2026-10-20",
+                "#2 [ ] 交房租 priority:urgent due:2026-10-15（逾期 3 天）",
+                "#3 [ ] 买菜 tag:home tag:shopping due:2026-10-18（今天到期）",
+                "#4 [ ] 读书 priority:low",
+            ]
+        );
+        assert_eq!(todo(&path, &["list", "priority>=high", "due<2026-11-01"]).unwrap().len(), 2);
+        assert_eq!(todo(&path, &["list", "tag:work"]).unwrap(), ["没有符合条件的任务"]);
+        assert_eq!(todo(&path, &["ls", "-s", "-priority", "tag:home or priority:low"]).unwrap().len(), 2);
+        assert_eq!(
+            todo(&path, &["list", "overdue"]).unwrap(),
+            ["#2 [ ] 交房租 priority:urgent due:2026-10-15（逾期 3 天）"]
+        );
+        assert_eq!(todo(&path, &["list", "due<"]).unwrap_err().to_string(), "查询第 5 列：'due' 后面缺少值");
+
+        let ids = |args: &[&str]| -> Vec<String> {
+            todo(&path, args).unwrap().iter().map(|line| line[..2].to_string()).collect()
+        };
+        assert_eq!(ids(&["list", "--sort", "due"]), ["#2", "#3", "#1", "#4"]);
+        assert_eq!(ids(&["list", "--sort", "priority"]), ["#2", "#1", "#3", "#4"]);
+        assert_eq!(ids(&["list", "--sort", "-priority,id"]), ["#4", "#3", "#1", "#2"]);
+
+        assert_eq!(
+            todo(&path, &["edit", "1", "--no-due", "--no-project", "--untag", "report", "-t", "weekly"]).unwrap(),
+            ["已修改 #1 [ ] 写周报 priority:high tag:weekly"]
+        );
+        todo(&path, &["done", "2"]).unwrap();
+        assert_eq!(ids(&["list", "done"]), ["#2"]);
+        assert_eq!(ids(&["list", "--all", "--sort", "status,-id"]), ["#4", "#3", "#1", "#2"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_highlight() {
+        let today = date("2026-10-18");
+        let mut task = Task::new(1, "交房租", Utc::now());
+        task.due = Some(date("2026-10-17"));
+        assert_eq!(show(&task, today, true), "\x1b[31m#1 [ ] 交房租 due:2026-10-17（逾期 1 天）\x1b[0m");
+        task.completed = Some(Utc::now());
+        assert_eq!(show(&task, today, true), "#1 [x] 交房租 due:2026-10-17");
+        task.due = Some(today);
+        task.completed = None;
+        assert_eq!(show(&task, today, true), "\x1b[33m#1 [ ] 交房租 due:2026-10-18（今天到期）\x1b[0m");
+    }
+}
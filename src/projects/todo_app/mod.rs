@@ -0,0 +1,15 @@
+//! 待办事项管理器
+//!
+//! 任务保存在一个 JSON 文件中，`todo_app` 二进制负责解析命令、显示结果。
+//! 每个任务有一个稳定的编号：编号按添加顺序递增，删除任务后也不会被重新使用，
+//! 所以脚本和笔记里引用的 `#3` 始终指向同一个任务。
+//!
+//! 任务可以有优先级、截止日期、项目和标签，用 [`Filter`] 查询，用 [`SortKey`] 排序。
+
+mod query;
+mod store;
+mod task;
+
+pub use query::{sort_tasks, Comparison, Filter, QueryError, SortField, SortKey};
+pub use store::{TaskStore, TaskUpdate, TodoError};
+pub use task::{parse_date, Priority, Task, TaskId};
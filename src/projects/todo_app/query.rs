@@ -0,0 +1,573 @@
+//! 任务的查询语言和排序
+//!
+//! 查询由空格分开的条件组成，条件之间默认是“并且”：
+//!
+//! ```text
+//! tag:work priority>=high due<2026-11-01 -done
+//! (project:home or tag:urgent) -done 周报
+//! ```
+//!
+//! - `done`：已完成；`overdue`：未完成且已经过了截止日期
+//! - `tag:名称`、`project:名称`，`project:none` 表示没有项目
+//! - `priority` 和 `due` 可以用 `:`、`=`、`!=`、`<`、`<=`、`>`、`>=` 比较，
+//!   日期可以写成 `2026-11-01`、`today`、`+3d` 等，`due:none` 表示没有截止日期
+//! - 其他的词匹配标题，不区分大小写；带空格的词用双引号括起来
+//! - `-条件` 或 `not 条件` 取反，`or` 表示“或者”，优先级低于“并且”，可以用括号分组
+//!
+//! 查询先解析成 [`Filter`] 语法树，再对每个任务求值。
+
+use super::task::{parse_date, Priority, Task};
+use chrono::NaiveDate;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// 查询条件的语法树
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// 所有条件都满足，没有条件时匹配所有任务
+    And(Vec<Filter>),
+    /// 至少满足一个条件
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+    /// 已完成
+    Done,
+    /// 未完成且截止日期早于给定的日期（今天）
+    Overdue(NaiveDate),
+    /// 标题包含这段文字，不区分大小写
+    Text(String),
+    /// 带有这个标签
+    Tag(String),
+    /// 属于这个项目，`None` 表示没有项目
+    Project(Option<String>),
+    Priority(Comparison, Priority),
+    /// 有截止日期，并且满足比较条件
+    Due(Comparison, NaiveDate),
+    /// 没有截止日期
+    NoDue,
+}
+
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    /// `ordering` 是左边的值和右边的值比较的结果
+    fn test(self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Eq => ordering == Ordering::Equal,
+            Comparison::Ne => ordering != Ordering::Equal,
+            Comparison::Lt => ordering == Ordering::Less,
+            Comparison::Le => ordering != Ordering::Greater,
+            Comparison::Gt => ordering == Ordering::Greater,
+            Comparison::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// 查询的语法错误
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    /// 出错的位置（从 1 开始的字符序号）
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "查询第 {} 列：{}", self.column, self.message)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl Filter {
+    /// 解析查询，`today` 用于 `overdue` 和 `today`、`+3d` 这样的相对日期
+    pub fn parse(query: &str, today: NaiveDate) -> Result<Filter, QueryError> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser { tokens, position: 0, today, end: query.chars().count() + 1, depth: 0 };
+        let filter = parser.or()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some((column, _)) => Err(error(column, "多余的右括号")),
+        }
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        match self {
+            Filter::And(filters) => filters.iter().all(|filter| filter.matches(task)),
+            Filter::Or(filters) => filters.iter().any(|filter| filter.matches(task)),
+            Filter::Not(filter) => !filter.matches(task),
+            Filter::Done => task.is_done(),
+            Filter::Overdue(today) => task.is_overdue(*today),
+            Filter::Text(text) => task.title.to_lowercase().contains(&text.to_lowercase()),
+            Filter::Tag(tag) => task.tags.iter().any(|name| name.eq_ignore_ascii_case(tag)),
+            Filter::Project(project) => match (&task.project, project) {
+                (Some(name), Some(project)) => name.eq_ignore_ascii_case(project),
+                (name, project) => name.is_none() && project.is_none(),
+            },
+            Filter::Priority(comparison, priority) => comparison.test(task.priority.cmp(priority)),
+            Filter::Due(comparison, date) => task.due.is_some_and(|due| comparison.test(due.cmp(date))),
+            Filter::NoDue => task.due.is_none(),
+        }
+    }
+}
+
+/// 词法记号
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    /// 条件前面的 `-`
+    Minus,
+    /// 一个词，引号已经去掉；`quoted` 表示整个词都在引号里，不作为关键字或字段
+    Word { text: String, quoted: bool },
+}
+
+/// 切分记号，记号带有起始列
+fn tokenize(query: &str) -> Result<Vec<(usize, Token)>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().enumerate().peekable();
+    while let Some(&(index, c)) = chars.peek() {
+        let column = index + 1;
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push((column, if c == '(' { Token::LeftParen } else { Token::RightParen }));
+            }
+            '-' => {
+                chars.next();
+                tokens.push((column, Token::Minus));
+            }
+            _ => {
+                let (mut text, mut quoted, mut unquoted) = (String::new(), false, false);
+                while let Some(&(index, c)) = chars.peek() {
+                    match c {
+                        '"' => {
+                            chars.next();
+                            quoted = true;
+                            loop {
+                                match chars.next() {
+                                    Some((_, '"')) => break,
+                                    Some((_, c)) => text.push(c),
+                                    None => return Err(error(index + 1, "缺少右引号")),
+                                }
+                            }
+                        }
+                        c if c.is_whitespace() || c == '(' || c == ')' => break,
+                        c => {
+                            chars.next();
+                            unquoted = true;
+                            text.push(c);
+                        }
+                    }
+                }
+                tokens.push((column, Token::Word { text, quoted: quoted && !unquoted }));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// 递归下降解析器
+///
+/// ```text
+/// or    := and ("or" and)*
+/// and   := unary ("and"? unary)*
+/// unary := ("-" | "not") unary | "(" or ")" | 条件
+/// ```
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    today: NaiveDate,
+    /// 查询末尾的列，用于“缺少条件”之类的错误
+    end: usize,
+    /// 当前所在的括号和取反的层数
+    depth: usize,
+}
+
+/// 括号和取反最多嵌套的层数，`unary` 是递归的，不加限制时很深的嵌套会耗尽栈空间
+const MAX_DEPTH: usize = 64;
+
+impl Parser {
+    fn peek(&self) -> Option<(usize, &Token)> {
+        self.tokens.get(self.position).map(|(column, token)| (*column, token))
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    /// 下一个记号是不是没有引号的关键字
+    fn keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some((_, Token::Word { text, quoted: false })) if text.eq_ignore_ascii_case(keyword))
+    }
+
+    fn or(&mut self) -> Result<Filter, QueryError> {
+        let mut filters = vec![self.and()?];
+        while self.keyword("or") {
+            let (column, _) = self.next().unwrap();
+            match self.and()? {
+                Filter::And(empty) if empty.is_empty() => return Err(error(column, "'or' 后面缺少条件")),
+                filter => filters.push(filter),
+            }
+        }
+        Ok(if filters.len() == 1 { filters.remove(0) } else { Filter::Or(filters) })
+    }
+
+    fn and(&mut self) -> Result<Filter, QueryError> {
+        let mut filters = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some((_, Token::RightParen)) => break,
+                Some(_) if self.keyword("or") => {
+                    if filters.is_empty() {
+                        return Err(error(self.peek().unwrap().0, "'or' 前面缺少条件"));
+                    }
+                    break;
+                }
+                Some(_) if self.keyword("and") => {
+                    let (column, _) = self.next().unwrap();
+                    if filters.is_empty() {
+                        return Err(error(column, "'and' 前面缺少条件"));
+                    }
+                    filters.push(self.unary()?);
+                }
+                Some(_) => filters.push(self.unary()?),
+            }
+        }
+        Ok(if filters.len() == 1 { filters.remove(0) } else { Filter::And(filters) })
+    }
+
+    fn unary(&mut self) -> Result<Filter, QueryError> {
+        let Some((column, token)) = self.next() else {
+            return Err(error(self.end, "缺少条件"));
+        };
+        if self.depth == MAX_DEPTH {
+            return Err(error(column, format!("括号或取反嵌套超过 {} 层", MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let filter = self.unary_from(column, token);
+        self.depth -= 1;
+        filter
+    }
+
+    /// 从已经读出的记号开始解析 `unary`
+    fn unary_from(&mut self, column: usize, token: Token) -> Result<Filter, QueryError> {
+        match token {
+            Token::Minus => Ok(Filter::Not(Box::new(self.unary()?))),
+            Token::Word { text, quoted: false } if text.eq_ignore_ascii_case("not") => {
+                Ok(Filter::Not(Box::new(self.unary()?)))
+            }
+            Token::LeftParen => {
+                let filter = self.or()?;
+                match self.next() {
+                    Some((_, Token::RightParen)) if filter == Filter::And(Vec::new()) => {
+                        Err(error(column, "括号中缺少条件"))
+                    }
+                    Some((_, Token::RightParen)) => Ok(filter),
+                    _ => Err(error(column, "缺少与这里匹配的右括号")),
+                }
+            }
+            Token::RightParen => Err(error(column, "缺少条件")),
+            Token::Word { text, quoted: true } => Ok(Filter::Text(text)),
+            Token::Word { text, quoted: false } => self.condition(column, &text),
+        }
+    }
+
+    /// 一个条件：关键字、`字段 运算符 值`，或者匹配标题的文字
+    fn condition(&self, column: usize, text: &str) -> Result<Filter, QueryError> {
+        match text.to_lowercase().as_str() {
+            "done" => return Ok(Filter::Done),
+            "overdue" => return Ok(Filter::Overdue(self.today)),
+            _ => {}
+        }
+        let Some(split) = text.find([':', '=', '!', '<', '>']) else {
+            return Ok(Filter::Text(text.to_string()));
+        };
+        let field = &text[..split];
+        let rest = &text[split..];
+        let (comparison, value) = [
+            ("<=", Comparison::Le),
+            (">=", Comparison::Ge),
+            ("!=", Comparison::Ne),
+            (":", Comparison::Eq),
+            ("=", Comparison::Eq),
+            ("<", Comparison::Lt),
+            (">", Comparison::Gt),
+        ]
+        .into_iter()
+        .find_map(|(operator, comparison)| rest.strip_prefix(operator).map(|value| (comparison, value)))
+        .ok_or_else(|| error(column + field.chars().count(), "无效的比较运算符"))?;
+        let value_column = column + text.chars().count() - value.chars().count();
+        if value.is_empty() {
+            return Err(error(value_column, format!("'{}' 后面缺少值", field)));
+        }
+
+        let filter = match field.to_lowercase().as_str() {
+            "tag" | "project" if !matches!(comparison, Comparison::Eq | Comparison::Ne) => {
+                return Err(error(column + field.chars().count(), format!("{} 只能用 : 或 != 比较", field)));
+            }
+            "tag" => Filter::Tag(value.to_string()),
+            "project" if value.eq_ignore_ascii_case("none") => Filter::Project(None),
+            "project" => Filter::Project(Some(value.to_string())),
+            "priority" => {
+                let priority = value.parse().map_err(|message| error(value_column, message))?;
+                Filter::Priority(comparison, priority)
+            }
+            "due" if value.eq_ignore_ascii_case("none") => match comparison {
+                Comparison::Eq => Filter::NoDue,
+                Comparison::Ne => Filter::Not(Box::new(Filter::NoDue)),
+                _ => return Err(error(value_column, "none 只能用 : 或 != 比较")),
+            },
+            "due" => {
+                let date = parse_date(value, self.today)
+                    .ok_or_else(|| error(value_column, format!("无效的日期 '{}'", value)))?;
+                Filter::Due(comparison, date)
+            }
+            _ => return Err(error(column, format!("未知的字段 '{}'（可选：tag, project, priority, due）", field))),
+        };
+        // tag 和 project 的 != 在这里统一取反，priority 和 due 的 != 由比较运算处理
+        Ok(match (comparison, &filter) {
+            (Comparison::Ne, Filter::Tag(_) | Filter::Project(_)) => Filter::Not(Box::new(filter)),
+            _ => filter,
+        })
+    }
+}
+
+fn error(column: usize, message: impl Into<String>) -> QueryError {
+    QueryError { column, message: message.into() }
+}
+
+/// 排序字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Id,
+    Title,
+    Priority,
+    Due,
+    Created,
+    Project,
+    Status,
+}
+
+/// 一个排序键，如 `due` 或 `-priority`
+///
+/// 每个字段有自己的默认顺序：优先级从高到低，截止日期从早到晚，未完成的任务在前，
+/// 其余字段从小到大；没有截止日期或项目的任务总是排在最后。前缀 `-` 反转默认顺序。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub reverse: bool,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, reverse) = match s.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (s, false),
+        };
+        let field = match name {
+            "id" => SortField::Id,
+            "title" => SortField::Title,
+            "priority" => SortField::Priority,
+            "due" => SortField::Due,
+            "created" => SortField::Created,
+            "project" => SortField::Project,
+            "status" => SortField::Status,
+            _ => {
+                return Err(format!(
+                    "未知的排序字段 '{}'（可选：id, title, priority, due, created, project, status）",
+                    name
+                ))
+            }
+        };
+        Ok(SortKey { field, reverse })
+    }
+}
+
+impl SortKey {
+    fn compare(self, a: &Task, b: &Task) -> Ordering {
+        // 缺少的值总是排在最后，不受 reverse 影响
+        fn missing_last<T: Ord>(a: &Option<T>, b: &Option<T>, reverse: bool) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) if reverse => b.cmp(a),
+                (Some(a), Some(b)) => a.cmp(b),
+                (a, b) => a.is_none().cmp(&b.is_none()),
+            }
+        }
+        let ordering = match self.field {
+            SortField::Due => return missing_last(&a.due, &b.due, self.reverse),
+            SortField::Project => return missing_last(&a.project, &b.project, self.reverse),
+            SortField::Id => a.id.cmp(&b.id),
+            SortField::Title => a.title.cmp(&b.title),
+            SortField::Priority => b.priority.cmp(&a.priority),
+            SortField::Created => a.created.cmp(&b.created),
+            SortField::Status => a.is_done().cmp(&b.is_done()),
+        };
+        if self.reverse { ordering.reverse() } else { ordering }
+    }
+}
+
+/// 按排序键依次比较，全部相同时按编号排列
+pub fn sort_tasks(tasks: &mut [&Task], keys: &[SortKey]) {
+    tasks.sort_by(|a, b| {
+        keys.iter()
+            .map(|key| key.compare(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.id.cmp(&b.id))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    fn parse(query: &str) -> Filter {
+        Filter::parse(query, today()).unwrap()
+    }
+
+    fn parse_error(query: &str) -> String {
+        Filter::parse(query, today()).unwrap_err().to_string()
+    }
+
+    /// 示例任务：
+    /// 1 写周报 high work,report 2026-10-20
+    /// 2 买牛奶 low home 2026-10-10
+    /// 3 读书 medium 已完成
+    /// 4 交房租 urgent home money 2026-10-15
+    fn tasks() -> Vec<Task> {
+        let now = Utc::now();
+        let task = |id, title, priority, project: Option<&str>, tags: &[&str], due: Option<&str>| Task {
+            priority,
+            project: project.map(str::to_string),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            due: due.map(date),
+            ..Task::new(id, title, now)
+        };
+        vec![
+            task(1, "写周报", Priority::High, Some("work"), &["report", "Work"], Some("2026-10-20")),
+            task(2, "买牛奶", Priority::Low, Some("home"), &[], Some("2026-10-10")),
+            Task { completed: Some(now), ..task(3, "读书 Rust", Priority::Medium, None, &[], None) },
+            task(4, "交房租", Priority::Urgent, Some("home"), &["money"], Some("2026-10-15")),
+        ]
+    }
+
+    fn ids(query: &str) -> Vec<u64> {
+        let filter = parse(query);
+        tasks().iter().filter(|task| filter.matches(task)).map(|task| task.id).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(""), Filter::And(vec![]));
+        assert_eq!(
+            parse("tag:work priority>=high due<2026-11-01 -done"),
+            Filter::And(vec![
+                Filter::Tag("work".to_string()),
+                Filter::Priority(Comparison::Ge, Priority::High),
+                Filter::Due(Comparison::Lt, date("2026-11-01")),
+                Filter::Not(Box::new(Filter::Done)),
+            ])
+        );
+        assert_eq!(
+            parse("(project:home or tag:x) and not \"or\""),
+            Filter::And(vec![
+                Filter::Or(vec![Filter::Project(Some("home".to_string())), Filter::Tag("x".to_string())]),
+                Filter::Not(Box::new(Filter::Text("or".to_string()))),
+            ])
+        );
+        assert_eq!(parse("due<=tomorrow"), Filter::Due(Comparison::Le, date("2026-10-19")));
+        assert_eq!(parse("tag!=work"), Filter::Not(Box::new(Filter::Tag("work".to_string()))));
+        assert_eq!(parse("project:none"), Filter::Project(None));
+        assert_eq!(parse("due!=none"), Filter::Not(Box::new(Filter::NoDue)));
+        assert_eq!(parse("overdue"), Filter::Overdue(today()));
+        assert_eq!(parse("\"写 周报\""), Filter::Text("写 周报".to_string()));
+        assert_eq!(parse("tag:\"long name\""), Filter::Tag("long name".to_string()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_error("priority>=p1"), "查询第 11 列：未知的优先级 'p1'（可选：low, medium, high, urgent）");
+        assert_eq!(parse_error("due<2026-13-01"), "查询第 5 列：无效的日期 '2026-13-01'");
+        assert_eq!(parse_error("tag>work"), "查询第 4 列：tag 只能用 : 或 != 比较");
+        assert_eq!(parse_error("size:3"), "查询第 1 列：未知的字段 'size'（可选：tag, project, priority, due）");
+        assert_eq!(parse_error("-done tag:"), "查询第 11 列：'tag' 后面缺少值");
+        assert_eq!(parse_error("due!3"), "查询第 4 列：无效的比较运算符");
+        assert_eq!(parse_error("(done"), "查询第 1 列：缺少与这里匹配的右括号");
+        assert_eq!(parse_error("done)"), "查询第 5 列：多余的右括号");
+        assert_eq!(parse_error("done -"), "查询第 7 列：缺少条件");
+        assert_eq!(parse_error("or done"), "查询第 1 列：'or' 前面缺少条件");
+        assert_eq!(parse_error("done or"), "查询第 6 列：'or' 后面缺少条件");
+        assert_eq!(parse_error("done ()"), "查询第 6 列：括号中缺少条件");
+        assert_eq!(parse_error("\"abc"), "查询第 1 列：缺少右引号");
+    }
+
+    #[test]
+    fn test_nesting_depth() {
+        let nested = |n: usize| format!("{}done{}", "(".repeat(n), ")".repeat(n));
+        assert!(Filter::parse(&nested(MAX_DEPTH - 1), today()).is_ok());
+        assert!(Filter::parse(&format!("{}done", "-".repeat(MAX_DEPTH - 1)), today()).is_ok());
+
+        assert_eq!(parse_error(&nested(50_000)), "查询第 65 列：括号或取反嵌套超过 64 层");
+        assert_eq!(parse_error(&format!("{}done", "not ".repeat(1000))), "查询第 257 列：括号或取反嵌套超过 64 层");
+    }
+
+    #[test]
+    fn test_matches() {
+        assert_eq!(ids(""), [1, 2, 3, 4]);
+        assert_eq!(ids("-done"), [1, 2, 4]);
+        assert_eq!(ids("tag:work priority>=high due<2026-11-01 -done"), [1]);
+        assert_eq!(ids("project:HOME"), [2, 4]);
+        assert_eq!(ids("project:none"), [3]);
+        assert_eq!(ids("priority<medium or priority:urgent"), [2, 4]);
+        assert_eq!(ids("priority!=medium -project:home"), [1]);
+        assert_eq!(ids("due>=2026-10-15"), [1, 4]);
+        assert_eq!(ids("due:none"), [3]);
+        assert_eq!(ids("overdue"), [2, 4]);
+        assert_eq!(ids("rust"), [3]);
+        assert_eq!(ids("-(tag:money or done) due<today"), [2]);
+    }
+
+    #[test]
+    fn test_sort() {
+        let tasks = tasks();
+        let sorted = |keys: &str| {
+            let keys: Vec<SortKey> = keys.split(',').map(|key| key.parse().unwrap()).collect();
+            let mut list: Vec<&Task> = tasks.iter().collect();
+            sort_tasks(&mut list, &keys);
+            list.iter().map(|task| task.id).collect::<Vec<_>>()
+        };
+        assert_eq!(sorted("priority"), [4, 1, 3, 2]);
+        assert_eq!(sorted("-priority"), [2, 3, 1, 4]);
+        assert_eq!(sorted("due"), [2, 4, 1, 3]);
+        assert_eq!(sorted("-due"), [1, 4, 2, 3]);
+        assert_eq!(sorted("project,-id"), [4, 2, 1, 3]);
+        assert_eq!(sorted("status,title"), [2, 4, 1, 3]);
+        assert_eq!(sorted("-status"), [3, 1, 2, 4]);
+        assert!("size".parse::<SortKey>().is_err());
+    }
+}
@@ -0,0 +1,427 @@
+//! 任务的 JSON 存储
+//!
+//! 文件格式：
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "next_id": 4,
+//!   "tasks": [
+//!     { "id": 1, "title": "买牛奶", "priority": "low", "created": "2026-10-01T09:00:00Z" },
+//!     {
+//!       "id": 3, "title": "写周报", "priority": "high", "due": "2026-10-09", "project": "work", "tags": ["report"],
+//!       "created": "2026-10-02T09:00:00Z", "completed": "2026-10-03T18:00:00Z"
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `next_id` 是下一个任务的编号，删除任务不会让它变小。优先级、截止日期、项目
+//! 和标签是可选字段，缺少时优先级按 `medium` 处理，其余为空。新增可选字段不改变
+//! 版本号，只有旧版本无法正确读取的改动才需要提升。
+
+use super::query::QueryError;
+use super::task::{Priority, Task, TaskId};
+use crate::utils::fs::write_atomic;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 当前的文件格式版本
+const FORMAT_VERSION: u32 = 1;
+
+/// 磁盘上的文件结构
+#[derive(Serialize, Deserialize)]
+struct TaskFile {
+    version: u32,
+    next_id: TaskId,
+    tasks: Vec<Task>,
+}
+
+/// 任务操作的错误
+#[derive(Debug)]
+pub enum TodoError {
+    /// 没有这个编号的任务
+    NotFound(TaskId),
+    /// 任务标题为空
+    EmptyTitle,
+    /// 标签或项目名称为空或者包含空白
+    InvalidName(String),
+    /// 查询语法错误
+    Query(QueryError),
+    /// 读写或解析任务文件失败
+    Io(io::Error),
+}
+
+impl fmt::Display for TodoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TodoError::NotFound(id) => write!(f, "没有编号为 {} 的任务", id),
+            TodoError::EmptyTitle => write!(f, "任务标题不能为空"),
+            TodoError::InvalidName(name) => write!(f, "无效的名称 '{}'：标签和项目不能为空，也不能包含空白", name),
+            TodoError::Query(error) => write!(f, "{}", error),
+            TodoError::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for TodoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TodoError::Io(error) => Some(error),
+            TodoError::Query(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TodoError {
+    fn from(error: io::Error) -> Self {
+        TodoError::Io(error)
+    }
+}
+
+impl From<QueryError> for TodoError {
+    fn from(error: QueryError) -> Self {
+        TodoError::Query(error)
+    }
+}
+
+impl From<serde_json::Error> for TodoError {
+    fn from(error: serde_json::Error) -> Self {
+        TodoError::Io(error.into())
+    }
+}
+
+/// 对任务属性的修改，`None` 和空列表表示保持不变
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskUpdate {
+    pub priority: Option<Priority>,
+    /// `Some(None)` 清除截止日期
+    pub due: Option<Option<NaiveDate>>,
+    /// `Some(None)` 清除项目
+    pub project: Option<Option<String>>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+}
+
+impl TaskUpdate {
+    /// 检查名称后修改任务；出错时任务保持不变
+    fn apply(self, task: &mut Task) -> Result<(), TodoError> {
+        let names = self.add_tags.iter().chain(&self.remove_tags).chain(self.project.iter().flatten());
+        if let Some(name) = names.into_iter().find(|name| name.is_empty() || name.contains(char::is_whitespace)) {
+            return Err(TodoError::InvalidName(name.clone()));
+        }
+        if let Some(priority) = self.priority {
+            task.priority = priority;
+        }
+        if let Some(due) = self.due {
+            task.due = due;
+        }
+        if let Some(project) = self.project {
+            task.project = project;
+        }
+        for tag in &self.remove_tags {
+            task.tags.remove(tag);
+        }
+        task.tags.extend(self.add_tags);
+        Ok(())
+    }
+}
+
+/// 任务列表，按编号排列
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskStore {
+    next_id: TaskId,
+    tasks: Vec<Task>,
+}
+
+impl Default for TaskStore {
+    fn default() -> Self {
+        Self {
+            next_id: 1,
+            tasks: Vec::new(),
+        }
+    }
+}
+
+impl TaskStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从 JSON 文件加载任务
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, TodoError> {
+        let content = fs::read_to_string(path)?;
+        Self::from_json(&content)
+    }
+
+    /// 加载任务，文件不存在时返回空的任务列表
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Result<Self, TodoError> {
+        match Self::load(path) {
+            Err(TodoError::Io(e)) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            result => result,
+        }
+    }
+
+    /// 把任务保存为 JSON 文件
+    ///
+    /// 先写临时文件再重命名，写入过程中崩溃也不会留下损坏的任务文件。
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), TodoError> {
+        Ok(write_atomic(path.as_ref(), &self.to_json()?)?)
+    }
+
+    /// 序列化为 JSON 字符串
+    pub fn to_json(&self) -> Result<String, TodoError> {
+        let file = TaskFile {
+            version: FORMAT_VERSION,
+            next_id: self.next_id,
+            tasks: self.tasks.clone(),
+        };
+        Ok(serde_json::to_string_pretty(&file)?)
+    }
+
+    /// 从 JSON 字符串解析
+    ///
+    /// 编号重复的文件视为损坏；`next_id` 不大于已有的编号时（例如手工编辑过文件）
+    /// 会自动调大，保证新任务不会和已有的任务同号。
+    pub fn from_json(json: &str) -> Result<Self, TodoError> {
+        let mut file: TaskFile = serde_json::from_str(json)?;
+        if file.version > FORMAT_VERSION {
+            return Err(invalid_data(format!("不支持的任务文件版本：{}", file.version)));
+        }
+        file.tasks.sort_by_key(|task| task.id);
+        if let Some(pair) = file.tasks.windows(2).find(|pair| pair[0].id == pair[1].id) {
+            return Err(invalid_data(format!("任务文件中有重复的编号：{}", pair[0].id)));
+        }
+        let max_id = file.tasks.last().map_or(0, |task| task.id);
+        Ok(Self {
+            next_id: file.next_id.max(max_id + 1),
+            tasks: file.tasks,
+        })
+    }
+
+    /// 所有任务，按编号排列
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    pub fn get(&self, id: TaskId) -> Option<&Task> {
+        self.index(id).ok().map(|index| &self.tasks[index])
+    }
+
+    /// 添加任务，返回新任务
+    pub fn add(&mut self, title: &str, update: TaskUpdate, now: DateTime<Utc>) -> Result<&Task, TodoError> {
+        let title = check_title(title)?;
+        let mut task = Task::new(self.next_id, title, now);
+        update.apply(&mut task)?;
+        self.next_id += 1;
+        self.tasks.push(task);
+        Ok(self.tasks.last().expect("刚刚添加的任务"))
+    }
+
+    /// 把任务标记为已完成，已经完成的任务保留原来的完成时间
+    pub fn complete(&mut self, id: TaskId, now: DateTime<Utc>) -> Result<&Task, TodoError> {
+        let task = self.get_mut(id)?;
+        task.completed.get_or_insert(now);
+        Ok(task)
+    }
+
+    /// 修改任务的标题和属性，`title` 为 `None` 时保持原来的标题
+    pub fn edit(&mut self, id: TaskId, title: Option<&str>, update: TaskUpdate) -> Result<&Task, TodoError> {
+        let title = title.map(check_title).transpose()?;
+        let task = self.get_mut(id)?;
+        update.apply(task)?;
+        if let Some(title) = title {
+            task.title = title.to_string();
+        }
+        Ok(task)
+    }
+
+    /// 删除任务，返回被删除的任务
+    ///
+    /// 只要有一个编号不存在就什么都不删除。
+    pub fn remove(&mut self, ids: &[TaskId]) -> Result<Vec<Task>, TodoError> {
+        let ids: BTreeSet<TaskId> = ids.iter().copied().collect();
+        if let Some(&missing) = ids.iter().find(|&&id| self.index(id).is_err()) {
+            return Err(TodoError::NotFound(missing));
+        }
+        let (removed, kept) = self.tasks.drain(..).partition(|task| ids.contains(&task.id));
+        self.tasks = kept;
+        Ok(removed)
+    }
+
+    fn index(&self, id: TaskId) -> Result<usize, TodoError> {
+        self.tasks
+            .binary_search_by_key(&id, |task| task.id)
+            .map_err(|_| TodoError::NotFound(id))
+    }
+
+    fn get_mut(&mut self, id: TaskId) -> Result<&mut Task, TodoError> {
+        let index = self.index(id)?;
+        Ok(&mut self.tasks[index])
+    }
+}
+
+fn check_title(title: &str) -> Result<&str, TodoError> {
+    match title.trim() {
+        "" => Err(TodoError::EmptyTitle),
+        title => Ok(title),
+    }
+}
+
+fn invalid_data(message: String) -> TodoError {
+    TodoError::Io(io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("learn_rust_todo_{}_{}.json", name, std::process::id()))
+    }
+
+    fn titles(store: &TaskStore) -> Vec<String> {
+        store.tasks().iter().map(|task| task.to_string()).collect()
+    }
+
+    #[test]
+    fn test_stable_ids() {
+        let now = Utc::now();
+        let mut store = TaskStore::new();
+        assert_eq!(store.add("买牛奶", TaskUpdate::default(), now).unwrap().id, 1);
+        assert_eq!(store.add("  写周报 ", TaskUpdate::default(), now).unwrap().id, 2);
+        assert_eq!(store.add("读书", TaskUpdate::default(), now).unwrap().id, 3);
+
+        let removed = store.remove(&[3, 1]).unwrap();
+        assert_eq!(removed.len(), 2);
+        // 删除后编号不会被重新使用
+        assert_eq!(store.add("跑步", TaskUpdate::default(), now).unwrap().id, 4);
+        assert_eq!(titles(&store), ["#2 [ ] 写周报", "#4 [ ] 跑步"]);
+        assert!(store.get(1).is_none());
+    }
+
+    #[test]
+    fn test_complete_and_edit() {
+        let earlier = Utc::now() - chrono::Duration::hours(1);
+        let mut store = TaskStore::new();
+        store.add("写周报", TaskUpdate::default(), earlier).unwrap();
+        assert_eq!(store.complete(1, earlier).unwrap().completed, Some(earlier));
+        // 再次完成不改变完成时间
+        assert_eq!(store.complete(1, Utc::now()).unwrap().completed, Some(earlier));
+        assert_eq!(store.edit(1, Some("写月报"), TaskUpdate::default()).unwrap().to_string(), "#1 [x] 写月报");
+
+        assert!(matches!(store.add(" ", TaskUpdate::default(), earlier), Err(TodoError::EmptyTitle)));
+        assert!(matches!(store.edit(1, Some(""), TaskUpdate::default()), Err(TodoError::EmptyTitle)));
+        assert!(matches!(store.complete(7, earlier), Err(TodoError::NotFound(7))));
+        assert_eq!(store.edit(9, None, TaskUpdate::default()).unwrap_err().to_string(), "没有编号为 9 的任务");
+    }
+
+    #[test]
+    fn test_update() {
+        let date = |text: &str| text.parse::<NaiveDate>().unwrap();
+        let mut store = TaskStore::new();
+        let update = TaskUpdate {
+            priority: Some(Priority::High),
+            due: Some(Some(date("2026-11-01"))),
+            project: Some(Some("work".to_string())),
+            add_tags: vec!["report".to_string(), "weekly".to_string()],
+            ..TaskUpdate::default()
+        };
+        let task = store.add("写周报", update, Utc::now()).unwrap();
+        assert_eq!(task.to_string(), "#1 [ ] 写周报 priority:high project:work tag:report tag:weekly due:2026-11-01");
+
+        let update = TaskUpdate {
+            due: Some(None),
+            project: Some(None),
+            add_tags: vec!["monthly".to_string()],
+            remove_tags: vec!["weekly".to_string(), "missing".to_string()],
+            ..TaskUpdate::default()
+        };
+        let task = store.edit(1, None, update).unwrap();
+        assert_eq!(task.to_string(), "#1 [ ] 写周报 priority:high tag:monthly tag:report");
+
+        // 名称无效时任务保持不变
+        let update = TaskUpdate { add_tags: vec!["two words".to_string()], ..TaskUpdate::default() };
+        assert_eq!(
+            store.edit(1, Some("写月报"), update).unwrap_err().to_string(),
+            "无效的名称 'two words'：标签和项目不能为空，也不能包含空白"
+        );
+        assert_eq!(store.get(1).unwrap().title, "写周报");
+        let update = TaskUpdate { project: Some(Some(String::new())), ..TaskUpdate::default() };
+        assert!(matches!(store.add("读书", update, Utc::now()), Err(TodoError::InvalidName(_))));
+        assert_eq!(store.add("读书", TaskUpdate::default(), Utc::now()).unwrap().id, 2);
+    }
+
+    #[test]
+    fn test_remove_is_all_or_nothing() {
+        let mut store = TaskStore::new();
+        store.add("买牛奶", TaskUpdate::default(), Utc::now()).unwrap();
+        assert!(matches!(store.remove(&[1, 5]), Err(TodoError::NotFound(5))));
+        assert_eq!(store.tasks().len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = temp_path("roundtrip");
+        let mut store = TaskStore::new();
+        store.add("买牛奶", TaskUpdate::default(), Utc::now()).unwrap();
+        store.add("写周报", TaskUpdate::default(), Utc::now()).unwrap();
+        store.complete(2, Utc::now()).unwrap();
+        store.remove(&[1]).unwrap();
+        store.save(&path).unwrap();
+
+        let mut loaded = TaskStore::load(&path).unwrap();
+        assert_eq!(loaded, store);
+        assert_eq!(loaded.add("读书", TaskUpdate::default(), Utc::now()).unwrap().id, 3);
+
+        // 临时文件不应残留
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        assert!(!Path::new(&tmp).exists());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(TaskStore::load_or_default(&path).unwrap(), TaskStore::new());
+    }
+
+    #[test]
+    fn test_from_json() {
+        // 手工编辑过的文件：next_id 太小、任务没有按编号排列
+        // 没有优先级等可选字段
+        let json = r#"{
+            "version": 1,
+            "next_id": 2,
+            "tasks": [
+                { "id": 5, "title": "写周报", "created": "2026-10-02T09:00:00Z", "completed": "2026-10-03T18:00:00Z" },
+                { "id": 2, "title": "买牛奶", "created": "2026-10-01T09:00:00Z" }
+            ]
+        }"#;
+        let mut store = TaskStore::from_json(json).unwrap();
+        assert_eq!(titles(&store), ["#2 [ ] 买牛奶", "#5 [x] 写周报"]);
+        assert_eq!(store.add("读书", TaskUpdate::default(), Utc::now()).unwrap().id, 6);
+
+        let duplicate = r#"{ "version": 1, "next_id": 3, "tasks": [
+            { "id": 1, "title": "a", "created": "2026-10-01T09:00:00Z" },
+            { "id": 1, "title": "b", "created": "2026-10-01T09:00:00Z" }
+        ] }"#;
+        assert_eq!(TaskStore::from_json(duplicate).unwrap_err().to_string(), "任务文件中有重复的编号：1");
+        assert!(TaskStore::from_json(r#"{ "version": 9, "next_id": 1, "tasks": [] }"#).is_err());
+        assert!(TaskStore::from_json("{").is_err());
+    }
+
+    #[test]
+    fn test_additive_fields_keep_version() {
+        let mut store = TaskStore::new();
+        let update = TaskUpdate { priority: Some(Priority::High), add_tags: vec!["report".to_string()], ..TaskUpdate::default() };
+        store.add("写周报", update, Utc::now()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&store.to_json().unwrap()).unwrap();
+        assert_eq!(value["version"], 1);
+        assert_eq!(value["tasks"][0]["priority"], "high");
+    }
+}
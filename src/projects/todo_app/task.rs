@@ -0,0 +1,199 @@
+//! 一条待办事项
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// 任务编号，从 1 开始
+pub type TaskId = u64;
+
+/// 优先级，按紧急程度从低到高排列
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 4] = [Priority::Low, Priority::Medium, Priority::High, Priority::Urgent];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+            Priority::Urgent => "urgent",
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Priority::ALL
+            .into_iter()
+            .find(|priority| priority.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("未知的优先级 '{}'（可选：low, medium, high, urgent）", s))
+    }
+}
+
+/// 待办事项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    pub id: TaskId,
+    pub title: String,
+    #[serde(default)]
+    pub priority: Priority,
+    /// 截止日期
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    pub created: DateTime<Utc>,
+    /// 完成的时间，未完成时为 `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed: Option<DateTime<Utc>>,
+}
+
+impl Task {
+    pub fn new(id: TaskId, title: &str, now: DateTime<Utc>) -> Self {
+        Self {
+            id,
+            title: title.to_string(),
+            priority: Priority::default(),
+            due: None,
+            project: None,
+            tags: BTreeSet::new(),
+            created: now,
+            completed: None,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.completed.is_some()
+    }
+
+    /// 未完成且截止日期已经过去
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.is_done() && self.due.is_some_and(|due| due < today)
+    }
+}
+
+/// 如 `#3 [x] 写周报 priority:high project:work tag:report due:2026-11-01`
+///
+/// 属性用查询语法显示，默认的优先级不显示。
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mark = if self.is_done() { 'x' } else { ' ' };
+        write!(f, "#{} [{}] {}", self.id, mark, self.title)?;
+        if self.priority != Priority::default() {
+            write!(f, " priority:{}", self.priority)?;
+        }
+        if let Some(project) = &self.project {
+            write!(f, " project:{}", project)?;
+        }
+        for tag in &self.tags {
+            write!(f, " tag:{}", tag)?;
+        }
+        if let Some(due) = self.due {
+            write!(f, " due:{}", due)?;
+        }
+        Ok(())
+    }
+}
+
+/// 解析日期：`2026-11-01`、`today`、`tomorrow`、`yesterday`，或者 `+3d`、`+2w` 表示几天或几周之后
+pub fn parse_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match text {
+        "today" => return Some(today),
+        "tomorrow" => return today.checked_add_signed(Duration::days(1)),
+        "yesterday" => return today.checked_sub_signed(Duration::days(1)),
+        _ => {}
+    }
+    if let Some(offset) = text.strip_prefix('+') {
+        let (count, days) = match offset.char_indices().last()? {
+            (index, 'd') => (&offset[..index], 1),
+            (index, 'w') => (&offset[..index], 7),
+            _ => return None,
+        };
+        let count: i64 = count.parse().ok()?;
+        return today.checked_add_signed(Duration::try_days(count.checked_mul(days)?)?);
+    }
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_display() {
+        let now = Utc::now();
+        let mut task = Task::new(3, "写周报", now);
+        assert_eq!(task.to_string(), "#3 [ ] 写周报");
+        task.completed = Some(now);
+        assert!(task.is_done());
+        assert_eq!(task.to_string(), "#3 [x] 写周报");
+
+        task.priority = Priority::High;
+        task.project = Some("work".to_string());
+        task.tags = ["weekly".to_string(), "report".to_string()].into();
+        task.due = Some(date("2026-11-01"));
+        assert_eq!(task.to_string(), "#3 [x] 写周报 priority:high project:work tag:report tag:weekly due:2026-11-01");
+    }
+
+    #[test]
+    fn test_priority() {
+        assert!(Priority::Low < Priority::Medium && Priority::High < Priority::Urgent);
+        assert_eq!("HIGH".parse::<Priority>(), Ok(Priority::High));
+        assert!("p1".parse::<Priority>().is_err());
+        assert_eq!(serde_json::to_string(&Priority::Urgent).unwrap(), "\"urgent\"");
+    }
+
+    #[test]
+    fn test_overdue() {
+        let today = date("2026-10-18");
+        let mut task = Task::new(1, "交房租", Utc::now());
+        assert!(!task.is_overdue(today));
+        task.due = Some(date("2026-10-18"));
+        assert!(!task.is_overdue(today));
+        task.due = Some(date("2026-10-17"));
+        assert!(task.is_overdue(today));
+        task.completed = Some(Utc::now());
+        assert!(!task.is_overdue(today));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let today = date("2026-10-18");
+        assert_eq!(parse_date("2026-11-01", today), Some(date("2026-11-01")));
+        assert_eq!(parse_date("today", today), Some(today));
+        assert_eq!(parse_date("tomorrow", today), Some(date("2026-10-19")));
+        assert_eq!(parse_date("yesterday", today), Some(date("2026-10-17")));
+        assert_eq!(parse_date("+3d", today), Some(date("2026-10-21")));
+        assert_eq!(parse_date("+2w", today), Some(date("2026-11-01")));
+        assert_eq!(parse_date("+d", today), None);
+        assert_eq!(parse_date("+99999999999999w", today), None);
+        assert_eq!(parse_date("2026-13-01", today), None);
+        assert_eq!(parse_date("下周", today), None);
+    }
+}
@@ -6,28 +6,24 @@
 //! - Result 枚举
 //! - 枚举方法
 
-fn main() {
-    println!("=== Rust 枚举学习 ===");
-    
-    // 1. 基本枚举
-    println!("\n1. 基本枚举：");
-    basic_enum_demo();
-    
-    // 2. 带数据的枚举
-    println!("\n2. 带数据的枚举：");
-    enum_with_data_demo();
-    
-    // 3. Option 枚举
-    println!("\n3. Option 枚举：");
-    option_enum_demo();
-    
-    // 4. Result 枚举
-    println!("\n4. Result 枚举：");
-    result_enum_demo();
-    
-    // 5. 枚举方法
-    println!("\n5. 枚举方法：");
-    enum_methods_demo();
+use std::io::{self, Write};
+
+use learn_rust::lesson::Demo;
+
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("basic_enums", "基本枚举", basic_enum_demo),
+    ("enums_with_data", "带数据的枚举", enum_with_data_demo),
+    ("option", "Option 枚举", option_enum_demo),
+    ("result", "Result 枚举", result_enum_demo),
+    ("enum_methods", "枚举方法", enum_methods_demo),
+];
+
+/// 本课的标题
+pub const TITLE: &str = "Rust 枚举学习";
+
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
 }
 
 // 基本枚举
@@ -64,15 +60,15 @@ enum Status {
     Cancelled,
 }
 
-fn basic_enum_demo() {
+fn basic_enum_demo(out: &mut dyn Write) -> io::Result<()> {
     let north = Direction::North;
     let south = Direction::South;
     
-    println!("方向: {north:?}, {south:?}");
+    writeln!(out, "方向: {north:?}, {south:?}")?;
     
     // 使用枚举
-    move_in_direction(Direction::East);
-    move_in_direction(Direction::West);
+    move_in_direction(out, Direction::East)?;
+    move_in_direction(out, Direction::West)?;
     
     // 枚举在 match 中的使用
     let directions = [
@@ -89,160 +85,170 @@ fn basic_enum_demo() {
             Direction::East => "向东",
             Direction::West => "向西",
         };
-        println!("移动方向: {description}");
+        writeln!(out, "移动方向: {description}")?;
     }
+    Ok(())
 }
 
-fn enum_with_data_demo() {
+fn enum_with_data_demo(out: &mut dyn Write) -> io::Result<()> {
     let m1 = Message::Quit;
     let m2 = Message::Move { x: 10, y: 20 };
     let m3 = Message::Write(String::from("Hello"));
     let m4 = Message::ChangeColor(255, 0, 0);
     
-    println!("消息列表:");
-    process_message(m1);
-    process_message(m2);
-    process_message(m3);
-    process_message(m4);
+    writeln!(out, "消息列表:")?;
+    process_message(out, m1)?;
+    process_message(out, m2)?;
+    process_message(out, m3)?;
+    process_message(out, m4)?;
     
     // IP 地址示例
     let ipv4 = IpAddr::V4(127, 0, 0, 1);
     let ipv6 = IpAddr::V6(String::from("::1"));
     
-    println!("\nIP 地址:");
-    print_ip_address(ipv4);
-    print_ip_address(ipv6);
+    writeln!(out, "\nIP 地址:")?;
+    print_ip_address(out, ipv4)?;
+    print_ip_address(out, ipv6)?;
     
     // 状态示例
     let current_status = Status::Active;
-    println!("\n当前状态: {current_status:?}");
+    writeln!(out, "\n当前状态: {current_status:?}")?;
     
     if current_status == Status::Active {
-        println!("系统正在运行");
+        writeln!(out, "系统正在运行")?;
     }
+    Ok(())
 }
 
-fn option_enum_demo() {
+#[allow(clippy::unnecessary_literal_unwrap)]
+fn option_enum_demo(out: &mut dyn Write) -> io::Result<()> {
     // Option 是 Rust 标准库中的枚举
     let some_number = Some(5);
-    let some_string = Some(String::from("hello"));
+    let _some_string = Some(String::from("hello"));
     let no_number: Option<i32> = None;
     
-    println!("Option 示例:");
-    print_option_number(some_number);
-    print_option_number(no_number);
+    writeln!(out, "Option 示例:")?;
+    print_option_number(out, some_number)?;
+    print_option_number(out, no_number)?;
     
     // Option 的实际使用
     let numbers = vec![1, 2, 3, 4, 5];
     
     match find_number(&numbers, 3) {
-        Some(index) => println!("找到数字 3 在索引 {index}"),
-        None => println!("未找到数字 3"),
+        Some(index) => writeln!(out, "找到数字 3 在索引 {index}")?,
+        None => writeln!(out, "未找到数字 3")?,
     }
     
     match find_number(&numbers, 10) {
-        Some(index) => println!("找到数字 10 在索引 {index}"),
-        None => println!("未找到数字 10"),
+        Some(index) => writeln!(out, "找到数字 10 在索引 {index}")?,
+        None => writeln!(out, "未找到数字 10")?,
     }
     
     // 使用 if let
     let config_max = Some(3u8);
     if let Some(max) = config_max {
-        println!("最大值配置为: {max}");
+        writeln!(out, "最大值配置为: {max}")?;
     }
     
     // 使用 unwrap_or
     let default_value = no_number.unwrap_or(0);
-    println!("带默认值: {default_value}");
+    writeln!(out, "带默认值: {default_value}")?;
+    Ok(())
 }
 
-fn result_enum_demo() {
+fn result_enum_demo(out: &mut dyn Write) -> io::Result<()> {
     // Result 用于可能失败的操作
     let good_result = divide(10.0, 2.0);
     let bad_result = divide(10.0, 0.0);
     
     match good_result {
-        Ok(value) => println!("除法结果: {value}"),
-        Err(error) => println!("除法错误: {error}"),
+        Ok(value) => writeln!(out, "除法结果: {value}")?,
+        Err(error) => writeln!(out, "除法错误: {error}")?,
     }
     
     match bad_result {
-        Ok(value) => println!("除法结果: {value}"),
-        Err(error) => println!("除法错误: {error}"),
+        Ok(value) => writeln!(out, "除法结果: {value}")?,
+        Err(error) => writeln!(out, "除法错误: {error}")?,
     }
     
     // 使用 ? 操作符
     match parse_and_double("10") {
-        Ok(result) => println!("解析并翻倍: {result}"),
-        Err(e) => println!("解析错误: {e}"),
+        Ok(result) => writeln!(out, "解析并翻倍: {result}")?,
+        Err(e) => writeln!(out, "解析错误: {e}")?,
     }
     
     match parse_and_double("abc") {
-        Ok(result) => println!("解析并翻倍: {result}"),
-        Err(e) => println!("解析错误: {e}"),
+        Ok(result) => writeln!(out, "解析并翻倍: {result}")?,
+        Err(e) => writeln!(out, "解析错误: {e}")?,
     }
     
     // 多种错误类型
     let file_result = read_file_size("example.txt");
     match file_result {
-        Ok(size) => println!("文件大小: {size} 字节"),
-        Err(error) => println!("读取文件错误: {error:?}"),
+        Ok(size) => writeln!(out, "文件大小: {size} 字节")?,
+        Err(error) => writeln!(out, "读取文件错误: {error:?}")?,
     }
+    Ok(())
 }
 
-fn enum_methods_demo() {
+fn enum_methods_demo(out: &mut dyn Write) -> io::Result<()> {
     let message = Message::Write(String::from("Hello, Rust!"));
     
     // 调用枚举的方法
-    message.process();
+    message.process(out)?;
     
     let length = message.length();
-    println!("消息长度: {length}");
+    writeln!(out, "消息长度: {length}")?;
     
     // 状态转换
     let mut status = Status::Pending;
-    println!("初始状态: {status:?}");
+    writeln!(out, "初始状态: {status:?}")?;
     
     status = status.next();
-    println!("下一个状态: {status:?}");
+    writeln!(out, "下一个状态: {status:?}")?;
     
     // 枚举的实用方法
     let direction = Direction::North;
-    println!("方向的相对方向: {:?}", direction.opposite());
-    println!("方向是否为水平: {}", direction.is_horizontal());
+    writeln!(out, "方向的相对方向: {:?}", direction.opposite())?;
+    writeln!(out, "方向是否为水平: {}", direction.is_horizontal())?;
+    Ok(())
 }
 
 // 辅助函数
-fn move_in_direction(direction: Direction) {
+fn move_in_direction(out: &mut dyn Write, direction: Direction) -> io::Result<()> {
     match direction {
-        Direction::North => println!("向北移动"),
-        Direction::South => println!("向南移动"),
-        Direction::East => println!("向东移动"),
-        Direction::West => println!("向西移动"),
+        Direction::North => writeln!(out, "向北移动")?,
+        Direction::South => writeln!(out, "向南移动")?,
+        Direction::East => writeln!(out, "向东移动")?,
+        Direction::West => writeln!(out, "向西移动")?,
     }
+    Ok(())
 }
 
-fn process_message(message: Message) {
+fn process_message(out: &mut dyn Write, message: Message) -> io::Result<()> {
     match message {
-        Message::Quit => println!("  退出消息"),
-        Message::Move { x, y } => println!("  移动到坐标 ({x}, {y})"),
-        Message::Write(text) => println!("  写入文本: {text}"),
-        Message::ChangeColor(r, g, b) => println!("  改变颜色为 RGB({r}, {g}, {b})"),
+        Message::Quit => writeln!(out, "  退出消息")?,
+        Message::Move { x, y } => writeln!(out, "  移动到坐标 ({x}, {y})")?,
+        Message::Write(text) => writeln!(out, "  写入文本: {text}")?,
+        Message::ChangeColor(r, g, b) => writeln!(out, "  改变颜色为 RGB({r}, {g}, {b})")?,
     }
+    Ok(())
 }
 
-fn print_ip_address(ip: IpAddr) {
+fn print_ip_address(out: &mut dyn Write, ip: IpAddr) -> io::Result<()> {
     match ip {
-        IpAddr::V4(a, b, c, d) => println!("  IPv4: {a}.{b}.{c}.{d}"),
-        IpAddr::V6(addr) => println!("  IPv6: {addr}"),
+        IpAddr::V4(a, b, c, d) => writeln!(out, "  IPv4: {a}.{b}.{c}.{d}")?,
+        IpAddr::V6(addr) => writeln!(out, "  IPv6: {addr}")?,
     }
+    Ok(())
 }
 
-fn print_option_number(option: Option<i32>) {
+fn print_option_number(out: &mut dyn Write, option: Option<i32>) -> io::Result<()> {
     match option {
-        Some(num) => println!("  数字: {num}"),
-        None => println!("  没有数字"),
+        Some(num) => writeln!(out, "  数字: {num}")?,
+        None => writeln!(out, "  没有数字")?,
     }
+    Ok(())
 }
 
 fn find_number(numbers: &[i32], target: i32) -> Option<usize> {
@@ -268,6 +274,7 @@ fn parse_and_double(s: &str) -> Result<i32, std::num::ParseIntError> {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)] // 并非每个变体都在演示中用到
 enum FileError {
     NotFound,
     PermissionDenied,
@@ -281,13 +288,14 @@ fn read_file_size(_filename: &str) -> Result<u64, FileError> {
 
 // 为 Message 实现方法
 impl Message {
-    fn process(&self) {
+    fn process(&self, out: &mut dyn Write) -> io::Result<()> {
         match self {
-            Message::Quit => println!("  处理退出请求"),
-            Message::Move { x, y } => println!("  处理移动到 ({x}, {y})"),
-            Message::Write(text) => println!("  处理写入: {text}"),
-            Message::ChangeColor(r, g, b) => println!("  处理颜色变更: RGB({r}, {g}, {b})"),
+            Message::Quit => writeln!(out, "  处理退出请求")?,
+            Message::Move { x, y } => writeln!(out, "  处理移动到 ({x}, {y})")?,
+            Message::Write(text) => writeln!(out, "  处理写入: {text}")?,
+            Message::ChangeColor(r, g, b) => writeln!(out, "  处理颜色变更: RGB({r}, {g}, {b})")?,
         }
+        Ok(())
     }
     
     fn length(&self) -> usize {
@@ -315,6 +323,7 @@ impl Direction {
         matches!(self, Direction::East | Direction::West)
     }
     
+    #[allow(dead_code)] // 方法示例，演示中没有调用
     fn is_vertical(&self) -> bool {
         matches!(self, Direction::North | Direction::South)
     }
@@ -331,6 +340,7 @@ impl Status {
         }
     }
     
+    #[allow(dead_code)] // 只在测试中使用
     fn is_active(&self) -> bool {
         *self == Status::Active
     }
@@ -403,6 +413,7 @@ mod tests {
     }
     
     #[test]
+    #[allow(clippy::unnecessary_literal_unwrap)]
     fn test_option_operations() {
         let some_value = Some(5);
         let no_value: Option<i32> = None;
@@ -7,35 +7,29 @@
 //! - 模式的类型和语法
 //! - 模式匹配的实际应用
 
-fn main() {
-    println!("=== Rust 模式匹配学习 ===");
+use std::io::{self, Write};
 
-    // 1. 基本模式匹配
-    println!("\n1. 基本模式匹配：");
-    basic_pattern_matching();
+use learn_rust::lesson::Demo;
 
-    // 2. 模式的类型
-    println!("\n2. 模式的类型：");
-    pattern_types_demo();
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("basic_matching", "基本模式匹配", basic_pattern_matching),
+    ("pattern_types", "模式的类型", pattern_types_demo),
+    ("if_let", "if let 语法", if_let_demo),
+    ("while_let", "while let 语法", while_let_demo),
+    ("function_parameters", "函数参数中的模式", function_parameters_demo),
+    ("complex_patterns", "复杂模式匹配", complex_patterns_demo),
+];
 
-    // 3. if let 语法
-    println!("\n3. if let 语法：");
-    if_let_demo();
+/// 本课的标题
+pub const TITLE: &str = "Rust 模式匹配学习";
 
-    // 4. while let 语法
-    println!("\n4. while let 语法：");
-    while_let_demo();
-
-    // 5. 函数参数中的模式
-    println!("\n5. 函数参数中的模式：");
-    function_parameters_demo();
-
-    // 6. 复杂模式匹配
-    println!("\n6. 复杂模式匹配：");
-    complex_patterns_demo();
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
 }
 
 #[derive(Debug)]
+#[allow(dead_code)] // 并非每个变体都在演示中用到
 enum Coin {
     Penny,
     Nickel,
@@ -44,6 +38,7 @@ enum Coin {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum UsState {
     Alabama,
     Alaska,
@@ -59,12 +54,14 @@ struct Point {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Color {
     Rgb(i32, i32, i32),
     Hsv(i32, i32, i32),
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Message {
     Quit,
     Move { x: i32, y: i32 },
@@ -72,15 +69,15 @@ enum Message {
     ChangeColor(Color),
 }
 
-fn basic_pattern_matching() {
+fn basic_pattern_matching(out: &mut dyn Write) -> io::Result<()> {
     // 基本值的匹配
     let x = 1;
 
     match x {
-        1 => println!("一"),
-        2 => println!("二"),
-        3 => println!("三"),
-        _ => println!("其他"),
+        1 => writeln!(out, "一")?,
+        2 => writeln!(out, "二")?,
+        3 => writeln!(out, "三")?,
+        _ => writeln!(out, "其他")?,
     }
 
     // 硬币价值计算
@@ -88,74 +85,77 @@ fn basic_pattern_matching() {
     let nickel = Coin::Nickel;
     let quarter = Coin::Quarter(UsState::California);
 
-    println!("硬币价值:");
-    println!("  Penny: {} 美分", value_in_cents(penny));
-    println!("  Nickel: {} 美分", value_in_cents(nickel));
-    println!("  Quarter: {} 美分", value_in_cents(quarter));
+    writeln!(out, "硬币价值:")?;
+    for (name, coin) in [("Penny", penny), ("Nickel", nickel), ("Quarter", quarter)] {
+        let cents = value_in_cents(out, coin)?;
+        writeln!(out, "  {}: {} 美分", name, cents)?;
+    }
 
     // Option 的匹配
     let five = Some(5);
     let six = plus_one(five);
     let none = plus_one(None);
 
-    println!("Option 匹配:");
-    println!("  Some(5) + 1 = {six:?}");
-    println!("  None + 1 = {none:?}");
+    writeln!(out, "Option 匹配:")?;
+    writeln!(out, "  Some(5) + 1 = {six:?}")?;
+    writeln!(out, "  None + 1 = {none:?}")?;
+    Ok(())
 }
 
-fn pattern_types_demo() {
+fn pattern_types_demo(out: &mut dyn Write) -> io::Result<()> {
     // 字面量匹配
     let x = 1;
     match x {
-        1 | 2 => println!("一或二"),
-        3 => println!("三"),
-        _ => println!("其他"),
+        1 | 2 => writeln!(out, "一或二")?,
+        3 => writeln!(out, "三")?,
+        _ => writeln!(out, "其他")?,
     }
 
     // 范围匹配
     let y = 5;
     match y {
-        1..=5 => println!("一到五"),
-        6..=10 => println!("六到十"),
-        _ => println!("其他范围"),
+        1..=5 => writeln!(out, "一到五")?,
+        6..=10 => writeln!(out, "六到十")?,
+        _ => writeln!(out, "其他范围")?,
     }
 
     // 字符范围匹配
     let c = 'c';
     match c {
-        'a'..='j' => println!("前十个字母"),
-        'k'..='z' => println!("后面的字母"),
-        _ => println!("其他字符"),
+        'a'..='j' => writeln!(out, "前十个字母")?,
+        'k'..='z' => writeln!(out, "后面的字母")?,
+        _ => writeln!(out, "其他字符")?,
     }
 
     // 解构结构体
     let p = Point { x: 0, y: 7 };
     match p {
-        Point { x, y: 0 } => println!("在 x 轴上，x = {x}"),
-        Point { x: 0, y } => println!("在 y 轴上，y = {y}"),
-        Point { x, y } => println!("不在轴上: ({x}, {y})"),
+        Point { x, y: 0 } => writeln!(out, "在 x 轴上，x = {x}")?,
+        Point { x: 0, y } => writeln!(out, "在 y 轴上，y = {y}")?,
+        Point { x, y } => writeln!(out, "不在轴上: ({x}, {y})")?,
     }
 
     // 解构枚举
     let msg = Message::ChangeColor(Color::Hsv(0, 160, 255));
     match msg {
-        Message::Quit => println!("退出"),
-        Message::Move { x, y } => println!("移动到 ({x}, {y})"),
-        Message::Write(text) => println!("写入: {text}"),
+        Message::Quit => writeln!(out, "退出")?,
+        Message::Move { x, y } => writeln!(out, "移动到 ({x}, {y})")?,
+        Message::Write(text) => writeln!(out, "写入: {text}")?,
         Message::ChangeColor(Color::Rgb(r, g, b)) => {
-            println!("改变颜色为 RGB({r}, {g}, {b})")
+            writeln!(out, "改变颜色为 RGB({r}, {g}, {b})")?
         }
         Message::ChangeColor(Color::Hsv(h, s, v)) => {
-            println!("改变颜色为 HSV({h}, {s}, {v})")
+            writeln!(out, "改变颜色为 HSV({h}, {s}, {v})")?
         }
     }
+    Ok(())
 }
 
-fn if_let_demo() {
+fn if_let_demo(out: &mut dyn Write) -> io::Result<()> {
     // 简单的 if let
     let config_max = Some(3u8);
     if let Some(max) = config_max {
-        println!("最大值配置为: {max}");
+        writeln!(out, "最大值配置为: {max}")?;
     }
 
     // if let 与 else
@@ -164,17 +164,17 @@ fn if_let_demo() {
     let age: Result<u8, _> = "34".parse();
 
     if let Some(color) = favorite_color {
-        println!("使用你喜欢的颜色 {color} 作为背景");
+        writeln!(out, "使用你喜欢的颜色 {color} 作为背景")?;
     } else if is_tuesday {
-        println!("周二是绿色的日子！");
+        writeln!(out, "周二是绿色的日子！")?;
     } else if let Ok(age) = age {
         if age > 30 {
-            println!("使用紫色作为背景颜色");
+            writeln!(out, "使用紫色作为背景颜色")?;
         } else {
-            println!("使用橙色作为背景颜色");
+            writeln!(out, "使用橙色作为背景颜色")?;
         }
     } else {
-        println!("使用蓝色作为背景颜色");
+        writeln!(out, "使用蓝色作为背景颜色")?;
     }
 
     // 处理枚举变体
@@ -182,55 +182,58 @@ fn if_let_demo() {
     let mut count = 0;
 
     if let Coin::Quarter(state) = coin {
-        println!("来自 {state:?} 州的25美分硬币！");
+        writeln!(out, "来自 {state:?} 州的25美分硬币！")?;
     } else {
         count += 1;
     }
 
-    println!("非25美分硬币计数: {count}");
+    writeln!(out, "非25美分硬币计数: {count}")?;
+    Ok(())
 }
 
-fn while_let_demo() {
+#[allow(clippy::useless_vec, clippy::while_let_on_iterator)] // 演示 while let
+fn while_let_demo(out: &mut dyn Write) -> io::Result<()> {
     // while let 与 Vec
     let mut stack = Vec::new();
     stack.push(1);
     stack.push(2);
     stack.push(3);
 
-    println!("弹出栈元素:");
+    writeln!(out, "弹出栈元素:")?;
     while let Some(top) = stack.pop() {
-        println!("  弹出: {top}");
+        writeln!(out, "  弹出: {top}")?;
     }
 
     // while let 与迭代器
     let v = vec!['a', 'b', 'c'];
     let mut iter = v.iter();
 
-    println!("迭代器元素:");
+    writeln!(out, "迭代器元素:")?;
     while let Some(value) = iter.next() {
-        println!("  值: {value}");
+        writeln!(out, "  值: {value}")?;
     }
 
     // while let 与 Result
     let operations = vec!["42", "not_a_number", "17", "invalid"];
 
-    println!("解析操作:");
+    writeln!(out, "解析操作:")?;
     for op in operations {
         match op.parse::<i32>() {
-            Ok(num) => println!("  成功解析: {num}"),
-            Err(_) => println!("  解析失败: {op}"),
+            Ok(num) => writeln!(out, "  成功解析: {num}")?,
+            Err(_) => writeln!(out, "  解析失败: {op}")?,
         }
     }
+    Ok(())
 }
 
-fn function_parameters_demo() {
+fn function_parameters_demo(out: &mut dyn Write) -> io::Result<()> {
     // 元组参数解构
     let point = (3, 5);
-    print_coordinates(point);
+    print_coordinates(out, point)?;
 
     // 结构体参数解构
     let origin = Point { x: 0, y: 0 };
-    print_point(&origin);
+    print_point(out, &origin)?;
 
     // 引用和模式
     let points = vec![
@@ -239,19 +242,21 @@ fn function_parameters_demo() {
         Point { x: 10, y: -3 },
     ];
 
-    println!("点的信息:");
+    writeln!(out, "点的信息:")?;
     for point in &points {
-        analyze_point(point);
+        analyze_point(out, point)?;
     }
+    Ok(())
 }
 
-fn complex_patterns_demo() {
+#[allow(clippy::match_single_binding)] // 演示在 match 中解构
+fn complex_patterns_demo(out: &mut dyn Write) -> io::Result<()> {
     // 守卫条件
     let num = Some(4);
 
     match num {
-        Some(x) if x < 5 => println!("小于五: {x}"),
-        Some(x) => println!("大于等于五: {x}"),
+        Some(x) if x < 5 => writeln!(out, "小于五: {x}")?,
+        Some(x) => writeln!(out, "大于等于五: {x}")?,
         None => (),
     }
 
@@ -263,10 +268,10 @@ fn complex_patterns_demo() {
             x: x_val @ 1..=5,
             y: y_val @ 1..=5,
         } => {
-            println!("在范围内移动到 ({x_val}, {y_val})");
+            writeln!(out, "在范围内移动到 ({x_val}, {y_val})")?;
         }
         Message::Move { x, y } => {
-            println!("移动到 ({x}, {y})");
+            writeln!(out, "移动到 ({x}, {y})")?;
         }
         _ => {}
     }
@@ -276,7 +281,7 @@ fn complex_patterns_demo() {
 
     match numbers {
         (first, _, third, _, fifth) => {
-            println!("一些数字: {first}, {third}, {fifth}");
+            writeln!(out, "一些数字: {first}, {third}, {fifth}")?;
         }
     }
 
@@ -284,27 +289,30 @@ fn complex_patterns_demo() {
     let origin = Point { x: 0, y: 0 };
 
     match origin {
-        Point { x, .. } => println!("x 坐标是 {x}"),
+        Point { x, .. } => writeln!(out, "x 坐标是 {x}")?,
     }
 
     // 嵌套的解构和匹配
     let ((feet, inches), Point { x, y }) = ((3, 10), Point { x: 3, y: -10 });
-    println!("脚: {feet}, 英寸: {inches}, 点: ({x}, {y})");
+    writeln!(out, "脚: {feet}, 英寸: {inches}, 点: ({x}, {y})")?;
+    Ok(())
 }
 
 // 辅助函数
-fn value_in_cents(coin: Coin) -> u8 {
-    match coin {
+fn value_in_cents(out: &mut dyn Write, coin: Coin) -> io::Result<u8> {
+    let cents = match coin {
         Coin::Penny => 1,
         Coin::Nickel => 5,
         Coin::Dime => 10,
         Coin::Quarter(state) => {
-            println!("来自 {state:?} 州的25美分硬币!");
+            writeln!(out, "来自 {state:?} 州的25美分硬币!")?;
             25
         }
-    }
+    };
+    Ok(cents)
 }
 
+#[allow(clippy::manual_map)] // 演示用 match 处理 Option
 fn plus_one(x: Option<i32>) -> Option<i32> {
     match x {
         None => None,
@@ -312,26 +320,30 @@ fn plus_one(x: Option<i32>) -> Option<i32> {
     }
 }
 
-fn print_coordinates((x, y): (i32, i32)) {
-    println!("当前位置: ({x}, {y})");
+fn print_coordinates(out: &mut dyn Write, (x, y): (i32, i32)) -> io::Result<()> {
+    writeln!(out, "当前位置: ({x}, {y})")?;
+    Ok(())
 }
 
-fn print_point(&Point { x, y }: &Point) {
-    println!("点的坐标: ({x}, {y})");
+fn print_point(out: &mut dyn Write, &Point { x, y }: &Point) -> io::Result<()> {
+    writeln!(out, "点的坐标: ({x}, {y})")?;
+    Ok(())
 }
 
-fn analyze_point(point: &Point) {
+fn analyze_point(out: &mut dyn Write, point: &Point) -> io::Result<()> {
     match point {
-        Point { x: 0, y: 0 } => println!("  原点"),
-        Point { x: 0, y } => println!("  在 y 轴上，y = {y}"),
-        Point { x, y: 0 } => println!("  在 x 轴上，x = {x}"),
-        Point { x, y } if x == y => println!("  在对角线上 ({x}, {y})"),
-        Point { x, y } => println!("  任意点 ({x}, {y})"),
+        Point { x: 0, y: 0 } => writeln!(out, "  原点")?,
+        Point { x: 0, y } => writeln!(out, "  在 y 轴上，y = {y}")?,
+        Point { x, y: 0 } => writeln!(out, "  在 x 轴上，x = {x}")?,
+        Point { x, y } if x == y => writeln!(out, "  在对角线上 ({x}, {y})")?,
+        Point { x, y } => writeln!(out, "  任意点 ({x}, {y})")?,
     }
+    Ok(())
 }
 
 // 实际应用示例：状态机
 #[derive(Debug, PartialEq)]
+#[allow(dead_code)] // 只在测试中使用
 enum State {
     Idle,
     Processing { progress: u8 },
@@ -339,32 +351,34 @@ enum State {
     Error(String),
 }
 
-fn process_state(state: State) -> State {
-    match state {
+#[allow(dead_code)] // 只在测试中使用
+fn process_state(out: &mut dyn Write, state: State) -> io::Result<State> {
+    let next = match state {
         State::Idle => {
-            println!("开始处理...");
+            writeln!(out, "开始处理...")?;
             State::Processing { progress: 0 }
         }
         State::Processing { progress } if progress < 100 => {
-            println!("处理中... {progress}%");
+            writeln!(out, "处理中... {progress}%")?;
             State::Processing {
                 progress: progress + 10,
             }
         }
         State::Processing { progress: 100 } => {
-            println!("处理完成!");
+            writeln!(out, "处理完成!")?;
             State::Complete
         }
         State::Processing { .. } => State::Error("进度超出范围".to_string()),
         State::Complete => {
-            println!("已经完成");
+            writeln!(out, "已经完成")?;
             State::Complete
         }
         State::Error(msg) => {
-            println!("错误状态: {msg}");
+            writeln!(out, "错误状态: {msg}")?;
             State::Idle
         }
-    }
+    };
+    Ok(next)
 }
 
 #[cfg(test)]
@@ -373,10 +387,10 @@ mod tests {
 
     #[test]
     fn test_value_in_cents() {
-        assert_eq!(value_in_cents(Coin::Penny), 1);
-        assert_eq!(value_in_cents(Coin::Nickel), 5);
-        assert_eq!(value_in_cents(Coin::Dime), 10);
-        assert_eq!(value_in_cents(Coin::Quarter(UsState::California)), 25);
+        assert_eq!(value_in_cents(&mut io::sink(), Coin::Penny).unwrap(), 1);
+        assert_eq!(value_in_cents(&mut io::sink(), Coin::Nickel).unwrap(), 5);
+        assert_eq!(value_in_cents(&mut io::sink(), Coin::Dime).unwrap(), 10);
+        assert_eq!(value_in_cents(&mut io::sink(), Coin::Quarter(UsState::California)).unwrap(), 25);
     }
 
     #[test]
@@ -424,15 +438,13 @@ mod tests {
     fn test_state_machine() {
         let mut state = State::Idle;
 
-        state = process_state(state);
+        state = process_state(&mut io::sink(), state).unwrap();
         assert_eq!(state, State::Processing { progress: 0 });
 
         // 模拟处理过程
-        while let State::Processing { progress } = state {
-            state = process_state(state);
-            if progress >= 90 {
-                break;
-            }
+        // 检查处理后的新状态：进度到 100 之后的下一步才是 Complete
+        while let State::Processing { .. } = state {
+            state = process_state(&mut io::sink(), state).unwrap();
         }
 
         assert_eq!(state, State::Complete);
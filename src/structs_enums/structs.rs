@@ -6,36 +6,30 @@
 //! - 结构体的所有权
 //! - 元组结构体和单元结构体
 
-fn main() {
-    println!("=== Rust 结构体学习 ===");
-    
-    // 1. 基本结构体
-    println!("\n1. 基本结构体：");
-    basic_struct_demo();
-    
-    // 2. 方法语法
-    println!("\n2. 方法语法：");
-    method_syntax_demo();
-    
-    // 3. 关联函数
-    println!("\n3. 关联函数：");
-    associated_functions_demo();
-    
-    // 4. 元组结构体
-    println!("\n4. 元组结构体：");
-    tuple_struct_demo();
-    
-    // 5. 单元结构体
-    println!("\n5. 单元结构体：");
-    unit_struct_demo();
-    
-    // 6. 结构体的所有权
-    println!("\n6. 结构体的所有权：");
-    struct_ownership_demo();
+use std::io::{self, Write};
+
+use learn_rust::lesson::Demo;
+
+/// 本课的各个小节：(名称, 标题, 演示函数)
+pub const SECTIONS: &[(&str, &str, Demo)] = &[
+    ("basic_structs", "基本结构体", basic_struct_demo),
+    ("method_syntax", "方法语法", method_syntax_demo),
+    ("associated_functions", "关联函数", associated_functions_demo),
+    ("tuple_structs", "元组结构体", tuple_struct_demo),
+    ("unit_structs", "单元结构体", unit_struct_demo),
+    ("struct_ownership", "结构体的所有权", struct_ownership_demo),
+];
+
+/// 本课的标题
+pub const TITLE: &str = "Rust 结构体学习";
+
+pub fn main() {
+    learn_rust::lesson::run_main(TITLE, SECTIONS);
 }
 
 // 基本结构体定义
 #[derive(Debug)]
+#[allow(dead_code)] // 部分字段只通过 Debug 输出
 struct User {
     active: bool,
     username: String,
@@ -52,12 +46,13 @@ struct Rectangle {
 
 // 带生命周期的结构体
 #[derive(Debug)]
+#[allow(dead_code)]
 struct Person<'a> {
     name: &'a str,
     age: u32,
 }
 
-fn basic_struct_demo() {
+fn basic_struct_demo(out: &mut dyn Write) -> io::Result<()> {
     // 创建结构体实例
     let user1 = User {
         active: true,
@@ -66,9 +61,9 @@ fn basic_struct_demo() {
         sign_in_count: 1,
     };
     
-    println!("用户信息: {user1:?}");
-    println!("用户名: {}", user1.username);
-    println!("邮箱: {}", user1.email);
+    writeln!(out, "用户信息: {user1:?}")?;
+    writeln!(out, "用户名: {}", user1.username)?;
+    writeln!(out, "邮箱: {}", user1.email)?;
     
     // 创建可变实例
     let mut user2 = User {
@@ -80,34 +75,35 @@ fn basic_struct_demo() {
     
     // 修改字段
     user2.email = String::from("newemail@example.com");
-    println!("修改后的邮箱: {}", user2.email);
+    writeln!(out, "修改后的邮箱: {}", user2.email)?;
     
     // 使用函数创建实例
     let user3 = build_user(
         String::from("user@test.com"), 
         String::from("testuser")
     );
-    println!("通过函数创建的用户: {user3:?}");
+    writeln!(out, "通过函数创建的用户: {user3:?}")?;
     
     // 结构体更新语法
     let user4 = User {
         email: String::from("updated@example.com"),
         ..user3  // 使用 user3 的其他字段值
     };
-    println!("使用更新语法的用户: {user4:?}");
+    writeln!(out, "使用更新语法的用户: {user4:?}")?;
     // 注意：user3 的 username 所有权被移动到 user4
+    Ok(())
 }
 
-fn method_syntax_demo() {
+fn method_syntax_demo(out: &mut dyn Write) -> io::Result<()> {
     let rect1 = Rectangle {
         width: 30,
         height: 50,
     };
     
-    println!("矩形: {rect1:?}");
-    println!("矩形面积: {}", rect1.area());
-    println!("矩形周长: {}", rect1.perimeter());
-    println!("是否为正方形: {}", rect1.is_square());
+    writeln!(out, "矩形: {rect1:?}")?;
+    writeln!(out, "矩形面积: {}", rect1.area())?;
+    writeln!(out, "矩形周长: {}", rect1.perimeter())?;
+    writeln!(out, "是否为正方形: {}", rect1.is_square())?;
     
     // 创建另一个矩形
     let rect2 = Rectangle {
@@ -115,65 +111,70 @@ fn method_syntax_demo() {
         height: 40,
     };
     
-    println!("rect1 能容纳 rect2 吗？{}", rect1.can_hold(&rect2));
+    writeln!(out, "rect1 能容纳 rect2 吗？{}", rect1.can_hold(&rect2))?;
     
     // 链式方法调用
     let rect3 = Rectangle { width: 20, height: 20 };
     let scaled = rect3.scale(2);
-    println!("缩放后的矩形: {scaled:?}");
+    writeln!(out, "缩放后的矩形: {scaled:?}")?;
+    Ok(())
 }
 
-fn associated_functions_demo() {
+fn associated_functions_demo(out: &mut dyn Write) -> io::Result<()> {
     // 使用关联函数创建实例
     let square = Rectangle::square(25);
-    println!("正方形: {square:?}");
-    println!("正方形面积: {}", square.area());
+    writeln!(out, "正方形: {square:?}")?;
+    writeln!(out, "正方形面积: {}", square.area())?;
     
     // 创建不同大小的矩形
     let rect = Rectangle::new(15, 25);
-    println!("新矩形: {rect:?}");
+    writeln!(out, "新矩形: {rect:?}")?;
     
     // 创建用户
     let admin = User::new_admin("admin", "admin@example.com");
-    println!("管理员用户: {admin:?}");
+    writeln!(out, "管理员用户: {admin:?}")?;
+    Ok(())
 }
 
-fn tuple_struct_demo() {
+fn tuple_struct_demo(out: &mut dyn Write) -> io::Result<()> {
     // 定义元组结构体
     #[derive(Debug)]
     struct Color(i32, i32, i32);
     
     #[derive(Debug)]
+    #[allow(dead_code)]
     struct Point(i32, i32, i32);
     
     let black = Color(0, 0, 0);
     let origin = Point(0, 0, 0);
     
-    println!("黑色: {black:?}");
-    println!("原点: {origin:?}");
+    writeln!(out, "黑色: {black:?}")?;
+    writeln!(out, "原点: {origin:?}")?;
     
     // 访问元组结构体的字段
-    println!("黑色的红色分量: {}", black.0);
-    println!("原点的 x 坐标: {}", origin.0);
+    writeln!(out, "黑色的红色分量: {}", black.0)?;
+    writeln!(out, "原点的 x 坐标: {}", origin.0)?;
     
     // 元组结构体的解构
     let Color(r, g, b) = black;
-    println!("RGB 值: r={r}, g={g}, b={b}");
+    writeln!(out, "RGB 值: r={r}, g={g}, b={b}")?;
+    Ok(())
 }
 
-fn unit_struct_demo() {
+fn unit_struct_demo(out: &mut dyn Write) -> io::Result<()> {
     // 单元结构体（没有字段）
     #[derive(Debug)]
     struct AlwaysEqual;
     
     let subject = AlwaysEqual;
-    println!("单元结构体: {subject:?}");
+    writeln!(out, "单元结构体: {subject:?}")?;
     
     // 单元结构体常用于实现特征
-    println!("单元结构体大小: {} 字节", std::mem::size_of::<AlwaysEqual>());
+    writeln!(out, "单元结构体大小: {} 字节", std::mem::size_of::<AlwaysEqual>())?;
+    Ok(())
 }
 
-fn struct_ownership_demo() {
+fn struct_ownership_demo(out: &mut dyn Write) -> io::Result<()> {
     // 结构体拥有其数据
     let user = User {
         active: true,
@@ -184,19 +185,20 @@ fn struct_ownership_demo() {
     
     // 移动结构体
     let moved_user = user;
-    println!("移动后的用户: {moved_user:?}");
+    writeln!(out, "移动后的用户: {moved_user:?}")?;
     // println!("{:?}", user); // 这会编译错误，user 已被移动
     
     // 使用引用的结构体
     let name = "borrowed";
     let person = Person { name, age: 30 };
-    println!("借用字符串的结构体: {person:?}");
+    writeln!(out, "借用字符串的结构体: {person:?}")?;
     
     // 克隆结构体
     let original = Rectangle { width: 10, height: 20 };
     let cloned = original.clone();
-    println!("原始矩形: {original:?}");
-    println!("克隆矩形: {cloned:?}");
+    writeln!(out, "原始矩形: {original:?}")?;
+    writeln!(out, "克隆矩形: {cloned:?}")?;
+    Ok(())
 }
 
 // 构建用户的函数
@@ -240,6 +242,7 @@ impl Rectangle {
     }
     
     // 修改矩形大小
+    #[allow(dead_code)] // 方法示例，演示中没有调用
     fn resize(&mut self, new_width: u32, new_height: u32) {
         self.width = new_width;
         self.height = new_height;
@@ -262,6 +265,7 @@ impl Rectangle {
     }
     
     // 创建默认矩形
+    #[allow(dead_code)]
     fn default() -> Rectangle {
         Rectangle {
             width: 1,
@@ -271,6 +275,7 @@ impl Rectangle {
 }
 
 // 为 User 实现方法
+#[allow(dead_code)] // 部分方法只在测试中使用
 impl User {
     // 创建新用户
     fn new(username: String, email: String) -> User {
@@ -309,6 +314,7 @@ impl User {
 }
 
 // 多个 impl 块
+#[allow(dead_code)] // 部分方法只在测试中使用
 impl Rectangle {
     // 计算对角线长度
     fn diagonal(&self) -> f64 {
@@ -0,0 +1,54 @@
+//! 文件读写辅助函数
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 原子地写入文件
+///
+/// 先写入同目录下的临时文件并同步到磁盘，再重命名覆盖目标文件，
+/// 中途崩溃时原来的文件保持不变。
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let tmp_path = temp_file_path(path);
+
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// 写入时使用的临时文件路径：`progress.json` -> `progress.json.tmp`
+fn temp_file_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic() {
+        let path = std::env::temp_dir().join(format!("learn_rust_write_atomic_{}.txt", std::process::id()));
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+
+        // 临时文件不应残留
+        assert!(!temp_file_path(&path).exists());
+        fs::remove_file(&path).unwrap();
+
+        // 写入失败时也不残留临时文件
+        let missing = std::env::temp_dir().join("learn_rust_missing_dir").join("file.txt");
+        assert!(write_atomic(&missing, "x").is_err());
+        assert!(!temp_file_path(&missing).exists());
+    }
+}